@@ -0,0 +1,515 @@
+// Bevy systems routinely take "complex" queries and lots of parameters;
+// https://github.com/bevyengine/bevy/issues/4601
+#![allow(clippy::type_complexity, clippy::too_many_arguments)]
+
+use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
+use bevy_common::{
+    audio::{Beep, BeepPlugin},
+    collision,
+    fx::{self, JuiceSettings, Particle, ParticlePlugin},
+    input,
+    motion::{self, Velocity},
+    pool::Pool,
+    settings::SettingsPlugin,
+};
+use rand::Rng;
+
+mod rocks;
+mod screens;
+
+use rocks::Asteroid;
+
+mod constants {
+    use bevy::prelude::*;
+    // These constants are defined in `Transform` units.
+    // Using the default 2D camera they correspond 1:1 with screen pixels.
+    pub const FIELD_SIZE: Vec2 = Vec2::new(1280.0, 720.0);
+    // how far past the edge of the field things go before they come back on the other side
+    pub const WRAP_MARGIN: f32 = 40.0;
+
+    pub const SHIP_RADIUS: f32 = 18.0;
+    // in radians per second
+    pub const SHIP_TURN_SPEED: f32 = 4.0;
+    pub const SHIP_THRUST: f32 = 400.0;
+    pub const SHIP_MAX_SPEED: f32 = 450.0;
+    // fraction of its speed the ship loses every second
+    pub const SHIP_DRAG: f32 = 0.6;
+    // how long the ship can't be hit for after it comes back, in seconds
+    pub const SHIP_INVULNERABLE_DURATION: f32 = 2.5;
+    // how often it blinks while it can't be hit
+    pub const SHIP_BLINK_INTERVAL: f32 = 0.15;
+
+    pub const BULLET_RADIUS: f32 = 3.0;
+    pub const BULLET_SPEED: f32 = 600.0;
+    pub const BULLET_LIFETIME: f32 = 1.0;
+    pub const MAX_BULLETS: usize = 5;
+
+    // how far either side of the shot asteroid's heading its pieces go
+    pub const SPLIT_DEGREES: f32 = 35.0;
+    // how many large asteroids the first wave has, every wave after has one more
+    pub const FIRST_WAVE_ASTEROIDS: usize = 4;
+    // new asteroids don't turn up any closer to the ship than this
+    pub const SAFE_DISTANCE: f32 = 200.0;
+    // sides on an asteroid, which is close enough to round to look like a rock
+    pub const ASTEROID_SIDES: usize = 7;
+
+    pub const STARTING_LIVES: usize = 3;
+
+    pub const EXPLOSION_PARTICLES: usize = 16;
+    pub const EXPLOSION_SPEED: f32 = 150.0;
+    pub const SHIP_EXPLOSION_PARTICLES: usize = 40;
+    pub const SHIP_EXPLOSION_SPEED: f32 = 250.0;
+
+    // in Hz and seconds
+    pub const SHOOT_BEEP: (f32, f32) = (880.0, 0.05);
+    pub const EXPLOSION_BEEP: (f32, f32) = (110.0, 0.15);
+    pub const SHIP_EXPLOSION_BEEP: (f32, f32) = (55.0, 0.5);
+
+    pub const BACKGROUND_COLOR: Color = Color::rgb(0.02, 0.02, 0.05);
+    pub const SHIP_COLOR: Color = Color::rgb(0.9, 0.9, 1.0);
+    pub const BULLET_COLOR: Color = Color::rgb(1.0, 0.9, 0.5);
+    pub const ASTEROID_COLOR: Color = Color::rgb(0.5, 0.45, 0.4);
+    pub const EXPLOSION_COLOR: Color = Color::rgb(1.0, 0.6, 0.2);
+    pub const HUD_FONT_SIZE: f32 = 28.0;
+    pub const HUD_COLOR: Color = Color::rgb(0.8, 0.8, 0.8);
+    pub const HUD_PADDING: f32 = 20.0;
+
+    pub const SCREEN_FONT_SIZE: f32 = 48.0;
+    pub const SCREEN_HINT_FONT_SIZE: f32 = 24.0;
+    pub const SCREEN_BACKDROP_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.6);
+    pub const SCREEN_Z_INDEX: i32 = 100;
+
+    // where settings are kept, see `bevy_common::storage`
+    pub const GAME_NAME: &str = "asteroids";
+}
+
+mod entities {
+    use bevy::prelude::*;
+
+    #[derive(Component, Debug)]
+    pub struct Ship;
+
+    // The ship just came back and can't be hit until this runs out
+    #[derive(Component, Debug, Deref, DerefMut)]
+    pub struct Invulnerable(pub Timer);
+
+    #[derive(Component, Debug, Deref, DerefMut)]
+    pub struct Bullet {
+        pub lifetime: Timer,
+    }
+
+    // Goes off one side of the field and comes back on the other
+    #[derive(Component, Debug)]
+    pub struct Wraps;
+
+    #[derive(Component, Debug)]
+    pub struct HudText;
+}
+
+use constants::*;
+
+#[derive(States, Debug, Clone, Copy, Default, Hash, PartialEq, Eq)]
+pub enum GameState {
+    #[default]
+    Playing,
+    // out of ships, see `screens.rs`
+    GameOver,
+}
+
+#[derive(Resource, Debug, Default, Deref, DerefMut)]
+pub struct Score(usize);
+
+#[derive(Resource, Debug, Deref, DerefMut)]
+pub struct Lives(usize);
+
+impl Default for Lives {
+    fn default() -> Self {
+        Self(STARTING_LIVES)
+    }
+}
+
+// How many waves there have been, the one being played is the last. Every wave has one
+// more asteroid than the one before.
+#[derive(Resource, Debug, Default, Deref, DerefMut)]
+pub struct Wave(usize);
+
+// Every asteroid shares one mesh, scaled to its size, and every bullet another
+#[derive(Resource, Debug)]
+struct Shapes {
+    asteroid: Handle<Mesh>,
+    asteroid_material: Handle<ColorMaterial>,
+    bullet: Handle<Mesh>,
+    bullet_material: Handle<ColorMaterial>,
+}
+
+fn beep((frequency, duration): (f32, f32)) -> Beep {
+    Beep {
+        frequency,
+        duration,
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    commands.spawn(Camera2dBundle::default());
+
+    // a triangle, pointing up the way the ship goes
+    commands.spawn((
+        MaterialMesh2dBundle {
+            mesh: meshes
+                .add(shape::RegularPolygon::new(SHIP_RADIUS, 3).into())
+                .into(),
+            material: materials.add(ColorMaterial::from(SHIP_COLOR)),
+            transform: Transform::from_xyz(0.0, 0.0, 1.0),
+            ..default()
+        },
+        entities::Ship,
+        entities::Wraps,
+        Velocity::default(),
+    ));
+
+    commands.insert_resource(Shapes {
+        asteroid: meshes.add(shape::RegularPolygon::new(1.0, ASTEROID_SIDES).into()),
+        asteroid_material: materials.add(ColorMaterial::from(ASTEROID_COLOR)),
+        bullet: meshes.add(shape::Circle::new(BULLET_RADIUS).into()),
+        bullet_material: materials.add(ColorMaterial::from(BULLET_COLOR)),
+    });
+
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font_size: HUD_FONT_SIZE,
+                    color: HUD_COLOR,
+                    ..default()
+                },
+            ),
+            transform: Transform::from_translation(Vec3::new(
+                0.0,
+                FIELD_SIZE.y / 2.0 - HUD_PADDING,
+                2.0,
+            )),
+            ..default()
+        },
+        entities::HudText,
+    ));
+}
+
+fn spawn_asteroid(
+    commands: &mut Commands,
+    shapes: &Shapes,
+    asteroid: Asteroid,
+    position: Vec2,
+    velocity: Vec2,
+) {
+    commands.spawn((
+        MaterialMesh2dBundle {
+            mesh: shapes.asteroid.clone().into(),
+            material: shapes.asteroid_material.clone(),
+            transform: Transform::from_translation(position.extend(0.0))
+                .with_scale(Vec3::splat(asteroid.radius())),
+            ..default()
+        },
+        asteroid,
+        entities::Wraps,
+        Velocity(velocity),
+    ));
+}
+
+// Starts the next wave once the last one's been cleared, which is straight away at the start
+// of a game
+fn next_wave(
+    mut commands: Commands,
+    shapes: Res<Shapes>,
+    asteroids: Query<(), With<Asteroid>>,
+    ships: Query<&Transform, With<entities::Ship>>,
+    mut wave: ResMut<Wave>,
+) {
+    if !asteroids.is_empty() {
+        return;
+    }
+    **wave += 1;
+
+    let ship = ships
+        .get_single()
+        .map_or(Vec2::ZERO, |ship| ship.translation.truncate());
+    let mut rng = rand::thread_rng();
+
+    for _ in 0..FIRST_WAVE_ASTEROIDS + **wave - 1 {
+        // anywhere that isn't right on top of the ship
+        let position = loop {
+            let position = Vec2::new(
+                rng.gen_range(-FIELD_SIZE.x / 2.0..FIELD_SIZE.x / 2.0),
+                rng.gen_range(-FIELD_SIZE.y / 2.0..FIELD_SIZE.y / 2.0),
+            );
+            if position.distance(ship) > SAFE_DISTANCE {
+                break position;
+            }
+        };
+        let direction = Vec2::from_angle(rng.gen_range(0.0..std::f32::consts::TAU));
+
+        spawn_asteroid(
+            &mut commands,
+            &shapes,
+            Asteroid::Large,
+            position,
+            direction * Asteroid::Large.speed(),
+        );
+    }
+}
+
+fn steer_ship(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut ships: Query<(&mut Transform, &mut Velocity), With<entities::Ship>>,
+    time: Res<Time>,
+) {
+    for (mut transform, mut velocity) in &mut ships {
+        // left turns anticlockwise
+        transform
+            .rotate_z(-input::horizontal(&keyboard_input) * SHIP_TURN_SPEED * time.delta_seconds());
+
+        if input::vertical(&keyboard_input) > 0.0 {
+            velocity.0 += transform.up().truncate() * SHIP_THRUST * time.delta_seconds();
+        }
+        velocity.0 *= (1.0 - SHIP_DRAG * time.delta_seconds()).max(0.0);
+        velocity.0 = velocity.clamp_length_max(SHIP_MAX_SPEED);
+    }
+}
+
+fn shoot(
+    mut commands: Commands,
+    keyboard_input: Res<Input<KeyCode>>,
+    ships: Query<(&Transform, &Velocity), With<entities::Ship>>,
+    bullets: Query<(), With<entities::Bullet>>,
+    shapes: Res<Shapes>,
+    mut beeps: EventWriter<Beep>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::Space) || bullets.iter().len() >= MAX_BULLETS {
+        return;
+    }
+
+    for (ship, velocity) in &ships {
+        let forward = ship.up().truncate();
+        commands.spawn((
+            MaterialMesh2dBundle {
+                mesh: shapes.bullet.clone().into(),
+                material: shapes.bullet_material.clone(),
+                transform: Transform::from_translation(
+                    (ship.translation.truncate() + forward * SHIP_RADIUS).extend(1.0),
+                ),
+                ..default()
+            },
+            entities::Bullet {
+                lifetime: Timer::from_seconds(BULLET_LIFETIME, TimerMode::Once),
+            },
+            entities::Wraps,
+            Velocity(velocity.0 + forward * BULLET_SPEED),
+        ));
+        beeps.send(beep(SHOOT_BEEP));
+    }
+}
+
+fn expire_bullets(
+    mut commands: Commands,
+    mut bullets: Query<(Entity, &mut entities::Bullet)>,
+    time: Res<Time>,
+) {
+    for (entity, mut bullet) in &mut bullets {
+        if bullet.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn wrap(mut query: Query<&mut Transform, With<entities::Wraps>>) {
+    for mut transform in &mut query {
+        let position = rocks::wrap(transform.translation.truncate());
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
+    }
+}
+
+fn shoot_asteroids(
+    mut commands: Commands,
+    shapes: Res<Shapes>,
+    bullets: Query<(Entity, &Transform), With<entities::Bullet>>,
+    asteroids: Query<(Entity, &Transform, &Velocity, &Asteroid)>,
+    mut particles: ResMut<Pool<Particle>>,
+    juice: Res<JuiceSettings>,
+    mut score: ResMut<Score>,
+    mut beeps: EventWriter<Beep>,
+) {
+    // an asteroid can only be broken up once, by whichever bullet got to it first
+    let mut shot = vec![];
+
+    for (bullet, bullet_transform) in &bullets {
+        let Some((entity, transform, velocity, asteroid)) =
+            asteroids.iter().find(|(entity, transform, _, asteroid)| {
+                !shot.contains(entity)
+                    && collision::circle_contact(
+                        bullet_transform.translation.truncate(),
+                        BULLET_RADIUS,
+                        transform.translation.truncate(),
+                        asteroid.radius(),
+                    )
+                    .is_some()
+            })
+        else {
+            continue;
+        };
+        shot.push(entity);
+
+        commands.entity(bullet).despawn();
+        commands.entity(entity).despawn();
+        **score += asteroid.points();
+
+        let position = transform.translation.truncate();
+        for (piece, velocity) in asteroid.pieces(velocity.0) {
+            spawn_asteroid(&mut commands, &shapes, piece, position, velocity);
+        }
+        fx::burst(
+            &mut commands,
+            &mut particles,
+            &juice,
+            position,
+            EXPLOSION_COLOR,
+            EXPLOSION_PARTICLES,
+            EXPLOSION_SPEED,
+        );
+        beeps.send(beep(EXPLOSION_BEEP));
+    }
+}
+
+fn crash_ship(
+    mut commands: Commands,
+    mut ships: Query<
+        (Entity, &mut Transform, &mut Velocity),
+        (With<entities::Ship>, Without<entities::Invulnerable>),
+    >,
+    asteroids: Query<(&Transform, &Asteroid), Without<entities::Ship>>,
+    mut particles: ResMut<Pool<Particle>>,
+    juice: Res<JuiceSettings>,
+    mut lives: ResMut<Lives>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut beeps: EventWriter<Beep>,
+) {
+    for (entity, mut transform, mut velocity) in &mut ships {
+        let position = transform.translation.truncate();
+        let crashed = asteroids.iter().any(|(asteroid_transform, asteroid)| {
+            collision::circle_contact(
+                position,
+                SHIP_RADIUS,
+                asteroid_transform.translation.truncate(),
+                asteroid.radius(),
+            )
+            .is_some()
+        });
+        if !crashed {
+            continue;
+        }
+
+        fx::burst(
+            &mut commands,
+            &mut particles,
+            &juice,
+            position,
+            EXPLOSION_COLOR,
+            SHIP_EXPLOSION_PARTICLES,
+            SHIP_EXPLOSION_SPEED,
+        );
+        beeps.send(beep(SHIP_EXPLOSION_BEEP));
+
+        **lives = lives.saturating_sub(1);
+        if **lives == 0 {
+            next_state.set(GameState::GameOver);
+        }
+
+        // back in the middle, with a moment to get out of the way
+        *transform = Transform::from_xyz(0.0, 0.0, 1.0);
+        velocity.0 = Vec2::ZERO;
+        commands
+            .entity(entity)
+            .insert(entities::Invulnerable(Timer::from_seconds(
+                SHIP_INVULNERABLE_DURATION,
+                TimerMode::Once,
+            )));
+    }
+}
+
+// Blinks the ship while it can't be hit, and stops once it can
+fn blink_ship(
+    mut commands: Commands,
+    mut ships: Query<(Entity, &mut Visibility, &mut entities::Invulnerable)>,
+    time: Res<Time>,
+) {
+    for (entity, mut visibility, mut invulnerable) in &mut ships {
+        if invulnerable.tick(time.delta()).finished() {
+            *visibility = Visibility::Inherited;
+            commands.entity(entity).remove::<entities::Invulnerable>();
+            continue;
+        }
+
+        let blinks = (invulnerable.elapsed_secs() / SHIP_BLINK_INTERVAL) as usize;
+        *visibility = if blinks.is_multiple_of(2) {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
+    }
+}
+
+fn update_hud(
+    score: Res<Score>,
+    lives: Res<Lives>,
+    wave: Res<Wave>,
+    mut hud: Query<&mut Text, With<entities::HudText>>,
+) {
+    if !score.is_changed() && !lives.is_changed() && !wave.is_changed() {
+        return;
+    }
+
+    for mut text in &mut hud {
+        text.sections[0].value =
+            format!("Wave {}    Score {}    Ships {}", **wave, **score, **lives);
+    }
+}
+
+fn main() {
+    App::new()
+        .add_plugins((
+            DefaultPlugins,
+            ParticlePlugin,
+            BeepPlugin,
+            SettingsPlugin { game: GAME_NAME },
+            screens::ScreensPlugin,
+        ))
+        .add_state::<GameState>()
+        .insert_resource(ClearColor(BACKGROUND_COLOR))
+        .init_resource::<Score>()
+        .init_resource::<Lives>()
+        .init_resource::<Wave>()
+        .add_systems(Startup, setup)
+        // the asteroids keep drifting behind the game over screen
+        .add_systems(FixedUpdate, (motion::apply_velocity, wrap).chain())
+        .add_systems(
+            FixedUpdate,
+            (
+                steer_ship,
+                expire_bullets,
+                shoot_asteroids,
+                crash_ship,
+                blink_ship,
+                next_wave,
+            )
+                .chain()
+                .before(motion::apply_velocity)
+                .run_if(in_state(GameState::Playing)),
+        )
+        .add_systems(Update, shoot.run_if(in_state(GameState::Playing)))
+        .add_systems(Update, (update_hud, bevy::window::close_on_esc))
+        .run();
+}
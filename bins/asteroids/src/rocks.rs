@@ -0,0 +1,109 @@
+use bevy::prelude::*;
+
+use crate::constants::*;
+
+// Asteroids come in three sizes. Shooting one breaks it into two of the next size down,
+// and the smallest just go away.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Asteroid {
+    Large,
+    Medium,
+    Small,
+}
+
+impl Asteroid {
+    pub fn radius(&self) -> f32 {
+        match self {
+            Asteroid::Large => 48.0,
+            Asteroid::Medium => 28.0,
+            Asteroid::Small => 14.0,
+        }
+    }
+
+    // smaller ones are faster and worth more
+    pub fn speed(&self) -> f32 {
+        match self {
+            Asteroid::Large => 60.0,
+            Asteroid::Medium => 100.0,
+            Asteroid::Small => 150.0,
+        }
+    }
+
+    pub fn points(&self) -> usize {
+        match self {
+            Asteroid::Large => 20,
+            Asteroid::Medium => 50,
+            Asteroid::Small => 100,
+        }
+    }
+
+    // what it breaks into when it's shot
+    pub fn split(&self) -> Option<Asteroid> {
+        match self {
+            Asteroid::Large => Some(Asteroid::Medium),
+            Asteroid::Medium => Some(Asteroid::Small),
+            Asteroid::Small => None,
+        }
+    }
+
+    // the pieces' velocities, either side of the one it was going
+    pub fn pieces(&self, velocity: Vec2) -> Vec<(Asteroid, Vec2)> {
+        let Some(piece) = self.split() else {
+            return vec![];
+        };
+        let direction = velocity.try_normalize().unwrap_or(Vec2::Y);
+        let angle = SPLIT_DEGREES.to_radians();
+
+        [angle, -angle]
+            .into_iter()
+            .map(|angle| {
+                (
+                    piece,
+                    Vec2::from_angle(angle).rotate(direction) * piece.speed(),
+                )
+            })
+            .collect()
+    }
+}
+
+// Where something that's drifted off one side of the field comes back on the other. Things
+// go `WRAP_MARGIN` past the edge first so they don't pop from one side to the other.
+pub fn wrap(position: Vec2) -> Vec2 {
+    let half = FIELD_SIZE / 2.0 + WRAP_MARGIN;
+    let wrap_axis = |value: f32, half: f32| {
+        if value > half {
+            value - 2.0 * half
+        } else if value < -half {
+            value + 2.0 * half
+        } else {
+            value
+        }
+    };
+    Vec2::new(wrap_axis(position.x, half.x), wrap_axis(position.y, half.y))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn asteroids_split_down_to_nothing() {
+        let large = Asteroid::Large.pieces(Vec2::X * 10.0);
+        assert_eq!(large.len(), 2);
+        assert!(large.iter().all(|(piece, velocity)| {
+            *piece == Asteroid::Medium && (velocity.length() - piece.speed()).abs() < 1e-3
+        }));
+        // and they head off either side of where it was going
+        assert!(large[0].1.y > 0.0 && large[1].1.y < 0.0);
+
+        assert!(Asteroid::Small.pieces(Vec2::X).is_empty());
+    }
+
+    #[test]
+    fn wrapping_only_happens_past_the_margin() {
+        let edge = FIELD_SIZE.x / 2.0 + WRAP_MARGIN;
+        assert_eq!(wrap(Vec2::new(edge - 1.0, 0.0)).x, edge - 1.0);
+        assert_eq!(wrap(Vec2::new(edge + 1.0, 0.0)).x, -edge + 1.0);
+        assert_eq!(wrap(Vec2::new(0.0, -FIELD_SIZE.y)).y, 2.0 * WRAP_MARGIN);
+    }
+}
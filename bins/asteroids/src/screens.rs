@@ -0,0 +1,98 @@
+use bevy::prelude::*;
+use bevy_common::{
+    motion::Velocity,
+    screens::{self, AddScreen},
+};
+
+use crate::{constants::*, entities, GameState, Lives, Score, Wave};
+
+#[derive(Component)]
+struct GameOverScreen;
+
+pub struct ScreensPlugin;
+
+impl Plugin for ScreensPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_screen::<GameOverScreen, _>(
+            GameState::GameOver,
+            (spawn_game_over_screen, hide_ship),
+        )
+        .add_systems(OnExit(GameState::GameOver), reset_game)
+        .add_systems(
+            Update,
+            leave_game_over_screen.run_if(in_state(GameState::GameOver)),
+        );
+    }
+}
+
+fn style(font_size: f32, color: Color) -> TextStyle {
+    TextStyle {
+        font_size,
+        color,
+        ..default()
+    }
+}
+
+fn spawn_game_over_screen(mut commands: Commands, wave: Res<Wave>, score: Res<Score>) {
+    commands
+        .spawn((
+            screens::overlay(SCREEN_BACKDROP_COLOR, SCREEN_Z_INDEX),
+            GameOverScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_sections([
+                TextSection::new("Game over\n\n", style(SCREEN_FONT_SIZE, Color::WHITE)),
+                TextSection::new(
+                    format!("score {} on wave {}\n\n", **score, **wave),
+                    style(SCREEN_HINT_FONT_SIZE, Color::WHITE),
+                ),
+                TextSection::new(
+                    "enter: play again",
+                    style(SCREEN_HINT_FONT_SIZE, Color::GRAY),
+                ),
+            ]));
+        });
+}
+
+// The asteroids carry on drifting about, but the ship's gone
+fn hide_ship(
+    mut commands: Commands,
+    mut ships: Query<(Entity, &mut Visibility), With<entities::Ship>>,
+) {
+    for (entity, mut visibility) in &mut ships {
+        *visibility = Visibility::Hidden;
+        commands.entity(entity).remove::<entities::Invulnerable>();
+    }
+}
+
+fn leave_game_over_screen(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        next_state.set(GameState::Playing);
+    }
+}
+
+// Clears the field for a new game, whose first wave starts once there's nothing left on it
+fn reset_game(
+    mut commands: Commands,
+    leftovers: Query<Entity, Or<(With<crate::Asteroid>, With<entities::Bullet>)>>,
+    mut ships: Query<(&mut Transform, &mut Velocity, &mut Visibility), With<entities::Ship>>,
+    mut score: ResMut<Score>,
+    mut lives: ResMut<Lives>,
+    mut wave: ResMut<Wave>,
+) {
+    for entity in &leftovers {
+        commands.entity(entity).despawn();
+    }
+    for (mut transform, mut velocity, mut visibility) in &mut ships {
+        *transform = Transform::from_xyz(0.0, 0.0, 1.0);
+        velocity.0 = Vec2::ZERO;
+        *visibility = Visibility::Inherited;
+    }
+
+    *score = Score::default();
+    *lives = Lives::default();
+    *wave = Wave::default();
+}
@@ -0,0 +1,74 @@
+use crate::constants::*;
+
+// One row of the brick grid per line and one brick per character: a digit is how many hits
+// the brick takes to break, anything else leaves a gap
+const LEVELS: [&[&str]; 3] = [
+    &["1111111111", "1111111111", "1111111111", "1111111111"],
+    &[
+        "2222222222",
+        "2111111112",
+        "2111111112",
+        "2111111112",
+        "2222222222",
+    ],
+    &[
+        "..333333..",
+        ".22222222.",
+        "1111111111",
+        ".22222222.",
+        "..333333..",
+    ],
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BrickLayout {
+    pub column: usize,
+    pub row: usize,
+    pub hit_points: u8,
+}
+
+// The bricks for `level`, counting from 0. After the last layout they start again from the
+// first, with a faster ball.
+pub fn bricks(level: usize) -> Vec<BrickLayout> {
+    LEVELS[level % LEVELS.len()]
+        .iter()
+        .enumerate()
+        .flat_map(|(row, line)| {
+            line.chars().enumerate().filter_map(move |(column, brick)| {
+                let hit_points = brick.to_digit(10)? as u8;
+                (hit_points > 0).then_some(BrickLayout {
+                    column,
+                    row,
+                    hit_points,
+                })
+            })
+        })
+        .collect()
+}
+
+pub fn ball_speed(level: usize) -> f32 {
+    BALL_SPEED * (1.0 + BALL_SPEEDUP_PER_LEVEL * level as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_layout_fits_the_grid() {
+        for level in 0..LEVELS.len() {
+            let bricks = bricks(level);
+            assert!(!bricks.is_empty());
+            for brick in bricks {
+                assert!(brick.column < BRICK_COLUMNS);
+                assert!((1..=MAX_BRICK_HIT_POINTS).contains(&brick.hit_points));
+            }
+        }
+    }
+
+    #[test]
+    fn layouts_repeat_faster() {
+        assert_eq!(bricks(0), bricks(LEVELS.len()));
+        assert!(ball_speed(LEVELS.len()) > ball_speed(0));
+    }
+}
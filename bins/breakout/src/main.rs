@@ -0,0 +1,476 @@
+// Bevy systems routinely take "complex" queries and lots of parameters;
+// https://github.com/bevyengine/bevy/issues/4601
+#![allow(clippy::type_complexity, clippy::too_many_arguments)]
+
+use bevy::{
+    prelude::*,
+    sprite::{collide_aabb::collide, MaterialMesh2dBundle},
+};
+use bevy_common::{
+    collision,
+    fx::{Flash, FlashPlugin},
+    input,
+    motion::{self, Velocity},
+};
+
+mod levels;
+mod screens;
+
+mod constants {
+    use bevy::prelude::*;
+    // These constants are defined in `Transform` units.
+    // Using the default 2D camera they correspond 1:1 with screen pixels.
+    pub const LEFT_WALL: f32 = -450.;
+    pub const RIGHT_WALL: f32 = 450.;
+    pub const TOP_WALL: f32 = 300.;
+    // there's no wall at the bottom, a ball that gets past the paddle is lost
+    pub const BOTTOM_WALL: f32 = -300.;
+    pub const WALL_THICKNESS: f32 = 10.0;
+
+    pub const PADDLE_SIZE: Vec3 = Vec3::new(120.0, 20.0, 0.0);
+    pub const PADDLE_Y: f32 = BOTTOM_WALL + 60.0;
+    pub const PADDLE_SPEED: f32 = 500.0;
+    // How close can the paddle get to the wall
+    pub const PADDLE_PADDING: f32 = 10.0;
+
+    pub const BALL_SIZE: Vec3 = Vec3::new(20.0, 20.0, 0.0);
+    pub const BALL_SPEED: f32 = 400.0;
+    // every level is this much faster than the last, as a fraction of `BALL_SPEED`
+    pub const BALL_SPEEDUP_PER_LEVEL: f32 = 0.1;
+    // how far from straight up the ball leaves the very end of the paddle. Hitting it with
+    // the middle sends the ball straight up.
+    pub const MAX_BOUNCE_DEGREES: f32 = 60.0;
+    // serves go up and a little to this side of vertical
+    pub const SERVE_DEGREES: f32 = 20.0;
+
+    pub const BRICK_SIZE: Vec2 = Vec2::new(80.0, 24.0);
+    pub const BRICK_GAP: f32 = 6.0;
+    pub const BRICK_COLUMNS: usize = 10;
+    // the top of the top row
+    pub const BRICKS_TOP: f32 = TOP_WALL - 60.0;
+    pub const MAX_BRICK_HIT_POINTS: u8 = 3;
+    pub const BRICK_POINTS: usize = 10;
+    pub const BRICK_FLASH_DURATION: f32 = 0.15;
+
+    pub const STARTING_LIVES: usize = 3;
+    // how long the level cleared screen stays up, in seconds
+    pub const LEVEL_CLEARED_DURATION: f32 = 2.0;
+
+    pub const BACKGROUND_COLOR: Color = Color::rgb(0.9, 0.9, 0.9);
+    pub const PADDLE_COLOR: Color = Color::rgb(0.3, 0.3, 0.7);
+    pub const BALL_COLOR: Color = Color::rgb(1.0, 0.5, 0.5);
+    pub const WALL_COLOR: Color = Color::rgb(0.8, 0.8, 0.8);
+    // by how many hits are left
+    pub const BRICK_COLORS: [Color; 3] = [
+        Color::rgb(0.5, 0.5, 1.0),
+        Color::rgb(0.3, 0.7, 0.4),
+        Color::rgb(0.8, 0.3, 0.3),
+    ];
+    pub const BRICK_FLASH_COLOR: Color = Color::WHITE;
+    pub const HUD_FONT_SIZE: f32 = 28.0;
+    pub const HUD_COLOR: Color = Color::rgb(0.3, 0.3, 0.3);
+    pub const HUD_PADDING: f32 = 20.0;
+
+    pub const SCREEN_FONT_SIZE: f32 = 48.0;
+    pub const SCREEN_HINT_FONT_SIZE: f32 = 24.0;
+    pub const SCREEN_BACKDROP_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.8);
+    pub const SCREEN_Z_INDEX: i32 = 100;
+}
+
+mod entities {
+    use super::constants::*;
+    use bevy::prelude::*;
+
+    #[derive(Component, Debug)]
+    pub struct Paddle;
+
+    #[derive(Component, Debug)]
+    pub struct Ball;
+
+    // Anything the ball bounces off
+    #[derive(Component, Debug)]
+    pub struct Collider;
+
+    #[derive(Component, Debug)]
+    pub struct Brick {
+        pub hit_points: u8,
+    }
+
+    impl Brick {
+        pub fn color(&self) -> Color {
+            BRICK_COLORS[(self.hit_points.clamp(1, MAX_BRICK_HIT_POINTS) - 1) as usize]
+        }
+    }
+
+    #[derive(Component, Debug)]
+    pub struct HudText;
+
+    #[derive(Bundle)]
+    pub struct Wall {
+        pub sprite_bundle: SpriteBundle,
+        pub collider: Collider,
+    }
+
+    impl Wall {
+        pub fn new(position: Vec2, size: Vec2) -> Self {
+            Self {
+                sprite_bundle: SpriteBundle {
+                    transform: Transform {
+                        translation: position.extend(0.0),
+                        scale: size.extend(1.0),
+                        ..default()
+                    },
+                    sprite: Sprite {
+                        color: WALL_COLOR,
+                        ..default()
+                    },
+                    ..default()
+                },
+                collider: Collider,
+            }
+        }
+    }
+}
+
+use constants::*;
+
+// The ball sits on the paddle until it's served, and the next one does after a life is lost
+#[derive(States, Debug, Clone, Copy, Default, Hash, PartialEq, Eq)]
+pub enum GameState {
+    #[default]
+    Serving,
+    Playing,
+    // every brick is gone, see `screens.rs`
+    LevelCleared,
+    // out of lives, see `screens.rs`
+    GameOver,
+}
+
+#[derive(Resource, Debug, Default, Deref, DerefMut)]
+pub struct Score(usize);
+
+#[derive(Resource, Debug, Deref, DerefMut)]
+pub struct Lives(usize);
+
+impl Default for Lives {
+    fn default() -> Self {
+        Self(STARTING_LIVES)
+    }
+}
+
+// Counting from 0, see `levels.rs`
+#[derive(Resource, Debug, Default, Deref, DerefMut)]
+pub struct Level(usize);
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    commands.spawn(Camera2dBundle::default());
+
+    commands.spawn((
+        SpriteBundle {
+            transform: Transform {
+                translation: Vec3::new(0.0, PADDLE_Y, 0.0),
+                scale: PADDLE_SIZE,
+                ..default()
+            },
+            sprite: Sprite {
+                color: PADDLE_COLOR,
+                ..default()
+            },
+            ..default()
+        },
+        entities::Paddle,
+        entities::Collider,
+    ));
+
+    commands.spawn((
+        MaterialMesh2dBundle {
+            mesh: meshes.add(shape::Circle::default().into()).into(),
+            material: materials.add(ColorMaterial::from(BALL_COLOR)),
+            transform: Transform::from_translation(Vec3::new(0.0, PADDLE_Y, 1.0))
+                .with_scale(BALL_SIZE),
+            ..default()
+        },
+        entities::Ball,
+        Velocity::default(),
+    ));
+
+    // Walls, on every side but the bottom
+    let height = TOP_WALL - BOTTOM_WALL;
+    let width = RIGHT_WALL - LEFT_WALL;
+    commands.spawn(entities::Wall::new(
+        Vec2::new(LEFT_WALL, 0.0),
+        Vec2::new(WALL_THICKNESS, height + WALL_THICKNESS),
+    ));
+    commands.spawn(entities::Wall::new(
+        Vec2::new(RIGHT_WALL, 0.0),
+        Vec2::new(WALL_THICKNESS, height + WALL_THICKNESS),
+    ));
+    commands.spawn(entities::Wall::new(
+        Vec2::new(0.0, TOP_WALL),
+        Vec2::new(width + WALL_THICKNESS, WALL_THICKNESS),
+    ));
+
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font_size: HUD_FONT_SIZE,
+                    color: HUD_COLOR,
+                    ..default()
+                },
+            ),
+            transform: Transform::from_translation(Vec3::new(0.0, TOP_WALL - HUD_PADDING, 1.0)),
+            ..default()
+        },
+        entities::HudText,
+    ));
+}
+
+// Lays out the current level's bricks, clearing away whatever's left of the last one
+fn spawn_level(
+    mut commands: Commands,
+    level: Res<Level>,
+    bricks: Query<Entity, With<entities::Brick>>,
+) {
+    for brick in &bricks {
+        commands.entity(brick).despawn();
+    }
+
+    // the grid is centered between the walls
+    let grid_width = BRICK_COLUMNS as f32 * (BRICK_SIZE.x + BRICK_GAP) - BRICK_GAP;
+    let left = -grid_width / 2.0 + BRICK_SIZE.x / 2.0;
+
+    for layout in levels::bricks(**level) {
+        let brick = entities::Brick {
+            hit_points: layout.hit_points,
+        };
+        commands.spawn((
+            SpriteBundle {
+                transform: Transform {
+                    translation: Vec3::new(
+                        left + layout.column as f32 * (BRICK_SIZE.x + BRICK_GAP),
+                        BRICKS_TOP
+                            - BRICK_SIZE.y / 2.0
+                            - layout.row as f32 * (BRICK_SIZE.y + BRICK_GAP),
+                        0.0,
+                    ),
+                    scale: BRICK_SIZE.extend(1.0),
+                    ..default()
+                },
+                sprite: Sprite {
+                    color: brick.color(),
+                    ..default()
+                },
+                ..default()
+            },
+            brick,
+            entities::Collider,
+        ));
+    }
+}
+
+fn move_paddle(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut paddles: Query<&mut Transform, With<entities::Paddle>>,
+    time: Res<Time>,
+) {
+    let limit = RIGHT_WALL - WALL_THICKNESS / 2.0 - PADDLE_SIZE.x / 2.0 - PADDLE_PADDING;
+
+    for mut transform in &mut paddles {
+        let x = transform.translation.x
+            + input::horizontal(&keyboard_input) * PADDLE_SPEED * time.delta_seconds();
+        transform.translation.x = x.clamp(-limit, limit);
+    }
+}
+
+// Keeps the ball on top of the paddle until it's served
+fn hold_ball(
+    paddles: Query<&Transform, With<entities::Paddle>>,
+    mut balls: Query<&mut Transform, (With<entities::Ball>, Without<entities::Paddle>)>,
+) {
+    let Ok(paddle) = paddles.get_single() else {
+        return;
+    };
+
+    for mut transform in &mut balls {
+        transform.translation.x = paddle.translation.x;
+        transform.translation.y = PADDLE_Y + PADDLE_SIZE.y / 2.0 + BALL_SIZE.y / 2.0;
+    }
+}
+
+fn serve(
+    keyboard_input: Res<Input<KeyCode>>,
+    level: Res<Level>,
+    mut balls: Query<&mut Velocity, With<entities::Ball>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if !keyboard_input.any_just_pressed([KeyCode::Space, KeyCode::Return]) {
+        return;
+    }
+
+    // off the way the paddle is going, or to the right
+    let lean = if input::horizontal(&keyboard_input) < 0.0 {
+        -1.0
+    } else {
+        1.0
+    };
+    let angle = (lean * SERVE_DEGREES).to_radians();
+    for mut velocity in &mut balls {
+        velocity.0 = Vec2::new(angle.sin(), angle.cos()) * levels::ball_speed(**level);
+    }
+    next_state.set(GameState::Playing);
+}
+
+fn bounce_ball(
+    mut commands: Commands,
+    mut balls: Query<(&mut Velocity, &Transform), With<entities::Ball>>,
+    mut colliders: Query<
+        (
+            Entity,
+            &Transform,
+            &mut Sprite,
+            Option<&mut entities::Brick>,
+            Option<&entities::Paddle>,
+        ),
+        (With<entities::Collider>, Without<entities::Ball>),
+    >,
+    mut score: ResMut<Score>,
+) {
+    for (mut velocity, ball) in &mut balls {
+        for (entity, transform, mut sprite, brick, paddle) in &mut colliders {
+            let Some(hit) = collide(
+                ball.translation,
+                ball.scale.truncate(),
+                transform.translation,
+                transform.scale.truncate(),
+            ) else {
+                continue;
+            };
+
+            // the paddle aims the ball by where it's hit, from straight up in the middle to
+            // `MAX_BOUNCE_DEGREES` at the ends
+            if paddle.is_some() {
+                if velocity.y < 0.0 {
+                    let offset =
+                        (ball.translation.x - transform.translation.x) / (transform.scale.x / 2.0);
+                    let angle = offset.clamp(-1.0, 1.0) * MAX_BOUNCE_DEGREES.to_radians();
+                    velocity.0 = Vec2::new(angle.sin(), angle.cos()) * velocity.length();
+                }
+                continue;
+            }
+
+            velocity.0 = collision::reflect(velocity.0, hit);
+
+            let Some(mut brick) = brick else {
+                continue;
+            };
+            **score += BRICK_POINTS;
+            brick.hit_points -= 1;
+            if brick.hit_points == 0 {
+                commands.entity(entity).despawn();
+            } else {
+                sprite.color = BRICK_FLASH_COLOR;
+                commands.entity(entity).insert(Flash::new(
+                    BRICK_FLASH_COLOR,
+                    brick.color(),
+                    BRICK_FLASH_DURATION,
+                ));
+            }
+        }
+    }
+}
+
+fn lose_ball(
+    mut balls: Query<(&Transform, &mut Velocity), With<entities::Ball>>,
+    mut lives: ResMut<Lives>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for (transform, mut velocity) in &mut balls {
+        if transform.translation.y > BOTTOM_WALL {
+            continue;
+        }
+
+        velocity.0 = Vec2::ZERO;
+        **lives = lives.saturating_sub(1);
+        next_state.set(if **lives == 0 {
+            GameState::GameOver
+        } else {
+            GameState::Serving
+        });
+    }
+}
+
+fn check_level_cleared(
+    bricks: Query<(), With<entities::Brick>>,
+    mut balls: Query<&mut Velocity, With<entities::Ball>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if !bricks.is_empty() {
+        return;
+    }
+
+    for mut velocity in &mut balls {
+        velocity.0 = Vec2::ZERO;
+    }
+    next_state.set(GameState::LevelCleared);
+}
+
+fn update_hud(
+    score: Res<Score>,
+    lives: Res<Lives>,
+    level: Res<Level>,
+    mut hud: Query<&mut Text, With<entities::HudText>>,
+) {
+    if !score.is_changed() && !lives.is_changed() && !level.is_changed() {
+        return;
+    }
+
+    for mut text in &mut hud {
+        text.sections[0].value = format!(
+            "Level {}    Score {}    Lives {}",
+            **level + 1,
+            **score,
+            **lives
+        );
+    }
+}
+
+fn main() {
+    App::new()
+        .add_plugins((DefaultPlugins, FlashPlugin, screens::ScreensPlugin))
+        .add_state::<GameState>()
+        .insert_resource(ClearColor(BACKGROUND_COLOR))
+        .init_resource::<Score>()
+        .init_resource::<Lives>()
+        .init_resource::<Level>()
+        .add_systems(Startup, (setup, spawn_level))
+        .add_systems(
+            FixedUpdate,
+            move_paddle.run_if(in_state(GameState::Serving).or_else(in_state(GameState::Playing))),
+        )
+        .add_systems(
+            FixedUpdate,
+            (
+                motion::apply_velocity,
+                bounce_ball,
+                lose_ball,
+                check_level_cleared,
+            )
+                .chain()
+                .after(move_paddle)
+                .run_if(in_state(GameState::Playing)),
+        )
+        .add_systems(
+            Update,
+            (hold_ball, serve)
+                .chain()
+                .run_if(in_state(GameState::Serving)),
+        )
+        .add_systems(Update, (update_hud, bevy::window::close_on_esc))
+        .run();
+}
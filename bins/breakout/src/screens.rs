@@ -0,0 +1,133 @@
+use bevy::prelude::*;
+use bevy_common::screens::{self, AddScreen};
+
+use crate::{constants::*, GameState, Level, Lives, Score};
+
+#[derive(Component)]
+struct LevelClearedScreen;
+
+#[derive(Component)]
+struct GameOverScreen;
+
+// Counts down the level cleared screen
+#[derive(Resource, Debug, Deref, DerefMut)]
+struct LevelClearedTimer(Timer);
+
+impl Default for LevelClearedTimer {
+    fn default() -> Self {
+        Self(Timer::from_seconds(LEVEL_CLEARED_DURATION, TimerMode::Once))
+    }
+}
+
+pub struct ScreensPlugin;
+
+impl Plugin for ScreensPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LevelClearedTimer>()
+            .add_screen::<LevelClearedScreen, _>(
+                GameState::LevelCleared,
+                spawn_level_cleared_screen,
+            )
+            .add_screen::<GameOverScreen, _>(GameState::GameOver, spawn_game_over_screen)
+            .add_systems(
+                OnExit(GameState::LevelCleared),
+                (next_level, crate::spawn_level).chain(),
+            )
+            .add_systems(
+                OnExit(GameState::GameOver),
+                (reset_game, crate::spawn_level).chain(),
+            )
+            .add_systems(
+                Update,
+                (
+                    end_level_cleared_screen.run_if(in_state(GameState::LevelCleared)),
+                    leave_game_over_screen.run_if(in_state(GameState::GameOver)),
+                ),
+            );
+    }
+}
+
+fn style(font_size: f32, color: Color) -> TextStyle {
+    TextStyle {
+        font_size,
+        color,
+        ..default()
+    }
+}
+
+fn spawn_level_cleared_screen(
+    mut commands: Commands,
+    level: Res<Level>,
+    score: Res<Score>,
+    mut timer: ResMut<LevelClearedTimer>,
+) {
+    timer.reset();
+
+    commands
+        .spawn((
+            screens::overlay(SCREEN_BACKDROP_COLOR, SCREEN_Z_INDEX),
+            LevelClearedScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_sections([
+                TextSection::new(
+                    format!("Level {} cleared\n\n", **level + 1),
+                    style(SCREEN_FONT_SIZE, Color::WHITE),
+                ),
+                TextSection::new(
+                    format!("score {}", **score),
+                    style(SCREEN_HINT_FONT_SIZE, Color::WHITE),
+                ),
+            ]));
+        });
+}
+
+fn end_level_cleared_screen(
+    mut timer: ResMut<LevelClearedTimer>,
+    mut next_state: ResMut<NextState<GameState>>,
+    time: Res<Time>,
+) {
+    if timer.tick(time.delta()).just_finished() {
+        next_state.set(GameState::Serving);
+    }
+}
+
+fn next_level(mut level: ResMut<Level>) {
+    **level += 1;
+}
+
+fn spawn_game_over_screen(mut commands: Commands, level: Res<Level>, score: Res<Score>) {
+    commands
+        .spawn((
+            screens::overlay(SCREEN_BACKDROP_COLOR, SCREEN_Z_INDEX),
+            GameOverScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_sections([
+                TextSection::new("Game over\n\n", style(SCREEN_FONT_SIZE, Color::WHITE)),
+                TextSection::new(
+                    format!("score {} on level {}\n\n", **score, **level + 1),
+                    style(SCREEN_HINT_FONT_SIZE, Color::WHITE),
+                ),
+                TextSection::new(
+                    "enter: play again",
+                    style(SCREEN_HINT_FONT_SIZE, Color::GRAY),
+                ),
+            ]));
+        });
+}
+
+fn leave_game_over_screen(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        next_state.set(GameState::Serving);
+    }
+}
+
+fn reset_game(mut score: ResMut<Score>, mut lives: ResMut<Lives>, mut level: ResMut<Level>) {
+    *score = Score::default();
+    *lives = Lives::default();
+    *level = Level::default();
+}
@@ -0,0 +1,162 @@
+use bevy::prelude::*;
+use bevy_common::collision;
+
+use crate::{constants::*, level::Tile};
+
+// What a body ran into while it was being moved
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Contacts {
+    pub ground: bool,
+    pub ceiling: bool,
+    pub wall: bool,
+}
+
+// Moves a box by `motion` through the level's tiles, one axis at a time so it slides along
+// whatever it hits instead of sticking to it. Solid tiles stop it from every side. One-way
+// platforms only catch it falling onto them from above, and not at all while it's
+// `dropping` through them.
+pub fn move_body(
+    position: Vec2,
+    size: Vec2,
+    motion: Vec2,
+    tiles: &[(Vec2, Tile)],
+    dropping: bool,
+) -> (Vec2, Contacts) {
+    let tile_size = Vec2::splat(TILE_SIZE);
+    let mut position = position;
+    let mut contacts = Contacts::default();
+
+    position.x += motion.x;
+    for (center, tile) in tiles {
+        if *tile != Tile::Solid
+            || collision::aabb_overlap(position, size, *center, tile_size).is_none()
+        {
+            continue;
+        }
+        let offset = (size.x + TILE_SIZE) / 2.0;
+        position.x = if motion.x > 0.0 {
+            center.x - offset
+        } else {
+            center.x + offset
+        };
+        contacts.wall = true;
+    }
+
+    let bottom = position.y - size.y / 2.0;
+    position.y += motion.y;
+    for (center, tile) in tiles {
+        if collision::aabb_overlap(position, size, *center, tile_size).is_none() {
+            continue;
+        }
+        let offset = (size.y + TILE_SIZE) / 2.0;
+        let top = center.y + TILE_SIZE / 2.0;
+
+        match tile {
+            Tile::Solid if motion.y > 0.0 => {
+                position.y = center.y - offset;
+                contacts.ceiling = true;
+            }
+            Tile::Solid => {
+                position.y = center.y + offset;
+                contacts.ground = true;
+            }
+            // only if it was above the platform before it moved
+            Tile::OneWay if motion.y <= 0.0 && !dropping && bottom >= top - LANDING_TOLERANCE => {
+                position.y = center.y + offset;
+                contacts.ground = true;
+            }
+            Tile::OneWay => {}
+        }
+    }
+
+    (position, contacts)
+}
+
+// Jumping is forgiving in two ways: a jump pressed a moment before landing still happens when
+// the player lands, and walking off a ledge leaves a moment of "coyote time" where they can
+// still jump as if they were on it.
+#[derive(Component, Debug, Default)]
+pub struct Jump {
+    // how much longer the player counts as on the ground, in seconds
+    coyote: f32,
+    // how much longer a jump that was pressed is waited on, in seconds
+    buffered: f32,
+}
+
+impl Jump {
+    pub fn press(&mut self) {
+        self.buffered = JUMP_BUFFER_TIME;
+    }
+
+    // Whether to jump this tick
+    pub fn update(&mut self, grounded: bool, delta: f32) -> bool {
+        if grounded {
+            self.coyote = COYOTE_TIME;
+        }
+
+        let jump = self.buffered > 0.0 && self.coyote > 0.0;
+        if jump {
+            self.coyote = 0.0;
+            self.buffered = 0.0;
+        }
+
+        self.coyote -= delta;
+        self.buffered -= delta;
+        jump
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BODY: Vec2 = Vec2::new(10.0, 10.0);
+
+    #[test]
+    fn one_way_platforms_only_catch_falling_bodies() {
+        let tiles = [(Vec2::ZERO, Tile::OneWay)];
+        let above = Vec2::new(0.0, (TILE_SIZE + BODY.y) / 2.0 + 1.0);
+
+        let (landed, contacts) = move_body(above, BODY, Vec2::new(0.0, -4.0), &tiles, false);
+        assert!(contacts.ground);
+        assert_eq!(landed.y, above.y - 1.0);
+
+        let (dropped, contacts) = move_body(above, BODY, Vec2::new(0.0, -4.0), &tiles, true);
+        assert!(!contacts.ground);
+        assert_eq!(dropped.y, above.y - 4.0);
+
+        // jumping up through it from underneath
+        let inside = Vec2::new(0.0, 2.0);
+        let (rising, contacts) = move_body(inside, BODY, Vec2::new(0.0, 4.0), &tiles, false);
+        assert_eq!(contacts, Contacts::default());
+        assert_eq!(rising.y, 6.0);
+    }
+
+    #[test]
+    fn solid_tiles_stop_bodies_from_the_side() {
+        let tiles = [(Vec2::ZERO, Tile::Solid)];
+        let left = Vec2::new(-(TILE_SIZE + BODY.x) / 2.0 - 1.0, 0.0);
+
+        let (moved, contacts) = move_body(left, BODY, Vec2::new(4.0, 0.0), &tiles, false);
+        assert!(contacts.wall);
+        assert_eq!(moved.x, left.x + 1.0);
+    }
+
+    #[test]
+    fn coyote_time_allows_late_jumps() {
+        let mut jump = Jump::default();
+        assert!(!jump.update(true, 0.01));
+
+        // walked off the edge, and jumped just after
+        assert!(!jump.update(false, COYOTE_TIME / 2.0));
+        jump.press();
+        assert!(jump.update(false, 0.01));
+
+        // too late
+        let mut jump = Jump::default();
+        jump.update(true, 0.01);
+        jump.update(false, COYOTE_TIME * 2.0);
+        jump.press();
+        assert!(!jump.update(false, 0.01));
+    }
+}
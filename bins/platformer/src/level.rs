@@ -0,0 +1,70 @@
+use bevy::prelude::*;
+
+use crate::constants::*;
+
+// The level, drawn in text: `#` is solid ground, `=` a platform that can be jumped up through
+// and stood on, `P` where the player starts and `G` the goal. Anything else is empty.
+pub const LEVEL: &str = include_str!("../levels/1.txt");
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tile {
+    Solid,
+    OneWay,
+}
+
+#[derive(Debug, Default)]
+pub struct Level {
+    // where each tile's center is, with the level centered on the origin
+    pub tiles: Vec<(Vec2, Tile)>,
+    pub start: Vec2,
+    pub goal: Vec2,
+    pub size: Vec2,
+}
+
+pub fn parse(text: &str) -> Level {
+    let rows: Vec<&str> = text.lines().collect();
+    let columns = rows
+        .iter()
+        .map(|row| row.chars().count())
+        .max()
+        .unwrap_or(0);
+    let size = Vec2::new(columns as f32, rows.len() as f32) * TILE_SIZE;
+
+    let mut level = Level { size, ..default() };
+    for (row, line) in rows.iter().enumerate() {
+        for (column, tile) in line.chars().enumerate() {
+            let center = Vec2::new(
+                (column as f32 + 0.5) * TILE_SIZE - size.x / 2.0,
+                size.y / 2.0 - (row as f32 + 0.5) * TILE_SIZE,
+            );
+            match tile {
+                '#' => level.tiles.push((center, Tile::Solid)),
+                '=' => level.tiles.push((center, Tile::OneWay)),
+                'P' => level.start = center,
+                'G' => level.goal = center,
+                _ => {}
+            }
+        }
+    }
+    level
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levels_are_centered_on_the_origin() {
+        let level = parse("#P\n=G\n");
+        assert_eq!(level.size, Vec2::splat(2.0 * TILE_SIZE));
+        assert_eq!(
+            level.tiles,
+            vec![
+                (Vec2::new(-0.5, 0.5) * TILE_SIZE, Tile::Solid),
+                (Vec2::new(-0.5, -0.5) * TILE_SIZE, Tile::OneWay),
+            ]
+        );
+        assert_eq!(level.start, Vec2::new(0.5, 0.5) * TILE_SIZE);
+        assert_eq!(level.goal, Vec2::new(0.5, -0.5) * TILE_SIZE);
+    }
+}
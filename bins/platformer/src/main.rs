@@ -0,0 +1,330 @@
+// Bevy systems routinely take "complex" queries and lots of parameters;
+// https://github.com/bevyengine/bevy/issues/4601
+#![allow(clippy::type_complexity, clippy::too_many_arguments)]
+
+use bevy::prelude::*;
+use bevy_common::{collision, input, motion::Velocity};
+
+mod controller;
+mod level;
+mod screens;
+
+use controller::Jump;
+use level::{Level, Tile};
+
+mod constants {
+    use bevy::prelude::*;
+    // These constants are defined in `Transform` units.
+    // Using the default 2D camera they correspond 1:1 with screen pixels.
+    pub const TILE_SIZE: f32 = 32.0;
+    pub const VIEW_WIDTH: f32 = 1280.0;
+
+    pub const PLAYER_SIZE: Vec2 = Vec2::new(24.0, 30.0);
+    pub const RUN_SPEED: f32 = 300.0;
+    // how quickly the player gets up to speed, or stops, on the ground and in the air
+    pub const GROUND_ACCELERATION: f32 = 3000.0;
+    pub const AIR_ACCELERATION: f32 = 1500.0;
+    pub const GRAVITY: f32 = 2000.0;
+    pub const MAX_FALL_SPEED: f32 = 900.0;
+    pub const JUMP_SPEED: f32 = 750.0;
+    // letting go of jump on the way up slows it to this, for shorter hops
+    pub const JUMP_RELEASE_SPEED: f32 = 400.0;
+    // in seconds, see `controller::Jump`
+    pub const COYOTE_TIME: f32 = 0.1;
+    pub const JUMP_BUFFER_TIME: f32 = 0.1;
+    // how far into a one-way platform the player can already be and still land on it, for
+    // rounding
+    pub const LANDING_TOLERANCE: f32 = 1.0;
+    // how far below the level the player falls before they're put back at the start
+    pub const FALL_LIMIT: f32 = 200.0;
+
+    pub const BACKGROUND_COLOR: Color = Color::rgb(0.55, 0.75, 0.9);
+    pub const SOLID_COLOR: Color = Color::rgb(0.35, 0.25, 0.2);
+    pub const ONE_WAY_COLOR: Color = Color::rgb(0.6, 0.45, 0.3);
+    // one-way platforms are drawn thinner than the tile, they can be stood on all the same
+    pub const ONE_WAY_THICKNESS: f32 = 8.0;
+    pub const PLAYER_COLOR: Color = Color::rgb(0.9, 0.3, 0.3);
+    pub const GOAL_COLOR: Color = Color::rgb(1.0, 0.85, 0.2);
+    pub const HUD_FONT_SIZE: f32 = 28.0;
+    pub const HUD_COLOR: Color = Color::WHITE;
+    pub const HUD_PADDING: f32 = 20.0;
+
+    pub const SCREEN_FONT_SIZE: f32 = 48.0;
+    pub const SCREEN_HINT_FONT_SIZE: f32 = 24.0;
+    pub const SCREEN_BACKDROP_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.6);
+    pub const SCREEN_Z_INDEX: i32 = 100;
+}
+
+mod entities {
+    use bevy::prelude::*;
+
+    #[derive(Component, Debug, Default)]
+    pub struct Player {
+        // whether they were standing on something after the last tick
+        pub grounded: bool,
+    }
+
+    #[derive(Component, Debug)]
+    pub struct Goal;
+
+    #[derive(Component, Debug)]
+    pub struct HudText;
+}
+
+use constants::*;
+
+#[derive(States, Debug, Clone, Copy, Default, Hash, PartialEq, Eq)]
+pub enum GameState {
+    #[default]
+    Playing,
+    // made it to the goal, see `screens.rs`
+    Won,
+}
+
+// The level being played, kept around for the tiles to collide with
+#[derive(Resource, Debug, Deref)]
+pub struct CurrentLevel(Level);
+
+// How long this run's taken, and how many times the player's fallen off the level
+#[derive(Resource, Debug, Default)]
+pub struct Run {
+    pub seconds: f32,
+    pub falls: usize,
+}
+
+fn setup(mut commands: Commands) {
+    let level = level::parse(level::LEVEL);
+
+    commands.spawn(Camera2dBundle::default());
+
+    for (center, tile) in &level.tiles {
+        let (color, size, center) = match tile {
+            Tile::Solid => (SOLID_COLOR, Vec2::splat(TILE_SIZE), *center),
+            // along the top of its tile, where it's stood on
+            Tile::OneWay => (
+                ONE_WAY_COLOR,
+                Vec2::new(TILE_SIZE, ONE_WAY_THICKNESS),
+                *center + Vec2::Y * (TILE_SIZE - ONE_WAY_THICKNESS) / 2.0,
+            ),
+        };
+        commands.spawn((
+            SpriteBundle {
+                transform: Transform::from_translation(center.extend(0.0))
+                    .with_scale(size.extend(1.0)),
+                sprite: Sprite { color, ..default() },
+                ..default()
+            },
+            *tile,
+        ));
+    }
+
+    commands.spawn((
+        SpriteBundle {
+            transform: Transform::from_translation(level.goal.extend(0.0))
+                .with_scale(Vec3::splat(TILE_SIZE)),
+            sprite: Sprite {
+                color: GOAL_COLOR,
+                ..default()
+            },
+            ..default()
+        },
+        entities::Goal,
+    ));
+
+    commands.spawn((
+        SpriteBundle {
+            transform: Transform::from_translation(level.start.extend(1.0))
+                .with_scale(PLAYER_SIZE.extend(1.0)),
+            sprite: Sprite {
+                color: PLAYER_COLOR,
+                ..default()
+            },
+            ..default()
+        },
+        entities::Player::default(),
+        Jump::default(),
+        Velocity::default(),
+    ));
+
+    // the HUD rides along with the camera
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font_size: HUD_FONT_SIZE,
+                    color: HUD_COLOR,
+                    ..default()
+                },
+            ),
+            transform: Transform::from_translation(Vec3::new(
+                0.0,
+                level.size.y / 2.0 + HUD_PADDING,
+                2.0,
+            )),
+            ..default()
+        },
+        entities::HudText,
+    ));
+
+    commands.insert_resource(CurrentLevel(level));
+}
+
+// Jumps are pressed here and happen on the next tick, so a press between ticks isn't missed
+fn buffer_jump(keyboard_input: Res<Input<KeyCode>>, mut jumps: Query<&mut Jump>) {
+    if !keyboard_input.any_just_pressed([KeyCode::Space].into_iter().chain(input::UP)) {
+        return;
+    }
+    for mut jump in &mut jumps {
+        jump.press();
+    }
+}
+
+fn move_player(
+    keyboard_input: Res<Input<KeyCode>>,
+    level: Res<CurrentLevel>,
+    mut players: Query<(
+        &mut Transform,
+        &mut Velocity,
+        &mut Jump,
+        &mut entities::Player,
+    )>,
+    time: Res<Time>,
+) {
+    let delta = time.delta_seconds();
+
+    for (mut transform, mut velocity, mut jump, mut player) in &mut players {
+        // towards running speed the way they're holding, or towards stopping
+        let acceleration = if player.grounded {
+            GROUND_ACCELERATION
+        } else {
+            AIR_ACCELERATION
+        };
+        let target = input::horizontal(&keyboard_input) * RUN_SPEED;
+        velocity.x += (target - velocity.x).clamp(-acceleration * delta, acceleration * delta);
+
+        velocity.y = (velocity.y - GRAVITY * delta).max(-MAX_FALL_SPEED);
+        if jump.update(player.grounded, delta) {
+            velocity.y = JUMP_SPEED;
+        }
+        let held = keyboard_input.any_pressed([KeyCode::Space].into_iter().chain(input::UP));
+        if !held {
+            velocity.y = velocity.y.min(JUMP_RELEASE_SPEED);
+        }
+
+        // holding down drops through one-way platforms
+        let dropping = input::vertical(&keyboard_input) < 0.0;
+        let (position, contacts) = controller::move_body(
+            transform.translation.truncate(),
+            PLAYER_SIZE,
+            velocity.0 * delta,
+            &level.tiles,
+            dropping,
+        );
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
+
+        if contacts.wall {
+            velocity.x = 0.0;
+        }
+        if contacts.ground || contacts.ceiling {
+            velocity.y = 0.0;
+        }
+        player.grounded = contacts.ground;
+    }
+}
+
+fn fall_off(
+    level: Res<CurrentLevel>,
+    mut players: Query<(&mut Transform, &mut Velocity), With<entities::Player>>,
+    mut run: ResMut<Run>,
+) {
+    for (mut transform, mut velocity) in &mut players {
+        if transform.translation.y > -level.size.y / 2.0 - FALL_LIMIT {
+            continue;
+        }
+        transform.translation = level.start.extend(1.0);
+        velocity.0 = Vec2::ZERO;
+        run.falls += 1;
+    }
+}
+
+fn reach_goal(
+    players: Query<&Transform, With<entities::Player>>,
+    goals: Query<&Transform, With<entities::Goal>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for player in &players {
+        for goal in &goals {
+            if collision::aabb_overlap(
+                player.translation.truncate(),
+                PLAYER_SIZE,
+                goal.translation.truncate(),
+                goal.scale.truncate(),
+            )
+            .is_some()
+            {
+                next_state.set(GameState::Won);
+            }
+        }
+    }
+}
+
+fn count_time(mut run: ResMut<Run>, time: Res<Time>) {
+    run.seconds += time.delta_seconds();
+}
+
+// Scrolls sideways with the player, without showing past either end of the level
+fn follow_player(
+    level: Res<CurrentLevel>,
+    players: Query<&Transform, With<entities::Player>>,
+    mut cameras: Query<&mut Transform, (With<Camera>, Without<entities::Player>)>,
+    mut hud: Query<
+        &mut Transform,
+        (
+            With<entities::HudText>,
+            Without<Camera>,
+            Without<entities::Player>,
+        ),
+    >,
+) {
+    let Ok(player) = players.get_single() else {
+        return;
+    };
+    let limit = ((level.size.x - VIEW_WIDTH) / 2.0).max(0.0);
+    let x = player.translation.x.clamp(-limit, limit);
+
+    for mut camera in &mut cameras {
+        camera.translation.x = x;
+    }
+    for mut text in &mut hud {
+        text.translation.x = x;
+    }
+}
+
+fn update_hud(run: Res<Run>, mut hud: Query<&mut Text, With<entities::HudText>>) {
+    for mut text in &mut hud {
+        text.sections[0].value = format!("Time {:.1}    Falls {}", run.seconds, run.falls);
+    }
+}
+
+fn main() {
+    App::new()
+        .add_plugins((DefaultPlugins, screens::ScreensPlugin))
+        .add_state::<GameState>()
+        .insert_resource(ClearColor(BACKGROUND_COLOR))
+        .init_resource::<Run>()
+        .add_systems(Startup, setup)
+        .add_systems(
+            FixedUpdate,
+            (move_player, fall_off, reach_goal, count_time)
+                .chain()
+                .run_if(in_state(GameState::Playing)),
+        )
+        .add_systems(
+            Update,
+            (buffer_jump, update_hud).run_if(in_state(GameState::Playing)),
+        )
+        .add_systems(Update, (follow_player, bevy::window::close_on_esc))
+        .run();
+}
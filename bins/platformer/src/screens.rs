@@ -0,0 +1,71 @@
+use bevy::prelude::*;
+use bevy_common::{
+    motion::Velocity,
+    screens::{self, AddScreen},
+};
+
+use crate::{constants::*, controller::Jump, entities, CurrentLevel, GameState, Run};
+
+#[derive(Component)]
+struct WonScreen;
+
+pub struct ScreensPlugin;
+
+impl Plugin for ScreensPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_screen::<WonScreen, _>(GameState::Won, spawn_won_screen)
+            .add_systems(OnExit(GameState::Won), restart)
+            .add_systems(Update, leave_won_screen.run_if(in_state(GameState::Won)));
+    }
+}
+
+fn style(font_size: f32, color: Color) -> TextStyle {
+    TextStyle {
+        font_size,
+        color,
+        ..default()
+    }
+}
+
+fn spawn_won_screen(mut commands: Commands, run: Res<Run>) {
+    commands
+        .spawn((
+            screens::overlay(SCREEN_BACKDROP_COLOR, SCREEN_Z_INDEX),
+            WonScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_sections([
+                TextSection::new("Level complete\n\n", style(SCREEN_FONT_SIZE, Color::WHITE)),
+                TextSection::new(
+                    format!("{:.1}s with {} falls\n\n", run.seconds, run.falls),
+                    style(SCREEN_HINT_FONT_SIZE, Color::WHITE),
+                ),
+                TextSection::new(
+                    "enter: play again",
+                    style(SCREEN_HINT_FONT_SIZE, Color::GRAY),
+                ),
+            ]));
+        });
+}
+
+fn leave_won_screen(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        next_state.set(GameState::Playing);
+    }
+}
+
+fn restart(
+    level: Res<CurrentLevel>,
+    mut players: Query<(&mut Transform, &mut Velocity, &mut Jump), With<entities::Player>>,
+    mut run: ResMut<Run>,
+) {
+    for (mut transform, mut velocity, mut jump) in &mut players {
+        transform.translation = level.start.extend(1.0);
+        velocity.0 = Vec2::ZERO;
+        *jump = Jump::default();
+    }
+    *run = Run::default();
+}
@@ -0,0 +1,421 @@
+// Bevy systems routinely take "complex" queries and lots of parameters;
+// https://github.com/bevyengine/bevy/issues/4601
+#![allow(clippy::type_complexity, clippy::too_many_arguments)]
+
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use bevy_common::input;
+use rand::seq::IteratorRandom;
+
+mod screens;
+
+mod constants {
+    use bevy::prelude::*;
+
+    // The board, in cells
+    pub const GRID_WIDTH: i32 = 24;
+    pub const GRID_HEIGHT: i32 = 18;
+    // in pixels, with the default 2D camera
+    pub const CELL_SIZE: f32 = 30.0;
+    // how much of a cell a segment fills, so they read as separate pieces
+    pub const SEGMENT_FILL: f32 = 0.9;
+    // the snake moves one cell every step, in seconds
+    pub const STEP_SECONDS: f64 = 0.12;
+    pub const STARTING_LENGTH: usize = 3;
+
+    pub const BACKGROUND_COLOR: Color = Color::rgb(0.1, 0.1, 0.1);
+    pub const BOARD_COLOR: Color = Color::rgb(0.15, 0.15, 0.15);
+    pub const HEAD_COLOR: Color = Color::rgb(0.4, 0.9, 0.4);
+    pub const SEGMENT_COLOR: Color = Color::rgb(0.2, 0.6, 0.2);
+    pub const FOOD_COLOR: Color = Color::rgb(0.9, 0.3, 0.3);
+    pub const HUD_FONT_SIZE: f32 = 28.0;
+    pub const HUD_COLOR: Color = Color::WHITE;
+    pub const HUD_PADDING: f32 = 24.0;
+
+    pub const SCREEN_FONT_SIZE: f32 = 48.0;
+    pub const SCREEN_HINT_FONT_SIZE: f32 = 24.0;
+    pub const SCREEN_BACKDROP_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.8);
+    pub const SCREEN_Z_INDEX: i32 = 100;
+
+    // kept between runs with `bevy_common::storage`
+    pub const GAME_NAME: &str = "snake";
+    pub const HIGH_SCORE_FILE: &str = "high_score";
+}
+
+mod entities {
+    use bevy::prelude::*;
+
+    // Where something is on the board. Its transform follows, see `place_cells`.
+    #[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Cell(pub IVec2);
+
+    // Any piece of the snake, the head included
+    #[derive(Component, Debug)]
+    pub struct Segment;
+
+    #[derive(Component, Debug)]
+    pub struct Food;
+
+    #[derive(Component, Debug)]
+    pub struct HudText;
+}
+
+use constants::*;
+
+#[derive(States, Debug, Clone, Copy, Default, Hash, PartialEq, Eq)]
+pub enum GameState {
+    #[default]
+    Playing,
+    // the snake ran into something, see `screens.rs`
+    GameOver,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    pub fn offset(&self) -> IVec2 {
+        match self {
+            Direction::Up => IVec2::Y,
+            Direction::Down => IVec2::NEG_Y,
+            Direction::Left => IVec2::NEG_X,
+            Direction::Right => IVec2::X,
+        }
+    }
+
+    pub fn opposite(&self) -> Self {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+        }
+    }
+}
+
+// Which way the snake is going, and the turns asked for since its last step. Turns are
+// queued so two quick key presses between steps both happen, rather than the second
+// replacing the first.
+#[derive(Resource, Debug)]
+pub struct Heading {
+    current: Direction,
+    queued: VecDeque<Direction>,
+}
+
+impl Default for Heading {
+    fn default() -> Self {
+        Self {
+            current: Direction::Right,
+            queued: VecDeque::new(),
+        }
+    }
+}
+
+impl Heading {
+    // turning straight back into itself, or not turning at all, is ignored
+    pub fn turn(&mut self, direction: Direction) {
+        let last = self.queued.back().copied().unwrap_or(self.current);
+        if direction != last && direction != last.opposite() && self.queued.len() < 2 {
+            self.queued.push_back(direction);
+        }
+    }
+
+    // the direction for the next step
+    pub fn step(&mut self) -> Direction {
+        if let Some(direction) = self.queued.pop_front() {
+            self.current = direction;
+        }
+        self.current
+    }
+}
+
+// The snake's segments from head to tail
+#[derive(Resource, Debug, Default)]
+pub struct Snake(VecDeque<Entity>);
+
+#[derive(Resource, Debug, Default, Deref, DerefMut)]
+pub struct Score(usize);
+
+// The best score on this machine, loaded at startup and saved whenever it's beaten
+#[derive(Resource, Debug, Default, Deref, DerefMut)]
+pub struct HighScore(usize);
+
+impl HighScore {
+    fn load() -> Self {
+        let score = bevy_common::storage::load(GAME_NAME, HIGH_SCORE_FILE)
+            .and_then(|score| score.trim().parse().ok())
+            .unwrap_or_default();
+        Self(score)
+    }
+
+    fn save(&self) {
+        if let Err(err) =
+            bevy_common::storage::save(GAME_NAME, HIGH_SCORE_FILE, &self.0.to_string())
+        {
+            println!("Couldn't save the high score: {err}");
+        }
+    }
+}
+
+pub fn on_board(cell: IVec2) -> bool {
+    (0..GRID_WIDTH).contains(&cell.x) && (0..GRID_HEIGHT).contains(&cell.y)
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn(Camera2dBundle::default());
+
+    commands.spawn(SpriteBundle {
+        transform: Transform {
+            scale: Vec3::new(
+                GRID_WIDTH as f32 * CELL_SIZE,
+                GRID_HEIGHT as f32 * CELL_SIZE,
+                1.0,
+            ),
+            ..default()
+        },
+        sprite: Sprite {
+            color: BOARD_COLOR,
+            ..default()
+        },
+        ..default()
+    });
+
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font_size: HUD_FONT_SIZE,
+                    color: HUD_COLOR,
+                    ..default()
+                },
+            ),
+            // above the board
+            transform: Transform::from_translation(Vec3::new(
+                0.0,
+                GRID_HEIGHT as f32 * CELL_SIZE / 2.0 + HUD_PADDING,
+                1.0,
+            )),
+            ..default()
+        },
+        entities::HudText,
+    ));
+}
+
+fn spawn_segment(commands: &mut Commands, cell: IVec2, color: Color) -> Entity {
+    commands
+        .spawn((
+            SpriteBundle {
+                sprite: Sprite { color, ..default() },
+                transform: Transform::from_scale(Vec3::splat(CELL_SIZE * SEGMENT_FILL)),
+                ..default()
+            },
+            entities::Segment,
+            entities::Cell(cell),
+        ))
+        .id()
+}
+
+// A fresh snake in the middle of the board heading right, and its first food
+fn start_game(
+    mut commands: Commands,
+    mut snake: ResMut<Snake>,
+    mut heading: ResMut<Heading>,
+    mut score: ResMut<Score>,
+    leftovers: Query<Entity, Or<(With<entities::Segment>, With<entities::Food>)>>,
+) {
+    for entity in &leftovers {
+        commands.entity(entity).despawn();
+    }
+
+    let head = IVec2::new(GRID_WIDTH / 2, GRID_HEIGHT / 2);
+    snake.0 = (0..STARTING_LENGTH)
+        .map(|i| {
+            let color = if i == 0 { HEAD_COLOR } else { SEGMENT_COLOR };
+            spawn_segment(&mut commands, head - IVec2::new(i as i32, 0), color)
+        })
+        .collect();
+    *heading = Heading::default();
+    **score = 0;
+
+    let occupied: Vec<_> = (0..STARTING_LENGTH)
+        .map(|i| head - IVec2::new(i as i32, 0))
+        .collect();
+    spawn_food(&mut commands, &occupied);
+}
+
+// Somewhere the snake isn't
+fn spawn_food(commands: &mut Commands, occupied: &[IVec2]) {
+    let Some(cell) = (0..GRID_WIDTH)
+        .flat_map(|x| (0..GRID_HEIGHT).map(move |y| IVec2::new(x, y)))
+        .filter(|cell| !occupied.contains(cell))
+        .choose(&mut rand::thread_rng())
+    else {
+        // the snake fills the whole board, there's nowhere left
+        return;
+    };
+
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: FOOD_COLOR,
+                ..default()
+            },
+            transform: Transform::from_scale(Vec3::splat(CELL_SIZE * SEGMENT_FILL)),
+            ..default()
+        },
+        entities::Food,
+        entities::Cell(cell),
+    ));
+}
+
+fn steer(keyboard_input: Res<Input<KeyCode>>, mut heading: ResMut<Heading>) {
+    for (keys, direction) in [
+        (input::UP, Direction::Up),
+        (input::DOWN, Direction::Down),
+        (input::LEFT, Direction::Left),
+        (input::RIGHT, Direction::Right),
+    ] {
+        if keyboard_input.any_just_pressed(keys) {
+            heading.turn(direction);
+        }
+    }
+}
+
+// Moves the snake one cell. Every segment moves up into the cell in front of it, so only
+// the head goes anywhere new. Eating leaves a new segment behind where the tail was.
+fn step(
+    mut commands: Commands,
+    mut snake: ResMut<Snake>,
+    mut heading: ResMut<Heading>,
+    mut score: ResMut<Score>,
+    mut cells: Query<&mut entities::Cell, Without<entities::Food>>,
+    food: Query<(Entity, &entities::Cell), With<entities::Food>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let positions: Vec<IVec2> = snake
+        .0
+        .iter()
+        .filter_map(|segment| cells.get(*segment).ok())
+        .map(|cell| cell.0)
+        .collect();
+    let Some(&head) = positions.first() else {
+        return;
+    };
+
+    let next = head + heading.step().offset();
+    let eaten = food.iter().find(|(_, cell)| cell.0 == next);
+    // the tail moves out of the way this step, unless the snake is growing
+    let body = if eaten.is_some() {
+        &positions[..]
+    } else {
+        &positions[..positions.len() - 1]
+    };
+    if !on_board(next) || body.contains(&next) {
+        next_state.set(GameState::GameOver);
+        return;
+    }
+
+    if let Some((food, _)) = eaten {
+        commands.entity(food).despawn();
+        **score += 1;
+
+        let tail = *positions.last().unwrap();
+        let segment = spawn_segment(&mut commands, tail, SEGMENT_COLOR);
+        snake.0.push_back(segment);
+
+        let mut occupied = positions.clone();
+        occupied.push(next);
+        spawn_food(&mut commands, &occupied);
+    }
+
+    // the head stays the same entity, it's only ever the body that grows
+    for (segment, position) in snake.0.iter().zip(std::iter::once(next).chain(positions)) {
+        if let Ok(mut cell) = cells.get_mut(*segment) {
+            cell.0 = position;
+        }
+    }
+}
+
+fn place_cells(mut cells: Query<(&entities::Cell, &mut Transform), Changed<entities::Cell>>) {
+    for (cell, mut transform) in &mut cells {
+        // cell (0, 0) is the bottom left corner of the board
+        let origin = -Vec2::new(GRID_WIDTH as f32, GRID_HEIGHT as f32) * CELL_SIZE / 2.0;
+        let position = origin + (cell.0.as_vec2() + 0.5) * CELL_SIZE;
+        transform.translation = position.extend(1.0);
+    }
+}
+
+fn update_hud(
+    score: Res<Score>,
+    high_score: Res<HighScore>,
+    mut hud: Query<&mut Text, With<entities::HudText>>,
+) {
+    if !score.is_changed() && !high_score.is_changed() {
+        return;
+    }
+
+    for mut text in &mut hud {
+        text.sections[0].value = format!("Score {}    Best {}", **score, **high_score);
+    }
+}
+
+fn record_high_score(score: Res<Score>, mut high_score: ResMut<HighScore>) {
+    if **score > **high_score {
+        **high_score = **score;
+        high_score.save();
+    }
+}
+
+fn main() {
+    App::new()
+        .add_plugins((DefaultPlugins, screens::ScreensPlugin))
+        .add_state::<GameState>()
+        .insert_resource(ClearColor(BACKGROUND_COLOR))
+        .insert_resource(Time::<Fixed>::from_seconds(STEP_SECONDS))
+        .insert_resource(HighScore::load())
+        .init_resource::<Heading>()
+        .init_resource::<Snake>()
+        .init_resource::<Score>()
+        .add_systems(Startup, setup)
+        .add_systems(OnEnter(GameState::Playing), start_game)
+        .add_systems(OnEnter(GameState::GameOver), record_high_score)
+        .add_systems(Update, steer.run_if(in_state(GameState::Playing)))
+        .add_systems(FixedUpdate, step.run_if(in_state(GameState::Playing)))
+        .add_systems(
+            Update,
+            (place_cells, update_hud, bevy::window::close_on_esc),
+        )
+        .run();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_snake_cant_turn_back_on_itself() {
+        let mut heading = Heading::default();
+        heading.turn(Direction::Left);
+        assert_eq!(heading.step(), Direction::Right);
+
+        heading.turn(Direction::Up);
+        heading.turn(Direction::Left);
+        assert_eq!(heading.step(), Direction::Up);
+        assert_eq!(heading.step(), Direction::Left);
+    }
+
+    #[test]
+    fn the_board_is_bounded() {
+        assert!(on_board(IVec2::ZERO));
+        assert!(on_board(IVec2::new(GRID_WIDTH - 1, GRID_HEIGHT - 1)));
+        assert!(!on_board(IVec2::new(-1, 0)));
+        assert!(!on_board(IVec2::new(0, GRID_HEIGHT)));
+    }
+}
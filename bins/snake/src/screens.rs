@@ -0,0 +1,67 @@
+use bevy::prelude::*;
+use bevy_common::screens::{self, AddScreen};
+
+use crate::{constants::*, GameState, HighScore, Score};
+
+#[derive(Component)]
+struct GameOverScreen;
+
+pub struct ScreensPlugin;
+
+impl Plugin for ScreensPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_screen::<GameOverScreen, _>(
+            GameState::GameOver,
+            spawn_game_over_screen.after(crate::record_high_score),
+        )
+        .add_systems(
+            Update,
+            leave_game_over_screen.run_if(in_state(GameState::GameOver)),
+        );
+    }
+}
+
+fn style(font_size: f32, color: Color) -> TextStyle {
+    TextStyle {
+        font_size,
+        color,
+        ..default()
+    }
+}
+
+// After the high score's been recorded, so a new best shows here
+fn spawn_game_over_screen(mut commands: Commands, score: Res<Score>, high_score: Res<HighScore>) {
+    let best = if **score > 0 && **score == **high_score {
+        "a new best!".to_string()
+    } else {
+        format!("best {}", **high_score)
+    };
+
+    commands
+        .spawn((
+            screens::overlay(SCREEN_BACKDROP_COLOR, SCREEN_Z_INDEX),
+            GameOverScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_sections([
+                TextSection::new("Game over\n\n", style(SCREEN_FONT_SIZE, Color::WHITE)),
+                TextSection::new(
+                    format!("score {}, {best}\n\n", **score),
+                    style(SCREEN_HINT_FONT_SIZE, Color::WHITE),
+                ),
+                TextSection::new(
+                    "enter: play again",
+                    style(SCREEN_HINT_FONT_SIZE, Color::GRAY),
+                ),
+            ]));
+        });
+}
+
+fn leave_game_over_screen(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        next_state.set(GameState::Playing);
+    }
+}
@@ -0,0 +1,46 @@
+// Retro CRT filter: barrel curvature, chromatic aberration, and scanlines.
+// Applied to the whole rendered frame by the `CrtPlugin` post-processing pass.
+
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+
+struct CrtSettings {
+    scanline_intensity: f32,
+    scanline_count: f32,
+    curvature: f32,
+    chromatic_aberration: f32,
+}
+@group(0) @binding(2) var<uniform> settings: CrtSettings;
+
+// bulge the image outwards like the glass of an old tube
+fn curve(uv: vec2<f32>) -> vec2<f32> {
+    let centered = uv * 2.0 - 1.0;
+    let offset = centered.yx * centered.yx * settings.curvature;
+    return (centered + centered * offset) * 0.5 + 0.5;
+}
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let uv = curve(in.uv);
+
+    // everything curved off the edge of the tube is black
+    if uv.x < 0.0 || uv.x > 1.0 || uv.y < 0.0 || uv.y > 1.0 {
+        return vec4<f32>(0.0, 0.0, 0.0, 1.0);
+    }
+
+    // split the channels further apart the closer we are to the edges. Sampled explicitly
+    // at mip 0 since implicit-derivative sampling isn't allowed after the early return.
+    let aberration = (uv - 0.5) * settings.chromatic_aberration;
+    let color = vec3<f32>(
+        textureSampleLevel(screen_texture, texture_sampler, uv + aberration, 0.0).r,
+        textureSampleLevel(screen_texture, texture_sampler, uv, 0.0).g,
+        textureSampleLevel(screen_texture, texture_sampler, uv - aberration, 0.0).b,
+    );
+
+    let scanline = sin(uv.y * settings.scanline_count * 3.14159265) * 0.5 + 0.5;
+    let darken = 1.0 - settings.scanline_intensity * (1.0 - scanline);
+
+    return vec4<f32>(color * darken, 1.0);
+}
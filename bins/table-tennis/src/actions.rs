@@ -0,0 +1,126 @@
+// What the player's asking for, whatever they're pressing to ask for it. Gameplay reads
+// `ActionState` instead of the keyboard, so anything else that plays for them (a gamepad, see
+// `stick.rs`, or the kiosk's stand-in) only has to fill it in.
+//
+// Menus still read their keys directly, they're not part of the match.
+use bevy::{input::InputSystem, prelude::*};
+use bevy_common::input;
+
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq)]
+pub struct ActionState {
+    // which way the paddle's being pushed, up and right from -1 to 1
+    pub move_axis: Vec2,
+    // these two are only set on the frame they're pressed
+    pub smash: bool,
+    pub pause: bool,
+}
+
+// The other paddle's actions, when somebody here is playing it, see `devices.rs`
+#[derive(Resource, Debug, Default)]
+pub struct OpponentActions(pub ActionState);
+
+// Which keys do what, for the whole keyboard or one player's half of it
+#[derive(Debug)]
+pub struct KeyLayout {
+    pub up: &'static [KeyCode],
+    pub down: &'static [KeyCode],
+    pub left: &'static [KeyCode],
+    pub right: &'static [KeyCode],
+    pub smash: &'static [KeyCode],
+    pub pause: &'static [KeyCode],
+}
+
+pub const WHOLE_KEYBOARD: KeyLayout = KeyLayout {
+    up: &input::UP,
+    down: &input::DOWN,
+    left: &input::LEFT,
+    right: &input::RIGHT,
+    smash: &[KeyCode::Space],
+    pause: &[KeyCode::Tab],
+};
+
+impl KeyLayout {
+    // the keys it doesn't share, which is all of them but pause
+    pub fn keys(&self) -> impl Iterator<Item = &KeyCode> {
+        [self.up, self.down, self.left, self.right, self.smash]
+            .into_iter()
+            .flatten()
+    }
+}
+
+// 1 for `positive`, -1 for `negative` and 0 for neither, `positive` winning if it's both
+fn axis(keyboard_input: &Input<KeyCode>, positive: &[KeyCode], negative: &[KeyCode]) -> f32 {
+    if keyboard_input.any_pressed(positive.iter().copied()) {
+        1.0
+    } else if keyboard_input.any_pressed(negative.iter().copied()) {
+        -1.0
+    } else {
+        0.0
+    }
+}
+
+impl ActionState {
+    pub fn from_keys(keyboard_input: &Input<KeyCode>, layout: &KeyLayout) -> Self {
+        Self {
+            move_axis: Vec2::new(
+                axis(keyboard_input, layout.right, layout.left),
+                axis(keyboard_input, layout.up, layout.down),
+            ),
+            smash: keyboard_input.any_just_pressed(layout.smash.iter().copied()),
+            pause: keyboard_input.any_just_pressed(layout.pause.iter().copied()),
+        }
+    }
+}
+
+// Where the actions are filled in each frame, before anything reads them, in order
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ActionSet {
+    // whatever the player's holding, the keyboard first
+    Devices,
+    // turned from the screen's directions to the court's, see `orientation.rs`
+    Orient,
+    // anything that plays instead of them takes over from the devices
+    StandIns,
+}
+
+pub struct ActionsPlugin;
+
+impl Plugin for ActionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ActionState>()
+            .init_resource::<OpponentActions>()
+            .configure_sets(
+                PreUpdate,
+                (ActionSet::Devices, ActionSet::Orient, ActionSet::StandIns)
+                    .chain()
+                    .after(InputSystem),
+            )
+            .add_systems(PreUpdate, read_keyboard.in_set(ActionSet::Devices));
+    }
+}
+
+pub fn read_keyboard(keyboard_input: Res<Input<KeyCode>>, mut actions: ResMut<ActionState>) {
+    *actions = ActionState::from_keys(&keyboard_input, &WHOLE_KEYBOARD);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_become_actions() {
+        let mut keyboard_input = Input::<KeyCode>::default();
+        keyboard_input.press(KeyCode::W);
+        keyboard_input.press(KeyCode::Left);
+        keyboard_input.press(KeyCode::Space);
+
+        assert_eq!(
+            ActionState::from_keys(&keyboard_input, &WHOLE_KEYBOARD),
+            ActionState {
+                move_axis: Vec2::new(-1.0, 1.0),
+                smash: true,
+                pause: false,
+            }
+        );
+    }
+}
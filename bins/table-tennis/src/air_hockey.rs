@@ -0,0 +1,334 @@
+use bevy::{
+    prelude::*,
+    sprite::collide_aabb::{collide, Collision},
+};
+use bevy_common::motion;
+
+use crate::{
+    constants::*,
+    entities,
+    mutators::MatchState,
+    rules::{variant_is, MatchRules, Variant},
+    settings::Settings,
+    CollisionEvent, Contact, TickSet,
+};
+
+// Horizontal speed of a paddle, which only moves sideways in air hockey and volleyball. Like
+// `PaddleVelocity`, it's integrated by hand so the paddle can be kept in its own half.
+#[derive(Component, Debug, Default, Deref, DerefMut)]
+pub struct PaddleStrafe(pub f32);
+
+// A run condition for the variants where paddles move sideways and strike the ball, rather
+// than it bouncing off them like a wall
+pub fn paddles_strafe(rules: Res<MatchRules>) -> bool {
+    matches!(rules.variant, Variant::AirHockey | Variant::Volleyball)
+}
+
+pub struct AirHockeyPlugin;
+
+impl Plugin for AirHockeyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(MatchState::Playing),
+            (
+                (spawn_goal_posts, narrow_goals).run_if(variant_is(Variant::AirHockey)),
+                take_over_paddles.run_if(paddles_strafe),
+            ),
+        )
+        .add_systems(OnExit(MatchState::Playing), restore_court)
+        .add_systems(
+            FixedUpdate,
+            (
+                strafe_player_paddle
+                    .run_if(crate::net::keyboard_is_local)
+                    .run_if(paddles_strafe),
+                strafe_ai_paddle
+                    .run_if(crate::net::computer_is_ai)
+                    .run_if(variant_is(Variant::AirHockey)),
+            )
+                .in_set(TickSet::Input),
+        )
+        .add_systems(
+            FixedUpdate,
+            (
+                strike_pucks
+                    .after(crate::check_ball_bounce_collisions)
+                    .run_if(paddles_strafe),
+                slow_pucks
+                    .after(motion::apply_velocity)
+                    .run_if(variant_is(Variant::AirHockey)),
+            )
+                .in_set(TickSet::Physics),
+        );
+    }
+}
+
+// The end walls either side of each goal. They're obstacles so they get themed and cleared
+// away with the mutator's.
+fn spawn_goal_posts(mut commands: Commands, settings: Res<Settings>) {
+    let height = TOP_WALL - AIR_HOCKEY_GOAL_HEIGHT / 2.0;
+    let y = (TOP_WALL + AIR_HOCKEY_GOAL_HEIGHT / 2.0) / 2.0;
+
+    for side in entities::Side::ALL {
+        for sign in [-1.0, 1.0] {
+            commands.spawn((
+                SpriteBundle {
+                    transform: Transform {
+                        translation: Vec3::new(side.wall_x(), sign * y, 0.0),
+                        scale: Vec3::new(WALL_THICKNESS, height, 0.0),
+                        ..default()
+                    },
+                    sprite: Sprite {
+                        color: settings.theme.palette().wall,
+                        ..default()
+                    },
+                    ..default()
+                },
+                entities::Obstacle,
+                entities::Collider,
+            ));
+        }
+    }
+}
+
+fn narrow_goals(mut goals: Query<&mut Transform, With<entities::GoalZone>>) {
+    for mut transform in &mut goals {
+        transform.scale.y = AIR_HOCKEY_GOAL_HEIGHT;
+    }
+}
+
+// The puck would just bounce off a paddle as if it were a wall, so `strike_pucks` handles
+// paddles instead of the usual collisions
+fn take_over_paddles(
+    mut commands: Commands,
+    paddles: Query<Entity, (With<entities::Paddle>, With<entities::Collider>)>,
+) {
+    for paddle in &paddles {
+        commands.entity(paddle).remove::<entities::Collider>();
+    }
+}
+
+// Puts back everything air hockey and volleyball changed, whichever variant was played
+fn restore_court(
+    mut commands: Commands,
+    mut goals: Query<(&mut Transform, &entities::Side), With<entities::GoalZone>>,
+    mut paddles: Query<
+        (Entity, &mut Transform, &mut PaddleStrafe, &entities::Side),
+        (With<entities::Paddle>, Without<entities::GoalZone>),
+    >,
+) {
+    for (mut transform, side) in &mut goals {
+        transform.translation = entities::GoalZone::position(*side).extend(0.0);
+        transform.scale = entities::GoalZone::size().extend(1.0);
+    }
+    for (entity, mut transform, mut strafe, side) in &mut paddles {
+        commands.entity(entity).insert(entities::Collider);
+        transform.translation.x = side.inset_x(GAP_BETWEEN_PADDLE_AND_WALL);
+        strafe.0 = 0.0;
+    }
+}
+
+// The range of x a paddle's center can be in, from its end of the court up to the middle
+pub fn half_court(side: entities::Side, width: f32) -> (f32, f32) {
+    let back = side.inset_x(WALL_THICKNESS / 2.0 + PADDLE_PADDING + width / 2.0);
+    let front = side.sign() * (PADDLE_PADDING + width / 2.0);
+    (back.min(front), back.max(front))
+}
+
+// Steps a paddle's horizontal speed towards `target` and moves it, stopping it at the edges
+// of its half
+pub fn strafe(
+    transform: &mut Transform,
+    strafe: &mut PaddleStrafe,
+    side: entities::Side,
+    target: f32,
+    max_speed: f32,
+    settings: &Settings,
+    dt: f32,
+) {
+    strafe.0 = settings
+        .gameplay
+        .acceleration_curve
+        .accelerate(strafe.0, target, max_speed, dt);
+
+    let (min, max) = half_court(side, transform.scale.x);
+    let x = transform.translation.x + strafe.0 * dt;
+    transform.translation.x = x.clamp(min, max);
+    if transform.translation.x != x {
+        strafe.0 = 0.0;
+    }
+}
+
+fn strafe_player_paddle(
+    actions: Res<crate::actions::ActionState>,
+    mut paddles: Query<
+        (
+            &mut Transform,
+            &mut PaddleStrafe,
+            &entities::Side,
+            &crate::powerups::PaddleBoost,
+        ),
+        (With<entities::Player>, With<entities::Paddle>),
+    >,
+    settings: Res<Settings>,
+    time: Res<Time>,
+) {
+    let direction = actions.move_axis.x;
+    for (mut transform, mut paddle_strafe, side, boost) in &mut paddles {
+        let max_speed = PADDLE_SPEED * boost.multiplier();
+        strafe(
+            &mut transform,
+            &mut paddle_strafe,
+            *side,
+            direction * max_speed,
+            max_speed,
+            &settings,
+            time.delta_seconds(),
+        );
+    }
+}
+
+// The computer charges at a puck in its half once it's got behind it, and otherwise falls
+// back to guard its goal, waiting for a puck that's got past it to come out first so it
+// doesn't knock it in. The usual AI (or ghost) still takes care of up and down.
+fn strafe_ai_paddle(
+    mut paddles: Query<
+        (
+            &mut Transform,
+            &mut PaddleStrafe,
+            &entities::Side,
+            &crate::powerups::PaddleBoost,
+        ),
+        (With<entities::AI>, With<entities::Paddle>),
+    >,
+    pucks: Query<&Transform, (With<entities::Ball>, Without<entities::Paddle>)>,
+    settings: Res<Settings>,
+    time: Res<Time>,
+) {
+    for (mut transform, mut paddle_strafe, side, boost) in &mut paddles {
+        // how far things are out from our goal
+        let depth = |x: f32| (side.wall_x() - x) * side.sign();
+        let paddle = depth(transform.translation.x);
+        let home = depth(side.inset_x(GAP_BETWEEN_PADDLE_AND_WALL));
+        let ours = pucks
+            .iter()
+            .map(|puck| puck.translation.x)
+            .filter(|x| x * side.sign() > 0.0)
+            .map(depth);
+
+        let max_speed = MAX_AI_PADDLE_SPEED * boost.multiplier();
+        let mut target = (paddle - home) / time.delta_seconds() * side.sign();
+        for puck in ours {
+            if puck > paddle {
+                target = -side.sign() * max_speed;
+                break;
+            }
+            if puck > home {
+                target = 0.0;
+            }
+        }
+
+        strafe(
+            &mut transform,
+            &mut paddle_strafe,
+            *side,
+            target.clamp(-max_speed, max_speed),
+            max_speed,
+            &settings,
+            time.delta_seconds(),
+        );
+    }
+}
+
+// How a puck leaves a paddle that `hit` it: it bounces off the paddle's side as seen from
+// the paddle, so a moving paddle hits it harder and a still puck gets knocked away. `None`
+// if they're already moving apart.
+pub fn strike(puck: Vec2, paddle: Vec2, hit: Collision) -> Option<Vec2> {
+    let relative = puck - paddle;
+    let bounced = bevy_common::collision::reflect(relative, hit);
+    (bounced != relative).then(|| (bounced + paddle).clamp_length_max(MAX_PUCK_SPEED))
+}
+
+pub fn strike_pucks(
+    mut pucks: Query<
+        (Entity, &mut Transform, &mut entities::Velocity),
+        (With<entities::Ball>, Without<entities::Paddle>),
+    >,
+    paddles: Query<
+        (
+            &Transform,
+            &entities::PaddleVelocity,
+            &PaddleStrafe,
+            &entities::Side,
+        ),
+        With<entities::Paddle>,
+    >,
+    mut collision_events: EventWriter<CollisionEvent>,
+) {
+    for (puck, mut puck_transform, mut velocity) in &mut pucks {
+        for (paddle, vertical, horizontal, side) in &paddles {
+            let puck_size = puck_transform.scale.truncate();
+            let paddle_size = paddle.scale.truncate();
+            let Some(hit) = collide(
+                puck_transform.translation,
+                puck_size,
+                paddle.translation,
+                paddle_size,
+            ) else {
+                continue;
+            };
+
+            let contact = Contact::along(
+                puck_transform.translation.y,
+                paddle.translation.y,
+                paddle_size.y,
+            );
+            // out to whichever side of the paddle it hit, so it isn't struck again next tick
+            let reach = (puck_size + paddle_size) / 2.0;
+            match hit {
+                Collision::Left => puck_transform.translation.x = paddle.translation.x - reach.x,
+                Collision::Right => puck_transform.translation.x = paddle.translation.x + reach.x,
+                Collision::Top => puck_transform.translation.y = paddle.translation.y + reach.y,
+                Collision::Bottom => puck_transform.translation.y = paddle.translation.y - reach.y,
+                // towards the other end
+                Collision::Inside => {
+                    puck_transform.translation.x = paddle.translation.x - side.sign() * reach.x
+                }
+            }
+
+            let paddle_velocity = Vec2::new(horizontal.0, vertical.0);
+            if let Some(struck) = strike(velocity.0, paddle_velocity, hit) {
+                velocity.0 = struck;
+                collision_events.send(CollisionEvent::Paddle(puck, *side, contact));
+            }
+        }
+    }
+}
+
+fn slow_pucks(mut pucks: Query<&mut entities::Velocity, With<entities::Ball>>, time: Res<Time>) {
+    for mut velocity in &mut pucks {
+        velocity.0 *= (1.0 - PUCK_FRICTION * time.delta_seconds()).max(0.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paddles_knock_still_pucks_away() {
+        // a paddle moving right into a puck on its right
+        let struck = strike(Vec2::ZERO, Vec2::new(400.0, 0.0), Collision::Right).unwrap();
+        assert_eq!(struck, Vec2::new(800.0, 0.0));
+
+        // a puck already getting away from the paddle is left alone
+        assert_eq!(
+            strike(
+                Vec2::new(500.0, 0.0),
+                Vec2::new(400.0, 0.0),
+                Collision::Right
+            ),
+            None
+        );
+    }
+}
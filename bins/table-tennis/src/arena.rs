@@ -0,0 +1,339 @@
+// The court is laid out by a scene, one for each `Arena` in `assets/arenas/`: the walls,
+// goals, paddles, obstacles, where the scores are written and where balls are served from,
+// each as a transform and the components saying what it is. It's built when the game starts,
+// and again when a match is set up on a different one. The scene only says where things are,
+// so once it's spawned the pieces are dressed with their sprites, text and the rest of what
+// they need to play here.
+use std::any::TypeId;
+
+use bevy::{prelude::*, reflect::TypeRegistry, scene::serde::SceneDeserializer};
+use serde::{de::DeserializeSeed, Deserialize, Serialize};
+
+use crate::{
+    air_hockey,
+    constants::*,
+    entities::{self, Competitor},
+    mutators::MatchState,
+    paddle_colors::PaddleColors,
+    powerups,
+    rules::{Ends, MatchRules},
+    settings::Settings,
+    theme::Palette,
+};
+
+// The court layouts to pick from on the pre-match screen. They all keep the classic walls
+// and goals at the ends, since the variants and the camera are built around them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Reflect, Serialize, Deserialize)]
+#[reflect(Serialize, Deserialize)]
+pub enum Arena {
+    #[default]
+    Classic,
+    // bays set back into the walls either side of the middle
+    Wide,
+    // pillars around the middle
+    Garden,
+    // a slanted wall across two corners
+    Diagonal,
+}
+
+impl Arena {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Arena::Classic => "Classic",
+            Arena::Wide => "Wide court",
+            Arena::Garden => "Obstacle garden",
+            Arena::Diagonal => "Diagonal walls",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            Arena::Classic => Arena::Wide,
+            Arena::Wide => Arena::Garden,
+            Arena::Garden => Arena::Diagonal,
+            Arena::Diagonal => Arena::Classic,
+        }
+    }
+
+    fn scene(&self) -> &'static str {
+        match self {
+            Arena::Classic => include_str!("../assets/arenas/classic.scn.ron"),
+            Arena::Wide => include_str!("../assets/arenas/wide.scn.ron"),
+            Arena::Garden => include_str!("../assets/arenas/garden.scn.ron"),
+            Arena::Diagonal => include_str!("../assets/arenas/diagonal.scn.ron"),
+        }
+    }
+}
+
+// Everything spawned from the arena's scene
+#[derive(Component, Debug)]
+pub struct ArenaPiece;
+
+// Which arena's on the court, once it's been built
+#[derive(Resource, Debug, Default)]
+struct OnCourt(Option<Arena>);
+
+// The thumbnail of the picked arena on the pre-match screen, see `spawn_preview`
+#[derive(Component, Debug)]
+struct ArenaPreview;
+
+pub struct ArenaPlugin;
+
+impl Plugin for ArenaPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<OnCourt>()
+            .add_systems(Startup, (build_arena, dress_arena).chain())
+            // before anything starting the match looks at the court
+            .add_systems(
+                OnExit(MatchState::PreMatch),
+                (build_arena, dress_arena).chain(),
+            )
+            .add_systems(
+                Update,
+                draw_preview
+                    .run_if(in_state(MatchState::PreMatch))
+                    .run_if(resource_changed::<MatchRules>().or_else(any_empty_preview)),
+            );
+    }
+}
+
+fn load(registry: &TypeRegistry, arena: Arena) -> DynamicScene {
+    let mut deserializer =
+        ron::Deserializer::from_str(arena.scene()).expect("assets/arenas should hold valid RON");
+    SceneDeserializer {
+        type_registry: registry,
+    }
+    .deserialize(&mut deserializer)
+    .expect("assets/arenas should hold valid scenes")
+}
+
+// Spawns the picked arena, unless it's already on the court
+fn build_arena(world: &mut World) {
+    let arena = world.resource::<MatchRules>().arena;
+    if world.resource::<OnCourt>().0 == Some(arena) {
+        return;
+    }
+
+    let mut pieces = world.query_filtered::<Entity, With<ArenaPiece>>();
+    for piece in pieces.iter(world).collect::<Vec<_>>() {
+        world.despawn(piece);
+    }
+
+    let registry = world.resource::<AppTypeRegistry>().clone();
+    let scene = load(&registry.read(), arena);
+    let mut entity_map = default();
+    scene
+        .write_to_world(world, &mut entity_map)
+        .expect("the arena's components should all be registered, see `registry.rs`");
+    for entity in entity_map.values() {
+        world.entity_mut(*entity).insert(ArenaPiece);
+    }
+    world.resource_mut::<OnCourt>().0 = Some(arena);
+}
+
+// Gives whatever the scene just spawned what it needs beyond a place and a name
+fn dress_arena(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    colors: Res<PaddleColors>,
+    ends: Res<Ends>,
+    walls: Query<
+        (Entity, &Transform),
+        (
+            Or<(With<entities::WallSide>, With<entities::Obstacle>)>,
+            Without<Sprite>,
+        ),
+    >,
+    goals: Query<(Entity, &Transform), (With<entities::GoalZone>, Without<Sprite>)>,
+    paddles: Query<(Entity, &Transform, &Competitor), (With<entities::Paddle>, Without<Sprite>)>,
+    scoreboards: Query<
+        (Entity, &Transform, &entities::Side),
+        (With<entities::ScoreboardText>, Without<Text>),
+    >,
+) {
+    let palette = settings.theme.palette();
+    let sprite = |transform: &Transform, color: Color| SpriteBundle {
+        transform: *transform,
+        sprite: Sprite { color, ..default() },
+        ..default()
+    };
+
+    for (entity, transform) in &walls {
+        commands
+            .entity(entity)
+            .insert(sprite(transform, palette.wall));
+    }
+
+    for (entity, transform) in &goals {
+        commands
+            .entity(entity)
+            .insert(sprite(transform, palette.goal));
+    }
+
+    for (entity, transform, competitor) in &paddles {
+        let side = ends.side_of(*competitor);
+        let mut transform = *transform;
+        transform.translation.x = side.inset_x(GAP_BETWEEN_PADDLE_AND_WALL);
+
+        let mut paddle = commands.entity(entity);
+        paddle.insert((
+            sprite(&transform, colors.paddle(*competitor, settings.theme)),
+            side,
+            entities::PaddleVelocity::default(),
+            air_hockey::PaddleStrafe::default(),
+            powerups::PaddleSize::default(),
+            powerups::PaddleBoost::default(),
+        ));
+        match competitor {
+            Competitor::Player => paddle.insert(entities::Player),
+            Competitor::Computer => paddle.insert(entities::AI),
+        };
+    }
+
+    for (entity, transform, side) in &scoreboards {
+        commands.entity(entity).insert(Text2dBundle {
+            text: Text::from_sections([TextSection::from_style(TextStyle {
+                font_size: SCOREBOARD_FONT_SIZE,
+                color: colors.scoreboard(ends.at(*side), settings.theme),
+                ..default()
+            })]),
+            transform: *transform,
+            ..default()
+        });
+    }
+}
+
+fn represents<T: 'static>(component: &dyn Reflect) -> bool {
+    component
+        .get_represented_type_info()
+        .is_some_and(|info| info.type_id() == TypeId::of::<T>())
+}
+
+// What of `scene` is drawn in its thumbnail: each piece's place on the court and its color.
+// Paddles are put at their ends the way `dress_arena` does.
+fn thumbnail(scene: &DynamicScene, palette: &Palette, ends: &Ends) -> Vec<(Rect, Color)> {
+    scene
+        .entities
+        .iter()
+        .filter_map(|entity| {
+            let mut transform = entity
+                .components
+                .iter()
+                .find(|component| represents::<Transform>(component.as_reflect()))
+                .and_then(|component| Transform::from_reflect(component.as_reflect()))?;
+
+            let mut color = None;
+            for component in &entity.components {
+                if component.is::<entities::WallSide>() || component.is::<entities::Obstacle>() {
+                    color = Some(palette.wall);
+                } else if component.is::<entities::GoalZone>() {
+                    color = Some(palette.goal);
+                } else if let Some(competitor) = component.downcast_ref::<Competitor>() {
+                    transform.translation.x = ends
+                        .side_of(*competitor)
+                        .inset_x(GAP_BETWEEN_PADDLE_AND_WALL);
+                    color = Some(palette.paddle);
+                }
+            }
+
+            let rect = Rect::from_center_size(
+                transform.translation.truncate(),
+                transform.scale.truncate(),
+            );
+            color.map(|color| (rect, color))
+        })
+        .collect()
+}
+
+// An empty box for `draw_preview` to fill, for the pre-match screen
+pub fn spawn_preview(parent: &mut ChildBuilder) {
+    parent.spawn((
+        NodeBundle {
+            style: Style {
+                width: Val::Px(ARENA_PREVIEW_SIZE.x),
+                height: Val::Px(ARENA_PREVIEW_SIZE.y),
+                margin: UiRect::left(Val::Px(ARENA_PREVIEW_MARGIN)),
+                ..default()
+            },
+            ..default()
+        },
+        ArenaPreview,
+    ));
+}
+
+// The pre-match screen's only just gone up
+fn any_empty_preview(previews: Query<(), (With<ArenaPreview>, Without<Children>)>) -> bool {
+    !previews.is_empty()
+}
+
+// Redraws the thumbnail from the picked arena's scene
+fn draw_preview(
+    mut commands: Commands,
+    rules: Res<MatchRules>,
+    settings: Res<Settings>,
+    ends: Res<Ends>,
+    registry: Res<AppTypeRegistry>,
+    previews: Query<Entity, With<ArenaPreview>>,
+) {
+    let scene = load(&registry.read(), rules.arena);
+    let palette = settings.theme.palette();
+    let pieces = thumbnail(&scene, &palette, &ends);
+    // court units to pixels, with the court's view fitted to the box
+    let scale = ARENA_PREVIEW_SIZE.x / COURT_VIEW.x;
+
+    for preview in &previews {
+        let mut preview = commands.entity(preview);
+        preview.despawn_descendants();
+        preview.insert(BackgroundColor(palette.background));
+        preview.with_children(|parent| {
+            for (rect, color) in &pieces {
+                parent.spawn(NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        left: Val::Px((rect.min.x + COURT_VIEW.x / 2.0) * scale),
+                        top: Val::Px((COURT_VIEW.y / 2.0 - rect.max.y) * scale),
+                        width: Val::Px(rect.width() * scale),
+                        height: Val::Px(rect.height() * scale),
+                        ..default()
+                    },
+                    background_color: (*color).into(),
+                    ..default()
+                });
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::RegistryPlugin;
+
+    #[test]
+    fn every_arena_loads_in_view() {
+        let mut app = App::new();
+        app.add_plugins(RegistryPlugin)
+            .register_type::<Transform>()
+            .register_type::<Vec3>()
+            .register_type::<Quat>();
+        let registry = app.world.resource::<AppTypeRegistry>().read();
+        let palette = crate::theme::Theme::Classic.palette();
+        let view = Rect::from_center_size(Vec2::ZERO, COURT_VIEW);
+
+        let mut arena = Arena::Classic;
+        loop {
+            let scene = load(&registry, arena);
+            let pieces = thumbnail(&scene, &palette, &Ends::default());
+            // two walls, two goals and two paddles at least
+            assert!(pieces.len() >= 6, "{arena:?}");
+            for (rect, _) in pieces {
+                assert_eq!(view.union(rect), view, "{arena:?}");
+            }
+
+            arena = arena.next();
+            if arena == Arena::Classic {
+                break;
+            }
+        }
+    }
+}
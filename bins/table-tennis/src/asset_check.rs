@@ -0,0 +1,158 @@
+// Sounds are loaded through `SoundLoader`, which keeps an eye on them until they've loaded.
+// One that's missing or won't decode is swapped for its synthesized stand-in (see `synth.rs`),
+// so the game isn't silently silent, and a panel in the corner says which. Text only ever uses
+// bevy's built-in font, so there's no font to go missing.
+use std::io::Cursor;
+
+use bevy::{asset::LoadState, ecs::system::SystemParam, prelude::*};
+
+use crate::{constants::*, synth::Synth};
+
+// Sounds that haven't finished loading yet
+#[derive(Resource, Debug, Default)]
+pub struct AssetCheck {
+    pending: Vec<(String, Handle<AudioSource>)>,
+}
+
+// What went wrong with the ones that have, and anything else that didn't load
+#[derive(Resource, Debug, Default)]
+pub struct AssetProblems(pub Vec<String>);
+
+// Loads sounds like the `AssetServer` does, and has them checked
+#[derive(SystemParam)]
+pub struct SoundLoader<'w> {
+    asset_server: Res<'w, AssetServer>,
+    check: ResMut<'w, AssetCheck>,
+    synth: Res<'w, Synth>,
+    sources: ResMut<'w, Assets<AudioSource>>,
+}
+
+impl SoundLoader<'_> {
+    pub fn load(&mut self, path: &str) -> Handle<AudioSource> {
+        // a mod's version wins over the file
+        if crate::mods::loaded().sounds.contains_key(path) {
+            return self.synthesized(path);
+        }
+        let handle = self.asset_server.load(path.to_string());
+        if !self
+            .check
+            .pending
+            .iter()
+            .any(|(pending, _)| pending == path)
+        {
+            self.check.pending.push((path.to_string(), handle.clone()));
+        }
+        handle
+    }
+
+    // One there's no file for, that's only ever synthesized
+    pub fn synthesized(&mut self, name: &str) -> Handle<AudioSource> {
+        self.sources.add(self.synth.sound(name))
+    }
+}
+
+#[derive(Component)]
+struct AssetProblemsPanel;
+
+pub struct AssetCheckPlugin;
+
+impl Plugin for AssetCheckPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AssetCheck>()
+            .init_resource::<AssetProblems>()
+            .add_systems(
+                Update,
+                (
+                    check_sounds.run_if(|check: Res<AssetCheck>| !check.pending.is_empty()),
+                    show_problems.run_if(resource_changed::<AssetProblems>()),
+                )
+                    .chain(),
+            );
+    }
+}
+
+// Whether rodio, which plays bevy's audio, can make sense of it
+fn decodes(source: &AudioSource) -> bool {
+    rodio::Decoder::new(Cursor::new(source.bytes.clone())).is_ok()
+}
+
+fn check_sounds(
+    asset_server: Res<AssetServer>,
+    mut check: ResMut<AssetCheck>,
+    mut problems: ResMut<AssetProblems>,
+    synth: Res<Synth>,
+    mut sources: ResMut<Assets<AudioSource>>,
+) {
+    let mut found = Vec::new();
+    check.pending.retain(|(path, handle)| {
+        let problem = match asset_server.load_state(handle) {
+            LoadState::Loaded => match sources.get(handle) {
+                Some(source) if decodes(source) => None,
+                _ => Some(format!("{path} won't play, it's synthesized instead")),
+            },
+            LoadState::Failed => Some(format!("{path} is missing, it's synthesized instead")),
+            LoadState::NotLoaded | LoadState::Loading => return true,
+        };
+        if let Some(problem) = problem {
+            warn!("{problem}");
+            // everything holding the handle gets the stand-in
+            sources.insert(handle.id(), synth.sound(path));
+            found.push(problem);
+        }
+        false
+    });
+    // only touched when there's something new, so the panel's only redrawn then
+    if !found.is_empty() {
+        problems.0.extend(found);
+    }
+}
+
+fn show_problems(
+    mut commands: Commands,
+    problems: Res<AssetProblems>,
+    panels: Query<Entity, With<AssetProblemsPanel>>,
+) {
+    for panel in &panels {
+        commands.entity(panel).despawn_recursive();
+    }
+    if problems.0.is_empty() {
+        return;
+    }
+
+    let mut lines = vec!["Not everything could be loaded:".to_string()];
+    lines.extend(problems.0.iter().cloned());
+    commands.spawn((
+        TextBundle::from_section(
+            lines.join("\n"),
+            TextStyle {
+                font_size: DIAGNOSTICS_FONT_SIZE,
+                color: ASSET_PROBLEMS_COLOR,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(DIAGNOSTICS_PADDING),
+            right: Val::Px(DIAGNOSTICS_PADDING),
+            ..default()
+        })
+        .with_background_color(SETTINGS_MENU_BACKDROP_COLOR),
+        AssetProblemsPanel,
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::synth::{self, Tone};
+
+    #[test]
+    fn synthesized_sounds_play_and_garbage_doesnt() {
+        assert!(decodes(&synth::render(&[Tone::default()])));
+        assert!(!decodes(&AudioSource {
+            bytes: Arc::from(&b"not a sound"[..]),
+        }));
+    }
+}
@@ -0,0 +1,273 @@
+// Everything that makes a noise sends a `PlaySound` down one of the buses rather than spawning
+// the audio itself. Each bus limits how many of its sounds play at once and how soon after one
+// another they can start, and sounds that duck the music (goals and the fanfare) turn the
+// music bus down for a moment. The backing track (see `music.rs`) and the match point
+// heartbeat are on it. A `SoundBank` picks one of a few samples, a little higher or lower and
+// louder or quieter each time, so the same event doesn't sound the same over and over.
+use std::ops::RangeInclusive;
+
+use bevy::{audio::Volume, prelude::*};
+use rand::{seq::SliceRandom, Rng};
+
+use crate::constants::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bus {
+    Music,
+    Sfx,
+}
+
+impl Bus {
+    const ALL: [Self; 2] = [Self::Music, Self::Sfx];
+
+    // how many of its sounds can play at once
+    fn polyphony(self) -> usize {
+        match self {
+            Self::Music => MUSIC_POLYPHONY,
+            Self::Sfx => SFX_POLYPHONY,
+        }
+    }
+
+    // the least time between one of its sounds starting and the next, in seconds
+    fn min_interval(self) -> f32 {
+        match self {
+            Self::Music => 0.0,
+            // the same collision can happen in contiguous frames
+            Self::Sfx => SFX_MIN_INTERVAL,
+        }
+    }
+}
+
+#[derive(Event, Debug, Clone)]
+pub struct PlaySound {
+    pub bus: Bus,
+    pub source: Handle<AudioSource>,
+    pub volume: f32,
+    pub speed: f32,
+    pub ducks_music: bool,
+}
+
+impl PlaySound {
+    pub fn new(bus: Bus, source: Handle<AudioSource>) -> Self {
+        Self {
+            bus,
+            source,
+            volume: 1.0,
+            speed: 1.0,
+            ducks_music: false,
+        }
+    }
+
+    // on top of however loud it already is
+    pub fn with_volume(mut self, volume: f32) -> Self {
+        self.volume *= volume;
+        self
+    }
+
+    // on top of however fast it already is, which pitches it up too
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed *= speed;
+        self
+    }
+
+    pub fn ducking(mut self) -> Self {
+        self.ducks_music = true;
+        self
+    }
+}
+
+// Takes on one sound, and how far each play of it can stray in speed and volume
+#[derive(Debug, Clone)]
+pub struct SoundBank {
+    bus: Bus,
+    samples: Vec<Handle<AudioSource>>,
+    speed: RangeInclusive<f32>,
+    volume: RangeInclusive<f32>,
+}
+
+impl SoundBank {
+    pub fn new(bus: Bus, samples: impl IntoIterator<Item = Handle<AudioSource>>) -> Self {
+        Self {
+            bus,
+            samples: samples.into_iter().collect(),
+            speed: 1.0..=1.0,
+            volume: 1.0..=1.0,
+        }
+    }
+
+    pub fn with_speed(mut self, speed: RangeInclusive<f32>) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    pub fn with_volume(mut self, volume: RangeInclusive<f32>) -> Self {
+        self.volume = volume;
+        self
+    }
+
+    // `None` if it hasn't any samples
+    pub fn pick(&self, rng: &mut impl Rng) -> Option<PlaySound> {
+        let source = self.samples.choose(rng)?.clone();
+        Some(
+            PlaySound::new(self.bus, source)
+                .with_speed(rng.gen_range(self.speed.clone()))
+                .with_volume(rng.gen_range(self.volume.clone())),
+        )
+    }
+}
+
+// Lets something through at most once every `interval` seconds
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RateLimiter {
+    interval: f32,
+    last: Option<f32>,
+}
+
+impl RateLimiter {
+    pub fn new(interval: f32) -> Self {
+        Self {
+            interval,
+            last: None,
+        }
+    }
+
+    // true if it's been long enough, which counts as the next go
+    pub fn allow(&mut self, now: f32) -> bool {
+        if self.last.is_some_and(|last| now - last < self.interval) {
+            return false;
+        }
+        self.last = Some(now);
+        true
+    }
+}
+
+#[derive(Resource, Debug)]
+pub struct Mixer {
+    limiters: [RateLimiter; 2],
+    // the music's turned down until then, in real seconds
+    ducked_until: f32,
+}
+
+impl Default for Mixer {
+    fn default() -> Self {
+        Self {
+            limiters: Bus::ALL.map(|bus| RateLimiter::new(bus.min_interval())),
+            ducked_until: 0.0,
+        }
+    }
+}
+
+impl Mixer {
+    // how loud the bus is at `now` compared to normal
+    pub fn level(&self, bus: Bus, now: f32) -> f32 {
+        if bus == Bus::Music && now < self.ducked_until {
+            MUSIC_DUCK_VOLUME
+        } else {
+            1.0
+        }
+    }
+}
+
+// A sound that's playing, on `bus`, at `volume` before any ducking
+#[derive(Component, Debug)]
+struct Voice {
+    bus: Bus,
+    volume: f32,
+}
+
+pub struct AudioBusPlugin;
+
+impl Plugin for AudioBusPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Mixer>()
+            .add_event::<PlaySound>()
+            // after everything's had its say this frame
+            .add_systems(PostUpdate, (mix, duck).chain());
+    }
+}
+
+fn mix(
+    mut commands: Commands,
+    mut sounds: EventReader<PlaySound>,
+    mut mixer: ResMut<Mixer>,
+    voices: Query<&Voice>,
+    time: Res<Time<Real>>,
+) {
+    let now = time.elapsed_seconds();
+    let mut playing = Bus::ALL.map(|bus| voices.iter().filter(|voice| voice.bus == bus).count());
+
+    for sound in sounds.read() {
+        let bus = sound.bus as usize;
+        if playing[bus] >= sound.bus.polyphony() || !mixer.limiters[bus].allow(now) {
+            continue;
+        }
+        playing[bus] += 1;
+        if sound.ducks_music {
+            mixer.ducked_until = now + MUSIC_DUCK_LENGTH;
+        }
+
+        commands.spawn((
+            AudioBundle {
+                source: sound.source.clone(),
+                settings: PlaybackSettings::DESPAWN
+                    .with_volume(Volume::new_relative(
+                        sound.volume * mixer.level(sound.bus, now),
+                    ))
+                    .with_speed(sound.speed),
+            },
+            Voice {
+                bus: sound.bus,
+                volume: sound.volume,
+            },
+        ));
+    }
+}
+
+// Keeps the music down while it's ducked, and brings it back up after
+fn duck(
+    mixer: Res<Mixer>,
+    global: Res<GlobalVolume>,
+    voices: Query<(&Voice, &AudioSink)>,
+    time: Res<Time<Real>>,
+) {
+    let now = time.elapsed_seconds();
+    for (voice, sink) in &voices {
+        if voice.bus == Bus::Music {
+            // setting it directly skips the global volume it started with
+            sink.set_volume(voice.volume * mixer.level(voice.bus, now) * global.volume.get());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    #[test]
+    fn rate_limiter_lets_one_through_per_interval() {
+        let mut limiter = RateLimiter::new(0.05);
+        assert!(limiter.allow(1.0));
+        assert!(!limiter.allow(1.02));
+        // still counting from the one that got through
+        assert!(!limiter.allow(1.04));
+        assert!(limiter.allow(1.06));
+    }
+
+    #[test]
+    fn banks_vary_within_their_ranges() {
+        let samples = [Handle::weak_from_u128(1), Handle::weak_from_u128(2)];
+        let bank = SoundBank::new(Bus::Sfx, samples.clone())
+            .with_speed(0.9..=1.1)
+            .with_volume(0.5..=1.0);
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..50 {
+            let sound = bank.pick(&mut rng).unwrap();
+            assert!(samples.contains(&sound.source));
+            assert!((0.9..=1.1).contains(&sound.speed));
+            assert!((0.5..=1.0).contains(&sound.volume));
+        }
+        assert!(SoundBank::new(Bus::Sfx, []).pick(&mut rng).is_none());
+    }
+}
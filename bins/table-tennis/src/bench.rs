@@ -0,0 +1,242 @@
+// A headless benchmark of the simulation core, for measuring the collision and velocity
+// systems before and after changing them:
+//
+//   table-tennis --bench-sim [ticks] [--balls n] [--obstacles n]
+//
+// Runs that many fixed ticks of an arena crowded with balls and obstacles, with no window,
+// rendering or AI, then prints how long each system took. The arena is laid out from a fixed
+// seed so runs are comparable.
+use std::time::Duration;
+
+use bevy::{
+    ecs::schedule::{ExecutorKind, ScheduleLabel},
+    prelude::*,
+    utils::Instant,
+};
+use rand::Rng;
+
+use crate::{constants::*, entities, mutators, rng::GameRng, settings, CollisionEvent};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BenchOptions {
+    pub ticks: usize,
+    pub balls: usize,
+    pub obstacles: usize,
+}
+
+impl Default for BenchOptions {
+    fn default() -> Self {
+        Self {
+            ticks: BENCH_TICKS,
+            balls: BENCH_BALLS,
+            obstacles: BENCH_OBSTACLES,
+        }
+    }
+}
+
+impl BenchOptions {
+    // `None` unless the arguments ask for a benchmark
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Result<Option<Self>, String> {
+        let mut args = args.into_iter().peekable();
+        if args.next_if(|arg| arg == "--bench-sim").is_none() {
+            return Ok(None);
+        }
+
+        let mut options = BenchOptions::default();
+        if let Some(ticks) = args.next_if(|arg| !arg.starts_with("--")) {
+            options.ticks = count("--bench-sim", Some(ticks))?;
+        }
+        while let Some(flag) = args.next() {
+            let value = args.next_if(|arg| !arg.starts_with("--"));
+            match flag.as_str() {
+                "--balls" => options.balls = count(&flag, value)?,
+                "--obstacles" => options.obstacles = count(&flag, value)?,
+                _ => return Err(format!("unknown benchmark flag: {flag}")),
+            }
+        }
+        Ok(Some(options))
+    }
+
+    pub fn from_env() -> Option<Self> {
+        Self::from_args(std::env::args().skip(1)).unwrap_or_else(|err| {
+            eprintln!("{err}, running the default benchmark");
+            Some(BenchOptions::default())
+        })
+    }
+}
+
+fn count(flag: &str, value: Option<String>) -> Result<usize, String> {
+    let value = value.ok_or_else(|| format!("{flag} needs a number"))?;
+    value
+        .parse()
+        .map_err(|_| format!("{flag} needs a number, not {value}"))
+}
+
+// Each measured system gets a schedule of its own so it can be timed alone
+#[derive(ScheduleLabel, Debug, Clone, Copy, Hash, PartialEq, Eq)]
+struct BenchSystem(usize);
+
+fn spawn_arena(world: &mut World, options: &BenchOptions) {
+    let mut rng = GameRng::seeded(0);
+
+    for side in [entities::WallSide::Top, entities::WallSide::Bottom] {
+        world.spawn((
+            Transform {
+                translation: side.position().extend(0.0),
+                scale: side.size().extend(1.0),
+                ..default()
+            },
+            entities::Collider,
+            side,
+        ));
+    }
+
+    for _ in 0..options.obstacles {
+        let position = Vec2::new(
+            rng.gen_range(LEFT_WALL / 2.0..RIGHT_WALL / 2.0),
+            rng.gen_range(BOTTOM_WALL / 2.0..TOP_WALL / 2.0),
+        );
+        world.spawn((
+            Transform::from_translation(position.extend(0.0)).with_scale(Vec3::new(
+                BENCH_OBSTACLE_SIZE,
+                BENCH_OBSTACLE_SIZE,
+                1.0,
+            )),
+            entities::Obstacle,
+            entities::Collider,
+        ));
+    }
+
+    for _ in 0..options.balls {
+        world.spawn((
+            Transform::from_translation(random_position(&mut rng).extend(1.0))
+                .with_scale(BALL_SIZE),
+            entities::Ball,
+            entities::Velocity(random_direction(&mut rng) * BALL_SPEED),
+        ));
+    }
+}
+
+fn random_position(rng: &mut GameRng) -> Vec2 {
+    Vec2::new(
+        rng.gen_range(LEFT_WALL..RIGHT_WALL),
+        rng.gen_range(BOTTOM_WALL..TOP_WALL),
+    )
+}
+
+fn random_direction(rng: &mut GameRng) -> Vec2 {
+    let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+    Vec2::new(angle.cos(), angle.sin())
+}
+
+// There are no goals or serves here, so balls that leave the arena are put back in it
+fn return_balls(mut balls: Query<&mut Transform, With<entities::Ball>>) {
+    for mut transform in &mut balls {
+        let position = transform.translation;
+        if !(LEFT_WALL..RIGHT_WALL).contains(&position.x)
+            || !(BOTTOM_WALL..TOP_WALL).contains(&position.y)
+        {
+            transform.translation.x = 0.0;
+            transform.translation.y = 0.0;
+        }
+    }
+}
+
+pub fn run(options: BenchOptions) {
+    let mut world = World::new();
+    world.insert_resource(Time::<()>::default());
+    world.insert_resource(settings::Settings::default());
+    world.insert_resource(mutators::Mutators::default());
+    world.init_resource::<Events<CollisionEvent>>();
+    spawn_arena(&mut world, &options);
+
+    // in the order a tick runs them
+    let mut systems = [
+        ("apply_velocity", Schedule::new(BenchSystem(0))),
+        (
+            "generate_ball_collide_events",
+            Schedule::new(BenchSystem(1)),
+        ),
+        (
+            "check_ball_bounce_collisions",
+            Schedule::new(BenchSystem(2)),
+        ),
+        ("check_ball_ball_collisions", Schedule::new(BenchSystem(3))),
+    ];
+    systems[0]
+        .1
+        .add_systems(bevy_common::motion::apply_velocity);
+    systems[1]
+        .1
+        .add_systems(crate::generate_ball_collide_events);
+    systems[2]
+        .1
+        .add_systems(crate::check_ball_bounce_collisions);
+    systems[3].1.add_systems(crate::check_ball_ball_collisions);
+    for (_, schedule) in &mut systems {
+        schedule.set_executor_kind(ExecutorKind::SingleThreaded);
+    }
+    let mut housekeeping = Schedule::new(BenchSystem(systems.len()));
+    housekeeping.add_systems(return_balls);
+
+    println!(
+        "Simulating {} ticks with {} balls and {} obstacles",
+        options.ticks, options.balls, options.obstacles
+    );
+    let timestep = Duration::from_secs_f64(1.0 / BENCH_TICK_RATE);
+    let mut totals = [Duration::ZERO; 4];
+    let mut collisions = 0;
+    for _ in 0..options.ticks {
+        world.resource_mut::<Time>().advance_by(timestep);
+        for ((_, schedule), total) in systems.iter_mut().zip(&mut totals) {
+            let started = Instant::now();
+            schedule.run(&mut world);
+            *total += started.elapsed();
+        }
+
+        housekeeping.run(&mut world);
+        let mut events = world.resource_mut::<Events<CollisionEvent>>();
+        collisions += events.drain().count();
+    }
+
+    let ticks = options.ticks.max(1) as f64;
+    for ((name, _), total) in systems.iter().zip(totals) {
+        println!(
+            "{name:>30}: {:>9.2} ms total, {:>8.2} µs/tick",
+            total.as_secs_f64() * 1000.0,
+            total.as_secs_f64() * 1e6 / ticks
+        );
+    }
+    let total: Duration = totals.iter().sum();
+    println!(
+        "{:>30}: {:>9.2} ms total, {:>8.2} µs/tick, {:.1} collisions/tick",
+        "all",
+        total.as_secs_f64() * 1000.0,
+        total.as_secs_f64() * 1e6 / ticks,
+        collisions as f64 / ticks
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> Result<Option<BenchOptions>, String> {
+        BenchOptions::from_args(args.split_whitespace().map(str::to_string))
+    }
+
+    #[test]
+    fn parse_bench_flags() {
+        assert_eq!(parse("--server 9000").unwrap(), None);
+        assert_eq!(parse("--bench-sim").unwrap(), Some(BenchOptions::default()));
+        assert_eq!(
+            parse("--bench-sim 500 --balls 64").unwrap(),
+            Some(BenchOptions {
+                ticks: 500,
+                balls: 64,
+                ..default()
+            })
+        );
+        assert!(parse("--bench-sim --balls").is_err());
+    }
+}
@@ -0,0 +1,176 @@
+// The pieces of the celebration when somebody wins a match: the winner's paddle bouncing,
+// confetti falling and a fanfare. `assets/sequences/victory.ron` puts them together with the
+// camera, see `sequence.rs`.
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    asset_check::SoundLoader,
+    audio_bus::{Bus, PlaySound},
+    constants::*,
+};
+
+// A paddle hopping in towards the court a few times, each lower than the last, before
+// settling back where it was
+#[derive(Component, Debug)]
+pub struct Bounce {
+    origin: Vec3,
+    // which way the court is
+    inwards: f32,
+    hops: usize,
+    timer: Timer,
+}
+
+impl Bounce {
+    pub fn new(origin: Vec3, inwards: f32, hops: usize, seconds: f32) -> Self {
+        Self {
+            origin,
+            inwards,
+            hops,
+            timer: Timer::from_seconds(seconds, TimerMode::Once),
+        }
+    }
+
+    // how far in the paddle is at `t`, from 0 to 1 of the way through
+    fn offset(&self, t: f32) -> f32 {
+        (t * self.hops as f32 * PI).sin().abs() * (1.0 - t) * CELEBRATION_BOUNCE_HEIGHT
+    }
+}
+
+#[derive(Component, Debug)]
+struct Confetti {
+    velocity: Vec2,
+    // where it is in its side to side drift
+    phase: f32,
+}
+
+// The fanfare's notes still to play
+#[derive(Resource, Debug, Default)]
+pub struct Fanfare {
+    elapsed: f32,
+    played: usize,
+}
+
+#[derive(Resource)]
+struct FanfareSound(Handle<AudioSource>);
+
+pub struct CelebrationPlugin;
+
+impl Plugin for CelebrationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, load_fanfare).add_systems(
+            Update,
+            (
+                bounce_paddles,
+                fall_confetti,
+                play_fanfare.run_if(resource_exists::<Fanfare>()),
+            ),
+        );
+    }
+}
+
+// Throws `count` pieces of confetti in above the court, to fall through it
+pub fn confetti(commands: &mut Commands, count: usize) {
+    let mut rng = rand::thread_rng();
+
+    for _ in 0..count {
+        let position = Vec3::new(
+            rng.gen_range(LEFT_WALL..RIGHT_WALL),
+            TOP_WALL + rng.gen_range(0.0..CONFETTI_SPREAD),
+            CONFETTI_Z,
+        );
+        let color = CONFETTI_COLORS[rng.gen_range(0..CONFETTI_COLORS.len())];
+
+        commands.spawn((
+            SpriteBundle {
+                transform: Transform::from_translation(position)
+                    .with_rotation(Quat::from_rotation_z(rng.gen_range(0.0..PI)))
+                    .with_scale(CONFETTI_SIZE.extend(1.0)),
+                sprite: Sprite { color, ..default() },
+                ..default()
+            },
+            Confetti {
+                velocity: Vec2::new(0.0, -rng.gen_range(CONFETTI_MIN_SPEED..CONFETTI_MAX_SPEED)),
+                phase: rng.gen_range(0.0..2.0 * PI),
+            },
+        ));
+    }
+}
+
+fn load_fanfare(mut commands: Commands, mut sounds: SoundLoader) {
+    commands.insert_resource(FanfareSound(sounds.load("high_beep_short.ogg")));
+}
+
+fn bounce_paddles(
+    mut commands: Commands,
+    mut paddles: Query<(Entity, &mut Transform, &mut Bounce)>,
+    time: Res<Time>,
+) {
+    for (entity, mut transform, mut bounce) in &mut paddles {
+        if bounce.timer.tick(time.delta()).finished() {
+            transform.translation = bounce.origin;
+            commands.entity(entity).remove::<Bounce>();
+            continue;
+        }
+        let offset = bounce.offset(bounce.timer.percent());
+        transform.translation = bounce.origin + Vec3::X * bounce.inwards * offset;
+    }
+}
+
+// Drifting side to side and spinning as it goes, gone once it's below the court
+fn fall_confetti(
+    mut commands: Commands,
+    mut pieces: Query<(Entity, &mut Transform, &mut Confetti)>,
+    time: Res<Time>,
+) {
+    let dt = time.delta_seconds();
+    for (entity, mut transform, mut confetti) in &mut pieces {
+        confetti.phase += CONFETTI_SWAY_SPEED * dt;
+        let sway = confetti.phase.sin() * CONFETTI_SWAY;
+        transform.translation += (confetti.velocity + Vec2::new(sway, 0.0)).extend(0.0) * dt;
+        transform.rotate_z(CONFETTI_SPIN * dt);
+
+        if transform.translation.y < BOTTOM_WALL - CONFETTI_SPREAD {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+// The bounce beep, each note higher than the last
+fn play_fanfare(
+    mut commands: Commands,
+    mut play: EventWriter<PlaySound>,
+    mut fanfare: ResMut<Fanfare>,
+    sound: Res<FanfareSound>,
+    time: Res<Time>,
+) {
+    fanfare.elapsed += time.delta_seconds();
+    while let Some((at, speed)) = FANFARE_NOTES.get(fanfare.played) {
+        if fanfare.elapsed < *at {
+            return;
+        }
+        play.send(
+            PlaySound::new(Bus::Sfx, sound.0.clone())
+                .with_speed(*speed)
+                .ducking(),
+        );
+        fanfare.played += 1;
+    }
+    commands.remove_resource::<Fanfare>();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounces_settle_back_down() {
+        let bounce = Bounce::new(Vec3::ZERO, 1.0, 3, 1.0);
+        assert_eq!(bounce.offset(0.0), 0.0);
+        assert!(bounce.offset(1.0).abs() < 1e-4);
+        // the first hop is the highest
+        assert!(bounce.offset(1.0 / 6.0) > bounce.offset(0.5));
+    }
+}
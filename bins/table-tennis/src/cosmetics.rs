@@ -0,0 +1,649 @@
+// Looks for the paddles, ball and trails that are earned by playing the computer: winning,
+// long rallies, shutouts and a high rating each unlock one. The locker, opened with L from
+// the pre-match screen, equips them for each side. What's unlocked and equipped is kept
+// between runs.
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+use bevy_common::{
+    input,
+    screens::{self, AddScreen},
+    storage,
+};
+
+use crate::{
+    constants::*,
+    entities::{self, Competitor, PerCompetitor},
+    mutators::MatchState,
+    rating::{self, Rating},
+    rules::{self, MatchRules, MatchWinner, Opponent},
+    settings::MenuState,
+    stats::MatchStats,
+    toast::Toast,
+};
+
+// Something done in a match against the computer
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum Milestone {
+    FirstWin,
+    LongRally,
+    Shutout,
+    HighRating,
+}
+
+impl Milestone {
+    const ALL: [Milestone; 4] = [
+        Milestone::FirstWin,
+        Milestone::LongRally,
+        Milestone::Shutout,
+        Milestone::HighRating,
+    ];
+
+    fn key(&self) -> &'static str {
+        match self {
+            Milestone::FirstWin => "first-win",
+            Milestone::LongRally => "long-rally",
+            Milestone::Shutout => "shutout",
+            Milestone::HighRating => "high-rating",
+        }
+    }
+
+    fn description(&self) -> String {
+        match self {
+            Milestone::FirstWin => "win a match against the computer".to_string(),
+            Milestone::LongRally => {
+                format!("keep a rally going for {COSMETICS_LONG_RALLY} returns")
+            }
+            Milestone::Shutout => "win without conceding a point".to_string(),
+            Milestone::HighRating => format!("reach a rating of {COSMETICS_HIGH_RATING:.0}"),
+        }
+    }
+}
+
+// Something that can be equipped, and what unlocks it. `ALL` starts with the one everybody
+// has.
+trait Cosmetic: Copy + PartialEq + 'static {
+    const ALL: &'static [Self];
+    const KIND: &'static str;
+
+    fn name(&self) -> &'static str;
+    fn unlocked_by(&self) -> Option<Milestone>;
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|item| item.name() == name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PaddleSkin {
+    #[default]
+    Plain,
+    // a dark band across the middle
+    Banded,
+    // bright ends
+    Capped,
+}
+
+impl Cosmetic for PaddleSkin {
+    const ALL: &'static [Self] = &[PaddleSkin::Plain, PaddleSkin::Banded, PaddleSkin::Capped];
+    const KIND: &'static str = "paddle";
+
+    fn name(&self) -> &'static str {
+        match self {
+            PaddleSkin::Plain => "Plain",
+            PaddleSkin::Banded => "Banded",
+            PaddleSkin::Capped => "Capped",
+        }
+    }
+
+    fn unlocked_by(&self) -> Option<Milestone> {
+        match self {
+            PaddleSkin::Plain => None,
+            PaddleSkin::Banded => Some(Milestone::FirstWin),
+            PaddleSkin::Capped => Some(Milestone::HighRating),
+        }
+    }
+}
+
+impl PaddleSkin {
+    // where the marks go on a paddle, in its own units
+    fn marks(&self) -> Vec<(Vec2, Vec2, Color)> {
+        match self {
+            PaddleSkin::Plain => vec![],
+            PaddleSkin::Banded => vec![(Vec2::ZERO, Vec2::new(1.0, 0.15), PADDLE_BAND_COLOR)],
+            PaddleSkin::Capped => vec![
+                (Vec2::new(0.0, 0.45), Vec2::new(1.0, 0.1), PADDLE_CAP_COLOR),
+                (Vec2::new(0.0, -0.45), Vec2::new(1.0, 0.1), PADDLE_CAP_COLOR),
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BallSkin {
+    #[default]
+    Round,
+    Hexagon,
+    Diamond,
+}
+
+impl Cosmetic for BallSkin {
+    const ALL: &'static [Self] = &[BallSkin::Round, BallSkin::Hexagon, BallSkin::Diamond];
+    const KIND: &'static str = "ball";
+
+    fn name(&self) -> &'static str {
+        match self {
+            BallSkin::Round => "Round",
+            BallSkin::Hexagon => "Hexagon",
+            BallSkin::Diamond => "Diamond",
+        }
+    }
+
+    fn unlocked_by(&self) -> Option<Milestone> {
+        match self {
+            BallSkin::Round => None,
+            BallSkin::Hexagon => Some(Milestone::LongRally),
+            BallSkin::Diamond => Some(Milestone::Shutout),
+        }
+    }
+}
+
+impl BallSkin {
+    // the same size as the round ball, which only changes how it looks
+    pub fn mesh(&self) -> Mesh {
+        match self {
+            BallSkin::Round => shape::Circle::default().into(),
+            BallSkin::Hexagon => shape::RegularPolygon::new(0.5, 6).into(),
+            BallSkin::Diamond => shape::RegularPolygon::new(0.5, 4).into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TrailStyle {
+    #[default]
+    Ghosts,
+    // thin and slow to fade
+    Streak,
+}
+
+impl Cosmetic for TrailStyle {
+    const ALL: &'static [Self] = &[TrailStyle::Ghosts, TrailStyle::Streak];
+    const KIND: &'static str = "trail";
+
+    fn name(&self) -> &'static str {
+        match self {
+            TrailStyle::Ghosts => "Ghosts",
+            TrailStyle::Streak => "Streak",
+        }
+    }
+
+    fn unlocked_by(&self) -> Option<Milestone> {
+        match self {
+            TrailStyle::Ghosts => None,
+            TrailStyle::Streak => Some(Milestone::Shutout),
+        }
+    }
+}
+
+impl TrailStyle {
+    pub fn lifetime(&self) -> f32 {
+        match self {
+            TrailStyle::Ghosts => PADDLE_TRAIL_LIFETIME,
+            TrailStyle::Streak => PADDLE_TRAIL_LIFETIME * STREAK_TRAIL_LIFETIME_MULTIPLIER,
+        }
+    }
+
+    // as a fraction of the paddle's
+    pub fn width(&self) -> f32 {
+        match self {
+            TrailStyle::Ghosts => 1.0,
+            TrailStyle::Streak => STREAK_TRAIL_WIDTH,
+        }
+    }
+}
+
+// What's been unlocked, and what each side has on
+#[derive(Resource, Debug, Default, PartialEq, Eq)]
+pub struct Cosmetics {
+    unlocked: HashSet<Milestone>,
+    pub paddles: PerCompetitor<PaddleSkin>,
+    pub trails: PerCompetitor<TrailStyle>,
+    // there's only the one
+    pub ball: BallSkin,
+}
+
+impl Cosmetics {
+    fn load() -> Self {
+        storage::load(GAME_NAME, COSMETICS_FILE)
+            .map(|contents| Self::parse(&contents))
+            .unwrap_or_default()
+    }
+
+    fn save(&self) {
+        if let Err(err) = storage::save(GAME_NAME, COSMETICS_FILE, &self.to_text()) {
+            warn!("Couldn't save the cosmetics: {err}");
+        }
+    }
+
+    // "unlocked" and its milestones, then each competitor's paddle and trail, then the ball.
+    // Anything unreadable, or equipped without being unlocked, is left at the default.
+    fn parse(contents: &str) -> Self {
+        let mut lines = contents.lines().map(|line| line.split_whitespace());
+        let mut cosmetics = Self::default();
+
+        if let Some(mut words) = lines.next() {
+            if words.next() == Some("unlocked") {
+                cosmetics.unlocked = words
+                    .filter_map(|key| Milestone::ALL.into_iter().find(|m| m.key() == key))
+                    .collect();
+            }
+        }
+        for competitor in Competitor::ALL {
+            let mut words = lines.next().into_iter().flatten();
+            cosmetics.paddles[competitor] = cosmetics.equippable(words.next());
+            cosmetics.trails[competitor] = cosmetics.equippable(words.next());
+        }
+        cosmetics.ball = cosmetics.equippable(lines.next().into_iter().flatten().next());
+        cosmetics
+    }
+
+    fn equippable<T: Cosmetic + Default>(&self, name: Option<&str>) -> T {
+        name.and_then(T::from_name)
+            .filter(|item| self.has(*item))
+            .unwrap_or_default()
+    }
+
+    fn to_text(&self) -> String {
+        let mut keys: Vec<_> = Milestone::ALL
+            .into_iter()
+            .filter(|milestone| self.unlocked.contains(milestone))
+            .map(|milestone| milestone.key())
+            .collect();
+        keys.insert(0, "unlocked");
+
+        let mut text = keys.join(" ") + "\n";
+        for competitor in Competitor::ALL {
+            text += &format!(
+                "{} {}\n",
+                self.paddles[competitor].name(),
+                self.trails[competitor].name()
+            );
+        }
+        text + self.ball.name() + "\n"
+    }
+
+    fn has<T: Cosmetic>(&self, item: T) -> bool {
+        item.unlocked_by()
+            .is_none_or(|milestone| self.unlocked.contains(&milestone))
+    }
+
+    // the next one along that's been unlocked
+    fn step<T: Cosmetic>(&self, current: T, step: f32) -> T {
+        let all = T::ALL;
+        let i = all.iter().position(|item| *item == current).unwrap_or(0);
+        (1..=all.len())
+            .map(|offset| {
+                let offset = if step < 0.0 {
+                    all.len() - offset
+                } else {
+                    offset
+                };
+                all[(i + offset) % all.len()]
+            })
+            .find(|item| self.has(*item))
+            .unwrap_or(current)
+    }
+}
+
+// The milestones a match against the computer reached, done or not
+fn reached(won: bool, stats: &MatchStats, rating: f32) -> Vec<Milestone> {
+    let conceded = stats
+        .points
+        .iter()
+        .any(|point| point.scorer != Competitor::Player);
+    let longest = stats.points.iter().map(|point| point.returns).max();
+
+    let mut milestones = Vec::new();
+    if won {
+        milestones.push(Milestone::FirstWin);
+    }
+    if longest.is_some_and(|returns| returns >= COSMETICS_LONG_RALLY) {
+        milestones.push(Milestone::LongRally);
+    }
+    if won && !conceded {
+        milestones.push(Milestone::Shutout);
+    }
+    if rating >= COSMETICS_HIGH_RATING {
+        milestones.push(Milestone::HighRating);
+    }
+    milestones
+}
+
+// Whether the locker is up, over the pre-match screen
+#[derive(States, Debug, Clone, Copy, Default, Hash, PartialEq, Eq)]
+pub enum LockerState {
+    #[default]
+    Closed,
+    Open,
+}
+
+// Every row in the locker
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LockerItem {
+    Paddle(Competitor),
+    Trail(Competitor),
+    Ball,
+}
+
+impl LockerItem {
+    const ALL: [LockerItem; 5] = [
+        LockerItem::Paddle(Competitor::Player),
+        LockerItem::Trail(Competitor::Player),
+        LockerItem::Paddle(Competitor::Computer),
+        LockerItem::Trail(Competitor::Computer),
+        LockerItem::Ball,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            LockerItem::Paddle(Competitor::Player) => "Player 1 paddle",
+            LockerItem::Trail(Competitor::Player) => "Player 1 trail",
+            LockerItem::Paddle(Competitor::Computer) => "Player 2 paddle",
+            LockerItem::Trail(Competitor::Computer) => "Player 2 trail",
+            LockerItem::Ball => "Ball",
+        }
+    }
+
+    fn value(&self, cosmetics: &Cosmetics) -> &'static str {
+        match self {
+            LockerItem::Paddle(competitor) => cosmetics.paddles[*competitor].name(),
+            LockerItem::Trail(competitor) => cosmetics.trails[*competitor].name(),
+            LockerItem::Ball => cosmetics.ball.name(),
+        }
+    }
+
+    fn adjust(&self, cosmetics: &mut Cosmetics, step: f32) {
+        match self {
+            LockerItem::Paddle(competitor) => {
+                cosmetics.paddles[*competitor] =
+                    cosmetics.step(cosmetics.paddles[*competitor], step)
+            }
+            LockerItem::Trail(competitor) => {
+                cosmetics.trails[*competitor] = cosmetics.step(cosmetics.trails[*competitor], step)
+            }
+            LockerItem::Ball => cosmetics.ball = cosmetics.step(cosmetics.ball, step),
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+struct LockerCursor(usize);
+
+#[derive(Component)]
+struct LockerScreen;
+
+#[derive(Component)]
+struct LockerScreenText;
+
+// A mark on a paddle from its skin, see `PaddleSkin::marks`
+#[derive(Component)]
+struct SkinMark;
+
+pub struct CosmeticsPlugin;
+
+impl Plugin for CosmeticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<LockerState>()
+            .insert_resource(Cosmetics::load())
+            .init_resource::<LockerCursor>()
+            .add_screen::<LockerScreen, _>(LockerState::Open, spawn_locker_screen)
+            .add_systems(OnExit(LockerState::Open), save_cosmetics)
+            .add_systems(
+                OnEnter(MatchState::GameOver),
+                unlock
+                    .after(rating::rate_match)
+                    .run_if(|rules: Res<MatchRules>| rules.opponent == Opponent::Computer)
+                    .run_if(not(rules::practicing))
+                    .run_if(crate::net::has_input_authority)
+                    .run_if(crate::net::keyboard_is_local)
+                    .run_if(not(crate::kiosk::attracting)),
+            )
+            .add_systems(
+                Update,
+                (
+                    open_locker
+                        .run_if(in_state(MatchState::PreMatch))
+                        .run_if(in_state(LockerState::Closed))
+                        .run_if(in_state(crate::daily::ChallengeState::Off))
+                        .run_if(in_state(crate::devices::Joining::Closed))
+                        .run_if(crate::net::has_input_authority),
+                    (navigate_locker, update_locker_screen_text)
+                        .chain()
+                        .run_if(in_state(LockerState::Open)),
+                )
+                    .run_if(in_state(MenuState::Closed)),
+            )
+            .add_systems(Update, (dress_paddles, shape_balls));
+    }
+}
+
+fn save_cosmetics(cosmetics: Res<Cosmetics>) {
+    cosmetics.save();
+}
+
+fn unlock(
+    winner: Res<MatchWinner>,
+    stats: Res<MatchStats>,
+    rating: Res<Rating>,
+    mut cosmetics: ResMut<Cosmetics>,
+    mut toasts: EventWriter<Toast>,
+) {
+    let won = winner.0 == Some(Competitor::Player);
+    let mut unlocked_any = false;
+
+    for milestone in reached(won, &stats, rating.rating) {
+        if !cosmetics.unlocked.insert(milestone) {
+            continue;
+        }
+        unlocked_any = true;
+        let names = unlocked_names(milestone);
+        toasts.send(Toast(format!("Unlocked: {}", names.join(", "))));
+    }
+    if unlocked_any {
+        cosmetics.save();
+    }
+}
+
+// everything `milestone` unlocks, like "Banded paddle"
+fn unlocked_names(milestone: Milestone) -> Vec<String> {
+    fn of<T: Cosmetic>(milestone: Milestone) -> impl Iterator<Item = String> {
+        T::ALL
+            .iter()
+            .filter(move |item| item.unlocked_by() == Some(milestone))
+            .map(|item| format!("{} {}", item.name(), T::KIND))
+    }
+    of::<PaddleSkin>(milestone)
+        .chain(of::<BallSkin>(milestone))
+        .chain(of::<TrailStyle>(milestone))
+        .collect()
+}
+
+fn open_locker(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut next_locker: ResMut<NextState<LockerState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::L) {
+        next_locker.set(LockerState::Open);
+    }
+}
+
+fn navigate_locker(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut cursor: ResMut<LockerCursor>,
+    mut cosmetics: ResMut<Cosmetics>,
+    mut next_locker: ResMut<NextState<LockerState>>,
+) {
+    let items = LockerItem::ALL.len();
+
+    if keyboard_input.any_just_pressed(input::UP) {
+        cursor.0 = (cursor.0 + items - 1) % items;
+    }
+    if keyboard_input.any_just_pressed(input::DOWN) {
+        cursor.0 = (cursor.0 + 1) % items;
+    }
+    if keyboard_input.any_just_pressed(input::LEFT) {
+        LockerItem::ALL[cursor.0].adjust(&mut cosmetics, -1.0);
+    } else if keyboard_input.any_just_pressed(input::RIGHT)
+        || keyboard_input.just_pressed(KeyCode::Space)
+    {
+        LockerItem::ALL[cursor.0].adjust(&mut cosmetics, 1.0);
+    }
+
+    if keyboard_input.any_just_pressed([KeyCode::L, KeyCode::Back, KeyCode::Return]) {
+        next_locker.set(LockerState::Closed);
+    }
+}
+
+fn locker_sections(cosmetics: &Cosmetics, cursor: usize) -> Vec<TextSection> {
+    let style = |color| TextStyle {
+        font_size: SETTINGS_MENU_FONT_SIZE,
+        color,
+        ..default()
+    };
+
+    let mut sections = vec![TextSection::new("Locker\n\n", style(Color::WHITE))];
+    for (i, item) in LockerItem::ALL.iter().enumerate() {
+        let (marker, color) = if i == cursor {
+            ("> ", Color::YELLOW)
+        } else {
+            ("  ", Color::WHITE)
+        };
+        sections.push(TextSection::new(
+            format!("{marker}{}: < {} >\n", item.label(), item.value(cosmetics)),
+            style(color),
+        ));
+    }
+
+    sections.push(TextSection::new("\nUnlocks\n", style(Color::GRAY)));
+    for milestone in Milestone::ALL {
+        let check = if cosmetics.unlocked.contains(&milestone) {
+            "x"
+        } else {
+            " "
+        };
+        sections.push(TextSection::new(
+            format!("  [{check}] {}", unlocked_names(milestone).join(", ")),
+            style(Color::WHITE),
+        ));
+        sections.push(TextSection::new(
+            format!(" - {}\n", milestone.description()),
+            style(Color::GRAY),
+        ));
+    }
+
+    sections.push(TextSection::new(
+        "\nup/down: select  left/right: change  l: back",
+        style(Color::GRAY),
+    ));
+    sections
+}
+
+fn spawn_locker_screen(
+    mut commands: Commands,
+    cosmetics: Res<Cosmetics>,
+    cursor: Res<LockerCursor>,
+) {
+    commands
+        .spawn((
+            screens::overlay(SETTINGS_MENU_BACKDROP_COLOR, SETTINGS_MENU_Z_INDEX - 1),
+            LockerScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_sections(locker_sections(&cosmetics, cursor.0)),
+                LockerScreenText,
+            ));
+        });
+}
+
+fn update_locker_screen_text(
+    cosmetics: Res<Cosmetics>,
+    cursor: Res<LockerCursor>,
+    mut text: Query<&mut Text, With<LockerScreenText>>,
+) {
+    if !cosmetics.is_changed() && !cursor.is_changed() {
+        return;
+    }
+    for mut text in &mut text {
+        text.sections = locker_sections(&cosmetics, cursor.0);
+    }
+}
+
+// Puts each paddle's skin on it whenever they change
+fn dress_paddles(
+    mut commands: Commands,
+    cosmetics: Res<Cosmetics>,
+    paddles: Query<(Entity, &Competitor), With<entities::Paddle>>,
+    marks: Query<Entity, With<SkinMark>>,
+) {
+    if !cosmetics.is_changed() {
+        return;
+    }
+    for mark in &marks {
+        commands.entity(mark).despawn_recursive();
+    }
+
+    for (paddle, competitor) in &paddles {
+        commands.entity(paddle).with_children(|parent| {
+            for (at, size, color) in cosmetics.paddles[*competitor].marks() {
+                parent.spawn((
+                    SpriteBundle {
+                        // just in front of the paddle, scaled along with it
+                        transform: Transform::from_translation(at.extend(0.1))
+                            .with_scale(size.extend(1.0)),
+                        sprite: Sprite { color, ..default() },
+                        ..default()
+                    },
+                    SkinMark,
+                ));
+            }
+        });
+    }
+}
+
+// Every new ball in the ball skin's shape, and every ball when the skin changes
+fn shape_balls(
+    cosmetics: Res<Cosmetics>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut balls: Query<(&mut bevy::sprite::Mesh2dHandle, Ref<entities::Ball>)>,
+) {
+    for (mut mesh, ball) in &mut balls {
+        if ball.is_added() || cosmetics.is_changed() {
+            mesh.0 = meshes.add(cosmetics.ball.mesh());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_unlocked_cosmetics_are_equipped() {
+        let mut cosmetics = Cosmetics::default();
+        assert_eq!(cosmetics.step(PaddleSkin::Plain, 1.0), PaddleSkin::Plain);
+
+        cosmetics.unlocked.insert(Milestone::HighRating);
+        assert_eq!(cosmetics.step(PaddleSkin::Plain, 1.0), PaddleSkin::Capped);
+        assert_eq!(cosmetics.step(PaddleSkin::Plain, -1.0), PaddleSkin::Capped);
+
+        cosmetics.paddles.computer = PaddleSkin::Capped;
+        assert_eq!(Cosmetics::parse(&cosmetics.to_text()), cosmetics);
+        // a locked one in the file doesn't count
+        assert_eq!(
+            Cosmetics::parse("unlocked\nBanded Streak\n").paddles.player,
+            PaddleSkin::Plain
+        );
+    }
+}
@@ -0,0 +1,121 @@
+// The crowd: a murmur that builds the longer a rally goes on, and a cheer that erupts when a
+// point's won. They're two synthesized loops (see `synth.rs`) playing the whole time, turned
+// up and down to follow `CrowdExcitement`. Match point has them on the edge of their seats.
+// The loops are looked after here rather than going through the buses in `audio_bus.rs`,
+// which are for one-off sounds.
+use bevy::{audio::Volume, prelude::*};
+
+use crate::{
+    asset_check::SoundLoader, constants::*, match_point::MatchPoint, mutators::MatchState,
+    personality::Rally, PointScored, TickSet,
+};
+
+#[derive(Resource, Debug, Default)]
+pub struct CrowdExcitement {
+    // how worked up the rally has them, from 0 to 1
+    pub rally: f32,
+    // the cheer after a point, from 1 when it's just been won dying away to 0
+    pub eruption: f32,
+}
+
+impl CrowdExcitement {
+    fn murmur(&self, match_point: bool) -> f32 {
+        let excitement = if match_point {
+            self.rally.max(CROWD_MATCH_POINT_EXCITEMENT)
+        } else {
+            self.rally
+        };
+        CROWD_MURMUR_QUIET + (CROWD_MURMUR_LOUD - CROWD_MURMUR_QUIET) * excitement
+    }
+
+    fn cheer(&self) -> f32 {
+        CROWD_CHEER_VOLUME * self.eruption
+    }
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+enum CrowdLayer {
+    Murmur,
+    Cheer,
+}
+
+pub struct CrowdPlugin;
+
+impl Plugin for CrowdPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CrowdExcitement>()
+            .add_systems(Startup, start_crowd)
+            .add_systems(FixedUpdate, excite.in_set(TickSet::Effects))
+            .add_systems(Update, follow_excitement);
+    }
+}
+
+fn start_crowd(mut commands: Commands, mut sounds: SoundLoader) {
+    for (layer, name) in [
+        (CrowdLayer::Murmur, "crowd_murmur"),
+        (CrowdLayer::Cheer, "crowd_cheer"),
+    ] {
+        commands.spawn((
+            AudioBundle {
+                source: sounds.synthesized(name),
+                // silent until there's a match on
+                settings: PlaybackSettings::LOOP.with_volume(Volume::new_relative(0.0)),
+            },
+            layer,
+        ));
+    }
+}
+
+fn excite(
+    mut points: EventReader<PointScored>,
+    rally: Res<Rally>,
+    mut excitement: ResMut<CrowdExcitement>,
+) {
+    if points.read().count() > 0 {
+        excitement.eruption = 1.0;
+    }
+    excitement.rally = (rally.returns as f32 / CROWD_RALLY_RETURNS as f32).min(1.0);
+}
+
+// Eases each loop towards how loud it should be, and lets the cheer die away
+fn follow_excitement(
+    mut excitement: ResMut<CrowdExcitement>,
+    match_point: Res<MatchPoint>,
+    state: Res<State<MatchState>>,
+    global: Res<GlobalVolume>,
+    layers: Query<(&CrowdLayer, &AudioSink)>,
+    time: Res<Time>,
+) {
+    let dt = time.delta_seconds();
+    excitement.eruption *= (-CROWD_ERUPTION_DECAY * dt).exp();
+
+    let playing = *state.get() == MatchState::Playing;
+    let ease = 1.0 - (-CROWD_SMOOTHING * dt).exp();
+    for (layer, sink) in &layers {
+        let target = match layer {
+            _ if !playing => 0.0,
+            CrowdLayer::Murmur => excitement.murmur(match_point.active()),
+            CrowdLayer::Cheer => excitement.cheer(),
+        } * global.volume.get();
+        let volume = sink.volume();
+        sink.set_volume(volume + (target - volume) * ease);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_murmur_builds_with_the_rally_and_match_point() {
+        let mut excitement = CrowdExcitement::default();
+        let quiet = excitement.murmur(false);
+        assert_eq!(quiet, CROWD_MURMUR_QUIET);
+        assert!(excitement.murmur(true) > quiet);
+
+        excitement.rally = 1.0;
+        assert_eq!(excitement.murmur(false), CROWD_MURMUR_LOUD);
+        // a long rally at match point is as loud as it gets, not louder
+        assert_eq!(excitement.murmur(true), CROWD_MURMUR_LOUD);
+    }
+}
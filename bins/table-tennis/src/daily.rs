@@ -0,0 +1,431 @@
+// The daily challenge, opened with D on the pre-match screen. The day's date seeds a few
+// mutators, which computer you're up against and the match's own randomness, so it's the
+// same challenge for everyone that day. There's one go at it a day, first to
+// `DAILY_TARGET_SCORE`, and how each went is kept between runs and shown on a calendar of
+// the month. Days turn over at midnight UTC.
+use std::{
+    collections::BTreeMap,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy::prelude::*;
+use bevy_common::{
+    screens::{self, AddScreen},
+    storage,
+};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+use crate::{
+    constants::*,
+    cosmetics::LockerState,
+    devices::Joining,
+    entities::{Competitor, PerCompetitor},
+    mutators::{MatchState, Mutator, Mutators},
+    personality,
+    rng::GameRng,
+    rules::{MatchMode, MatchRules, MatchWinner, Opponent},
+    settings::MenuState,
+    Scoreboard,
+};
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+#[derive(States, Debug, Clone, Copy, Default, Hash, PartialEq, Eq)]
+pub enum ChallengeState {
+    #[default]
+    Off,
+    // the calendar's up
+    Calendar,
+    Playing,
+}
+
+// Days since 1970-01-01
+fn today() -> i64 {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+    (seconds / 86_400) as i64
+}
+
+// (year, month, day of the month) of `day`, both from 1. See Howard Hinnant's
+// `civil_from_days`.
+fn date(day: i64) -> (i64, u32, u32) {
+    let z = day + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    // counting from March, so the leap day's at the end
+    let month = (5 * day_of_year + 2) / 153;
+    let day_of_month = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month as u32, day_of_month as u32)
+}
+
+// 0 for Monday. 1970-01-01 was a Thursday.
+fn weekday(day: i64) -> i64 {
+    (day + 3).rem_euclid(7)
+}
+
+// What a day's challenge is
+#[derive(Debug, Clone, PartialEq)]
+struct Challenge {
+    seed: u64,
+    mutators: Vec<Mutator>,
+    personality: usize,
+}
+
+impl Challenge {
+    fn on(day: i64) -> Self {
+        let seed = day as u64 ^ DAILY_SEED_STREAM;
+        let mut rng = StdRng::seed_from_u64(seed);
+        let count = rng.gen_range(DAILY_MUTATORS);
+        let mutators = Mutator::ALL
+            .choose_multiple(&mut rng, count)
+            .copied()
+            .collect();
+        let personality = rng.gen_range(0..personality::all().len());
+
+        Self {
+            seed,
+            mutators,
+            personality,
+        }
+    }
+
+    // against the computer in whichever variant and on whatever court's picked, everything
+    // else as it comes
+    fn rules(&self, picked: &MatchRules) -> MatchRules {
+        MatchRules {
+            variant: picked.variant,
+            arena: picked.arena,
+            opponent: Opponent::Computer,
+            personality: self.personality,
+            mode: MatchMode::Points,
+            target_score: Some(DAILY_TARGET_SCORE),
+            games: 1,
+            ..default()
+        }
+    }
+}
+
+// How a day's go went
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Attempt {
+    // and never finished
+    Started,
+    Finished {
+        won: bool,
+        score: PerCompetitor<usize>,
+    },
+}
+
+// Every day's go, by day
+#[derive(Resource, Debug, Default, PartialEq, Eq)]
+struct ChallengeLog(BTreeMap<i64, Attempt>);
+
+impl ChallengeLog {
+    fn load() -> Self {
+        storage::load(GAME_NAME, DAILY_FILE)
+            .map(|contents| Self::parse(&contents))
+            .unwrap_or_default()
+    }
+
+    fn save(&self) {
+        if let Err(err) = storage::save(GAME_NAME, DAILY_FILE, &self.to_text()) {
+            warn!("Couldn't save the daily challenges: {err}");
+        }
+    }
+
+    // A line a day: the day, then "started", or "won" or "lost" and the score. Anything
+    // unreadable is skipped.
+    fn parse(contents: &str) -> Self {
+        let attempts = contents.lines().filter_map(|line| {
+            let mut words = line.split_whitespace();
+            let day = words.next()?.parse().ok()?;
+            let attempt = match words.next()? {
+                "started" => Attempt::Started,
+                outcome @ ("won" | "lost") => Attempt::Finished {
+                    won: outcome == "won",
+                    score: PerCompetitor {
+                        player: words.next()?.parse().ok()?,
+                        computer: words.next()?.parse().ok()?,
+                    },
+                },
+                _ => return None,
+            };
+            Some((day, attempt))
+        });
+        Self(attempts.collect())
+    }
+
+    fn to_text(&self) -> String {
+        self.0
+            .iter()
+            .map(|(day, attempt)| match attempt {
+                Attempt::Started => format!("{day} started\n"),
+                Attempt::Finished { won, score } => {
+                    let outcome = if *won { "won" } else { "lost" };
+                    format!("{day} {outcome} {} {}\n", score.player, score.computer)
+                }
+            })
+            .collect()
+    }
+}
+
+// The rules, mutators and randomness from before the challenge, put back after it, and the
+// day it's for
+#[derive(Resource)]
+struct Stash {
+    day: i64,
+    rules: MatchRules,
+    mutators: Mutators,
+    rng: GameRng,
+}
+
+#[derive(Component)]
+struct CalendarScreen;
+
+pub struct DailyPlugin;
+
+impl Plugin for DailyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<ChallengeState>()
+            .insert_resource(ChallengeLog::load())
+            .add_screen::<CalendarScreen, _>(ChallengeState::Calendar, spawn_calendar_screen)
+            .add_systems(
+                Update,
+                (
+                    open_calendar
+                        .run_if(in_state(MatchState::PreMatch))
+                        .run_if(in_state(ChallengeState::Off))
+                        .run_if(in_state(LockerState::Closed))
+                        .run_if(in_state(Joining::Closed)),
+                    start_challenge.run_if(in_state(ChallengeState::Calendar)),
+                )
+                    .run_if(in_state(MenuState::Closed))
+                    .run_if(crate::net::has_input_authority)
+                    .run_if(crate::net::keyboard_is_local),
+            )
+            .add_systems(
+                OnEnter(MatchState::GameOver),
+                record_attempt.run_if(in_state(ChallengeState::Playing)),
+            )
+            // a rematch is back on the player's own rules
+            .add_systems(
+                OnExit(MatchState::GameOver),
+                end_challenge.run_if(in_state(ChallengeState::Playing)),
+            )
+            .add_systems(
+                OnEnter(MatchState::PreMatch),
+                end_challenge.run_if(in_state(ChallengeState::Playing)),
+            );
+    }
+}
+
+fn open_calendar(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut next_challenge: ResMut<NextState<ChallengeState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::D) {
+        next_challenge.set(ChallengeState::Calendar);
+    }
+}
+
+fn calendar_sections(log: &ChallengeLog, today: i64) -> Vec<TextSection> {
+    let style = |color| TextStyle {
+        font_size: SETTINGS_MENU_FONT_SIZE,
+        color,
+        ..default()
+    };
+    let (year, month, day_of_month) = date(today);
+    let month_name = MONTHS[month as usize - 1];
+    let challenge = Challenge::on(today);
+    let mutators: Vec<_> = challenge.mutators.iter().map(Mutator::name).collect();
+
+    let mut sections = vec![
+        TextSection::new(
+            format!("Daily challenge, {day_of_month} {month_name} {year}\n\n"),
+            style(Color::WHITE),
+        ),
+        TextSection::new(
+            format!(
+                "Mutators: {}\nComputer plays as: {}\nFirst to {DAILY_TARGET_SCORE}\n\n",
+                mutators.join(", "),
+                personality::all()[challenge.personality].name,
+            ),
+            style(Color::WHITE),
+        ),
+        TextSection::new(
+            format!("{month_name}\nMo Tu We Th Fr Sa Su\n"),
+            style(Color::GRAY),
+        ),
+    ];
+
+    let first = today - (day_of_month as i64 - 1);
+    sections.push(TextSection::new(
+        "   ".repeat(weekday(first) as usize),
+        style(Color::WHITE),
+    ));
+    let mut day = first;
+    while date(day).1 == month {
+        let color = match log.0.get(&day) {
+            Some(Attempt::Finished { won: true, .. }) => DAILY_WON_COLOR,
+            Some(Attempt::Finished { won: false, .. }) => DAILY_LOST_COLOR,
+            Some(Attempt::Started) => Color::GRAY,
+            None if day == today => Color::YELLOW,
+            None => Color::WHITE,
+        };
+        let end = if weekday(day) == 6 { "\n" } else { " " };
+        sections.push(TextSection::new(
+            format!("{:>2}{end}", date(day).2),
+            style(color),
+        ));
+        day += 1;
+    }
+
+    let footer = match log.0.get(&today) {
+        None => "\n\nenter: play  d: back".to_string(),
+        Some(Attempt::Started) => {
+            "\n\nToday's go wasn't finished. Back tomorrow!  d: back".to_string()
+        }
+        Some(Attempt::Finished { won, score }) => format!(
+            "\n\nYou {} today's {}-{}. Back tomorrow!  d: back",
+            if *won { "won" } else { "lost" },
+            score.player,
+            score.computer
+        ),
+    };
+    sections.push(TextSection::new(footer, style(Color::GRAY)));
+    sections
+}
+
+fn spawn_calendar_screen(mut commands: Commands, log: Res<ChallengeLog>) {
+    commands
+        .spawn((
+            screens::overlay(SETTINGS_MENU_BACKDROP_COLOR, SETTINGS_MENU_Z_INDEX - 1),
+            CalendarScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_sections(calendar_sections(&log, today())));
+        });
+}
+
+// Only once a day, and it counts from the moment it's started
+fn start_challenge(
+    mut commands: Commands,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut rules: ResMut<MatchRules>,
+    mut mutators: ResMut<Mutators>,
+    mut rng: ResMut<GameRng>,
+    mut log: ResMut<ChallengeLog>,
+    mut next_challenge: ResMut<NextState<ChallengeState>>,
+    mut next_match: ResMut<NextState<MatchState>>,
+) {
+    if keyboard_input.any_just_pressed([KeyCode::D, KeyCode::Back]) {
+        next_challenge.set(ChallengeState::Off);
+        return;
+    }
+    let day = today();
+    if !keyboard_input.just_pressed(KeyCode::Return) || log.0.contains_key(&day) {
+        return;
+    }
+
+    let challenge = Challenge::on(day);
+    let challenge_rules = challenge.rules(&rules);
+    commands.insert_resource(Stash {
+        day,
+        rules: std::mem::replace(&mut *rules, challenge_rules),
+        mutators: std::mem::replace(&mut *mutators, challenge.mutators.iter().copied().collect()),
+        rng: std::mem::replace(&mut *rng, GameRng::seeded(challenge.seed)),
+    });
+    log.0.insert(day, Attempt::Started);
+    log.save();
+
+    next_challenge.set(ChallengeState::Playing);
+    next_match.set(MatchState::Playing);
+}
+
+fn record_attempt(
+    scores: Res<Scoreboard>,
+    winner: Res<MatchWinner>,
+    stash: Option<Res<Stash>>,
+    mut log: ResMut<ChallengeLog>,
+) {
+    let Some(stash) = stash else {
+        return;
+    };
+    log.0.insert(
+        stash.day,
+        Attempt::Finished {
+            won: winner.0 == Some(Competitor::Player),
+            score: **scores,
+        },
+    );
+    log.save();
+}
+
+fn end_challenge(
+    mut commands: Commands,
+    stash: Option<ResMut<Stash>>,
+    mut rng: ResMut<GameRng>,
+    mut next_challenge: ResMut<NextState<ChallengeState>>,
+) {
+    if let Some(mut stash) = stash {
+        commands.insert_resource(stash.rules.clone());
+        commands.insert_resource(stash.mutators.clone());
+        std::mem::swap(&mut *rng, &mut stash.rng);
+        commands.remove_resource::<Stash>();
+    }
+    next_challenge.set(ChallengeState::Off);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dates_line_up() {
+        assert_eq!(date(0), (1970, 1, 1));
+        assert_eq!(date(11_016), (2000, 2, 29));
+        assert_eq!(date(20_741), (2026, 10, 15));
+        // a Thursday
+        assert_eq!(weekday(20_741), 3);
+    }
+
+    #[test]
+    fn challenges_are_the_same_all_day_and_kept() {
+        assert_eq!(Challenge::on(20_741), Challenge::on(20_741));
+        let challenge = Challenge::on(20_741);
+        assert!(DAILY_MUTATORS.contains(&challenge.mutators.len()));
+
+        let mut log = ChallengeLog::default();
+        log.0.insert(20_740, Attempt::Started);
+        log.0.insert(
+            20_741,
+            Attempt::Finished {
+                won: true,
+                score: PerCompetitor {
+                    player: 7,
+                    computer: 4,
+                },
+            },
+        );
+        assert_eq!(ChallengeLog::parse(&log.to_text()), log);
+    }
+}
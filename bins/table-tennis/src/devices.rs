@@ -0,0 +1,339 @@
+// Two players on one machine: before a match against a second player, each presses something
+// on whatever they're going to play with to take the next paddle. The keyboard splits in
+// half (WASD and space, or the arrows and right ctrl) and every gamepad is its own device.
+// From then on each paddle only listens to its own device, see `read_seats`.
+use bevy::prelude::*;
+use bevy_common::screens::{self, AddScreen};
+
+use crate::{
+    actions::{ActionSet, ActionState, KeyLayout, OpponentActions},
+    air_hockey::{self, PaddleStrafe},
+    constants::*,
+    entities::{self, Competitor, PerCompetitor},
+    mutators::MatchState,
+    powerups::PaddleBoost,
+    rules::{MatchRules, Opponent, Variant},
+    settings::Settings,
+    stick, PaddleWallHit, TickSet,
+};
+
+pub const LEFT_HAND: KeyLayout = KeyLayout {
+    up: &[KeyCode::W],
+    down: &[KeyCode::S],
+    left: &[KeyCode::A],
+    right: &[KeyCode::D],
+    smash: &[KeyCode::Space],
+    pause: &[KeyCode::Tab],
+};
+
+pub const RIGHT_HAND: KeyLayout = KeyLayout {
+    up: &[KeyCode::Up],
+    down: &[KeyCode::Down],
+    left: &[KeyCode::Left],
+    right: &[KeyCode::Right],
+    smash: &[KeyCode::ControlRight],
+    pause: &[KeyCode::Tab],
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Device {
+    KeyboardLeft,
+    KeyboardRight,
+    Gamepad(Gamepad),
+}
+
+impl Device {
+    pub fn name(&self) -> String {
+        match self {
+            Device::KeyboardLeft => "Keyboard (WASD)".to_string(),
+            Device::KeyboardRight => "Keyboard (arrows)".to_string(),
+            Device::Gamepad(gamepad) => format!("Gamepad {}", gamepad.id + 1),
+        }
+    }
+
+    fn layout(&self) -> Option<&'static KeyLayout> {
+        match self {
+            Device::KeyboardLeft => Some(&LEFT_HAND),
+            Device::KeyboardRight => Some(&RIGHT_HAND),
+            Device::Gamepad(_) => None,
+        }
+    }
+}
+
+// Which device drives each paddle. Nobody's assigned in a match against the computer, when
+// the player can use any of them.
+#[derive(Resource, Debug, Default)]
+pub struct PlayerDevices(pub PerCompetitor<Option<Device>>);
+
+impl PlayerDevices {
+    // the next paddle without a device
+    fn free_seat(&self) -> Option<Competitor> {
+        Competitor::ALL
+            .into_iter()
+            .find(|competitor| self.0[*competitor].is_none())
+    }
+
+    fn seated(&self, device: Device) -> bool {
+        Competitor::ALL
+            .iter()
+            .any(|competitor| self.0[*competitor] == Some(device))
+    }
+
+    // `device` takes the next free paddle, unless it's already got one. Returns whether
+    // everybody's in.
+    fn join(&mut self, device: Device) -> bool {
+        if !self.seated(device) {
+            if let Some(seat) = self.free_seat() {
+                self.0[seat] = Some(device);
+            }
+        }
+        self.free_seat().is_none()
+    }
+}
+
+// Whether the "press a button to join" screen is up, over the pre-match screen
+#[derive(States, Debug, Clone, Copy, Default, Hash, PartialEq, Eq)]
+pub enum Joining {
+    #[default]
+    Closed,
+    Open,
+}
+
+#[derive(Component)]
+struct JoinScreen;
+
+#[derive(Component)]
+struct JoinScreenText;
+
+pub struct DevicesPlugin;
+
+impl Plugin for DevicesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<Joining>()
+            .init_resource::<PlayerDevices>()
+            .add_screen::<JoinScreen, _>(Joining::Open, spawn_join_screen)
+            .add_systems(OnEnter(MatchState::PreMatch), clear_devices)
+            .add_systems(
+                Update,
+                (join, update_join_screen_text)
+                    .chain()
+                    .run_if(in_state(Joining::Open)),
+            )
+            .add_systems(
+                PreUpdate,
+                read_seats
+                    .after(stick::read_stick)
+                    .in_set(ActionSet::Devices)
+                    .run_if(|devices: Res<PlayerDevices>| devices.0 != PerCompetitor::default()),
+            )
+            .add_systems(
+                FixedUpdate,
+                drive_opponent_paddle
+                    .run_if(|rules: Res<MatchRules>| rules.opponent == Opponent::Local)
+                    .run_if(crate::net::keyboard_is_local)
+                    .in_set(TickSet::Input),
+            );
+    }
+}
+
+fn clear_devices(mut devices: ResMut<PlayerDevices>) {
+    *devices = PlayerDevices::default();
+}
+
+// Whatever was just pressed on, the keyboard's halves before any gamepad
+fn pressed_device(
+    keyboard_input: &Input<KeyCode>,
+    buttons: &Input<GamepadButton>,
+) -> Option<Device> {
+    [Device::KeyboardLeft, Device::KeyboardRight]
+        .into_iter()
+        .find(|device| {
+            device
+                .layout()
+                .is_some_and(|layout| keyboard_input.any_just_pressed(layout.keys().copied()))
+        })
+        .or_else(|| {
+            buttons
+                .get_just_pressed()
+                .next()
+                .map(|button| Device::Gamepad(button.gamepad))
+        })
+}
+
+fn join(
+    keyboard_input: Res<Input<KeyCode>>,
+    buttons: Res<Input<GamepadButton>>,
+    mut devices: ResMut<PlayerDevices>,
+    mut next_joining: ResMut<NextState<Joining>>,
+    mut next_state: ResMut<NextState<MatchState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Back) {
+        *devices = PlayerDevices::default();
+        next_joining.set(Joining::Closed);
+        return;
+    }
+    let Some(device) = pressed_device(&keyboard_input, &buttons) else {
+        return;
+    };
+
+    if devices.join(device) {
+        next_joining.set(Joining::Closed);
+        next_state.set(MatchState::Playing);
+    }
+}
+
+fn join_screen_text(devices: &PlayerDevices) -> String {
+    let seat = |competitor: Competitor| {
+        devices.0[competitor].map_or("press a button to join".to_string(), |device| device.name())
+    };
+    format!(
+        "Player 1: {}\nPlayer 2: {}",
+        seat(Competitor::Player),
+        seat(Competitor::Computer)
+    )
+}
+
+fn spawn_join_screen(mut commands: Commands, devices: Res<PlayerDevices>) {
+    let style = |font_size, color| TextStyle {
+        font_size,
+        color,
+        ..default()
+    };
+
+    commands
+        .spawn((
+            screens::overlay(SETTINGS_MENU_BACKDROP_COLOR, SETTINGS_MENU_Z_INDEX - 1),
+            JoinScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Who's playing?\n\n",
+                style(GAME_OVER_FONT_SIZE, Color::WHITE),
+            ));
+            parent.spawn((
+                TextBundle::from_section(
+                    join_screen_text(&devices),
+                    style(SETTINGS_MENU_FONT_SIZE, Color::WHITE),
+                ),
+                JoinScreenText,
+            ));
+            parent.spawn(TextBundle::from_section(
+                "\n\nwasd + space, arrows + right ctrl, or a gamepad\nbackspace: back",
+                style(SETTINGS_MENU_FONT_SIZE, Color::GRAY),
+            ));
+        });
+}
+
+fn update_join_screen_text(
+    devices: Res<PlayerDevices>,
+    mut texts: Query<&mut Text, With<JoinScreenText>>,
+) {
+    if !devices.is_changed() {
+        return;
+    }
+    for mut text in &mut texts {
+        text.sections[0].value = join_screen_text(&devices);
+    }
+}
+
+// Each paddle's actions from its own device alone
+fn read_seats(
+    keyboard_input: Res<Input<KeyCode>>,
+    axes: Res<Axis<GamepadAxis>>,
+    buttons: Res<Input<GamepadButton>>,
+    settings: Res<Settings>,
+    devices: Res<PlayerDevices>,
+    mut player: ResMut<ActionState>,
+    mut opponent: ResMut<OpponentActions>,
+) {
+    for competitor in Competitor::ALL {
+        let actions = match devices.0[competitor] {
+            None => ActionState::default(),
+            Some(Device::Gamepad(gamepad)) => {
+                stick::gamepad_actions(gamepad, &axes, &buttons, &settings.stick)
+            }
+            Some(device) => device.layout().map_or_else(ActionState::default, |layout| {
+                ActionState::from_keys(&keyboard_input, layout)
+            }),
+        };
+        match competitor {
+            Competitor::Player => *player = actions,
+            Competitor::Computer => opponent.0 = actions,
+        }
+    }
+}
+
+// The second player's paddle, moved the same ways as the first's
+fn drive_opponent_paddle(
+    rules: Res<MatchRules>,
+    actions: Res<OpponentActions>,
+    mut paddles: Query<
+        (
+            &mut Transform,
+            &mut entities::PaddleVelocity,
+            &mut PaddleStrafe,
+            &entities::Side,
+            &Competitor,
+            &PaddleBoost,
+        ),
+        With<entities::Paddle>,
+    >,
+    walls: Query<&Transform, (With<entities::WallSide>, Without<entities::Paddle>)>,
+    settings: Res<Settings>,
+    mut wall_hits: EventWriter<PaddleWallHit>,
+    time: Res<Time>,
+) {
+    let dt = time.delta_seconds();
+    for (mut transform, mut velocity, mut strafe, side, competitor, boost) in &mut paddles {
+        if *competitor != Competitor::Computer {
+            continue;
+        }
+        let max_speed = PADDLE_SPEED * boost.multiplier();
+
+        // volleyball paddles stay on the floor
+        if rules.variant != Variant::Volleyball {
+            if let Some(speed) = crate::drive_paddle(
+                &mut transform,
+                &mut velocity,
+                actions.0.move_axis.y * max_speed,
+                max_speed,
+                &walls,
+                &settings,
+                dt,
+            ) {
+                wall_hits.send(PaddleWallHit { speed });
+            }
+        }
+        if rules.variant != Variant::TableTennis {
+            air_hockey::strafe(
+                &mut transform,
+                &mut strafe,
+                *side,
+                actions.0.move_axis.x * max_speed,
+                max_speed,
+                &settings,
+                dt,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn devices_take_the_next_free_paddle_once() {
+        let mut devices = PlayerDevices::default();
+        assert!(!devices.join(Device::KeyboardRight));
+        assert!(!devices.join(Device::KeyboardRight));
+        assert!(devices.join(Device::Gamepad(Gamepad::new(0))));
+        assert_eq!(
+            devices.0,
+            PerCompetitor {
+                player: Some(Device::KeyboardRight),
+                computer: Some(Device::Gamepad(Gamepad::new(0))),
+            }
+        );
+    }
+}
@@ -0,0 +1,111 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    constants::*, entities::Competitor, mutators::MatchState, rng::GameRng, settings::Settings,
+    CollisionEvent, PointScored, Scoreboard, TickSet,
+};
+
+// How well the adaptive computer is playing, from 0 (its worst) to 1 (as well as it does with
+// adaptation off). It plays better while the player's ahead and worse while they're behind,
+// so matches stay close. How quickly it follows the score is the "Adaptive computer" setting.
+#[derive(Resource, Debug)]
+pub struct Difficulty {
+    pub skill: f32,
+    // how far off the computer is aiming this rally, from -1 to 1 of `MAX_AI_AIM_ERROR`
+    aim_error: f32,
+}
+
+impl Default for Difficulty {
+    fn default() -> Self {
+        Self {
+            skill: STARTING_AI_SKILL,
+            aim_error: 0.0,
+        }
+    }
+}
+
+impl Difficulty {
+    // how long ago the ball was where the computer is reacting to, in seconds
+    pub fn reaction_delay(&self) -> f32 {
+        MAX_AI_REACTION_DELAY * (1.0 - self.skill)
+    }
+
+    pub fn aim_error(&self) -> f32 {
+        MAX_AI_AIM_ERROR * (1.0 - self.skill) * self.aim_error
+    }
+}
+
+// The skill that keeps a match at `player` to `computer` close
+pub fn target_skill(player: usize, computer: usize) -> f32 {
+    let lead = player as f32 - computer as f32;
+    (STARTING_AI_SKILL + lead * AI_SKILL_PER_POINT).clamp(0.0, 1.0)
+}
+
+// A run condition for the computer adapting to the player
+pub fn adaptive(settings: Res<Settings>) -> bool {
+    settings.gameplay.adaptive_ai > 0.0
+}
+
+pub struct DifficultyPlugin;
+
+impl Plugin for DifficultyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Difficulty>()
+            .add_systems(OnExit(MatchState::PreMatch), reset_difficulty)
+            .add_systems(OnExit(MatchState::GameOver), reset_difficulty)
+            .add_systems(
+                FixedUpdate,
+                (adapt_to_score, miss_aim)
+                    .in_set(TickSet::Effects)
+                    .run_if(adaptive),
+            );
+    }
+}
+
+fn reset_difficulty(mut difficulty: ResMut<Difficulty>) {
+    *difficulty = Difficulty::default();
+}
+
+// After every rally, part of the way towards the skill that evens things up
+fn adapt_to_score(
+    mut points: EventReader<PointScored>,
+    scores: Res<Scoreboard>,
+    settings: Res<Settings>,
+    mut difficulty: ResMut<Difficulty>,
+) {
+    if points.read().count() == 0 {
+        return;
+    }
+
+    let target = target_skill(scores[Competitor::Player], scores[Competitor::Computer]);
+    difficulty.skill += (target - difficulty.skill) * settings.gameplay.adaptive_ai;
+}
+
+// Every return gives the computer a new mistake to make
+fn miss_aim(
+    mut collision_events: EventReader<CollisionEvent>,
+    mut difficulty: ResMut<Difficulty>,
+    mut rng: ResMut<GameRng>,
+) {
+    let returned = collision_events
+        .read()
+        .any(|ev| matches!(ev, CollisionEvent::Paddle(..) | CollisionEvent::Partner(..)));
+    if returned {
+        difficulty.aim_error = rng.gen_range(-1.0..=1.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skill_follows_the_lead() {
+        assert_eq!(target_skill(3, 3), STARTING_AI_SKILL);
+        assert!(target_skill(5, 2) > STARTING_AI_SKILL);
+        assert!(target_skill(2, 5) < STARTING_AI_SKILL);
+        assert_eq!(target_skill(0, 100), 0.0);
+        assert_eq!(target_skill(100, 0), 1.0);
+    }
+}
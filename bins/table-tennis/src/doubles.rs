@@ -0,0 +1,392 @@
+// Doubles: each side gets a second paddle further up the court, driven by the computer. It
+// covers whichever half of the court its teammate isn't in, going for a ball coming down
+// that half and leaving the rest to them. Above the player's partner, "MINE" or "YOURS" says
+// which it's doing with the next ball.
+//
+// Only for table tennis played here. The netcode and the other variants all expect one
+// paddle a side.
+use bevy::{prelude::*, sprite::collide_aabb::collide};
+use bevy_common::collision;
+
+use crate::{
+    constants::*,
+    entities::{self, Competitor, Side},
+    mutators::{MatchState, Mutators},
+    net::NetRole,
+    paddle_colors::PaddleColors,
+    prediction,
+    rules::{Ends, MatchRules, Variant},
+    settings::Settings,
+    CollisionEvent, Contact, TickSet,
+};
+
+// Who's taking the next ball coming to a side
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Call {
+    Mine,
+    Yours,
+}
+
+impl Call {
+    fn text(&self) -> &'static str {
+        match self {
+            Call::Mine => "MINE",
+            Call::Yours => "YOURS",
+        }
+    }
+}
+
+// The computer's paddle alongside another on the same side
+#[derive(Component, Debug, Default)]
+pub struct Partner {
+    // covering the top half of the court rather than the bottom
+    top: bool,
+    // what it's doing about the ball that's coming, if one is
+    call: Option<Call>,
+}
+
+// What `partner` is calling
+#[derive(Component, Debug)]
+struct CallText {
+    partner: Entity,
+}
+
+pub struct DoublesPlugin;
+
+impl Plugin for DoublesPlugin {
+    fn build(&self, app: &mut App) {
+        // gone between games, they're put back at whichever end their side's at next
+        app.add_systems(
+            OnEnter(MatchState::Playing),
+            spawn_partners.run_if(playing_doubles),
+        )
+        .add_systems(OnExit(MatchState::Playing), despawn_partners)
+        .add_systems(
+            FixedUpdate,
+            (
+                cover_zones.in_set(TickSet::Input),
+                return_balls
+                    .in_set(TickSet::Physics)
+                    .after(crate::check_ball_bounce_collisions),
+            )
+                .run_if(playing_doubles),
+        )
+        .add_systems(Update, show_calls);
+    }
+}
+
+// Whether doubles can be picked for a match with these rules
+pub fn available(rules: &MatchRules, role: &NetRole) -> bool {
+    rules.variant == Variant::TableTennis && *role == NetRole::Local
+}
+
+fn playing_doubles(rules: Res<MatchRules>, role: Res<NetRole>) -> bool {
+    rules.doubles && available(&rules, &role)
+}
+
+fn spawn_partners(
+    mut commands: Commands,
+    ends: Res<Ends>,
+    colors: Res<PaddleColors>,
+    settings: Res<Settings>,
+) {
+    for side in Side::ALL {
+        let competitor = ends.at(side);
+        let x = side.inset_x(GAP_BETWEEN_PADDLE_AND_WALL + DOUBLES_PARTNER_OFFSET);
+        let partner = commands
+            .spawn((
+                SpriteBundle {
+                    transform: Transform::from_xyz(x, 0.0, 0.0).with_scale(PADDLE_SIZE),
+                    sprite: Sprite {
+                        color: colors.paddle(competitor, settings.theme),
+                        ..default()
+                    },
+                    ..default()
+                },
+                // not a `Collider`, or it'd knock its teammate's returns back at them
+                entities::Paddle,
+                side,
+                entities::PaddleVelocity::default(),
+                Partner::default(),
+            ))
+            .id();
+
+        if competitor == Competitor::Player {
+            commands.spawn((
+                Text2dBundle {
+                    text: Text::from_sections([TextSection::from_style(TextStyle {
+                        font_size: DOUBLES_CALL_FONT_SIZE,
+                        color: DOUBLES_CALL_COLOR,
+                        ..default()
+                    })]),
+                    ..default()
+                },
+                CallText { partner },
+            ));
+        }
+    }
+}
+
+fn despawn_partners(
+    mut commands: Commands,
+    partners: Query<Entity, Or<(With<Partner>, With<CallText>)>>,
+) {
+    for entity in &partners {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn cover_zones(
+    mut partners: Query<
+        (
+            &mut Transform,
+            &mut entities::PaddleVelocity,
+            &mut Partner,
+            &Side,
+        ),
+        With<entities::Paddle>,
+    >,
+    teammates: Query<(&Transform, &Side), (With<entities::Paddle>, Without<Partner>)>,
+    balls: Query<
+        (&Transform, &entities::Velocity),
+        (With<entities::Ball>, Without<entities::Paddle>),
+    >,
+    walls: Query<&Transform, (With<entities::WallSide>, Without<entities::Paddle>)>,
+    mutators: Res<Mutators>,
+    rules: Res<MatchRules>,
+    settings: Res<Settings>,
+    time: Res<Time>,
+) {
+    let predictable = prediction::predictable(mutators, rules);
+    let middle = (TOP_WALL + BOTTOM_WALL) / 2.0;
+
+    for (mut transform, mut velocity, mut partner, side) in &mut partners {
+        // swapping over once the teammate's well into the half it was covering
+        if let Some((teammate, _)) = teammates.iter().find(|(_, s)| *s == side) {
+            let y = teammate.translation.y - middle;
+            if partner.top && y > DOUBLES_SWAP_MARGIN {
+                partner.top = false;
+            } else if !partner.top && y < -DOUBLES_SWAP_MARGIN {
+                partner.top = true;
+            }
+        }
+        let (bottom, top) = if partner.top {
+            (middle, TOP_WALL)
+        } else {
+            (BOTTOM_WALL, middle)
+        };
+
+        // where the first ball heading this way will cross in front of it
+        let distance = |ball: &Transform| (side.wall_x() - ball.translation.x).abs();
+        let arrival = balls
+            .iter()
+            .filter(|(_, velocity)| velocity.x * side.sign() > 0.0)
+            .min_by(|(a, _), (b, _)| distance(a).total_cmp(&distance(b)))
+            .map(|(ball, velocity)| {
+                predictable
+                    .then(|| prediction::predict(ball, velocity.0, &transform, *side))
+                    .and_then(|trajectory| trajectory.intercept)
+                    .map_or(ball.translation.y, |intercept| intercept.position.y)
+            });
+
+        partner.call = arrival.map(|y| {
+            if (bottom..top).contains(&y) {
+                Call::Mine
+            } else {
+                Call::Yours
+            }
+        });
+        let target = match (partner.call, arrival) {
+            (Some(Call::Mine), Some(y)) => y,
+            // otherwise back to the middle of its half
+            _ => (bottom + top) / 2.0,
+        };
+
+        let speed = ((target - transform.translation.y) / time.delta_seconds())
+            .clamp(-MAX_AI_PADDLE_SPEED, MAX_AI_PADDLE_SPEED);
+        crate::drive_paddle(
+            &mut transform,
+            &mut velocity,
+            speed,
+            MAX_AI_PADDLE_SPEED,
+            &walls,
+            &settings,
+            time.delta_seconds(),
+        );
+    }
+}
+
+// Only the balls coming at its side. Ones its teammate's sent back go straight past.
+fn return_balls(
+    mut balls: Query<(Entity, &Transform, &mut entities::Velocity), With<entities::Ball>>,
+    partners: Query<
+        (&Transform, &entities::PaddleVelocity, &Side),
+        (With<Partner>, Without<entities::Ball>),
+    >,
+    settings: Res<Settings>,
+    mut collision_events: EventWriter<CollisionEvent>,
+) {
+    for (ball, ball_transform, mut velocity) in &mut balls {
+        for (transform, paddle_velocity, side) in &partners {
+            if velocity.x * side.sign() <= 0.0
+                || collide(
+                    ball_transform.translation,
+                    ball_transform.scale.truncate(),
+                    transform.translation,
+                    transform.scale.truncate(),
+                )
+                .is_none()
+            {
+                continue;
+            }
+
+            // dragged along with the paddle, as the others do
+            let speed = velocity.length();
+            let mut returned = Vec2::new(-velocity.x, velocity.y);
+            returned.y += paddle_velocity.0 * settings.gameplay.momentum_transfer;
+            velocity.0 = collision::enforce_min_horizontal(
+                returned.normalize_or_zero() * speed,
+                MIN_HORIZONTAL_SPEED_FRACTION,
+            );
+
+            collision_events.send(CollisionEvent::Partner(
+                ball,
+                *side,
+                Contact::along(
+                    ball_transform.translation.y,
+                    transform.translation.y,
+                    transform.scale.y,
+                ),
+            ));
+        }
+    }
+}
+
+fn show_calls(
+    partners: Query<(&Transform, &Partner)>,
+    mut texts: Query<(&mut Text, &mut Transform, &CallText), Without<Partner>>,
+) {
+    for (mut text, mut transform, call_text) in &mut texts {
+        let Ok((partner_transform, partner)) = partners.get(call_text.partner) else {
+            continue;
+        };
+        let call = partner.call.map_or("", |call| call.text());
+        if text.sections[0].value != call {
+            text.sections[0].value = call.to_string();
+        }
+
+        let above = partner_transform.scale.y / 2.0 + DOUBLES_CALL_GAP;
+        transform.translation.x = partner_transform.translation.x;
+        transform.translation.y = partner_transform.translation.y + above;
+        transform.translation.z = 1.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn partners_take_the_half_their_teammate_isnt_in() {
+        let mut app = App::new();
+        app.init_resource::<Mutators>()
+            .init_resource::<MatchRules>()
+            .init_resource::<Settings>()
+            .init_resource::<Time>()
+            .add_systems(Update, cover_zones);
+        app.world
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs_f64(1.0 / 64.0));
+
+        // the player's up at the top
+        app.world.spawn((
+            entities::Paddle,
+            Side::Left,
+            Transform::from_xyz(-390.0, TOP_WALL / 2.0, 0.0).with_scale(PADDLE_SIZE),
+        ));
+        let partner = app
+            .world
+            .spawn((
+                entities::Paddle,
+                Side::Left,
+                Transform::from_xyz(-300.0, 0.0, 0.0).with_scale(PADDLE_SIZE),
+                entities::PaddleVelocity::default(),
+                Partner {
+                    top: true,
+                    call: None,
+                },
+            ))
+            .id();
+        let ball = app
+            .world
+            .spawn((
+                entities::Ball,
+                Transform::from_xyz(200.0, BOTTOM_WALL / 2.0, 0.0).with_scale(BALL_SIZE),
+                entities::Velocity(Vec2::new(-BALL_SPEED, 0.0)),
+            ))
+            .id();
+
+        // coming down the bottom, which the partner's moved over to cover
+        app.update();
+        let covering = app.world.get::<Partner>(partner).unwrap();
+        assert!(!covering.top);
+        assert_eq!(covering.call, Some(Call::Mine));
+        assert!(app.world.get::<Transform>(partner).unwrap().translation.y < 0.0);
+
+        // and at the top it's the player's
+        app.world.get_mut::<Transform>(ball).unwrap().translation.y = TOP_WALL / 2.0;
+        app.update();
+        assert_eq!(
+            app.world.get::<Partner>(partner).unwrap().call,
+            Some(Call::Yours)
+        );
+
+        // nothing's coming once it's heading the other way
+        app.world.get_mut::<entities::Velocity>(ball).unwrap().0.x = BALL_SPEED;
+        app.update();
+        assert_eq!(app.world.get::<Partner>(partner).unwrap().call, None);
+    }
+
+    #[test]
+    fn partners_only_return_balls_coming_their_way() {
+        let mut app = App::new();
+        app.add_event::<CollisionEvent>()
+            .init_resource::<Settings>()
+            .add_systems(Update, return_balls);
+
+        app.world.spawn((
+            Partner::default(),
+            Side::Left,
+            Transform::from_xyz(-300.0, 0.0, 0.0).with_scale(PADDLE_SIZE),
+            entities::PaddleVelocity::default(),
+        ));
+        let mut ball = |velocity: Vec2| {
+            app.world
+                .spawn((
+                    entities::Ball,
+                    Transform::from_xyz(-300.0, 0.0, 0.0).with_scale(BALL_SIZE),
+                    entities::Velocity(velocity),
+                ))
+                .id()
+        };
+        let incoming = ball(Vec2::new(-BALL_SPEED, 0.0));
+        // just sent back by the player behind it
+        let returned = ball(Vec2::new(BALL_SPEED, 0.0));
+
+        let mut reader = app.world.resource::<Events<CollisionEvent>>().get_reader();
+        app.update();
+
+        let velocity = |app: &App, ball| app.world.get::<entities::Velocity>(ball).unwrap().0;
+        assert_eq!(velocity(&app, incoming), Vec2::new(BALL_SPEED, 0.0));
+        assert_eq!(velocity(&app, returned), Vec2::new(BALL_SPEED, 0.0));
+        let hits: Vec<_> = reader
+            .read(app.world.resource::<Events<CollisionEvent>>())
+            .collect();
+        assert!(matches!(
+            hits[..],
+            [CollisionEvent::Partner(ball, Side::Left, _)] if *ball == incoming
+        ));
+    }
+}
@@ -0,0 +1,263 @@
+// Everything about the last match, saved as it ends, for anyone who wants to pick through
+// their games elsewhere:
+//
+//   table-tennis --export-last-match out.json
+//
+// A path ending in .csv gets just the points, one per row. E on the game over screen saves a
+// copy of the match beside the rating and settings.
+use std::{collections::BTreeMap, path::Path};
+
+use bevy::prelude::*;
+use bevy_common::storage;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    constants::*,
+    entities::Competitor,
+    mutators::{MatchState, Mutator, Mutators},
+    rules::{Games, MatchClock, MatchRules, MatchWinner, RuleItem},
+    settings::{Settings, SettingsItem},
+    stats::{Heatmap, MatchStats},
+    toast::Toast,
+};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PointExport {
+    pub game: usize,
+    pub scorer: String,
+    pub points: usize,
+    pub player_score: usize,
+    pub computer_score: usize,
+    pub returns: usize,
+    pub at: f32,
+    pub duration: f32,
+}
+
+// Counted from the top of the paddle or goal down
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HeatmapExport {
+    pub player: Vec<usize>,
+    pub computer: Vec<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MatchExport {
+    pub winner: Option<String>,
+    pub player_games: usize,
+    pub computer_games: usize,
+    // seconds in play, see `rules::MatchClock`
+    pub duration: f32,
+    // as shown on the pre-match screen and in the settings menu
+    pub rules: BTreeMap<String, String>,
+    pub mutators: Vec<String>,
+    pub settings: BTreeMap<String, String>,
+    pub points: Vec<PointExport>,
+    pub returns: HeatmapExport,
+    pub conceded: HeatmapExport,
+}
+
+impl MatchExport {
+    fn new(
+        winner: &MatchWinner,
+        games: &Games,
+        clock: &MatchClock,
+        rules: &MatchRules,
+        mutators: &Mutators,
+        settings: &Settings,
+        stats: &MatchStats,
+    ) -> Self {
+        let heatmap = |heatmaps: &crate::entities::PerCompetitor<Heatmap>| HeatmapExport {
+            player: heatmaps[Competitor::Player].counts().to_vec(),
+            computer: heatmaps[Competitor::Computer].counts().to_vec(),
+        };
+
+        Self {
+            winner: winner.0.map(|winner| winner.name().to_string()),
+            player_games: games.won[Competitor::Player],
+            computer_games: games.won[Competitor::Computer],
+            duration: clock.elapsed,
+            rules: RuleItem::ALL
+                .iter()
+                .map(|item| (item.label().to_string(), item.value(rules)))
+                .collect(),
+            mutators: Mutator::ALL
+                .iter()
+                .filter(|mutator| mutators.contains(**mutator))
+                .map(|mutator| mutator.name().to_string())
+                .collect(),
+            settings: SettingsItem::ALL
+                .iter()
+                .map(|item| (item.label().to_string(), item.value(settings)))
+                .collect(),
+            points: stats
+                .points
+                .iter()
+                .map(|point| PointExport {
+                    game: point.game,
+                    scorer: point.scorer.name().to_string(),
+                    points: point.points,
+                    player_score: point.score[Competitor::Player],
+                    computer_score: point.score[Competitor::Computer],
+                    returns: point.returns,
+                    at: point.at,
+                    duration: point.duration,
+                })
+                .collect(),
+            returns: heatmap(&stats.returns),
+            conceded: heatmap(&stats.conceded),
+        }
+    }
+
+    fn json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("a match always serializes")
+    }
+
+    fn csv(&self) -> String {
+        let mut csv =
+            "game,scorer,points,player_score,computer_score,returns,at,duration\n".to_string();
+        for point in &self.points {
+            csv += &format!(
+                "{},{},{},{},{},{},{:.3},{:.3}\n",
+                point.game,
+                point.scorer,
+                point.points,
+                point.player_score,
+                point.computer_score,
+                point.returns,
+                point.at,
+                point.duration
+            );
+        }
+        csv
+    }
+}
+
+// The path given to `--export-last-match`, if it was
+pub fn path_from_env() -> Option<String> {
+    let mut args = std::env::args().skip(1);
+    if args.next()? != "--export-last-match" {
+        return None;
+    }
+    Some(args.next().unwrap_or_else(|| "last-match.json".to_string()))
+}
+
+pub fn run(path: &str) {
+    let export = storage::load(GAME_NAME, LAST_MATCH_FILE)
+        .and_then(|json| serde_json::from_str::<MatchExport>(&json).ok());
+    let Some(export) = export else {
+        eprintln!("no match has been played yet");
+        std::process::exit(1);
+    };
+
+    let contents = match Path::new(path).extension().and_then(|ext| ext.to_str()) {
+        Some("csv") => export.csv(),
+        _ => export.json(),
+    };
+    if let Err(err) = std::fs::write(path, contents) {
+        eprintln!("couldn't write {path}: {err}");
+        std::process::exit(1);
+    }
+    println!("wrote the last match to {path}");
+}
+
+// The match that's just finished
+#[derive(Resource, Debug)]
+struct LastMatch(MatchExport);
+
+pub struct ExportPlugin;
+
+impl Plugin for ExportPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(MatchState::GameOver), save_last_match)
+            .add_systems(
+                Update,
+                export_on_key
+                    .run_if(in_state(MatchState::GameOver))
+                    .run_if(crate::net::keyboard_is_local)
+                    .run_if(not(crate::kiosk::attracting)),
+            );
+    }
+}
+
+fn save_last_match(
+    mut commands: Commands,
+    winner: Res<MatchWinner>,
+    games: Res<Games>,
+    clock: Res<MatchClock>,
+    rules: Res<MatchRules>,
+    mutators: Res<Mutators>,
+    settings: Res<Settings>,
+    stats: Res<MatchStats>,
+) {
+    let export = MatchExport::new(
+        &winner, &games, &clock, &rules, &mutators, &settings, &stats,
+    );
+    if let Err(err) = storage::save(GAME_NAME, LAST_MATCH_FILE, &export.json()) {
+        warn!("couldn't save the last match: {err}");
+    }
+    commands.insert_resource(LastMatch(export));
+}
+
+fn export_on_key(
+    keyboard_input: Res<Input<KeyCode>>,
+    last: Option<Res<LastMatch>>,
+    mut toasts: EventWriter<Toast>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::E) {
+        return;
+    }
+    let Some(last) = last else {
+        return;
+    };
+
+    let seconds = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+    let file = format!("match-{seconds}.json");
+    let toast = match storage::save(GAME_NAME, &file, &last.0.json()) {
+        Ok(()) => match storage::path(GAME_NAME, &file) {
+            Some(path) => format!("Exported to {}", path.display()),
+            None => "Exported".to_string(),
+        },
+        Err(err) => format!("Couldn't export: {err}"),
+    };
+    toasts.send(Toast(toast));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exports_round_trip_and_flatten_to_csv() {
+        let mut stats = MatchStats::default();
+        stats.points.push(crate::stats::PointRecord {
+            game: 0,
+            scorer: Competitor::Computer,
+            points: 1,
+            score: crate::entities::PerCompetitor {
+                player: 0,
+                computer: 1,
+            },
+            returns: 4,
+            at: 3.5,
+            duration: 3.5,
+        });
+        let export = MatchExport::new(
+            &MatchWinner(Some(Competitor::Computer)),
+            &Games::default(),
+            &MatchClock::default(),
+            &MatchRules::default(),
+            &Mutators::default(),
+            &Settings::default(),
+            &stats,
+        );
+
+        let json: MatchExport = serde_json::from_str(&export.json()).unwrap();
+        assert_eq!(json, export);
+        assert_eq!(
+            export.csv().lines().nth(1),
+            Some("0,Computer,1,0,1,4,3.500,3.500")
+        );
+    }
+}
@@ -0,0 +1,112 @@
+// What happens when the window goes into the background: a single-player match can pause
+// itself (see `idle.rs`), and everything can go quiet until it's back.
+use bevy::{prelude::*, window::WindowFocused};
+
+use crate::{
+    constants::*,
+    idle::{self, Idle, PauseReason},
+    mutators::MatchState,
+    net::NetRole,
+    settings::{MenuState, Settings},
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WhenUnfocused {
+    KeepPlaying,
+    #[default]
+    Pause,
+    Quieten,
+    PauseAndQuieten,
+}
+
+impl WhenUnfocused {
+    pub fn name(&self) -> &'static str {
+        match self {
+            WhenUnfocused::KeepPlaying => "Keep playing",
+            WhenUnfocused::Pause => "Pause",
+            WhenUnfocused::Quieten => "Quieten",
+            WhenUnfocused::PauseAndQuieten => "Pause and quieten",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            WhenUnfocused::KeepPlaying => WhenUnfocused::Pause,
+            WhenUnfocused::Pause => WhenUnfocused::Quieten,
+            WhenUnfocused::Quieten => WhenUnfocused::PauseAndQuieten,
+            WhenUnfocused::PauseAndQuieten => WhenUnfocused::KeepPlaying,
+        }
+    }
+
+    fn pauses(&self) -> bool {
+        matches!(self, WhenUnfocused::Pause | WhenUnfocused::PauseAndQuieten)
+    }
+
+    fn quietens(&self) -> bool {
+        matches!(
+            self,
+            WhenUnfocused::Quieten | WhenUnfocused::PauseAndQuieten
+        )
+    }
+}
+
+// The volume from before it was turned down, to put back
+#[derive(Resource, Debug, Default)]
+struct Ducked(Option<f32>);
+
+pub struct FocusPlugin;
+
+impl Plugin for FocusPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Ducked>()
+            // nothing else is meant to be on a kiosk's screen
+            .add_systems(Update, watch_focus.run_if(not(crate::kiosk::enabled)));
+    }
+}
+
+fn watch_focus(
+    mut focus: EventReader<WindowFocused>,
+    settings: Res<Settings>,
+    role: Res<NetRole>,
+    match_state: Res<State<MatchState>>,
+    menu: Res<State<MenuState>>,
+    mut idle: ResMut<Idle>,
+    mut next_menu: ResMut<NextState<MenuState>>,
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    mut ducked: ResMut<Ducked>,
+    mut volume: ResMut<GlobalVolume>,
+    sinks: Query<&AudioSink>,
+) {
+    let Some(focused) = focus.read().last().map(|ev| ev.focused) else {
+        return;
+    };
+    let when = settings.gameplay.when_unfocused;
+
+    if focused {
+        // keys let go of in another window were never seen being released
+        keyboard_input.reset_all();
+        if let Some(level) = ducked.0.take() {
+            *volume = GlobalVolume::new(level);
+            for sink in &sinks {
+                sink.set_volume(sink.volume() / UNFOCUSED_VOLUME);
+            }
+        }
+        return;
+    }
+
+    // other people's matches carry on without us
+    let single_player = *role == NetRole::Local
+        && *match_state.get() == MatchState::Playing
+        && *menu.get() == MenuState::Closed;
+    if when.pauses() && single_player {
+        idle::pause(&mut idle, PauseReason::FocusLost, &mut next_menu);
+    }
+    if when.quietens() && ducked.0.is_none() {
+        let level = volume.volume.get();
+        ducked.0 = Some(level);
+        *volume = GlobalVolume::new(level * UNFOCUSED_VOLUME);
+        for sink in &sinks {
+            sink.set_volume(sink.volume() * UNFOCUSED_VOLUME);
+        }
+    }
+}
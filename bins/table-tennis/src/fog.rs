@@ -0,0 +1,98 @@
+use bevy::prelude::*;
+
+use crate::{
+    constants::*,
+    entities,
+    mutators::{self, MatchState, Mutator},
+    settings::Settings,
+    CollisionEvent,
+};
+
+// Covers the middle of the court for the fog of war mutator. Balls disappear under it, paddles
+// are never in there.
+#[derive(Component, Debug)]
+struct Fog {
+    // briefly thins the fog after a ball bounces off a wall inside it
+    reveal: Timer,
+}
+
+pub struct FogPlugin;
+
+impl Plugin for FogPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(MatchState::Playing),
+            spawn_fog.run_if(mutators::enabled(Mutator::FogOfWar)),
+        )
+        .add_systems(OnExit(MatchState::Playing), despawn_fog)
+        .add_systems(
+            Update,
+            (reveal_fog, fade_fog)
+                .chain()
+                .run_if(in_state(MatchState::Playing)),
+        );
+    }
+}
+
+fn spawn_fog(mut commands: Commands, settings: Res<Settings>) {
+    let mut reveal = Timer::from_seconds(FOG_REVEAL_DURATION, TimerMode::Once);
+    reveal.tick(reveal.duration());
+
+    commands.spawn((
+        SpriteBundle {
+            transform: Transform {
+                // above the balls and power-ups
+                translation: Vec3::new(0.0, 0.0, FOG_Z),
+                // fit between the walls so they stay visible
+                scale: Vec3::new(
+                    FOG_HALF_WIDTH * 2.0,
+                    TOP_WALL - BOTTOM_WALL - WALL_THICKNESS,
+                    1.0,
+                ),
+                ..default()
+            },
+            sprite: Sprite {
+                color: settings.theme.palette().background,
+                ..default()
+            },
+            ..default()
+        },
+        Fog { reveal },
+    ));
+}
+
+fn despawn_fog(mut commands: Commands, fog: Query<Entity, With<Fog>>) {
+    for fog in &fog {
+        commands.entity(fog).despawn();
+    }
+}
+
+fn reveal_fog(
+    mut collision_events: EventReader<CollisionEvent>,
+    balls: Query<&Transform, With<entities::Ball>>,
+    mut fog: Query<&mut Fog>,
+) {
+    let bounced_in_fog = collision_events.read().any(|ev| match ev {
+        CollisionEvent::Wall(ball, _) => balls
+            .get(*ball)
+            .is_ok_and(|transform| transform.translation.x.abs() < FOG_HALF_WIDTH),
+        _ => false,
+    });
+
+    if bounced_in_fog {
+        for mut fog in &mut fog {
+            fog.reveal.reset();
+        }
+    }
+}
+
+fn fade_fog(settings: Res<Settings>, mut fog: Query<(&mut Sprite, &mut Fog)>, time: Res<Time>) {
+    // follow the theme's background so the fog blends in
+    let background = settings.theme.palette().background;
+
+    for (mut sprite, mut fog) in &mut fog {
+        fog.reveal.tick(time.delta());
+        let alpha = FOG_REVEAL_ALPHA + (1.0 - FOG_REVEAL_ALPHA) * fog.reveal.percent();
+        sprite.color = background.with_a(alpha);
+    }
+}
@@ -0,0 +1,51 @@
+use bevy::prelude::*;
+use bevy_common::fx::{Flash, FlashPlugin, JuiceSettings};
+
+use crate::{constants::*, entities, rules::Ends, settings::Settings, PointScored};
+
+pub struct FxPlugin;
+
+impl Plugin for FxPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(FlashPlugin).add_systems(
+            Update,
+            (
+                apply_juice.run_if(resource_changed::<Settings>()),
+                flash_goals,
+            ),
+        );
+    }
+}
+
+// Hands the game feel slider to every effect that reads `JuiceSettings`, bevy-common's
+// and ours
+fn apply_juice(settings: Res<Settings>, mut juice: ResMut<JuiceSettings>) {
+    let intensity = settings.juice;
+    if juice.intensity != intensity {
+        juice.intensity = intensity;
+    }
+}
+
+// Briefly lights up a goal that was just scored in, fading back to its normal color
+fn flash_goals(
+    mut commands: Commands,
+    mut points: EventReader<PointScored>,
+    goals: Query<(Entity, &entities::Side), With<entities::GoalZone>>,
+    ends: Res<Ends>,
+    settings: Res<Settings>,
+) {
+    let palette = settings.theme.palette();
+
+    for point in points.read() {
+        for (entity, side) in &goals {
+            // the goal the ball went into is the one the scorer attacks
+            if *side != ends.side_of(point.scorer) {
+                commands.entity(entity).insert(Flash::new(
+                    palette.goal_flash,
+                    palette.goal,
+                    GOAL_FLASH_DURATION,
+                ));
+            }
+        }
+    }
+}
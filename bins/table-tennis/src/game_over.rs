@@ -0,0 +1,237 @@
+use bevy::prelude::*;
+use bevy_common::screens::{self, AddScreen};
+
+use crate::{
+    constants::*,
+    difficulty::Difficulty,
+    entities::Competitor,
+    instant_replay::{self, InstantReplay, ReplayFinished},
+    mutators::MatchState,
+    rating::{self, Rating},
+    rules::{format_clock, Games, MatchClock, MatchRules, MatchWinner, OpeningEnds},
+    sequence::{self, SequenceFinished},
+    settings::Settings,
+    stats::{self, MatchStats},
+};
+
+#[derive(Component)]
+struct GameOverScreen;
+
+#[derive(Component)]
+struct IntermissionScreen;
+
+// Counts down the break between games
+#[derive(Resource, Debug, Deref, DerefMut)]
+struct IntermissionTimer(Timer);
+
+impl Default for IntermissionTimer {
+    fn default() -> Self {
+        Self(Timer::from_seconds(INTERMISSION_DURATION, TimerMode::Once))
+    }
+}
+
+pub struct GameOverPlugin;
+
+impl Plugin for GameOverPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<IntermissionTimer>()
+            .add_screen::<GameOverScreen, _>(
+                MatchState::GameOver,
+                spawn_game_over_screen.after(rating::rate_match),
+            )
+            .add_systems(
+                Update,
+                (
+                    reveal_game_over_screen,
+                    leave_game_over_screen
+                        .run_if(not(sequence::playing))
+                        .run_if(not(instant_replay::playing)),
+                )
+                    .run_if(in_state(MatchState::GameOver))
+                    .run_if(crate::net::has_input_authority)
+                    // any key ends a demo instead, see `kiosk.rs`
+                    .run_if(not(crate::kiosk::attracting)),
+            )
+            .add_screen::<IntermissionScreen, _>(
+                MatchState::Intermission,
+                spawn_intermission_screen.after(instant_replay::start_replay),
+            )
+            .add_systems(
+                Update,
+                (
+                    reveal_intermission_screen.run_if(on_event::<ReplayFinished>()),
+                    end_intermission.run_if(not(instant_replay::playing)),
+                )
+                    .run_if(in_state(MatchState::Intermission)),
+            );
+    }
+}
+
+// e.g. "You 2 - 1 Computer"
+fn games_score(games: &Games) -> String {
+    format!(
+        "{} {} - {} {}",
+        Competitor::Player.name(),
+        games.won[Competitor::Player],
+        games.won[Competitor::Computer],
+        Competitor::Computer.name(),
+    )
+}
+
+fn overlay() -> NodeBundle {
+    screens::overlay(SETTINGS_MENU_BACKDROP_COLOR, SETTINGS_MENU_Z_INDEX - 1)
+}
+
+fn style(font_size: f32, color: Color) -> TextStyle {
+    TextStyle {
+        font_size,
+        color,
+        ..default()
+    }
+}
+
+fn spawn_game_over_screen(
+    mut commands: Commands,
+    winner: Res<MatchWinner>,
+    rules: Res<MatchRules>,
+    games: Res<Games>,
+    rating: Res<Rating>,
+    settings: Res<Settings>,
+    difficulty: Res<Difficulty>,
+    match_stats: Res<MatchStats>,
+    clock: Res<MatchClock>,
+) {
+    let headline = match winner.0 {
+        Some(Competitor::Player) => "You win!",
+        Some(Competitor::Computer) => "The computer wins",
+        None => "Game over",
+    };
+
+    let mut sections = vec![TextSection::new(
+        format!("{headline}\n\n"),
+        style(GAME_OVER_FONT_SIZE, Color::WHITE),
+    )];
+    if rules.games > 1 {
+        sections.push(TextSection::new(
+            format!("{}\n\n", games_score(&games)),
+            style(SETTINGS_MENU_FONT_SIZE, Color::WHITE),
+        ));
+    }
+    sections.push(TextSection::new(
+        format!("played for {}\n\n", format_clock(clock.elapsed.floor())),
+        style(SETTINGS_MENU_FONT_SIZE, Color::GRAY),
+    ));
+    if let Some(change) = rating.change {
+        sections.push(TextSection::new(
+            format!("rating {:.0} ({change:+.0})\n\n", rating.rating),
+            style(SETTINGS_MENU_FONT_SIZE, Color::WHITE),
+        ));
+    }
+    if settings.gameplay.adaptive_ai > 0.0 {
+        sections.push(TextSection::new(
+            format!("computer skill {:.0}%\n\n", difficulty.skill * 100.0),
+            style(SETTINGS_MENU_FONT_SIZE, Color::GRAY),
+        ));
+    }
+    let export = if cfg!(feature = "capture") {
+        "e: export  "
+    } else {
+        ""
+    };
+    let hint = TextSection::new(
+        format!("r: rematch  s: swap sides and rematch  {export}enter: new match"),
+        style(SETTINGS_MENU_FONT_SIZE, Color::GRAY),
+    );
+
+    let mut screen = overlay();
+    screen.style.flex_direction = FlexDirection::Column;
+    // held back until the winner's celebrated, see `sequence.rs`
+    if winner.0.is_some() {
+        screen.visibility = Visibility::Hidden;
+    }
+    commands
+        .spawn((screen, GameOverScreen))
+        .with_children(|parent| {
+            parent.spawn(
+                TextBundle::from_sections(sections).with_text_alignment(TextAlignment::Center),
+            );
+            stats::spawn_score_graph(parent, &match_stats);
+            stats::spawn_heatmaps(parent, &match_stats);
+            parent.spawn(TextBundle::from_sections([hint]));
+        });
+}
+
+fn reveal_game_over_screen(
+    mut finished: EventReader<SequenceFinished>,
+    mut screens: Query<&mut Visibility, With<GameOverScreen>>,
+) {
+    if finished
+        .read()
+        .any(|SequenceFinished(name)| *name == "victory")
+    {
+        for mut visibility in &mut screens {
+            *visibility = Visibility::Inherited;
+        }
+    }
+}
+
+// A rematch keeps the rules and mutators and skips the pre-match screen
+fn leave_game_over_screen(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut opening: ResMut<OpeningEnds>,
+    mut next_state: ResMut<NextState<MatchState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        next_state.set(MatchState::PreMatch);
+    } else if keyboard_input.just_pressed(KeyCode::R) {
+        next_state.set(MatchState::Playing);
+    } else if keyboard_input.just_pressed(KeyCode::S) {
+        opening.swap();
+        next_state.set(MatchState::Playing);
+    }
+}
+
+fn spawn_intermission_screen(
+    mut commands: Commands,
+    games: Res<Games>,
+    replay: Res<InstantReplay>,
+    mut timer: ResMut<IntermissionTimer>,
+) {
+    timer.reset();
+
+    let mut screen = overlay();
+    // the countdown starts once the winning point's been replayed
+    if replay.is_playing() {
+        screen.visibility = Visibility::Hidden;
+    }
+    commands
+        .spawn((screen, IntermissionScreen))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_sections([
+                TextSection::new(
+                    format!("Game {}\n\n", games.played() + 1),
+                    style(GAME_OVER_FONT_SIZE, Color::WHITE),
+                ),
+                TextSection::new(
+                    games_score(&games),
+                    style(SETTINGS_MENU_FONT_SIZE, Color::WHITE),
+                ),
+            ]));
+        });
+}
+
+fn reveal_intermission_screen(mut screens: Query<&mut Visibility, With<IntermissionScreen>>) {
+    for mut visibility in &mut screens {
+        *visibility = Visibility::Inherited;
+    }
+}
+
+fn end_intermission(
+    mut timer: ResMut<IntermissionTimer>,
+    mut next_state: ResMut<NextState<MatchState>>,
+    time: Res<Time>,
+) {
+    if timer.tick(time.delta()).just_finished() {
+        next_state.set(MatchState::Playing);
+    }
+}
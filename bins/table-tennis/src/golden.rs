@@ -0,0 +1,201 @@
+// Golden replays: a scripted player plays a few thousand ticks against the computer in the
+// headless app, and where everything ends up is compared with what it did last time, in
+// `golden/`. Anything that changes the physics or the order systems run in shows up here.
+//
+// After a change that's meant to alter the simulation, look over the differences and
+// regenerate them with `UPDATE_GOLDEN=1 cargo test -p table-tennis golden`. That's also how a
+// new one's recorded, a missing one fails.
+use std::{
+    fmt::Write,
+    path::PathBuf,
+    sync::{Mutex, PoisonError},
+    time::Duration,
+};
+
+use bevy::{ecs::schedule::ExecutorKind, log::LogPlugin, prelude::*, time::TimeUpdateStrategy};
+
+use crate::{
+    build_app, entities,
+    mutators::MatchState,
+    net::{self, NetOptions},
+    rng::GameRng,
+    rules::{MatchRules, Variant},
+    settings::Settings,
+    Scoreboard,
+};
+
+const TICKS: usize = 3000;
+// how often positions are written down
+const SAMPLE_EVERY: usize = 250;
+const SEED: u64 = 7373;
+
+const RECORD: &str = "UPDATE_GOLDEN=1 cargo test -p table-tennis golden";
+
+// One replay at a time, each with its own empty data directory, see `check`
+static STORAGE: Mutex<()> = Mutex::new(());
+
+// The player's inputs, held for so many ticks each and repeated: which way up and which way
+// right, from -1 to 1
+const SCRIPT: [(f32, f32, usize); 5] = [
+    (1.0, 0.0, 45),
+    (0.0, 1.0, 20),
+    (-1.0, 0.0, 60),
+    (0.0, -1.0, 15),
+    (1.0, 1.0, 30),
+];
+
+fn script(tick: usize) -> (f32, f32) {
+    let length: usize = SCRIPT.iter().map(|(_, _, ticks)| ticks).sum();
+    let mut tick = tick % length;
+    for (up, right, ticks) in SCRIPT {
+        if tick < ticks {
+            return (up, right);
+        }
+        tick -= ticks;
+    }
+    unreachable!()
+}
+
+fn hold(keyboard_input: &mut Input<KeyCode>, direction: f32, positive: KeyCode, negative: KeyCode) {
+    keyboard_input.release(positive);
+    keyboard_input.release(negative);
+    if direction > 0.0 {
+        keyboard_input.press(positive);
+    } else if direction < 0.0 {
+        keyboard_input.press(negative);
+    }
+}
+
+fn headless_app(variant: Variant) -> App {
+    let mut app = build_app(
+        net::headless_plugins().disable::<LogPlugin>(),
+        NetOptions::default(),
+    );
+    // exactly one fixed tick every update, with systems always run in the same order
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+        1.0 / 64.0,
+    )))
+    .insert_resource(GameRng::seeded(SEED))
+    .insert_resource(MatchRules {
+        variant,
+        ..default()
+    });
+    // serves go straight away and hard hits don't stop the court, the replays are about the
+    // rallies rather than the effects around them
+    let mut settings = app.world.resource_mut::<Settings>();
+    settings.gameplay.serve_countdown = 0.0;
+    settings.juice = 0.0;
+    for (_, schedule) in app.world.resource_mut::<Schedules>().iter_mut() {
+        schedule.set_executor_kind(ExecutorKind::SingleThreaded);
+    }
+
+    app.update();
+    app.world
+        .resource_mut::<NextState<MatchState>>()
+        .set(MatchState::Playing);
+    app
+}
+
+fn sample(world: &mut World, tick: usize, report: &mut String) {
+    let scoreboard = world.resource::<Scoreboard>();
+    let _ = write!(
+        report,
+        "tick {tick}: score {}-{}",
+        scoreboard[entities::Competitor::Player],
+        scoreboard[entities::Competitor::Computer]
+    );
+
+    let mut paddles = world.query::<(&Transform, &entities::Competitor)>();
+    for (transform, competitor) in paddles.iter(world) {
+        let _ = write!(
+            report,
+            " {}@({:.2}, {:.2})",
+            competitor.name(),
+            transform.translation.x,
+            transform.translation.y
+        );
+    }
+    let mut balls =
+        world.query_filtered::<(&Transform, &entities::Velocity), With<entities::Ball>>();
+    for (transform, velocity) in balls.iter(world) {
+        let _ = write!(
+            report,
+            " ball@({:.2}, {:.2}) moving ({:.2}, {:.2})",
+            transform.translation.x, transform.translation.y, velocity.x, velocity.y
+        );
+    }
+    report.push('\n');
+}
+
+fn replay(variant: Variant) -> String {
+    let mut app = headless_app(variant);
+    let mut report = String::new();
+
+    for tick in 0..TICKS {
+        let (up, right) = script(tick);
+        let mut keyboard_input = app.world.resource_mut::<Input<KeyCode>>();
+        hold(&mut keyboard_input, up, KeyCode::Up, KeyCode::Down);
+        hold(&mut keyboard_input, right, KeyCode::Right, KeyCode::Left);
+
+        app.update();
+        if tick % SAMPLE_EVERY == 0 {
+            sample(&mut app.world, tick, &mut report);
+        }
+    }
+
+    report
+}
+
+fn check(name: &str, variant: Variant) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("golden")
+        .join(format!("{name}.txt"));
+
+    // the app loads and saves settings, ratings and unlocks through `storage`. Pointed at an
+    // empty directory, the player's own saves (or another replay's) can't change how it goes.
+    let report = {
+        let _storage = STORAGE.lock().unwrap_or_else(PoisonError::into_inner);
+        let data = std::env::temp_dir().join(format!("golden-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&data);
+        std::env::set_var("XDG_DATA_HOME", &data);
+        let report = replay(variant);
+        let _ = std::fs::remove_dir_all(&data);
+        report
+    };
+
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, report).unwrap();
+        return;
+    }
+    let Ok(golden) = std::fs::read_to_string(&path) else {
+        panic!(
+            "no golden replay at {}, record it with `{RECORD}`",
+            path.display()
+        );
+    };
+    for (line, (expected, actual)) in golden.lines().zip(report.lines()).enumerate() {
+        assert_eq!(
+            actual,
+            expected,
+            "{name} went differently on line {}",
+            line + 1
+        );
+    }
+    assert_eq!(report.lines().count(), golden.lines().count());
+}
+
+#[test]
+fn golden_table_tennis() {
+    check("table_tennis", Variant::TableTennis);
+}
+
+#[test]
+fn golden_air_hockey() {
+    check("air_hockey", Variant::AirHockey);
+}
+
+#[test]
+fn golden_volleyball() {
+    check("volleyball", Variant::Volleyball);
+}
@@ -0,0 +1,213 @@
+// Hit-stop: a smash, or a return of a ball that's already flying, freezes the court for a
+// few ticks so the hit lands with some weight. The fixed timestep keeps its pace through it:
+// frames are still drawn and ticks still come due, they just don't run the simulation while
+// it's stopped, so nothing piles up to be caught up on afterwards. It's counted in ticks
+// rather than seconds so it stops for the same number of them on every machine.
+use bevy::prelude::*;
+use bevy_common::fx::JuiceSettings;
+
+use crate::{
+    constants::*,
+    entities,
+    smash::{self, Smashed},
+    CollisionEvent, TickSet,
+};
+
+#[derive(Resource, Debug, Default)]
+pub struct HitStop {
+    ticks_left: u32,
+    // whether the tick that's running is one of them
+    frozen: bool,
+}
+
+impl HitStop {
+    // for at least `ticks`, another hard hit while it's stopped doesn't add to it
+    pub fn start(&mut self, ticks: u32) {
+        self.ticks_left = self.ticks_left.max(ticks);
+    }
+
+    // whether this tick is a frozen one, using it up
+    fn tick(&mut self) -> bool {
+        let frozen = self.ticks_left > 0;
+        self.ticks_left = self.ticks_left.saturating_sub(1);
+        frozen
+    }
+}
+
+// A run condition for the simulation's ticks, see `TickSet`
+pub fn running(hit_stop: Res<HitStop>) -> bool {
+    !hit_stop.frozen
+}
+
+pub struct HitStopPlugin;
+
+impl Plugin for HitStopPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HitStop>().add_systems(
+            FixedUpdate,
+            (
+                // every tick, stopped or not, so it can start again
+                count_down.before(TickSet::Input),
+                stop_on_hard_hits
+                    .after(smash::smash_returns)
+                    .in_set(TickSet::Effects),
+            ),
+        );
+    }
+}
+
+fn count_down(mut hit_stop: ResMut<HitStop>) {
+    hit_stop.frozen = hit_stop.tick();
+}
+
+fn stop_on_hard_hits(
+    mut collision_events: EventReader<CollisionEvent>,
+    mut smashes: EventReader<Smashed>,
+    balls: Query<&entities::Velocity, With<entities::Ball>>,
+    juice: Res<JuiceSettings>,
+    time: Res<Time>,
+    mut hit_stop: ResMut<HitStop>,
+) {
+    let smashed = smashes.read().count() > 0;
+    let fast = collision_events.read().any(|ev| match ev {
+        CollisionEvent::Paddle(ball, _, _) | CollisionEvent::Partner(ball, _, _) => balls
+            .get(*ball)
+            .is_ok_and(|velocity| velocity.length() >= HIT_STOP_MIN_SPEED),
+        _ => false,
+    });
+    if !smashed && !fast {
+        return;
+    }
+
+    // however many whole ticks come closest, none with the juice all the way down
+    let ticks = juice.scale(HIT_STOP_DURATION) / time.delta_seconds();
+    hit_stop.start(ticks.round() as u32);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{
+        entities::Side,
+        rules::{self, Ends, MatchRules, Streak},
+        Contact, GoalEvent, PointScored,
+    };
+
+    // One ball returned hard on the first tick as another goes in, and how many ticks have
+    // run since
+    #[derive(Resource)]
+    struct Rally {
+        returned: Entity,
+        scored: Entity,
+        ticks: u32,
+    }
+
+    fn rally(
+        mut rally: ResMut<Rally>,
+        mut collisions: EventWriter<CollisionEvent>,
+        mut goals: EventWriter<GoalEvent>,
+    ) {
+        if rally.ticks == 0 {
+            collisions.send(CollisionEvent::Paddle(
+                rally.returned,
+                Side::Left,
+                Contact(0.0),
+            ));
+            goals.send(GoalEvent {
+                ball: rally.scored,
+                defender: Side::Right,
+                crossing: Contact(0.0),
+            });
+        }
+        rally.ticks += 1;
+    }
+
+    #[test]
+    fn stops_for_the_longest_hit() {
+        let mut hit_stop = HitStop::default();
+        assert!(!hit_stop.tick());
+
+        hit_stop.start(3);
+        assert!(hit_stop.tick());
+        // a lighter hit partway through doesn't stretch it
+        hit_stop.start(1);
+        assert!(hit_stop.tick());
+        assert!(hit_stop.tick());
+        assert!(!hit_stop.tick());
+    }
+
+    #[test]
+    fn a_point_on_a_hard_hit_is_scored_once() {
+        let mut app = App::new();
+        app.add_event::<CollisionEvent>()
+            .add_event::<Smashed>()
+            .add_event::<GoalEvent>()
+            .add_event::<PointScored>()
+            .init_resource::<HitStop>()
+            .init_resource::<JuiceSettings>()
+            .init_resource::<MatchRules>()
+            .init_resource::<Streak>()
+            .init_resource::<Ends>()
+            .init_resource::<Time>()
+            .configure_sets(
+                Update,
+                (
+                    TickSet::Input,
+                    TickSet::Physics,
+                    TickSet::Scoring,
+                    TickSet::Effects,
+                )
+                    .chain()
+                    .run_if(running),
+            )
+            .add_systems(
+                Update,
+                (
+                    count_down.before(TickSet::Input),
+                    rally.in_set(TickSet::Physics),
+                    rules::award_points.in_set(TickSet::Scoring),
+                    stop_on_hard_hits.in_set(TickSet::Effects),
+                ),
+            );
+        let returned = app
+            .world
+            .spawn((
+                entities::Ball,
+                entities::Velocity(Vec2::X * HIT_STOP_MIN_SPEED),
+            ))
+            .id();
+        let scored = app
+            .world
+            .spawn((entities::Ball, entities::Velocity(Vec2::NEG_X)))
+            .id();
+        app.insert_resource(Rally {
+            returned,
+            scored,
+            ticks: 0,
+        });
+        let tick = Duration::from_secs_f64(1.0 / 64.0);
+        app.world.resource_mut::<Time>().advance_by(tick);
+
+        let mut reader = app.world.resource::<Events<PointScored>>().get_reader();
+        let mut points = 0;
+        let mut step = |app: &mut App| {
+            app.update();
+            points += reader
+                .read(app.world.resource::<Events<PointScored>>())
+                .count();
+            app.world.resource::<Rally>().ticks
+        };
+
+        // the point's won on the tick that starts the stop
+        assert_eq!(step(&mut app), 1);
+        let frozen = (HIT_STOP_DURATION / tick.as_secs_f32()).round() as u32;
+        assert!(frozen > 0);
+        for _ in 0..frozen {
+            assert_eq!(step(&mut app), 1);
+        }
+        assert_eq!(step(&mut app), 2);
+        assert_eq!(points, 1);
+    }
+}
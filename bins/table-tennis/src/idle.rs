@@ -0,0 +1,149 @@
+// Pauses a match by itself when nobody's pressed anything for a while (someone's walked away),
+// or for `focus.rs` when the window goes into the background. Any key carries on.
+use bevy::prelude::*;
+use bevy_common::screens::{self, AddScreen};
+
+use crate::{
+    constants::*,
+    mutators::MatchState,
+    settings::{MenuState, Settings},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PauseReason {
+    Inactive,
+    FocusLost,
+}
+
+impl PauseReason {
+    fn headline(&self) -> &'static str {
+        match self {
+            PauseReason::Inactive => "Paused due to inactivity",
+            PauseReason::FocusLost => "Paused in the background",
+        }
+    }
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct Idle {
+    // since the last key was pressed or held
+    seconds: f32,
+    reason: Option<PauseReason>,
+}
+
+impl Idle {
+    // whether `delta` more seconds without input is long enough to pause after `limit`
+    fn wait(&mut self, delta: f32, limit: f32) -> bool {
+        self.seconds += delta;
+        limit > 0.0 && self.seconds >= limit
+    }
+}
+
+#[derive(Component)]
+struct PausedScreen;
+
+pub struct IdlePlugin;
+
+impl Plugin for IdlePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Idle>()
+            .add_screen::<PausedScreen, _>(MenuState::Paused, spawn_paused_screen)
+            .add_systems(OnEnter(MatchState::Playing), reset_idle)
+            .add_systems(
+                Update,
+                (
+                    watch_input
+                        .run_if(in_state(MatchState::Playing))
+                        .run_if(in_state(MenuState::Closed))
+                        .run_if(crate::net::has_input_authority)
+                        .run_if(crate::net::keyboard_is_local)
+                        // a kiosk goes back to attract mode instead
+                        .run_if(not(crate::kiosk::enabled)),
+                    resume.run_if(in_state(MenuState::Paused)),
+                ),
+            );
+    }
+}
+
+fn reset_idle(mut idle: ResMut<Idle>) {
+    *idle = Idle::default();
+}
+
+pub fn pause(idle: &mut Idle, reason: PauseReason, next_menu: &mut NextState<MenuState>) {
+    idle.reason = Some(reason);
+    next_menu.set(MenuState::Paused);
+}
+
+fn watch_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    settings: Res<Settings>,
+    mut idle: ResMut<Idle>,
+    mut next_menu: ResMut<NextState<MenuState>>,
+    time: Res<Time<Real>>,
+) {
+    if keyboard_input.get_pressed().next().is_some() {
+        idle.seconds = 0.0;
+        return;
+    }
+    if idle.wait(time.delta_seconds(), settings.gameplay.idle_pause) {
+        pause(&mut idle, PauseReason::Inactive, &mut next_menu);
+    }
+}
+
+fn spawn_paused_screen(mut commands: Commands, idle: Res<Idle>) {
+    let headline = idle.reason.map_or("Paused", |reason| reason.headline());
+
+    commands
+        .spawn((
+            screens::overlay(SETTINGS_MENU_BACKDROP_COLOR, SETTINGS_MENU_Z_INDEX - 1),
+            PausedScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_sections([
+                TextSection::new(
+                    format!("{headline}\n\n"),
+                    TextStyle {
+                        font_size: GAME_OVER_FONT_SIZE,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                ),
+                TextSection::new(
+                    "press any key to carry on",
+                    TextStyle {
+                        font_size: SETTINGS_MENU_FONT_SIZE,
+                        color: Color::GRAY,
+                        ..default()
+                    },
+                ),
+            ]));
+        });
+}
+
+// Tab opens the settings menu as usual
+fn resume(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut idle: ResMut<Idle>,
+    mut next_menu: ResMut<NextState<MenuState>>,
+) {
+    if keyboard_input
+        .get_just_pressed()
+        .any(|key| *key != KeyCode::Tab)
+    {
+        *idle = Idle::default();
+        next_menu.set(MenuState::Closed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pauses_after_the_limit_unless_its_off() {
+        let mut idle = Idle::default();
+        assert!(!idle.wait(30.0, 60.0));
+        assert!(idle.wait(30.0, 60.0));
+        assert!(!idle.wait(600.0, 0.0));
+    }
+}
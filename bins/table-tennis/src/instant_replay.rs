@@ -0,0 +1,190 @@
+// When a goal ends a game or the match, the last few seconds of the rally are played back in
+// slow motion from the `SnapshotBuffer` before the intermission or game over screen comes up.
+// Stand-ins act it out: the real paddles and balls are hidden until it's over. Space or enter
+// skips it.
+use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
+
+use crate::{
+    constants::*,
+    cosmetics::Cosmetics,
+    entities,
+    mutators::MatchState,
+    rules::{self, MatchMode},
+    settings::Settings,
+    snapshot::{Snapshot, SnapshotBuffer},
+};
+
+#[derive(Resource, Debug, Default)]
+pub struct InstantReplay {
+    frames: Vec<Snapshot>,
+    // how far through, in ticks
+    at: f32,
+    ball_look: Option<(Handle<Mesh>, Handle<ColorMaterial>)>,
+}
+
+impl InstantReplay {
+    pub fn is_playing(&self) -> bool {
+        !self.frames.is_empty()
+    }
+
+    // the frame to show, or `None` once it's over
+    fn frame(&self) -> Option<&Snapshot> {
+        self.frames.get(self.at as usize)
+    }
+}
+
+#[derive(Event, Debug, Clone, Copy)]
+pub struct ReplayFinished;
+
+// Acts out a real paddle's part
+#[derive(Component)]
+struct ReplayPaddle(Entity);
+
+#[derive(Component)]
+struct ReplayBall;
+
+#[derive(Component)]
+struct ReplayOverlay;
+
+pub struct InstantReplayPlugin;
+
+impl Plugin for InstantReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InstantReplay>()
+            .add_event::<ReplayFinished>()
+            // only goals end games in points matches, the others run out of time or lives
+            .add_systems(
+                OnEnter(MatchState::GameOver),
+                start_replay.run_if(rules::mode_is(MatchMode::Points)),
+            )
+            .add_systems(
+                OnEnter(MatchState::Intermission),
+                start_replay.run_if(rules::mode_is(MatchMode::Points)),
+            )
+            .add_systems(Update, play_replay.run_if(playing));
+    }
+}
+
+// A run condition for while a replay's on
+pub fn playing(replay: Res<InstantReplay>) -> bool {
+    replay.is_playing()
+}
+
+pub fn start_replay(
+    mut commands: Commands,
+    snapshots: Res<SnapshotBuffer>,
+    mut replay: ResMut<InstantReplay>,
+    settings: Res<Settings>,
+    cosmetics: Res<Cosmetics>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut paddles: Query<(Entity, &Sprite, &mut Visibility), With<entities::Paddle>>,
+    mut balls: Query<&mut Visibility, (With<entities::Ball>, Without<entities::Paddle>)>,
+) {
+    if snapshots.latest().is_none() {
+        return;
+    }
+    *replay = InstantReplay {
+        frames: snapshots.recent(INSTANT_REPLAY_TICKS).cloned().collect(),
+        at: 0.0,
+        ball_look: Some((
+            meshes.add(cosmetics.ball.mesh()),
+            materials.add(ColorMaterial::from(settings.theme.palette().ball)),
+        )),
+    };
+
+    for (entity, sprite, mut visibility) in &mut paddles {
+        *visibility = Visibility::Hidden;
+        commands.spawn((
+            SpriteBundle {
+                sprite: sprite.clone(),
+                ..default()
+            },
+            ReplayPaddle(entity),
+        ));
+    }
+    for mut visibility in &mut balls {
+        *visibility = Visibility::Hidden;
+    }
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(INSTANT_REPLAY_OVERLAY_PADDING),
+                    bottom: Val::Px(INSTANT_REPLAY_OVERLAY_PADDING),
+                    ..default()
+                },
+                ..default()
+            },
+            ReplayOverlay,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "REPLAY",
+                TextStyle {
+                    font_size: INSTANT_REPLAY_FONT_SIZE,
+                    color: INSTANT_REPLAY_COLOR,
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn play_replay(
+    mut commands: Commands,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut replay: ResMut<InstantReplay>,
+    mut actors: Query<(Entity, &ReplayPaddle, &mut Transform)>,
+    replay_balls: Query<Entity, With<ReplayBall>>,
+    overlays: Query<Entity, With<ReplayOverlay>>,
+    mut hidden: Query<&mut Visibility, Or<(With<entities::Paddle>, With<entities::Ball>)>>,
+    mut finished: EventWriter<ReplayFinished>,
+    time: Res<Time>,
+    fixed: Res<Time<Fixed>>,
+) {
+    replay.at += time.delta_seconds() / fixed.timestep().as_secs_f32() * INSTANT_REPLAY_SPEED;
+    let skipped = keyboard_input.any_just_pressed([KeyCode::Space, KeyCode::Return]);
+
+    // the balls are drawn afresh every frame, there could be any number of them
+    for ball in &replay_balls {
+        commands.entity(ball).despawn();
+    }
+
+    let Some(frame) = replay.frame().filter(|_| !skipped) else {
+        // back to the real thing
+        for (actor, _, _) in &actors {
+            commands.entity(actor).despawn();
+        }
+        for overlay in &overlays {
+            commands.entity(overlay).despawn_recursive();
+        }
+        for mut visibility in &mut hidden {
+            *visibility = Visibility::Inherited;
+        }
+        *replay = InstantReplay::default();
+        finished.send(ReplayFinished);
+        return;
+    };
+
+    for (_, actor, mut transform) in &mut actors {
+        if let Some((_, at)) = frame.paddles.iter().find(|(paddle, _)| *paddle == actor.0) {
+            *transform = *at;
+        }
+    }
+    let Some((mesh, material)) = &replay.ball_look else {
+        return;
+    };
+    for ball in &frame.balls {
+        commands.spawn((
+            MaterialMesh2dBundle {
+                mesh: mesh.clone().into(),
+                material: material.clone(),
+                transform: Transform::from_translation(ball.position).with_scale(BALL_SIZE),
+                ..default()
+            },
+            ReplayBall,
+        ));
+    }
+}
@@ -0,0 +1,286 @@
+// Running on a dedicated demo machine, with `--kiosk`: fullscreen with no cursor, Esc doesn't
+// quit and only Ctrl+Alt+Q does. Left alone on the menu or a finished match it goes into
+// attract mode, the computer playing a stand-in for the player at each variant in turn, and
+// any key stops the demo and goes back to the menu. A match somebody's walked away from is
+// ended for them.
+use bevy::{
+    app::AppExit,
+    input::{keyboard::KeyboardInput, ButtonState},
+    prelude::*,
+};
+use bevy_common::screens::AddScreen;
+
+use crate::{
+    actions::{ActionSet, ActionState},
+    constants::*,
+    cosmetics::LockerState,
+    devices::Joining,
+    entities,
+    mutators::{MatchState, Mutators},
+    net::NetOptions,
+    rules::{MatchRules, Opponent, Variant},
+    settings::MenuState,
+    tutorial::TutorialState,
+    volleyball,
+};
+
+#[derive(States, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum AttractState {
+    #[default]
+    Off,
+    Demo,
+}
+
+// The rules and mutators picked before the demos, put back after them
+#[derive(Resource, Debug)]
+struct Stash {
+    rules: MatchRules,
+    mutators: Mutators,
+}
+
+#[derive(Resource, Debug, Default)]
+struct Attract {
+    // since somebody last pressed a key
+    idle: f32,
+    // since the last demo finished
+    break_time: f32,
+    next: Variant,
+}
+
+#[derive(Component)]
+struct AttractPrompt;
+
+pub struct KioskPlugin {
+    pub enabled: bool,
+}
+
+impl Plugin for KioskPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<AttractState>();
+        if !self.enabled {
+            return;
+        }
+
+        app.init_resource::<Attract>()
+            .add_screen::<AttractPrompt, _>(AttractState::Demo, spawn_prompt)
+            // the stand-in player pushes its paddle in place of the keyboard
+            .add_systems(
+                PreUpdate,
+                steer
+                    .in_set(ActionSet::StandIns)
+                    .run_if(in_state(AttractState::Demo))
+                    .run_if(in_state(MatchState::Playing)),
+            )
+            .add_systems(
+                Update,
+                (
+                    quit_on_combo,
+                    wait_for_players
+                        .run_if(in_state(AttractState::Off))
+                        .run_if(in_state(TutorialState::Off)),
+                    (wake, next_demo.run_if(in_state(MatchState::GameOver)))
+                        .run_if(in_state(AttractState::Demo)),
+                ),
+            );
+    }
+}
+
+// A run condition for running as a kiosk
+pub fn enabled(options: Res<NetOptions>) -> bool {
+    options.kiosk
+}
+
+// A run condition for a demo playing, which nobody's really playing
+pub fn attracting(state: Res<State<AttractState>>) -> bool {
+    *state.get() == AttractState::Demo
+}
+
+fn pressed_anything(keys: &mut EventReader<KeyboardInput>) -> bool {
+    keys.read().any(|key| key.state == ButtonState::Pressed)
+}
+
+fn quit_on_combo(keyboard_input: Res<Input<KeyCode>>, mut exit: EventWriter<AppExit>) {
+    if keyboard_input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight])
+        && keyboard_input.any_pressed([KeyCode::AltLeft, KeyCode::AltRight])
+        && keyboard_input.just_pressed(KeyCode::Q)
+    {
+        exit.send(AppExit);
+    }
+}
+
+fn wait_for_players(
+    mut commands: Commands,
+    mut keys: EventReader<KeyboardInput>,
+    mut attract: ResMut<Attract>,
+    mut rules: ResMut<MatchRules>,
+    mut mutators: ResMut<Mutators>,
+    match_state: Res<State<MatchState>>,
+    mut next_state: ResMut<NextState<MatchState>>,
+    mut next_attract: ResMut<NextState<AttractState>>,
+    mut next_menu: ResMut<NextState<MenuState>>,
+    mut next_joining: ResMut<NextState<Joining>>,
+    mut next_locker: ResMut<NextState<LockerState>>,
+    time: Res<Time<Real>>,
+) {
+    if pressed_anything(&mut keys) {
+        attract.idle = 0.0;
+        return;
+    }
+    attract.idle += time.delta_seconds();
+
+    match match_state.get() {
+        MatchState::PreMatch | MatchState::GameOver if attract.idle >= KIOSK_ATTRACT_DELAY => {
+            commands.insert_resource(Stash {
+                rules: std::mem::take(&mut *rules),
+                mutators: std::mem::take(&mut *mutators),
+            });
+            next_menu.set(MenuState::Closed);
+            next_joining.set(Joining::Closed);
+            next_locker.set(LockerState::Closed);
+            start_demo(&mut attract, &mut rules, &mut next_state);
+            next_attract.set(AttractState::Demo);
+        }
+        // back to the menu, where attract mode takes over straight away
+        MatchState::Playing | MatchState::Intermission if attract.idle >= KIOSK_ABANDON_DELAY => {
+            attract.idle = KIOSK_ATTRACT_DELAY;
+            next_menu.set(MenuState::Closed);
+            next_state.set(MatchState::PreMatch);
+        }
+        _ => (),
+    }
+}
+
+// A quick match against the computer at the next variant
+fn start_demo(
+    attract: &mut Attract,
+    rules: &mut MatchRules,
+    next_state: &mut NextState<MatchState>,
+) {
+    *rules = MatchRules {
+        variant: attract.next,
+        opponent: Opponent::Computer,
+        target_score: Some(KIOSK_DEMO_TARGET_SCORE),
+        ..default()
+    };
+    attract.next = attract.next.next();
+    attract.break_time = 0.0;
+    next_state.set(MatchState::Playing);
+}
+
+fn next_demo(
+    mut attract: ResMut<Attract>,
+    mut rules: ResMut<MatchRules>,
+    mut next_state: ResMut<NextState<MatchState>>,
+    time: Res<Time<Real>>,
+) {
+    attract.break_time += time.delta_seconds();
+    if attract.break_time >= KIOSK_DEMO_BREAK {
+        start_demo(&mut attract, &mut rules, &mut next_state);
+    }
+}
+
+// Any key at all puts back whatever was picked and goes to the menu for a real match
+fn wake(
+    mut commands: Commands,
+    mut keys: EventReader<KeyboardInput>,
+    mut attract: ResMut<Attract>,
+    stash: Option<Res<Stash>>,
+    mut next_state: ResMut<NextState<MatchState>>,
+    mut next_attract: ResMut<NextState<AttractState>>,
+) {
+    if !pressed_anything(&mut keys) {
+        return;
+    }
+
+    if let Some(stash) = stash {
+        commands.insert_resource(stash.rules.clone());
+        commands.insert_resource(stash.mutators.clone());
+        commands.remove_resource::<Stash>();
+    }
+    attract.idle = 0.0;
+    next_attract.set(AttractState::Off);
+    next_state.set(MatchState::PreMatch);
+}
+
+// The stand-in heads straight for the ball coming its way, or under it in volleyball. The
+// smoke test plays with it too, see `smoke_test.rs`.
+pub fn steer(
+    rules: Res<MatchRules>,
+    mut actions: ResMut<ActionState>,
+    paddles: Query<(&Transform, &entities::Side), (With<entities::Player>, With<entities::Paddle>)>,
+    balls: Query<
+        (&Transform, &entities::Velocity),
+        (With<entities::Ball>, Without<entities::Paddle>),
+    >,
+) {
+    let Ok((paddle, side)) = paddles.get_single() else {
+        return;
+    };
+    let position = paddle.translation.truncate();
+    let ball = balls
+        .iter()
+        .find(|(_, velocity)| velocity.x * side.sign() > 0.0)
+        .or_else(|| balls.iter().next());
+
+    let target = ball.map_or(position, |(ball, velocity)| {
+        let at = ball.translation.truncate();
+        if rules.variant != Variant::Volleyball {
+            return at;
+        }
+        let top = paddle.translation.y + paddle.scale.y / 2.0;
+        Vec2::new(
+            volleyball::landing_x(at, velocity.0, top).unwrap_or(at.x),
+            position.y,
+        )
+    });
+    actions.move_axis = towards(position, target);
+}
+
+fn towards(from: Vec2, to: Vec2) -> Vec2 {
+    ((to - from) / KIOSK_AUTOPILOT_RANGE).clamp(Vec2::NEG_ONE, Vec2::ONE)
+}
+
+fn spawn_prompt(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    bottom: Val::Px(KIOSK_PROMPT_PADDING),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            AttractPrompt,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "press any key to play",
+                TextStyle {
+                    font_size: KIOSK_PROMPT_FONT_SIZE,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ));
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steering_eases_off_near_the_ball() {
+        let paddle = Vec2::new(-300.0, 0.0);
+        assert_eq!(
+            towards(paddle, Vec2::new(-300.0, 400.0)),
+            Vec2::new(0.0, 1.0)
+        );
+        assert_eq!(
+            towards(paddle, Vec2::new(-300.0, -KIOSK_AUTOPILOT_RANGE / 2.0)),
+            Vec2::new(0.0, -0.5)
+        );
+    }
+}
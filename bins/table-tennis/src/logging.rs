@@ -0,0 +1,142 @@
+// Logging through `tracing`: everything goes to stderr at the level picked with `--log-level`
+// (or whatever `RUST_LOG` says), and the most recent lines are kept for the log window, which
+// shows under the diagnostics on F3. Goals and points are logged at debug level, like
+// collisions. Building with the `trace` feature wraps every system in a span, so each line
+// says which system it's from.
+use std::{collections::VecDeque, fmt::Write, sync::Mutex};
+
+use bevy::{
+    log::Level,
+    prelude::*,
+    utils::tracing::{
+        field::{Field, Visit},
+        Event as TracingEvent, Subscriber,
+    },
+};
+use tracing_subscriber::{layer::Context, prelude::*, EnvFilter, Layer};
+
+use crate::{constants::*, GoalEvent, PointScored};
+
+// The last `LOG_WINDOW_LINES` lines logged, oldest first
+static RECENT: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+// Keeps every line that gets past the filter for the log window
+struct Recent;
+
+impl<S: Subscriber> Layer<S> for Recent {
+    fn on_event(&self, event: &TracingEvent<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        let mut line = format!("{} {}:", metadata.level(), metadata.target());
+        event.record(&mut LineWriter(&mut line));
+
+        let Ok(mut recent) = RECENT.lock() else {
+            return;
+        };
+        if recent.len() >= LOG_WINDOW_LINES {
+            recent.pop_front();
+        }
+        recent.push_back(line);
+    }
+}
+
+// The message, then any other fields as `name=value`
+struct LineWriter<'a>(&'a mut String);
+
+impl Visit for LineWriter<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        let _ = if field.name() == "message" {
+            write!(self.0, " {value:?}")
+        } else {
+            write!(self.0, " {}={value:?}", field.name())
+        };
+    }
+}
+
+// Stands in for bevy's `LogPlugin`, which has no way to add the log window's layer
+pub struct LoggingPlugin {
+    pub level: Level,
+}
+
+impl Plugin for LoggingPlugin {
+    fn build(&self, app: &mut App) {
+        let filter = EnvFilter::try_from_default_env()
+            .or_else(|_| EnvFilter::try_new(format!("{},{LOG_FILTER}", self.level)))
+            .unwrap_or_else(|_| EnvFilter::new(LOG_FILTER));
+        let subscriber = tracing_subscriber::registry()
+            .with(filter)
+            .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
+            .with(Recent);
+        // there's only one per process, a second app keeps the first's
+        if subscriber.try_init().is_err() {
+            warn!("logging was already set up");
+        }
+
+        app.add_systems(Startup, spawn_log_window).add_systems(
+            Update,
+            (log_gameplay_events, toggle_log_window, update_log_window).chain(),
+        );
+    }
+}
+
+#[derive(Component)]
+struct LogWindow;
+
+fn spawn_log_window(mut commands: Commands) {
+    let mut window = TextBundle::from_section(
+        "",
+        TextStyle {
+            font_size: LOG_WINDOW_FONT_SIZE,
+            color: Color::WHITE,
+            ..default()
+        },
+    )
+    .with_style(Style {
+        position_type: PositionType::Absolute,
+        bottom: Val::Px(DIAGNOSTICS_PADDING),
+        left: Val::Px(DIAGNOSTICS_PADDING),
+        max_width: Val::Percent(LOG_WINDOW_WIDTH),
+        ..default()
+    })
+    .with_background_color(SETTINGS_MENU_BACKDROP_COLOR);
+    window.visibility = Visibility::Hidden;
+
+    commands.spawn((window, LogWindow));
+}
+
+fn log_gameplay_events(mut goals: EventReader<GoalEvent>, mut points: EventReader<PointScored>) {
+    for goal in goals.read() {
+        debug!(?goal, "goal");
+    }
+    for point in points.read() {
+        debug!(?point, "point scored");
+    }
+}
+
+// along with the network diagnostics
+fn toggle_log_window(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut windows: Query<&mut Visibility, With<LogWindow>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::F3) {
+        return;
+    }
+    for mut visibility in &mut windows {
+        *visibility = match *visibility {
+            Visibility::Hidden => Visibility::Visible,
+            _ => Visibility::Hidden,
+        };
+    }
+}
+
+fn update_log_window(mut windows: Query<(&mut Text, &Visibility), With<LogWindow>>) {
+    for (mut text, visibility) in &mut windows {
+        if *visibility == Visibility::Hidden {
+            continue;
+        }
+        let Ok(recent) = RECENT.lock() else {
+            continue;
+        };
+        let lines: Vec<&str> = recent.iter().map(String::as_str).collect();
+        text.sections[0].value = lines.join("\n");
+    }
+}
@@ -1,29 +1,193 @@
+// Bevy systems routinely take "complex" queries and lots of parameters;
+// https://github.com/bevyengine/bevy/issues/4601
+#![allow(clippy::type_complexity, clippy::too_many_arguments)]
+
 use std::{
     collections::HashSet,
-    iter::{repeat, Flatten, Repeat},
+    hash::{Hash, Hasher},
 };
 
 use bevy::{
-    prelude::*,
-    sprite::collide_aabb::{collide, Collision},
+    app::PluginGroupBuilder, prelude::*, sprite::collide_aabb::collide,
     sprite::MaterialMesh2dBundle,
 };
+use bevy_common::{collision, motion};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+mod actions;
+mod air_hockey;
+mod arena;
+mod asset_check;
+mod audio_bus;
+#[cfg(feature = "debug-tools")]
+mod bench;
+mod celebration;
+mod cosmetics;
+mod crowd;
+#[cfg(feature = "post-processing")]
+mod crt;
+mod daily;
+mod devices;
+mod difficulty;
+mod doubles;
+#[cfg(feature = "capture")]
+mod export;
+mod focus;
+mod fog;
+mod fx;
+mod game_over;
+#[cfg(test)]
+mod golden;
+mod hit_stop;
+mod idle;
+mod instant_replay;
+mod kiosk;
+mod logging;
+mod match_point;
+#[cfg(feature = "debug-tools")]
+mod metrics;
+mod minimap;
+mod mods;
+mod movement;
+mod music;
+mod mutators;
+mod net;
+mod orientation;
+mod overload;
+mod pacing;
+mod paddle_colors;
+mod personality;
+mod portals;
+#[cfg(feature = "post-processing")]
+mod post_processing;
+mod powerups;
+mod prediction;
+mod presence;
+mod rating;
+mod registry;
+mod replay;
+mod rng;
+mod rules;
+mod rumble;
+mod savestate;
+mod sequence;
+mod serve;
+mod settings;
+mod smash;
+mod smoke_test;
+mod snapshot;
+mod split_screen;
+mod stats;
+mod stick;
+mod synth;
+mod theme;
+mod toast;
+mod tutorial;
+mod units;
+mod volleyball;
+mod wall_fx;
 
 mod constants {
     use bevy::prelude::*;
-    // These constants are defined in `Transform` units.
-    // Using the default 2D camera they correspond 1:1 with screen pixels.
+    // These constants are defined in court units, a centimetre each, which have nothing to
+    // do with screen pixels, see `units.rs`.
     pub const PADDLE_SIZE: Vec3 = Vec3::new(20.0, 120.0, 0.0);
     pub const GAP_BETWEEN_PADDLE_AND_WALL: f32 = 60.0;
     pub const PADDLE_SPEED: f32 = 500.0;
     // How close can the paddle get to the wall
     pub const PADDLE_PADDING: f32 = 10.0;
+    // units/s², see `movement::AccelerationCurve`
+    pub const PADDLE_ACCELERATION: f32 = 4000.0;
+    pub const PADDLE_FRICTION: f32 = 3000.0;
+    // the smooth curve never drops below this fraction of full acceleration
+    pub const SMOOTH_ACCELERATION_FLOOR: f32 = 0.2;
+    pub const DEFAULT_MOMENTUM_TRANSFER: f32 = 0.2;
+    pub const MOMENTUM_TRANSFER_STEP: f32 = 0.05;
+    pub const MAX_MOMENTUM_TRANSFER: f32 = 0.5;
+    // how far past its ends the player's paddle still returns a ball, in units
+    pub const DEFAULT_EDGE_GRACE: f32 = 3.0;
+    pub const MAX_EDGE_GRACE: f32 = 8.0;
+    // how many ticks a smash press is held on to for, see `smash.rs`
+    pub const DEFAULT_INPUT_BUFFER: u32 = 4;
+    pub const MAX_INPUT_BUFFER: u32 = 12;
+    // fraction of its speed a paddle keeps when it bounces off a wall
+    pub const PADDLE_WALL_RESTITUTION: f32 = 0.2;
+    pub const THUD_MIN_SPEED: f32 = 250.0;
+    // how far each play of a sound can stray from its sample's speed (and so pitch) and
+    // volume, see `audio_bus.rs`
+    pub const SOUND_SPEED_VARIATION: std::ops::RangeInclusive<f32> = 0.94..=1.06;
+    pub const SOUND_VOLUME_VARIATION: std::ops::RangeInclusive<f32> = 0.85..=1.0;
+    // XORed with the seed for the sounds' own stream, see `rng.rs`
+    pub const SOUND_RNG_STREAM: u64 = 0x50_55_4e_44;
+    // playback speed of the beep used for goals, slower is lower
+    pub const GOAL_SOUND_SPEED: f32 = 0.5;
+    pub const GOAL_FLASH_DURATION: f32 = 0.4;
+    pub const GOAL_FLASH_COLOR: Color = Color::WHITE;
+    pub const NEON_GOAL_FLASH_COLOR: Color = Color::rgb(4.0, 1.5, 4.0);
 
     // We set the z-value of the ball to 1 so it renders on top in the case of overlapping sprites.
     pub const BALL_STARTING_POSITION: Vec3 = Vec3::new(-50.0, 0.0, 1.0);
     pub const BALL_SIZE: Vec3 = Vec3::new(30.0, 30.0, 0.0);
     pub const BALL_SPEED: f32 = 400.0;
-    pub const INITIAL_BALL_DIRECTION: Vec2 = Vec2::new(0.5, -0.5);
+    // serves leave at a random angle up to this far either side of horizontal
+    pub const DEFAULT_SERVE_CONE_DEGREES: f32 = 45.0;
+    pub const SERVE_CONE_STEP_DEGREES: f32 = 5.0;
+    // anything wider spends too long bouncing between the walls
+    pub const MAX_SERVE_CONE_DEGREES: f32 = 60.0;
+    // the share of a ball's speed that always goes across the court, so it can't end up
+    // bouncing straight up and down. Matches `MAX_SERVE_CONE_DEGREES`.
+    pub const MIN_HORIZONTAL_SPEED_FRACTION: f32 = 0.5;
+    // a ball moving across the court slower than this share of its speed is stalling...
+    pub const STALL_HORIZONTAL_SPEED_FRACTION: f32 = 0.2;
+    // ...and gets nudged back into play once it's been stalling this long, in seconds
+    pub const STALL_TIMEOUT: f32 = 1.0;
+    pub const MAX_BALLS: usize = 5;
+    pub const DEFAULT_MULTI_BALLS: usize = 3;
+    // multi-ball serves are spread out vertically by this much
+    pub const MULTI_BALL_SPACING: f32 = 45.0;
+    // cells of the collision broad phase, a few balls across
+    pub const BROAD_PHASE_CELL_SIZE: f32 = 100.0;
+
+    // The picked arena's thumbnail on the pre-match screen, see `arena.rs`
+    pub const ARENA_PREVIEW_SIZE: Vec2 = Vec2::new(320.0, 180.0);
+    pub const ARENA_PREVIEW_MARGIN: f32 = 40.0;
+
+    // Mutators, see `mutators.rs`
+    // units/s² pulling balls down
+    pub const GRAVITY: f32 = 300.0;
+    pub const BIG_PADDLES_MULTIPLIER: f32 = 1.4;
+    // balls speed up by this much on every return, up to the max
+    pub const SPEED_RAMP_MULTIPLIER: f32 = 1.05;
+    pub const MAX_RAMPED_BALL_SPEED: f32 = 900.0;
+    pub const OBSTACLE_SIZE: Vec3 = Vec3::new(20.0, 80.0, 0.0);
+    pub const OBSTACLE_POSITIONS: [Vec2; 2] = [Vec2::new(0.0, 150.0), Vec2::new(0.0, -150.0)];
+    // the fog covers the middle third of the court
+    pub const FOG_HALF_WIDTH: f32 = (RIGHT_WALL - LEFT_WALL) / 6.0;
+    pub const FOG_Z: f32 = 2.0;
+    // how see-through the fog gets when a ball bounces inside it, and for how long
+    pub const FOG_REVEAL_ALPHA: f32 = 0.4;
+    pub const FOG_REVEAL_DURATION: f32 = 0.25;
+    // portals sit over the middle of the top and bottom walls, poking out past them
+    pub const PORTAL_X: f32 = 0.0;
+    pub const PORTAL_SIZE: Vec3 = Vec3::new(120.0, WALL_THICKNESS * 3.0, 0.0);
+    pub const PORTAL_COLOR: Color = Color::rgb(0.3, 0.8, 1.0);
+    pub const SWIRL_SIZE: Vec3 = Vec3::new(40.0, 40.0, 0.0);
+    pub const SWIRL_DURATION: f32 = 0.4;
+    // radians/s at the end of the swirl
+    pub const SWIRL_SPIN_SPEED: f32 = 30.0;
+
+    // Air hockey, see `air_hockey.rs`
+    // how tall the slot in each end wall is
+    pub const AIR_HOCKEY_GOAL_HEIGHT: f32 = 200.0;
+    // fraction of its speed the puck loses every second
+    pub const PUCK_FRICTION: f32 = 0.4;
+    pub const MAX_PUCK_SPEED: f32 = 900.0;
+    // Volleyball, see `volleyball.rs`
+    pub const NET_SIZE: Vec3 = Vec3::new(10.0, 160.0, 0.0);
+    // a ball that's bumped leaves at least this fast upwards, and towards the net
+    pub const BUMP_SPEED: f32 = 480.0;
+    pub const BUMP_FORWARD_SPEED: f32 = 120.0;
 
     pub const WALL_THICKNESS: f32 = 10.0;
     // x coordinates
@@ -32,54 +196,663 @@ mod constants {
     // y coordinates
     pub const BOTTOM_WALL: f32 = -300.;
     pub const TOP_WALL: f32 = 300.;
-
-    // Update the paddle position,
-    // making sure it doesn't cause the paddle to leave the arena
-    pub const PADDLE_TOP_BOUND: f32 =
-        TOP_WALL - WALL_THICKNESS / 2.0 - PADDLE_SIZE.y / 2.0 - PADDLE_PADDING;
-    pub const PADDLE_BOTTOM_BOUND: f32 =
-        BOTTOM_WALL + WALL_THICKNESS / 2.0 + PADDLE_SIZE.y / 2.0 + PADDLE_PADDING;
+    // how much of the world a window shows at least, the court with room around it, and on
+    // end in portrait, see `orientation.rs`
+    pub const COURT_VIEW: Vec2 = Vec2::new(1280.0, 720.0);
+    pub const PORTRAIT_VIEW: Vec2 = Vec2::new(720.0, 1280.0);
+    // the picture-in-picture, see `minimap.rs`. Its size is a fraction of the window's width.
+    pub const DEFAULT_MINIMAP_SIZE: f32 = 0.25;
+    pub const MINIMAP_SIZE_STEP: f32 = 0.05;
+    pub const MAX_MINIMAP_SIZE: f32 = 0.5;
+    // logical pixels in from the window's corner
+    pub const MINIMAP_MARGIN: f32 = 16.0;
+    pub const MINIMAP_BACKGROUND: Color = Color::rgb(0.02, 0.02, 0.05);
+    // how far out of view the court can be before it counts, in world units
+    pub const MINIMAP_LEEWAY: f32 = 1.0;
+    // see `split_screen.rs`, the room left around the court in each half and the render layer
+    // nothing's on, for the main camera to draw only the UI
+    pub const SPLIT_SCREEN_MARGIN: f32 = 20.0;
+    pub const SPLIT_SCREEN_UI_LAYER: u8 = 31;
 
     // These values are exact
     pub const BACKGROUND_COLOR: Color = Color::BLACK;
     pub const PADDLE_COLOR: Color = Color::WHITE;
     pub const BALL_COLOR: Color = Color::RED;
     pub const WALL_COLOR: Color = Color::DARK_GRAY;
+    pub const GOAL_COLOR: Color = Color::rgb(0.12, 0.12, 0.12);
+    pub const SCOREBOARD_COLOR: Color = Color::GRAY;
+
+    // Neon theme. Channels above 1.0 are intentional: they only glow once bloom is applied
+    // to the HDR camera.
+    pub const NEON_BACKGROUND_COLOR: Color = Color::rgb(0.02, 0.0, 0.05);
+    pub const NEON_PADDLE_COLOR: Color = Color::rgb(0.5, 2.5, 3.0);
+    pub const NEON_BALL_COLOR: Color = Color::rgb(4.0, 0.6, 2.5);
+    pub const NEON_WALL_COLOR: Color = Color::rgb(0.6, 0.2, 1.6);
+    pub const NEON_GOAL_COLOR: Color = Color::rgb(0.25, 0.05, 0.4);
+    pub const NEON_SCOREBOARD_COLOR: Color = Color::rgb(1.2, 1.2, 1.6);
 
     pub const MAX_AI_PADDLE_SPEED: f32 = 500.0;
+    // which end of the court the keyboard starts at unless the settings say otherwise, the AI
+    // takes the other
+    pub const PLAYER_SIDE: super::entities::Side = super::entities::Side::Right;
+    // receives the first serve of the first game, after that it alternates between games
+    pub const FIRST_SERVE_RECEIVER: super::entities::Competitor =
+        super::entities::Competitor::Player;
 
     pub const SCOREBOARD_FONT_SIZE: f32 = 40.0;
     pub const SCOREBOARD_PADDING_X: f32 =
         WALL_THICKNESS + GAP_BETWEEN_PADDLE_AND_WALL + (RIGHT_WALL - LEFT_WALL) / 5.0;
     pub const SCOREBOARD_PADDING_Y: f32 = (TOP_WALL - BOTTOM_WALL) / 10.0 + WALL_THICKNESS;
+    pub const STREAK_FONT_SIZE: f32 = 24.0;
+    pub const GAME_OVER_FONT_SIZE: f32 = 48.0;
+    // timed matches, in seconds
+    pub const DEFAULT_MATCH_LENGTH: f32 = 180.0;
+    pub const MATCH_LENGTH_STEP: f32 = 30.0;
+    pub const MAX_MATCH_LENGTH: f32 = 600.0;
+    pub const MATCH_CLOCK_FONT_SIZE: f32 = 32.0;
+    pub const MATCH_CLOCK_COLOR: Color = Color::WHITE;
+    // how long the match has been going, along the bottom
+    pub const MATCH_DURATION_FONT_SIZE: f32 = 18.0;
+    pub const MATCH_DURATION_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.4);
+    pub const MAX_TARGET_SCORE: usize = 21;
+    pub const MAX_GAMES: usize = 7;
+    pub const INTERMISSION_DURATION: f32 = 3.0;
+    // the ghost opponent's paddle is see-through
+    pub const GHOST_ALPHA: f32 = 0.4;
+    pub const DEFAULT_LIVES: usize = 3;
+    pub const MAX_LIVES: usize = 9;
+    pub const LIFE_ICON_SIZE: Vec3 = Vec3::new(10.0, 10.0, 0.0);
+    pub const LIFE_ICON_SPACING: f32 = 16.0;
+    pub const LIFE_ICON_COLOR: Color = Color::RED;
+    pub const STREAK_COLOR: Color = Color::GOLD;
+
+    // see `doubles.rs`. Partners play this much further up the court than their teammates,
+    // and swap halves once their teammate's this far into the one they were covering.
+    pub const DOUBLES_PARTNER_OFFSET: f32 = 90.0;
+    pub const DOUBLES_SWAP_MARGIN: f32 = 40.0;
+    pub const DOUBLES_CALL_FONT_SIZE: f32 = 18.0;
+    pub const DOUBLES_CALL_COLOR: Color = Color::WHITE;
+    // between the top of the partner's paddle and its call
+    pub const DOUBLES_CALL_GAP: f32 = 16.0;
+
+    // Networking, see `net/`
+    pub const DEFAULT_PORT: u16 = 7373;
+    // hosts take browser clients over WebSockets on the port after the UDP one
+    #[cfg(feature = "networking")]
+    pub const WEBSOCKET_PORT_OFFSET: u16 = 1;
+    // comfortably under any real network's MTU
+    #[cfg(feature = "networking")]
+    pub const MAX_DATAGRAM_SIZE: usize = 1200;
+    // clients check in this often, in seconds, and are dropped after going quiet for
+    // the timeout
+    #[cfg(feature = "networking")]
+    pub const KEEPALIVE_INTERVAL: f32 = 1.0;
+    #[cfg(feature = "networking")]
+    pub const PEER_TIMEOUT: f32 = 3.0;
+    // a headless server has no monitor to sync frames to
+    pub const SERVER_FRAME_RATE: f64 = 60.0;
+    // in seconds after a match ends
+    #[cfg(feature = "networking")]
+    pub const SERVER_REMATCH_DELAY: f32 = 5.0;
+    // how long, in seconds, a player who drops out mid-match has to come back
+    #[cfg(feature = "networking")]
+    pub const RECONNECT_TIMEOUT: f32 = 30.0;
+    pub const LIVE_FONT_SIZE: f32 = 20.0;
+    pub const LIVE_COLOR: Color = Color::RED;
+    pub const LIVE_PADDING_Y: f32 = SCOREBOARD_PADDING_Y / 2.0;
+    // how much of each new round trip measurement goes into the smoothed one
+    #[cfg(feature = "networking")]
+    pub const ROUND_TRIP_SMOOTHING: f32 = 0.1;
+    pub const DIAGNOSTICS_FONT_SIZE: f32 = 16.0;
+    pub const DIAGNOSTICS_PADDING: f32 = 8.0;
+    // see `logging.rs`. The window's width is a percentage of the screen's.
+    pub const LOG_FILTER: &str = "wgpu=error,naga=warn";
+    pub const LOG_WINDOW_LINES: usize = 20;
+    pub const LOG_WINDOW_FONT_SIZE: f32 = 12.0;
+    pub const LOG_WINDOW_WIDTH: f32 = 60.0;
+    // see `synth.rs`. The beep's for sounds it has no tones for, pitch in Hz, length in seconds.
+    pub const SYNTH_FILE: &str = "synth.ron";
+    pub const SYNTH_SAMPLE_RATE: u32 = 44100;
+    pub const SYNTH_BEEP_PITCH: f32 = 880.0;
+    pub const SYNTH_BEEP_LENGTH: f32 = 0.1;
+    pub const SYNTH_BEEP_VOLUME: f32 = 0.5;
+    pub const ASSET_PROBLEMS_COLOR: Color = Color::rgb(1.0, 0.6, 0.3);
+    // unacknowledged reliable messages go out again this often, in seconds, and the last
+    // this many received are remembered to drop duplicates
+    #[cfg(feature = "networking")]
+    pub const RELIABLE_RESEND_INTERVAL: f32 = 0.2;
+    #[cfg(feature = "networking")]
+    pub const RELIABLE_HISTORY: usize = 64;
+    // the host sends a checksum every so many ticks, and clients keep this many snapshots to
+    // check them against, see `net/desync.rs`
+    #[cfg(feature = "networking")]
+    pub const DESYNC_CHECK_INTERVAL: u32 = 32;
+    #[cfg(feature = "networking")]
+    pub const DESYNC_HISTORY: usize = 128;
+    #[cfg(feature = "networking")]
+    pub const DESYNC_FILE: &str = "desync.txt";
+    pub const EMOTE_DURATION: f32 = 2.5;
+    // emotes fade out over the end of their duration
+    pub const EMOTE_FADE_DURATION: f32 = 0.5;
+    pub const EMOTE_FONT_SIZE: f32 = 20.0;
+    pub const EMOTE_COLOR: Color = Color::WHITE;
+    pub const EMOTE_INSET_X: f32 = 60.0;
+    pub const EMOTE_PADDING_Y: f32 = 10.0;
+
+    // Elo, see `rating.rs`
+    pub const STARTING_RATING: f32 = 1000.0;
+    pub const COMPUTER_RATING: f32 = 1200.0;
+    // the most a single match can move a rating
+    pub const RATING_K: f32 = 32.0;
+    // kept between runs with `bevy_common::storage`
+    pub const GAME_NAME: &str = "table-tennis";
+    pub const RATING_FILE: &str = "rating";
+    // the daily challenge, see `daily.rs`
+    pub const DAILY_FILE: &str = "daily";
+    pub const DAILY_TARGET_SCORE: usize = 7;
+    // how many mutators a day's challenge has on
+    pub const DAILY_MUTATORS: std::ops::RangeInclusive<usize> = 1..=3;
+    // XORed with the day for its challenge's seed
+    pub const DAILY_SEED_STREAM: u64 = 0x44_41_49_4c_59;
+    pub const DAILY_WON_COLOR: Color = Color::rgb(0.3, 0.9, 0.4);
+    pub const DAILY_LOST_COLOR: Color = Color::rgb(1.0, 0.35, 0.3);
+    // `--bench-sim`, see `bench.rs`
+    #[cfg(feature = "debug-tools")]
+    pub const BENCH_TICKS: usize = 10_000;
+    #[cfg(feature = "debug-tools")]
+    pub const BENCH_BALLS: usize = 200;
+    #[cfg(feature = "debug-tools")]
+    pub const BENCH_OBSTACLES: usize = 50;
+    #[cfg(feature = "debug-tools")]
+    pub const BENCH_OBSTACLE_SIZE: f32 = 40.0;
+    #[cfg(feature = "debug-tools")]
+    pub const BENCH_TICK_RATE: f64 = 64.0;
+    // `--smoke-test`, see `smoke_test.rs`
+    pub const SMOKE_TEST_TICKS: usize = 900;
+    // the player's stand-in stops going for the ball after so many returns
+    pub const SMOKE_TEST_RALLY_RETURNS: usize = 2;
+    // F6's trajectory lines, see `prediction.rs`
+    pub const TRAJECTORY_MAX_BOUNCES: usize = 4;
+    pub const TRAJECTORY_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.4);
+    // the incoming ball's path in practice, in dashes this many ticks long with gaps as long
+    pub const PRACTICE_PATH_DASH: usize = 3;
+    pub const PRACTICE_PATH_MAX_TIME: f32 = 4.0;
+    pub const PRACTICE_PATH_COLOR: Color = Color::rgba(1.0, 0.9, 0.4, 0.6);
+    // the adaptive computer, see `difficulty.rs`
+    pub const STARTING_AI_SKILL: f32 = 0.5;
+    // how much each point the player leads by raises the skill it heads for
+    pub const AI_SKILL_PER_POINT: f32 = 0.15;
+    // at its worst, in seconds and units
+    pub const MAX_AI_REACTION_DELAY: f32 = 0.25;
+    pub const MAX_AI_AIM_ERROR: f32 = 80.0;
+    pub const ADAPTIVE_AI_STEP: f32 = 0.25;
+    // pausing by itself, see `idle.rs`, in seconds
+    pub const DEFAULT_IDLE_PAUSE: f32 = 60.0;
+    pub const IDLE_PAUSE_STEP: f32 = 30.0;
+    pub const MAX_IDLE_PAUSE: f32 = 300.0;
+    // how loud everything is while the window's in the background, see `focus.rs`
+    pub const UNFOCUSED_VOLUME: f32 = 0.2;
+    // running on a demo machine, see `kiosk.rs`, in seconds
+    pub const KIOSK_ATTRACT_DELAY: f32 = 30.0;
+    // a match left alone this long is over
+    pub const KIOSK_ABANDON_DELAY: f32 = 45.0;
+    // how long a demo's result stays up before the next one
+    pub const KIOSK_DEMO_BREAK: f32 = 5.0;
+    pub const KIOSK_DEMO_TARGET_SCORE: usize = 3;
+    // how far off the ball the stand-in player has to be to move at full speed
+    pub const KIOSK_AUTOPILOT_RANGE: f32 = 40.0;
+    pub const KIOSK_PROMPT_FONT_SIZE: f32 = 32.0;
+    pub const KIOSK_PROMPT_PADDING: f32 = 60.0;
+    // computer personalities, see `personality.rs`
+    pub const SMASH_SPEEDUP: f32 = 1.5;
+    // the least of its top speed a tired computer keeps
+    pub const MIN_AI_STAMINA: f32 = 0.5;
+    // scripted sequences' text and arrow, see `sequence.rs`
+    pub const SEQUENCE_TEXT_PADDING: f32 = 40.0;
+    pub const SEQUENCE_ARROW_LENGTH: f32 = 60.0;
+    // between the arrow's tip and what it's pointing at
+    pub const SEQUENCE_ARROW_GAP: f32 = 20.0;
+    pub const SEQUENCE_ARROW_COLOR: Color = Color::YELLOW;
+    // announcement banners, see `toast.rs`
+    pub const TOAST_TOP: f32 = 100.0;
+    pub const TOAST_PADDING: f32 = 20.0;
+    // how far off the right of the screen a banner slides in from
+    pub const TOAST_SLIDE_DISTANCE: f32 = 600.0;
+    // in seconds
+    pub const TOAST_SLIDE_DURATION: f32 = 0.25;
+    pub const TOAST_DURATION: f32 = 2.0;
+    pub const TOAST_FONT_SIZE: f32 = 32.0;
+    // a rally has to be at least this long to be announced as the longest yet
+    pub const LONGEST_RALLY_MIN_RETURNS: usize = 10;
+    // the score graph on the game over screen, see `stats.rs`
+    pub const SCORE_GRAPH_SIZE: Vec2 = Vec2::new(480.0, 120.0);
+    pub const SCORE_GRAPH_LINE_WIDTH: f32 = 2.0;
+    pub const SCORE_GRAPH_COLOR: Color = Color::WHITE;
+    // the even-score line through the middle
+    pub const SCORE_GRAPH_AXIS_COLOR: Color = Color::DARK_GRAY;
+    // the heatmaps of where returns and goals land, top to bottom
+    pub const HEATMAP_BUCKETS: usize = 8;
+    pub const HEATMAP_CELL_SIZE: Vec2 = Vec2::new(40.0, 14.0);
+    pub const HEATMAP_COLD_COLOR: Color = Color::rgb(0.15, 0.15, 0.3);
+    pub const HEATMAP_HOT_COLOR: Color = Color::rgb(1.0, 0.4, 0.1);
+    // how fast the paddle has to be going for a return to count as angled
+    pub const ANGLED_RETURN_MIN_PADDLE_SPEED: f32 = 100.0;
+    // aim assist's marker, see `prediction::AimAssist`
+    pub const AIM_ASSIST_MAX_SPEED: f32 = 600.0;
+    pub const AIM_MARKER_SIZE: Vec3 = Vec3::new(6.0, 30.0, 1.0);
+    pub const AIM_MARKER_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.25);
+    // under the ball and paddles
+    pub const AIM_MARKER_Z: f32 = -1.0;
+    #[cfg(feature = "debug-tools")]
+    pub const METRICS_FILE: &str = "metrics.csv";
+    // saved as every match ends, see `export.rs`
+    #[cfg(feature = "capture")]
+    pub const LAST_MATCH_FILE: &str = "last-match.json";
+    // the metrics graph shows this many of the most recent frames, see `metrics.rs`
+    #[cfg(feature = "debug-tools")]
+    pub const METRICS_GRAPH_SAMPLES: usize = 120;
+    #[cfg(feature = "debug-tools")]
+    pub const METRICS_GRAPH_BAR_WIDTH: f32 = 2.0;
+    #[cfg(feature = "debug-tools")]
+    pub const METRICS_GRAPH_HEIGHT: f32 = 80.0;
+    // pixels per millisecond
+    #[cfg(feature = "debug-tools")]
+    pub const METRICS_GRAPH_SCALE: f32 = 2.0;
+    // a frame at 60 Hz, in milliseconds
+    #[cfg(feature = "debug-tools")]
+    pub const METRICS_FRAME_BUDGET: f32 = 1000.0 / 60.0;
+
+    pub const SETTINGS_MENU_FONT_SIZE: f32 = 24.0;
+    pub const SETTINGS_MENU_BACKDROP_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.8);
+    pub const SETTINGS_MENU_Z_INDEX: i32 = 100;
+
+    // a gamepad's analog stick, from 0 to 1 pushed, see `stick.rs`
+    pub const DEFAULT_STICK_DEAD_ZONE: f32 = 0.15;
+    pub const STICK_DEAD_ZONE_STEP: f32 = 0.05;
+    pub const MAX_STICK_DEAD_ZONE: f32 = 0.5;
+    pub const DEFAULT_STICK_SENSITIVITY: f32 = 1.0;
+    pub const STICK_SENSITIVITY_STEP: f32 = 0.1;
+    pub const MIN_STICK_SENSITIVITY: f32 = 0.5;
+    pub const MAX_STICK_SENSITIVITY: f32 = 2.0;
+    // the settings menu's picture of the stick, in pixels
+    pub const STICK_VISUALIZER_SIZE: f32 = 120.0;
+    pub const STICK_VISUALIZER_PADDING: f32 = 40.0;
+    pub const STICK_DOT_SIZE: f32 = 10.0;
+    pub const STICK_VISUALIZER_COLOR: Color = Color::rgb(0.15, 0.15, 0.15);
+    pub const STICK_DEAD_ZONE_COLOR: Color = Color::rgb(0.3, 0.1, 0.1);
+    // gamepad rumble, see `rumble.rs`, from 0 to 1 and in seconds
+    pub const DEFAULT_RUMBLE_HITS: f32 = 0.4;
+    pub const DEFAULT_RUMBLE_GOALS: f32 = 0.8;
+    pub const DEFAULT_RUMBLE_SMASHES: f32 = 0.6;
+    pub const RUMBLE_STEP: f32 = 0.1;
+    // the gentlest a return ever feels, however slow the ball
+    pub const MIN_RUMBLE_HIT_FRACTION: f32 = 0.25;
+    pub const RUMBLE_HIT_DURATION: f32 = 0.08;
+    pub const RUMBLE_GOAL_DURATION: f32 = 0.35;
+    pub const RUMBLE_SMASH_DURATION: f32 = 0.15;
+
+    pub const DEFAULT_BLOOM_INTENSITY: f32 = 0.3;
+    pub const BLOOM_INTENSITY_STEP: f32 = 0.05;
+    // how much of the effects to show, see `fx.rs`
+    pub const DEFAULT_JUICE: f32 = 1.0;
+    pub const JUICE_STEP: f32 = 0.1;
+    // the frame rate caps to pick from, 0 for none, see `pacing.rs`
+    pub const FRAME_CAPS: [u32; 5] = [0, 30, 60, 120, 144];
+    // how often menus and pauses redraw with power saving on, in seconds
+    pub const POWER_SAVING_FRAME_TIME: f32 = 0.1;
+    // the fixed ticks' real time over the match time they simulate, see `overload.rs`
+    pub const OVERLOAD_LOAD: f32 = 0.9;
+    // low enough that ticking at the full rate again doesn't overload it straight back
+    pub const OVERLOAD_CLEARED: f32 = 0.4;
+    // how much of each frame's load goes into the smoothed one
+    pub const OVERLOAD_SMOOTHING: f32 = 0.05;
+    pub const OVERLOAD_WARNING_COLOR: Color = Color::rgb(1.0, 0.6, 0.2);
+
+    #[cfg(feature = "post-processing")]
+    pub const VIGNETTE_IMAGE_SIZE: u32 = 256;
+    // fraction of the distance to the corner where the darkening starts
+    #[cfg(feature = "post-processing")]
+    pub const VIGNETTE_INNER_RADIUS: f32 = 0.45;
+    // alpha at the very corners
+    #[cfg(feature = "post-processing")]
+    pub const VIGNETTE_STRENGTH: f32 = 0.85;
+    #[cfg(feature = "post-processing")]
+    pub const VIGNETTE_Z_INDEX: i32 = 10;
+
+    // CRT filter, all in screen UV space
+    #[cfg(feature = "post-processing")]
+    pub const CRT_SCANLINE_INTENSITY: f32 = 0.35;
+    #[cfg(feature = "post-processing")]
+    pub const CRT_SCANLINE_COUNT: f32 = 320.0;
+    #[cfg(feature = "post-processing")]
+    pub const CRT_CURVATURE: f32 = 0.08;
+    #[cfg(feature = "post-processing")]
+    pub const CRT_CHROMATIC_ABERRATION: f32 = 0.006;
+
+    pub const POWERUP_SIZE: Vec3 = Vec3::new(24.0, 24.0, 0.0);
+    pub const POWERUP_SPAWN_INTERVAL: f32 = 6.0;
+    pub const MAX_POWERUPS: usize = 2;
+    // power-ups only appear in the middle of the court, so neither side can camp them
+    pub const POWERUP_SPAWN_HALF_WIDTH: f32 = 150.0;
+    pub const POWERUP_SPAWN_HALF_HEIGHT: f32 = 200.0;
+    // how long an effect lasts once collected, in seconds
+    pub const POWERUP_DURATION: f32 = 8.0;
+    pub const PADDLE_RESIZE_DURATION: f32 = 0.3;
+    pub const GROW_PADDLE_MULTIPLIER: f32 = 1.5;
+    pub const SHRINK_OPPONENT_MULTIPLIER: f32 = 0.6;
+    pub const GROW_PADDLE_COLOR: Color = Color::GREEN;
+    pub const SHRINK_OPPONENT_COLOR: Color = Color::ORANGE;
+    pub const BOOST_SPEED_MULTIPLIER: f32 = 1.6;
+    pub const BOOST_COLOR: Color = Color::CYAN;
+
+    // see `paddle_colors.rs`
+    pub const PADDLE_COLORS_FILE: &str = "paddle_colors";
+    // how much brighter the picked colors are in the neon theme
+    pub const NEON_SWATCH_BRIGHTNESS: f32 = 2.5;
+    // how often a moving paddle drops a ghost behind it, in seconds
+    pub const PADDLE_TRAIL_INTERVAL: f32 = 0.03;
+    pub const PADDLE_TRAIL_LIFETIME: f32 = 0.25;
+    pub const PADDLE_TRAIL_MIN_DISTANCE: f32 = 4.0;
+    pub const PADDLE_TRAIL_ALPHA: f32 = 0.5;
+
+    // see `cosmetics.rs`
+    pub const COSMETICS_FILE: &str = "cosmetics";
+    pub const COSMETICS_LONG_RALLY: usize = 20;
+    pub const COSMETICS_HIGH_RATING: f32 = 1300.0;
+    pub const PADDLE_BAND_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.4);
+    pub const PADDLE_CAP_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.7);
+    pub const STREAK_TRAIL_LIFETIME_MULTIPLIER: f32 = 3.0;
+    pub const STREAK_TRAIL_WIDTH: f32 = 0.4;
+
+    // the end of a match, see `celebration.rs`
+    pub const CELEBRATION_BOUNCE_HEIGHT: f32 = 40.0;
+    // how far over to the winner's paddle the camera goes
+    pub const CELEBRATION_FOCUS_SHARE: f32 = 0.4;
+    pub const CONFETTI_COLORS: [Color; 5] = [
+        Color::rgb(1.0, 0.3, 0.3),
+        Color::rgb(1.0, 0.85, 0.2),
+        Color::rgb(0.3, 0.9, 0.4),
+        Color::rgb(0.3, 0.6, 1.0),
+        Color::rgb(0.9, 0.4, 1.0),
+    ];
+    pub const CONFETTI_SIZE: Vec2 = Vec2::new(6.0, 10.0);
+    // how far above the court it starts, and below it goes
+    pub const CONFETTI_SPREAD: f32 = 300.0;
+    pub const CONFETTI_MIN_SPEED: f32 = 120.0;
+    pub const CONFETTI_MAX_SPEED: f32 = 260.0;
+    pub const CONFETTI_SWAY: f32 = 60.0;
+    pub const CONFETTI_SWAY_SPEED: f32 = 4.0;
+    // radians a second
+    pub const CONFETTI_SPIN: f32 = 3.0;
+    pub const CONFETTI_Z: f32 = 6.0;
+    // when each note plays, and how fast the beep's sped up for it
+    pub const FANFARE_NOTES: [(f32, f32); 4] = [(0.0, 1.0), (0.15, 1.25), (0.3, 1.5), (0.5, 2.0)];
+
+    // see `audio_bus.rs`. Intervals and lengths are in seconds.
+    pub const MUSIC_POLYPHONY: usize = 4;
+    pub const SFX_POLYPHONY: usize = 6;
+    pub const SFX_MIN_INTERVAL: f32 = 0.05;
+    pub const MUSIC_DUCK_VOLUME: f32 = 0.3;
+    pub const MUSIC_DUCK_LENGTH: f32 = 0.8;
+
+    // see `crowd.rs`. The murmur's loudest once a rally's this many returns long. Decay and
+    // smoothing are per second.
+    pub const CROWD_RALLY_RETURNS: usize = 16;
+    pub const CROWD_MURMUR_QUIET: f32 = 0.1;
+    pub const CROWD_MURMUR_LOUD: f32 = 0.45;
+    pub const CROWD_MATCH_POINT_EXCITEMENT: f32 = 0.6;
+    pub const CROWD_CHEER_VOLUME: f32 = 0.7;
+    pub const CROWD_ERUPTION_DECAY: f32 = 1.2;
+    pub const CROWD_SMOOTHING: f32 = 4.0;
+
+    // see `music.rs`. The intensity's at its height once a rally's this many returns long
+    // or the ball's at its fastest. A layer takes `MUSIC_LAYER_FADE` of it to come in.
+    pub const MUSIC_RALLY_RETURNS: usize = 12;
+    pub const MUSIC_ARP_ENTERS_AT: f32 = 0.35;
+    pub const MUSIC_DRUMS_ENTER_AT: f32 = 0.7;
+    pub const MUSIC_LAYER_FADE: f32 = 0.2;
+    pub const MUSIC_VOLUME: f32 = 0.5;
+    // how much faster it's going at its most intense
+    pub const MUSIC_SPEED_UP: f32 = 0.08;
+    pub const MUSIC_SMOOTHING: f32 = 3.0;
+
+    // see `mods.rs`. Sounds are in seconds.
+    pub const MODS_DIR: &str = "mods";
+    pub const MOD_MAX_HDR_CHANNEL: f32 = 10.0;
+    pub const MOD_MAX_SOUND_LENGTH: f32 = 30.0;
+
+    // see `match_point.rs`
+    pub const MATCH_POINT_EDGE_WIDTH: f32 = 12.0;
+    pub const MATCH_POINT_EDGE_COLOR: Color = Color::rgba(1.0, 0.15, 0.1, 0.6);
+    pub const MATCH_POINT_BANNER_COLOR: Color = Color::rgb(1.0, 0.3, 0.25);
+    pub const MATCH_POINT_BANNER_FONT_SIZE: f32 = 36.0;
+    pub const MATCH_POINT_BANNER_TOP: f32 = 70.0;
+    // how dim the glow and banner get between heartbeats
+    pub const MATCH_POINT_MIN_ALPHA: f32 = 0.35;
+    pub const MATCH_POINT_HEARTBEAT_PERIOD: f32 = 0.9;
+    // when in each period the two thuds land
+    pub const MATCH_POINT_HEARTBEAT_BEATS: [f32; 2] = [0.0, 0.22];
+    pub const MATCH_POINT_HEARTBEAT_VOLUME: f32 = 0.35;
+    pub const MATCH_POINT_HEARTBEAT_SPEED: f32 = 0.6;
+
+    // see `snapshot.rs`, ten seconds of ticks at bevy's default 64 a second
+    pub const SNAPSHOT_BUFFER_TICKS: usize = 640;
+
+    // see `instant_replay.rs`, five seconds of ticks at bevy's default 64 a second
+    pub const INSTANT_REPLAY_TICKS: usize = 320;
+    pub const INSTANT_REPLAY_SPEED: f32 = 0.5;
+    pub const INSTANT_REPLAY_FONT_SIZE: f32 = 32.0;
+    pub const INSTANT_REPLAY_COLOR: Color = Color::rgb(1.0, 0.25, 0.2);
+    pub const INSTANT_REPLAY_OVERLAY_PADDING: f32 = 24.0;
+
+    // see `serve.rs`. The countdown's in seconds, 0 for serving straight away.
+    pub const DEFAULT_SERVE_COUNTDOWN: f32 = 0.6;
+    pub const SERVE_COUNTDOWN_STEP: f32 = 0.2;
+    pub const MAX_SERVE_COUNTDOWN: f32 = 1.6;
+    // rings pulsing out from the ball, this many over the countdown
+    pub const SERVE_RING_PULSES: f32 = 2.0;
+    pub const SERVE_RING_RADIUS: f32 = 45.0;
+    pub const SERVE_ARROW_LENGTH: f32 = 60.0;
+    // between the ball's edge and the arrow's tail
+    pub const SERVE_ARROW_GAP: f32 = 10.0;
+
+    // see `wall_fx.rs`. Strengths are how far the goals go towards their flash color at the
+    // height of a pulse. Smoothing's per second.
+    pub const GOAL_PULSE_PERIOD: f32 = 2.4;
+    pub const GOAL_PULSE_STRENGTH: f32 = 0.15;
+    pub const GOAL_PULSE_MATCH_POINT_STRENGTH: f32 = 0.5;
+    pub const GOAL_PULSE_SMOOTHING: f32 = 3.0;
+
+    // see `hit_stop.rs`. A return at this speed or more stops the court, as does a smash.
+    pub const HIT_STOP_MIN_SPEED: f32 = 750.0;
+    // in seconds, rounded to whole ticks
+    pub const HIT_STOP_DURATION: f32 = 0.04;
 }
 
 mod entities {
+    use std::ops::{Index, IndexMut};
+
     use super::constants::*;
     use bevy::prelude::*;
+    use serde::{Deserialize, Serialize};
 
-    #[derive(Component, Debug, Clone, Hash, PartialEq, Eq)]
+    #[derive(
+        Component, Debug, Default, Clone, Hash, PartialEq, Eq, Reflect, Serialize, Deserialize,
+    )]
+    #[reflect(Component, Serialize, Deserialize)]
     pub struct Paddle;
 
-    #[derive(Component, Debug, Hash, PartialEq, Eq)]
+    // Which end of the court something belongs to. Paddles, goals and scoreboards all carry
+    // one, and it's how events say who was involved.
+    #[derive(
+        Component, Debug, Default, Clone, Copy, Hash, PartialEq, Eq, Reflect, Serialize, Deserialize,
+    )]
+    #[reflect(Component, Serialize, Deserialize)]
+    pub enum Side {
+        #[default]
+        Left,
+        Right,
+    }
+
+    impl Side {
+        pub const ALL: [Side; 2] = [Side::Left, Side::Right];
+
+        pub fn name(&self) -> &'static str {
+            match self {
+                Side::Left => "Left",
+                Side::Right => "Right",
+            }
+        }
+
+        pub fn opponent(&self) -> Self {
+            match self {
+                Side::Left => Side::Right,
+                Side::Right => Side::Left,
+            }
+        }
+
+        // -1 on the left, 1 on the right. Multiply an x offset by it to point it at this side.
+        pub fn sign(&self) -> f32 {
+            match self {
+                Side::Left => -1.0,
+                Side::Right => 1.0,
+            }
+        }
+
+        // the x coordinate of this end of the court
+        pub fn wall_x(&self) -> f32 {
+            match self {
+                Side::Left => LEFT_WALL,
+                Side::Right => RIGHT_WALL,
+            }
+        }
+
+        // the x coordinate `distance` in from this end of the court
+        pub fn inset_x(&self, distance: f32) -> f32 {
+            self.wall_x() - self.sign() * distance
+        }
+    }
+
+    // Who's playing. Scores, lives and games belong to a competitor, who can change ends
+    // between games, while `Side` is a fixed end of the court.
+    #[derive(
+        Component, Debug, Default, Clone, Copy, Hash, PartialEq, Eq, Reflect, Serialize, Deserialize,
+    )]
+    #[reflect(Component, Serialize, Deserialize)]
+    pub enum Competitor {
+        #[default]
+        Player,
+        Computer,
+    }
+
+    impl Competitor {
+        pub const ALL: [Competitor; 2] = [Competitor::Player, Competitor::Computer];
+
+        pub fn opponent(&self) -> Self {
+            match self {
+                Competitor::Player => Competitor::Computer,
+                Competitor::Computer => Competitor::Player,
+            }
+        }
+
+        pub fn name(&self) -> &'static str {
+            match self {
+                Competitor::Player => "You",
+                Competitor::Computer => "Computer",
+            }
+        }
+    }
+
+    // One of something for each competitor, indexed by `Competitor`
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Reflect, Serialize, Deserialize)]
+    pub struct PerCompetitor<T> {
+        pub player: T,
+        pub computer: T,
+    }
+
+    impl<T: Clone> PerCompetitor<T> {
+        pub fn splat(value: T) -> Self {
+            Self {
+                player: value.clone(),
+                computer: value,
+            }
+        }
+    }
+
+    impl<T> Index<Competitor> for PerCompetitor<T> {
+        type Output = T;
+
+        fn index(&self, competitor: Competitor) -> &T {
+            match competitor {
+                Competitor::Player => &self.player,
+                Competitor::Computer => &self.computer,
+            }
+        }
+    }
+
+    impl<T> IndexMut<Competitor> for PerCompetitor<T> {
+        fn index_mut(&mut self, competitor: Competitor) -> &mut T {
+            match competitor {
+                Competitor::Player => &mut self.player,
+                Competitor::Computer => &mut self.computer,
+            }
+        }
+    }
+
+    // The paddle is driven by the keyboard
+    #[derive(Component, Debug, Default, Hash, PartialEq, Eq, Reflect, Serialize, Deserialize)]
+    #[reflect(Component, Serialize, Deserialize)]
     pub struct Player;
 
-    #[derive(Component, Debug, Hash, PartialEq, Eq)]
+    // The paddle is driven by the computer
+    #[derive(Component, Debug, Default, Hash, PartialEq, Eq, Reflect, Serialize, Deserialize)]
+    #[reflect(Component, Serialize, Deserialize)]
     pub struct AI;
 
-    #[derive(Component, Debug, Clone, Hash, PartialEq, Eq)]
+    #[derive(
+        Component, Debug, Default, Clone, Hash, PartialEq, Eq, Reflect, Serialize, Deserialize,
+    )]
+    #[reflect(Component, Serialize, Deserialize)]
     pub struct Ball;
 
-    #[derive(Component, Debug)]
+    #[derive(Component, Debug, Default, Reflect, Serialize, Deserialize)]
+    #[reflect(Component, Serialize, Deserialize)]
     pub struct Collider;
 
-    #[derive(Component, Deref, DerefMut)]
-    pub struct Velocity(pub Vec2);
+    // A block in the court that balls bounce off, from the obstacles mutator or air hockey's
+    // end walls
+    #[derive(Component, Debug, Default, Reflect, Serialize, Deserialize)]
+    #[reflect(Component, Serialize, Deserialize)]
+    pub struct Obstacle;
+
+    // How long a ball has been bouncing (nearly) straight up and down
+    #[derive(Component, Debug, Deref, DerefMut, Reflect, Serialize, Deserialize)]
+    #[reflect(Component, Serialize, Deserialize)]
+    pub struct StallTimer(pub Timer);
+
+    impl Default for StallTimer {
+        fn default() -> Self {
+            Self(Timer::from_seconds(STALL_TIMEOUT, TimerMode::Once))
+        }
+    }
+
+    pub use bevy_common::motion::Velocity;
 
-    #[derive(Component, Debug)]
+    // Vertical speed of a paddle. Kept apart from `Velocity` since paddles integrate their own
+    // movement so they can be clamped to the arena.
+    #[derive(Component, Debug, Default, Deref, DerefMut, Reflect, Serialize, Deserialize)]
+    #[reflect(Component, Serialize, Deserialize)]
+    pub struct PaddleVelocity(pub f32);
+
+    #[derive(Component, Debug, Default, Reflect, Serialize, Deserialize)]
+    #[reflect(Component, Serialize, Deserialize)]
     pub struct ScoreboardText;
 
-    // This bundle is a collection of the components that define a "wall" in our game
+    // Where balls are served from
+    #[derive(Component, Debug, Default, Reflect, Serialize, Deserialize)]
+    #[reflect(Component, Serialize, Deserialize)]
+    pub struct ServeSpot;
+
     #[derive(Bundle)]
     pub struct Walls {
         pub sprite_bundle: SpriteBundle,
@@ -87,60 +860,51 @@ mod entities {
         pub side: WallSide,
     }
 
-    #[derive(Component, Debug, Clone, Hash, PartialEq, Eq)]
+    #[derive(
+        Component, Debug, Default, Clone, Hash, PartialEq, Eq, Reflect, Serialize, Deserialize,
+    )]
+    #[reflect(Component, Serialize, Deserialize)]
     pub enum WallSide {
+        #[default]
         Top,
         Bottom,
-        Player,
-        Enemy,
     }
 
     impl WallSide {
         pub fn position(&self) -> Vec2 {
             match self {
-                WallSide::Enemy => Vec2::new(LEFT_WALL, 0.),
-                WallSide::Player => Vec2::new(RIGHT_WALL, 0.),
                 WallSide::Bottom => Vec2::new(0., BOTTOM_WALL),
                 WallSide::Top => Vec2::new(0., TOP_WALL),
             }
         }
 
         pub fn size(&self) -> Vec2 {
-            let arena_height = TOP_WALL - BOTTOM_WALL;
             let arena_width = RIGHT_WALL - LEFT_WALL;
             // Make sure we haven't messed up our constants
-            assert!(arena_height > 0.0);
             assert!(arena_width > 0.0);
 
-            match self {
-                WallSide::Enemy | WallSide::Player => {
-                    Vec2::new(WALL_THICKNESS, arena_height + WALL_THICKNESS)
-                }
-                WallSide::Bottom | WallSide::Top => {
-                    Vec2::new(arena_width + WALL_THICKNESS, WALL_THICKNESS)
-                }
-            }
+            Vec2::new(arena_width + WALL_THICKNESS, WALL_THICKNESS)
         }
     }
 
-    impl Walls {
-        pub fn new(location: WallSide) -> Self {
-            Self {
-                sprite_bundle: SpriteBundle {
-                    transform: Transform {
-                        translation: location.position().extend(0.0),
-                        scale: location.size().extend(1.0),
-                        ..default()
-                    },
-                    sprite: Sprite {
-                        color: WALL_COLOR,
-                        ..default()
-                    },
-                    ..default()
-                },
-                collider: Collider,
-                side: location,
-            }
+    // The open ends of the court. Nothing bounces off a goal: a ball that reaches one is out,
+    // and the point goes against the side defending it.
+    #[derive(
+        Component, Debug, Default, Clone, Hash, PartialEq, Eq, Reflect, Serialize, Deserialize,
+    )]
+    #[reflect(Component, Serialize, Deserialize)]
+    pub struct GoalZone;
+
+    impl GoalZone {
+        pub fn position(side: Side) -> Vec2 {
+            Vec2::new(side.wall_x(), 0.)
+        }
+
+        pub fn size() -> Vec2 {
+            let arena_height = TOP_WALL - BOTTOM_WALL;
+            assert!(arena_height > 0.0);
+
+            Vec2::new(WALL_THICKNESS, arena_height + WALL_THICKNESS)
         }
     }
 }
@@ -148,267 +912,523 @@ mod entities {
 fn spawn_ball(
     materials: &mut ResMut<Assets<ColorMaterial>>,
     meshes: &mut ResMut<Assets<Mesh>>,
+    color: Color,
+    position: Vec3,
+    direction: Vec2,
 ) -> (
     MaterialMesh2dBundle<ColorMaterial>,
     entities::Ball,
     entities::Velocity,
+    entities::StallTimer,
 ) {
     (
         MaterialMesh2dBundle {
             mesh: meshes.add(shape::Circle::default().into()).into(),
-            material: materials.add(ColorMaterial::from(constants::BALL_COLOR)),
-            transform: Transform::from_translation(constants::BALL_STARTING_POSITION)
-                .with_scale(constants::BALL_SIZE),
+            material: materials.add(ColorMaterial::from(color)),
+            transform: Transform::from_translation(position).with_scale(constants::BALL_SIZE),
             ..default()
         },
         entities::Ball,
-        entities::Velocity(constants::INITIAL_BALL_DIRECTION.normalize() * constants::BALL_SPEED),
+        entities::Velocity(direction.normalize() * constants::BALL_SPEED),
+        entities::StallTimer::default(),
     )
 }
 
-#[derive(Debug, Hash, PartialEq, Eq)]
-enum Owner {
-    Player,
-    AI,
+// A random direction towards `receiver`, within `cone_degrees` of horizontal
+fn serve_direction(rng: &mut rng::GameRng, receiver: entities::Side, cone_degrees: f32) -> Vec2 {
+    let cone = cone_degrees
+        .min(constants::MAX_SERVE_CONE_DEGREES)
+        .to_radians();
+    let angle = rng.gen_range(-cone..=cone);
+    let direction = Vec2::new(receiver.sign() * angle.cos(), angle.sin());
+
+    collision::enforce_min_horizontal(direction, constants::MIN_HORIZONTAL_SPEED_FRACTION)
 }
 
-#[derive(Debug, Event, Hash, PartialEq, Eq)]
-enum CollisionEvent {
-    Wall(entities::Ball, entities::WallSide),
-    Paddle(entities::Ball, entities::Paddle, Owner),
+// Where the arena says to serve from, see `entities::ServeSpot`
+fn serve_spot(spots: &Query<&Transform, With<entities::ServeSpot>>) -> Vec3 {
+    spots
+        .iter()
+        .next()
+        .map_or(constants::BALL_STARTING_POSITION, |spot| spot.translation)
+}
+
+// Serve a fresh set of balls from `spot` at `receiver`. In multi-ball they're spaced out so
+// they don't start on top of each other, and each gets its own angle.
+fn serve_balls(
+    commands: &mut Commands,
+    materials: &mut ResMut<Assets<ColorMaterial>>,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    settings: &settings::Settings,
+    mutators: &mutators::Mutators,
+    rng: &mut rng::GameRng,
+    spot: Vec3,
+    receiver: entities::Side,
+) {
+    let count = mutators.balls(settings);
+    for i in 0..count {
+        let offset = i as f32 - (count - 1) as f32 / 2.0;
+        let position = spot + Vec3::new(0.0, offset * constants::MULTI_BALL_SPACING, 0.0);
+        let direction = serve_direction(rng, receiver, settings.gameplay.serve_cone_degrees);
+
+        let (mut mesh, marker, mut velocity, stall) = spawn_ball(
+            materials,
+            meshes,
+            settings.theme.palette().ball,
+            position,
+            direction,
+        );
+        let mut ball = commands.spawn_empty();
+        // held where it is for a moment first, see `serve.rs`
+        let countdown = settings.gameplay.serve_countdown;
+        if countdown > 0.0 {
+            ball.insert(serve::Serving::hold(
+                countdown,
+                &mut mesh.transform,
+                &mut velocity,
+            ));
+        }
+        ball.insert((mesh, marker, velocity, stall));
+    }
 }
 
-#[derive(Resource, Default)]
-pub struct Scoreboard {
-    pub ai: usize,
-    pub player: usize,
+// The stages of a simulation tick, run in this order in `FixedUpdate`
+#[derive(SystemSet, Debug, Clone, Copy, Hash, PartialEq, Eq)]
+enum TickSet {
+    // paddles decide where they're going
+    Input,
+    // everything moves and collides, producing collision and goal events
+    Physics,
+    // goal events are turned into points and the score is updated
+    Scoring,
+    // anything reacting to the above: sounds, serves, power-ups
+    Effects,
 }
 
-// provides an alternating collision sound.
-#[derive(Resource)]
-struct CollisionSound {
-    iter: Flatten<Repeat<Vec<Handle<AudioSource>>>>,
-    // the same collion can occur in contiguous frames, debounce them with this instant
-    last: f32,
+// Where along a paddle or goal the ball hit it, from -1 at the bottom to 1 at the top
+#[derive(Debug, Clone, Copy)]
+struct Contact(f32);
+
+impl Contact {
+    fn along(position: f32, center: f32, length: f32) -> Self {
+        Self(((position - center) / (length / 2.0)).clamp(-1.0, 1.0))
+    }
 }
 
-impl FromIterator<Handle<AudioSource>> for CollisionSound {
-    fn from_iter<T: IntoIterator<Item = Handle<AudioSource>>>(iter: T) -> Self {
-        CollisionSound {
-            iter: repeat(iter.into_iter().collect::<Vec<_>>()).flatten(),
-            last: 0.,
-        }
+// compared bit for bit, so collisions can be deduplicated in a set
+impl PartialEq for Contact {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.to_bits() == other.0.to_bits()
     }
 }
 
-impl CollisionSound {
-    // returns a sound if we haven't played one recently, otherwise None
-    fn next(&mut self, time: f32) -> Option<Handle<AudioSource>> {
-        if time - self.last < 0.05 {
-            return None;
-        }
+impl Eq for Contact {}
 
-        self.last = time;
-        self.iter.next()
+impl Hash for Contact {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.to_bits().hash(state);
     }
 }
 
-fn setup(
+// Each variant leads with the ball entity involved
+#[derive(Debug, Event, Hash, PartialEq, Eq)]
+enum CollisionEvent {
+    Wall(Entity, entities::WallSide),
+    Paddle(Entity, entities::Side, Contact),
+    // returned by a partner in doubles rather than the side's own paddle, see `doubles.rs`
+    Partner(Entity, entities::Side, Contact),
+    Obstacle(Entity),
+    // two balls bounced off each other in multi-ball
+    Ball(Entity, Entity),
+}
+
+// A ball went into the goal that `defender` is protecting. This is the raw sensor reading,
+// react to `PointScored` instead.
+#[derive(Debug, Event)]
+struct GoalEvent {
+    ball: Entity,
+    defender: entities::Side,
+    // where along the goal line it went in
+    crossing: Contact,
+}
+
+// A point was won. Everything that cares about scoring (the scoreboard, serving, sounds, FX)
+// listens for this rather than picking apart raw collisions.
+#[derive(Debug, Event)]
+struct PointScored {
+    scorer: entities::Competitor,
+    // how many points it was worth under the match's scoring rules
+    points: usize,
+    // the ball that went out
+    ball: Entity,
+}
+
+// A paddle was driven into a wall at `speed`
+#[derive(Debug, Event)]
+struct PaddleWallHit {
+    speed: f32,
+}
+
+#[derive(Resource)]
+struct ThudSound(audio_bus::SoundBank);
+
+#[derive(Resource)]
+struct GoalSound(audio_bus::SoundBank);
+
+#[derive(Resource, Default, Deref, DerefMut, Reflect, Serialize, Deserialize)]
+#[reflect(Resource, Serialize, Deserialize)]
+pub struct Scoreboard(entities::PerCompetitor<usize>);
+
+// the bounce beeps, one or the other at random
+#[derive(Resource)]
+struct CollisionSound(audio_bus::SoundBank);
+
+fn setup(mut commands: Commands, mut sounds: asset_check::SoundLoader) {
+    // Camera. The court itself is laid out by `arena.rs`.
+    commands.spawn(Camera2dBundle::default());
+
+    let sfx = |samples: Vec<Handle<AudioSource>>| {
+        audio_bus::SoundBank::new(audio_bus::Bus::Sfx, samples)
+            .with_speed(constants::SOUND_SPEED_VARIATION)
+            .with_volume(constants::SOUND_VOLUME_VARIATION)
+    };
+    commands.insert_resource(CollisionSound(sfx(vec![
+        sounds.load("high_beep_short.ogg"),
+        sounds.load("low_beep_short.ogg"),
+    ])));
+    commands.insert_resource(ThudSound(sfx(vec![sounds.load("thud_short.wav")])));
+    commands.insert_resource(GoalSound(sfx(vec![sounds.load("low_beep_short.ogg")])));
+}
+
+// At the start of every game, once the mutators are picked
+fn start_game(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
-    asset_server: Res<AssetServer>,
+    settings: Res<settings::Settings>,
+    mutators: Res<mutators::Mutators>,
+    mut rng: ResMut<rng::GameRng>,
+    mut scores: ResMut<Scoreboard>,
+    games: Res<rules::Games>,
+    ends: Res<rules::Ends>,
+    spots: Query<&Transform, With<entities::ServeSpot>>,
 ) {
-    // Camera
-    commands.spawn(Camera2dBundle::default());
+    *scores = Scoreboard::default();
 
-    let player_paddle_x = constants::RIGHT_WALL - constants::GAP_BETWEEN_PADDLE_AND_WALL;
-    let ai_paddle_x = constants::LEFT_WALL + constants::GAP_BETWEEN_PADDLE_AND_WALL;
-
-    commands.spawn((
-        SpriteBundle {
-            transform: Transform {
-                translation: Vec3::new(player_paddle_x, 0.0, 0.0),
-                scale: constants::PADDLE_SIZE,
-                ..default()
-            },
-            sprite: Sprite {
-                color: constants::PADDLE_COLOR,
-                ..default()
-            },
-            ..default()
-        },
-        entities::Player,
-        entities::Paddle,
-        entities::Collider,
-    ));
-
-    commands.spawn((
-        SpriteBundle {
-            transform: Transform {
-                translation: Vec3::new(ai_paddle_x, 0.0, 0.0),
-                scale: constants::PADDLE_SIZE,
-                ..default()
-            },
-            sprite: Sprite {
-                color: constants::PADDLE_COLOR,
-                ..default()
-            },
-            ..default()
-        },
-        entities::AI,
-        entities::Paddle,
-        entities::Collider,
-    ));
-
-    // Walls
-    commands.spawn(entities::Walls::new(entities::WallSide::Top));
-    commands.spawn(entities::Walls::new(entities::WallSide::Bottom));
-    commands.spawn(entities::Walls::new(entities::WallSide::Enemy));
-    commands.spawn(entities::Walls::new(entities::WallSide::Player));
-
-    // Ball
-    commands.spawn(spawn_ball(&mut materials, &mut meshes));
-
-    // AI Score
-    commands.spawn((
-        Text2dBundle {
-            text: Text::from_sections([TextSection::from_style(TextStyle {
-                font_size: constants::SCOREBOARD_FONT_SIZE,
-                color: Color::GRAY,
-                ..default()
-            })]),
-            transform: Transform::from_translation(Vec3::new(
-                constants::LEFT_WALL + constants::SCOREBOARD_PADDING_X,
-                constants::TOP_WALL - constants::SCOREBOARD_PADDING_Y,
-                1.0,
-            )),
-            ..default()
-        },
-        entities::ScoreboardText,
-        entities::AI,
-    ));
-
-    // Player Score
-    commands.spawn((
-        Text2dBundle {
-            text: Text::from_sections([TextSection::from_style(TextStyle {
-                font_size: constants::SCOREBOARD_FONT_SIZE,
-                color: Color::GRAY,
-                ..default()
-            })]),
-            transform: Transform::from_translation(Vec3::new(
-                constants::RIGHT_WALL - constants::SCOREBOARD_PADDING_X,
-                constants::TOP_WALL - constants::SCOREBOARD_PADDING_Y,
-                1.0,
-            )),
-            ..default()
-        },
-        entities::ScoreboardText,
-        entities::Player,
-    ));
+    let receiver = if games.played().is_multiple_of(2) {
+        constants::FIRST_SERVE_RECEIVER
+    } else {
+        constants::FIRST_SERVE_RECEIVER.opponent()
+    };
+
+    serve_balls(
+        &mut commands,
+        &mut materials,
+        &mut meshes,
+        &settings,
+        &mutators,
+        &mut rng,
+        serve_spot(&spots),
+        ends.side_of(receiver),
+    );
+}
+
+// Moves the paddles to whichever end their competitor is playing from
+fn place_paddles(
+    ends: Res<rules::Ends>,
+    mut paddles: Query<
+        (
+            &mut Transform,
+            &mut entities::Side,
+            &mut entities::PaddleVelocity,
+            &entities::Competitor,
+        ),
+        With<entities::Paddle>,
+    >,
+) {
+    for (mut transform, mut side, mut velocity, competitor) in &mut paddles {
+        *side = ends.side_of(*competitor);
+        transform.translation.x = side.inset_x(constants::GAP_BETWEEN_PADDLE_AND_WALL);
+        transform.translation.y = 0.0;
+        velocity.0 = 0.0;
+    }
+}
+
+fn clear_balls(mut commands: Commands, balls: Query<Entity, With<entities::Ball>>) {
+    for ball in &balls {
+        commands.entity(ball).despawn();
+    }
+}
 
-    commands.insert_resource(CollisionSound::from_iter([
-        asset_server.load("high_beep_short.ogg"),
-        asset_server.load("low_beep_short.ogg"),
-    ]));
+// Steps a paddle's velocity towards `target` and moves it, knocking it back off any walls
+// it runs into. Returns the speed of the impact if it hit one.
+fn drive_paddle<'a>(
+    transform: &mut Transform,
+    velocity: &mut entities::PaddleVelocity,
+    target: f32,
+    max_speed: f32,
+    walls: impl IntoIterator<Item = &'a Transform>,
+    settings: &settings::Settings,
+    dt: f32,
+) -> Option<f32> {
+    velocity.0 = settings
+        .gameplay
+        .acceleration_curve
+        .accelerate(velocity.0, target, max_speed, dt);
+
+    transform.translation.y += velocity.0 * dt;
+
+    collision::resolve_vertical(
+        transform,
+        &mut velocity.0,
+        walls,
+        constants::PADDLE_PADDING,
+        constants::PADDLE_WALL_RESTITUTION,
+    )
 }
 
 fn move_player_paddle(
-    keyboard_input: Res<Input<KeyCode>>,
-    mut query: Query<&mut Transform, (With<entities::Player>, With<entities::Paddle>)>,
+    actions: Res<actions::ActionState>,
+    mut query: Query<
+        (
+            &mut Transform,
+            &mut entities::PaddleVelocity,
+            &powerups::PaddleBoost,
+        ),
+        (With<entities::Player>, With<entities::Paddle>),
+    >,
+    walls: Query<&Transform, (With<entities::WallSide>, Without<entities::Paddle>)>,
+    settings: Res<settings::Settings>,
+    mut wall_hits: EventWriter<PaddleWallHit>,
     time: Res<Time>,
 ) {
-    let mut paddle_transform = query.single_mut();
-    let direction = if keyboard_input.any_pressed([KeyCode::Up, KeyCode::W, KeyCode::K]) {
-        1.0
-    } else if keyboard_input.any_pressed([KeyCode::Down, KeyCode::S, KeyCode::J]) {
-        -1.0
-    } else {
-        0.0
+    let Ok((mut paddle_transform, mut paddle_velocity, boost)) = query.get_single_mut() else {
+        return;
     };
+    let direction = actions.move_axis.y;
+
+    let max_speed = constants::PADDLE_SPEED * boost.multiplier();
+    if let Some(speed) = drive_paddle(
+        &mut paddle_transform,
+        &mut paddle_velocity,
+        direction * max_speed,
+        max_speed,
+        &walls,
+        &settings,
+        time.delta_seconds(),
+    ) {
+        wall_hits.send(PaddleWallHit { speed });
+    }
+}
 
-    // Calculate the new horizontal paddle position based on player input
-    let new_paddle_position =
-        paddle_transform.translation.y + direction * constants::PADDLE_SPEED * time.delta_seconds();
-
-    paddle_transform.translation.y =
-        new_paddle_position.clamp(constants::PADDLE_BOTTOM_BOUND, constants::PADDLE_TOP_BOUND);
+// Plays back the player's own inputs from their last match on the opponent's paddle
+fn ghost_paddle(
+    mut ghost: ResMut<replay::GhostPlayback>,
+    mut query: Query<
+        (
+            &mut Transform,
+            &mut entities::PaddleVelocity,
+            &powerups::PaddleBoost,
+        ),
+        (With<entities::AI>, With<entities::Paddle>),
+    >,
+    walls: Query<&Transform, (With<entities::WallSide>, Without<entities::Paddle>)>,
+    settings: Res<settings::Settings>,
+    mut wall_hits: EventWriter<PaddleWallHit>,
+    time: Res<Time>,
+) {
+    let Ok((mut paddle_transform, mut paddle_velocity, boost)) = query.get_single_mut() else {
+        return;
+    };
+    let direction = ghost.next();
+
+    let max_speed = constants::PADDLE_SPEED * boost.multiplier();
+    if let Some(speed) = drive_paddle(
+        &mut paddle_transform,
+        &mut paddle_velocity,
+        direction * max_speed,
+        max_speed,
+        &walls,
+        &settings,
+        time.delta_seconds(),
+    ) {
+        wall_hits.send(PaddleWallHit { speed });
+    }
 }
 
 fn enemy_paddle_ai(
-    mut paddle_query: Query<&mut Transform, (With<entities::AI>, With<entities::Paddle>)>,
-    ball_query: Query<&Transform, (With<entities::Ball>, Without<entities::AI>)>,
+    mut paddle_query: Query<
+        (
+            &mut Transform,
+            &mut entities::PaddleVelocity,
+            &powerups::PaddleBoost,
+            &entities::Side,
+        ),
+        (With<entities::AI>, With<entities::Paddle>),
+    >,
+    ball_query: Query<
+        (&Transform, &entities::Velocity),
+        (With<entities::Ball>, Without<entities::AI>),
+    >,
+    walls: Query<&Transform, (With<entities::WallSide>, Without<entities::Paddle>)>,
+    settings: Res<settings::Settings>,
+    mutators: Res<mutators::Mutators>,
+    rules: Res<rules::MatchRules>,
+    difficulty: Res<difficulty::Difficulty>,
+    rally: Res<personality::Rally>,
+    mut wall_hits: EventWriter<PaddleWallHit>,
     time: Res<Time>,
 ) {
-    let mut paddle_transform = paddle_query.single_mut();
+    let Ok((mut paddle_transform, mut paddle_velocity, boost, side)) =
+        paddle_query.get_single_mut()
+    else {
+        return;
+    };
 
-    let ball_transform = ball_query.single();
+    // keep an eye on whichever ball will reach us first: the closest one heading our way,
+    // or just the closest one if they're all going the other way
+    let distance = |transform: &Transform| (side.wall_x() - transform.translation.x).abs();
+    let incoming = ball_query
+        .iter()
+        .filter(|(_, velocity)| velocity.x * side.sign() > 0.0)
+        .min_by(|(a, _), (b, _)| distance(a).total_cmp(&distance(b)))
+        .or_else(|| {
+            ball_query
+                .iter()
+                .min_by(|(a, _), (b, _)| distance(a).total_cmp(&distance(b)))
+        });
 
     // anticipate next ball position, adjust paddle. clamp to a player speed
-    let next_y = ball_transform.translation.y;
+    let max_speed = constants::MAX_AI_PADDLE_SPEED
+        * boost.multiplier()
+        * personality::chosen(&rules).stamina(rally.returns);
+    let target = match incoming {
+        // the speed that would put us on the ball this tick, or where it's going to be if
+        // we're anticipating it
+        Some((ball_transform, velocity)) => {
+            let anticipated = (settings.gameplay.predictive_ai
+                && prediction::predictable(mutators, rules))
+            .then(|| prediction::predict(ball_transform, velocity.0, &paddle_transform, *side))
+            .and_then(|trajectory| trajectory.intercept);
+            let mut target_y = anticipated.map_or(ball_transform.translation.y, |intercept| {
+                intercept.position.y
+            });
+            // the adaptive computer is slow to react and a little off
+            if settings.gameplay.adaptive_ai > 0.0 {
+                target_y += difficulty.aim_error() - velocity.y * difficulty.reaction_delay();
+            }
+            ((target_y - paddle_transform.translation.y) / time.delta_seconds())
+                .clamp(-max_speed, max_speed)
+        }
+        // between serves, coast to a stop
+        None => 0.0,
+    };
 
-    // Calculate the new horizontal paddle position based on player input
-    let new_paddle_position = paddle_transform.translation.y
-        + (next_y - paddle_transform.translation.y).clamp(
-            -constants::MAX_AI_PADDLE_SPEED * time.delta_seconds(),
-            constants::MAX_AI_PADDLE_SPEED * time.delta_seconds(),
-        );
+    if let Some(speed) = drive_paddle(
+        &mut paddle_transform,
+        &mut paddle_velocity,
+        target,
+        max_speed,
+        &walls,
+        &settings,
+        time.delta_seconds(),
+    ) {
+        wall_hits.send(PaddleWallHit { speed });
+    }
+}
 
-    paddle_transform.translation.y =
-        new_paddle_position.clamp(constants::PADDLE_BOTTOM_BOUND, constants::PADDLE_TOP_BOUND);
+// not while a ball's waiting to be served, it'd leave with whatever it picked up
+fn apply_gravity(
+    mut query: Query<&mut entities::Velocity, (With<entities::Ball>, Without<serve::Serving>)>,
+    time: Res<Time>,
+) {
+    for mut velocity in &mut query {
+        velocity.y -= constants::GRAVITY * time.delta_seconds();
+    }
 }
 
-fn apply_velocity(mut query: Query<(&mut Transform, &entities::Velocity)>, time: Res<Time>) {
-    for (mut transform, velocity) in &mut query {
-        transform.translation.x += velocity.x * time.delta_seconds();
-        transform.translation.y += velocity.y * time.delta_seconds();
+// How big a collider is to the ball. The player's paddle reaches a little past its ends, so
+// a return that only just clips an edge isn't lost between ticks.
+fn reach(transform: &Transform, player: bool, settings: &settings::Settings) -> Vec2 {
+    let mut size = transform.scale.truncate();
+    if player {
+        size.y += 2.0 * settings.gameplay.edge_grace;
     }
+    size
+}
+
+// The broad phase for balls against colliders, each filed by its index in `colliders`
+fn collider_grid<'a>(
+    colliders: impl IntoIterator<Item = (&'a Transform, bool)>,
+    settings: &settings::Settings,
+) -> collision::Grid<usize> {
+    let mut grid = collision::Grid::new(constants::BROAD_PHASE_CELL_SIZE);
+    for (index, (transform, player)) in colliders.into_iter().enumerate() {
+        grid.insert(
+            index,
+            transform.translation.truncate(),
+            reach(transform, player, settings),
+        );
+    }
+    grid
 }
 
 fn generate_ball_collide_events(
-    ball_q: Query<(&entities::Ball, &Transform), With<entities::Ball>>,
+    ball_q: Query<(Entity, &Transform), With<entities::Ball>>,
     collider_q: Query<
         (
             &Transform,
-            (Option<&entities::AI>, Option<&entities::Player>),
-            (Option<&entities::WallSide>, Option<&entities::Paddle>),
+            Option<&entities::WallSide>,
+            Option<&entities::Side>,
+            Has<entities::Player>,
         ),
         With<entities::Collider>,
     >,
+    settings: Res<settings::Settings>,
     mut collision_events: EventWriter<CollisionEvent>,
 ) {
-    let (ball, ball_transform) = ball_q.single();
-    let ball_size = ball_transform.scale.truncate();
     let mut events = HashSet::new();
+    let colliders: Vec<_> = collider_q.iter().collect();
+    let grid = collider_grid(
+        colliders
+            .iter()
+            .map(|(transform, _, _, player)| (*transform, *player)),
+        &settings,
+    );
+
+    for (ball, ball_transform) in &ball_q {
+        let ball_size = ball_transform.scale.truncate();
+
+        // check collision with walls
+        for index in grid.candidates(ball_transform.translation.truncate(), ball_size) {
+            let (transform, wall, side, player) = colliders[index];
+            if collide(
+                ball_transform.translation,
+                ball_size,
+                transform.translation,
+                reach(transform, player, &settings),
+            )
+            .is_none()
+            {
+                continue;
+            }
 
-    // check collision with walls
-    for (transform, player_kind, entity_kind) in &collider_q {
-        if collide(
-            ball_transform.translation,
-            ball_size,
-            transform.translation,
-            transform.scale.truncate(),
-        )
-        .is_none()
-        {
-            continue;
+            let ev = match (wall, side) {
+                (Some(wall), _) => CollisionEvent::Wall(ball, wall.clone()),
+                (None, Some(side)) => CollisionEvent::Paddle(
+                    ball,
+                    *side,
+                    Contact::along(
+                        ball_transform.translation.y,
+                        transform.translation.y,
+                        transform.scale.y,
+                    ),
+                ),
+                (None, None) => CollisionEvent::Obstacle(ball),
+            };
+            debug!("Collision: {ev:?}");
+            events.insert(ev);
         }
-
-        let ball = ball.to_owned();
-        // yuck
-        let ev = match (player_kind, entity_kind) {
-            (_, (Some(ws), None)) => CollisionEvent::Wall(ball, ws.clone()),
-            ((Some(_), None), (None, Some(pd))) => {
-                CollisionEvent::Paddle(ball, pd.clone(), Owner::AI)
-            }
-            ((None, Some(_)), (None, Some(pd))) => {
-                CollisionEvent::Paddle(ball, pd.clone(), Owner::Player)
-            }
-            other => unreachable!("cannot reach {other:?}"),
-        };
-        println!("Collision: {ev:?}");
-        events.insert(ev);
     }
 
     for ev in events {
@@ -416,153 +1436,487 @@ fn generate_ball_collide_events(
     }
 }
 
+fn detect_goals(
+    balls: Query<(Entity, &Transform), With<entities::Ball>>,
+    goals: Query<(&Transform, &entities::Side), With<entities::GoalZone>>,
+    mut goal_events: EventWriter<GoalEvent>,
+) {
+    for (ball, ball_transform) in &balls {
+        // a ball can only go out once
+        if let Some((goal, side)) = goals.iter().find(|(transform, _)| {
+            collide(
+                ball_transform.translation,
+                ball_transform.scale.truncate(),
+                transform.translation,
+                transform.scale.truncate(),
+            )
+            .is_some()
+        }) {
+            goal_events.send(GoalEvent {
+                ball,
+                defender: *side,
+                crossing: Contact::along(
+                    ball_transform.translation.y,
+                    goal.translation.y,
+                    goal.scale.y,
+                ),
+            });
+        }
+    }
+}
+
 fn check_ball_bounce_collisions(
-    mut ball_query: Query<(&mut entities::Velocity, &Transform), With<entities::Ball>>,
-    collider_query: Query<&Transform, With<entities::Collider>>,
+    mut ball_query: Query<(&mut entities::Velocity, &mut Transform), With<entities::Ball>>,
+    collider_query: Query<
+        (
+            &Transform,
+            Option<&entities::PaddleVelocity>,
+            Has<entities::Player>,
+        ),
+        (With<entities::Collider>, Without<entities::Ball>),
+    >,
+    settings: Res<settings::Settings>,
+    mutators: Res<mutators::Mutators>,
 ) {
-    let (mut ball_velocity, ball_transform) = ball_query.single_mut();
-    let ball_size = ball_transform.scale.truncate();
-
-    for transform in &collider_query {
-        let collision = collide(
-            ball_transform.translation,
-            ball_size,
-            transform.translation,
-            transform.scale.truncate(),
-        );
-        if let Some(collision) = collision {
-            // reflect the ball when it collides
-            let mut reflect_x = false;
-            let mut reflect_y = false;
-
-            // only reflect if the ball's velocity is going in the opposite direction of the
-            // collision
-            match collision {
-                Collision::Left => reflect_x = ball_velocity.x > 0.0,
-                Collision::Right => reflect_x = ball_velocity.x < 0.0,
-                Collision::Top => reflect_y = ball_velocity.y < 0.0,
-                Collision::Bottom => reflect_y = ball_velocity.y > 0.0,
-                Collision::Inside => (),
+    let colliders: Vec<_> = collider_query.iter().collect();
+    let grid = collider_grid(
+        colliders
+            .iter()
+            .map(|(transform, _, player)| (*transform, *player)),
+        &settings,
+    );
+
+    for (mut ball_velocity, mut ball_transform) in &mut ball_query {
+        let ball_size = ball_transform.scale.truncate();
+
+        for index in grid.candidates(ball_transform.translation.truncate(), ball_size) {
+            let (transform, paddle_velocity, player) = colliders[index];
+            // push the ball back out of what it hit and reflect it off that side
+            let Some((position, mut reflected)) = collision::resolve_box(
+                ball_transform.translation.truncate(),
+                ball_size,
+                ball_velocity.0,
+                transform.translation.truncate(),
+                reach(transform, player, &settings),
+            ) else {
+                continue;
+            };
+            ball_transform.translation.x = position.x;
+            ball_transform.translation.y = position.y;
+
+            // a moving paddle drags the ball along with it when it's returned. The ball
+            // keeps its speed so this only changes the angle it leaves at.
+            if let Some(paddle_velocity) = paddle_velocity {
+                if reflected.x != ball_velocity.x {
+                    let mut speed = reflected.length();
+                    if mutators.contains(mutators::Mutator::SpeedRamp) {
+                        speed = (speed * constants::SPEED_RAMP_MULTIPLIER)
+                            .min(constants::MAX_RAMPED_BALL_SPEED);
+                    }
+                    reflected.y += paddle_velocity.0 * settings.gameplay.momentum_transfer;
+                    reflected = reflected.normalize_or_zero() * speed;
+                    reflected = collision::enforce_min_horizontal(
+                        reflected,
+                        constants::MIN_HORIZONTAL_SPEED_FRACTION,
+                    );
+                }
             }
 
-            // reflect velocity on the x-axis if we hit something on the x-axis
-            if reflect_x {
-                ball_velocity.x = -ball_velocity.x;
-            }
+            ball_velocity.0 = reflected;
+        }
+    }
+}
+
+// Balls in multi-ball bounce off each other like billiard balls
+fn check_ball_ball_collisions(
+    mut balls: Query<(Entity, &mut Transform, &mut entities::Velocity), With<entities::Ball>>,
+    mut collision_events: EventWriter<CollisionEvent>,
+) {
+    // only balls sharing a cell of the broad phase can touch
+    let placed: Vec<_> = balls
+        .iter()
+        .map(|(entity, transform, _)| {
+            (
+                entity,
+                transform.translation.truncate(),
+                transform.scale.truncate(),
+            )
+        })
+        .collect();
+    let mut grid = collision::Grid::new(constants::BROAD_PHASE_CELL_SIZE);
+    for (index, (_, center, size)) in placed.iter().enumerate() {
+        grid.insert(index, *center, *size);
+    }
 
-            // reflect velocity on the y-axis if we hit something on the y-axis
-            if reflect_y {
-                ball_velocity.y = -ball_velocity.y;
+    for (index, (a, center, size)) in placed.iter().enumerate() {
+        for other in grid.candidates(*center, *size) {
+            if other <= index {
+                continue;
+            }
+            let Ok([(a, mut a_transform, mut a_velocity), (b, mut b_transform, mut b_velocity)]) =
+                balls.get_many_mut([*a, placed[other].0])
+            else {
+                continue;
+            };
+
+            // the ball mesh is a unit circle, so its radius is half its scale
+            let Some(contact) = collision::circle_contact(
+                a_transform.translation.truncate(),
+                a_transform.scale.x / 2.0,
+                b_transform.translation.truncate(),
+                b_transform.scale.x / 2.0,
+            ) else {
+                continue;
+            };
+
+            // push them apart so they don't get stuck inside each other
+            let separation = (contact.normal * contact.depth / 2.0).extend(0.0);
+            a_transform.translation -= separation;
+            b_transform.translation += separation;
+
+            if let Some((a_bounced, b_bounced)) =
+                collision::elastic_bounce(a_velocity.0, b_velocity.0, contact.normal)
+            {
+                a_velocity.0 = a_bounced;
+                b_velocity.0 = b_bounced;
+                collision_events.send(CollisionEvent::Ball(a, b));
             }
         }
     }
 }
 
-fn tally_score(mut collision_events: EventReader<CollisionEvent>, mut scores: ResMut<Scoreboard>) {
-    for ev in collision_events.read() {
-        match ev {
-            CollisionEvent::Paddle(_, _, _) => (),
-            CollisionEvent::Wall(_, entities::WallSide::Enemy) => scores.ai += 1,
-            CollisionEvent::Wall(_, entities::WallSide::Player) => scores.player += 1,
-            CollisionEvent::Wall(_, _) => (),
+// Wall bounces never change a ball's horizontal speed, so one that ends up going (nearly)
+// straight up and down after a ball-ball collision would never come back. Give stalled
+// balls a push towards whichever side they were drifting to.
+fn unstick_stalled_balls(
+    mut balls: Query<(&mut entities::Velocity, &mut entities::StallTimer), With<entities::Ball>>,
+    time: Res<Time>,
+) {
+    for (mut velocity, mut stall) in &mut balls {
+        let stalling =
+            velocity.x.abs() < velocity.length() * constants::STALL_HORIZONTAL_SPEED_FRACTION;
+        if !stalling {
+            stall.reset();
+            continue;
         }
+
+        if stall.tick(time.delta()).finished() {
+            velocity.0 = collision::enforce_min_horizontal(
+                velocity.0,
+                constants::MIN_HORIZONTAL_SPEED_FRACTION,
+            );
+            stall.reset();
+        }
+    }
+}
+
+fn tally_score(mut points: EventReader<PointScored>, mut scores: ResMut<Scoreboard>) {
+    for point in points.read() {
+        scores[point.scorer] += point.points;
     }
 }
 
 fn update_scoreboard(
-    mut player_scoreboard: Query<
-        &mut Text,
-        (
-            With<entities::ScoreboardText>,
-            With<entities::Player>,
-            Without<entities::AI>,
-        ),
-    >,
-    mut ai_scoreboard: Query<
-        &mut Text,
-        (
-            With<entities::ScoreboardText>,
-            With<entities::AI>,
-            Without<entities::Player>,
-        ),
-    >,
+    mut scoreboards: Query<(&mut Text, &entities::Side), With<entities::ScoreboardText>>,
     scores: Res<Scoreboard>,
+    ends: Res<rules::Ends>,
 ) {
-    player_scoreboard.single_mut().sections[0].value = scores.player.to_string();
-    ai_scoreboard.single_mut().sections[0].value = scores.ai.to_string();
+    // scoreboards stay put and show whoever is playing from their end
+    for (mut text, side) in &mut scoreboards {
+        text.sections[0].value = scores[ends.at(*side)].to_string();
+    }
 }
 
 fn handle_round_over(
     mut commands: Commands,
-    mut collision_events: EventReader<CollisionEvent>,
+    mut points: EventReader<PointScored>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
     ball_query: Query<Entity, With<entities::Ball>>,
+    settings: Res<settings::Settings>,
+    mutators: Res<mutators::Mutators>,
+    mut rng: ResMut<rng::GameRng>,
+    ends: Res<rules::Ends>,
+    spots: Query<&Transform, With<entities::ServeSpot>>,
 ) {
-    use entities::WallSide::*;
+    let mut scored = HashSet::new();
+    // whoever lost the last point receives the next serve
+    let mut receiver = None;
+    for point in points.read() {
+        scored.insert(point.ball);
+        receiver = Some(point.scorer.opponent());
+    }
 
-    let Some(_) = collision_events
-        .read()
-        .find(|ev| matches!(ev, CollisionEvent::Wall(_, Player | Enemy)))
-    else {
+    let Some(receiver) = receiver else {
         return;
     };
 
-    let ball = ball_query.single();
-    commands.entity(ball).despawn();
-    commands.spawn(spawn_ball(&mut materials, &mut meshes));
+    for ball in &scored {
+        commands.entity(*ball).despawn();
+    }
+
+    // in multi-ball the point carries on until every ball is gone
+    if ball_query.iter().all(|ball| scored.contains(&ball)) {
+        serve_balls(
+            &mut commands,
+            &mut materials,
+            &mut meshes,
+            &settings,
+            &mutators,
+            &mut rng,
+            serve_spot(&spots),
+            ends.side_of(receiver),
+        );
+    }
 }
 
 fn play_collision_sound(
-    mut commands: Commands,
     mut collision_events: EventReader<CollisionEvent>,
-    mut sound: ResMut<CollisionSound>,
-    time: Res<Time<Real>>,
+    sound: Res<CollisionSound>,
+    mut rng: ResMut<rng::SoundRng>,
+    mut play: EventWriter<audio_bus::PlaySound>,
 ) {
-    if collision_events.read().any(|ev| {
-        !matches!(
-            ev,
-            CollisionEvent::Wall(_, entities::WallSide::Player | entities::WallSide::Enemy)
-        ) || matches!(ev, CollisionEvent::Paddle(_, _, _))
-    }) {
+    // goals are sensors and never produce collisions, so anything here is a bounce
+    if collision_events.read().next().is_some() {
         collision_events.clear(); // consume them all
 
-        let time = time.elapsed_seconds();
-
-        if let Some(source) = sound.next(time) {
-            commands.spawn(AudioBundle {
-                source,
-                settings: PlaybackSettings::DESPAWN,
-            });
+        if let Some(sound) = sound.0.pick(&mut **rng) {
+            play.send(sound);
         }
     }
 }
 
+fn play_paddle_thud(
+    mut play: EventWriter<audio_bus::PlaySound>,
+    mut wall_hits: EventReader<PaddleWallHit>,
+    sound: Res<ThudSound>,
+    mut rng: ResMut<rng::SoundRng>,
+) {
+    // the AI nudges the walls all the time, only play for proper slams
+    let Some(speed) = wall_hits
+        .read()
+        .map(|hit| hit.speed)
+        .filter(|speed| *speed >= constants::THUD_MIN_SPEED)
+        .reduce(f32::max)
+    else {
+        return;
+    };
+
+    if let Some(sound) = sound.0.pick(&mut **rng) {
+        play.send(sound.with_volume((speed / constants::PADDLE_SPEED).min(1.0)));
+    }
+}
+
+fn play_goal_sound(
+    mut play: EventWriter<audio_bus::PlaySound>,
+    mut points: EventReader<PointScored>,
+    sound: Res<GoalSound>,
+    mut rng: ResMut<rng::SoundRng>,
+) {
+    if points.read().next().is_none() {
+        return;
+    }
+
+    // the bounce beep, pitched way down
+    if let Some(sound) = sound.0.pick(&mut **rng) {
+        play.send(sound.with_speed(constants::GOAL_SOUND_SPEED).ducking());
+    }
+}
+
 fn main() {
-    App::new()
-        .add_plugins(DefaultPlugins)
-        .insert_resource(ClearColor(constants::BACKGROUND_COLOR))
-        .insert_resource(Scoreboard::default())
-        .add_event::<CollisionEvent>()
-        .add_systems(Startup, setup)
-        // Add our gameplay simulation systems to the fixed timestep schedule
-        // which runs at 64 Hz by default
-        .add_systems(
-            FixedUpdate,
+    #[cfg(feature = "debug-tools")]
+    if let Some(options) = bench::BenchOptions::from_env() {
+        bench::run(options);
+        return;
+    }
+    #[cfg(feature = "capture")]
+    if let Some(path) = export::path_from_env() {
+        export::run(&path);
+        return;
+    }
+    if smoke_test::requested() {
+        std::process::exit(smoke_test::run());
+    }
+    let net_options = net::NetOptions::from_env();
+    build_app(net::default_plugins(&net_options), net_options).run();
+}
+
+// Everything but running it, so tests can step the simulation themselves
+fn build_app(default_plugins: PluginGroupBuilder, net_options: net::NetOptions) -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        default_plugins,
+        settings::SettingsPlugin,
+        mutators::MutatorsPlugin,
+        rules::RulesPlugin,
+        game_over::GameOverPlugin,
+        replay::ReplayPlugin,
+        net::NetPlugin {
+            options: net_options.clone(),
+        },
+        fog::FogPlugin,
+        portals::PortalPlugin,
+        powerups::PowerUpPlugin,
+        fx::FxPlugin,
+        air_hockey::AirHockeyPlugin,
+        volleyball::VolleyballPlugin,
+    ))
+    .add_plugins(doubles::DoublesPlugin)
+    .add_plugins((
+        bevy_common::window::WindowMemoryPlugin {
+            game: constants::GAME_NAME,
+        },
+        rating::RatingPlugin,
+        daily::DailyPlugin,
+        presence::PresencePlugin,
+        prediction::PredictionPlugin,
+        difficulty::DifficultyPlugin,
+        personality::PersonalityPlugin,
+        sequence::SequencePlugin,
+        stats::StatsPlugin,
+        idle::IdlePlugin,
+        focus::FocusPlugin,
+        pacing::PacingPlugin,
+        overload::OverloadPlugin,
+        toast::ToastPlugin,
+        tutorial::TutorialPlugin,
+    ))
+    .add_plugins((
+        actions::ActionsPlugin,
+        stick::StickPlugin,
+        devices::DevicesPlugin,
+        rumble::RumblePlugin,
+        smash::SmashPlugin,
+        paddle_colors::PaddleColorsPlugin,
+        cosmetics::CosmeticsPlugin,
+        celebration::CelebrationPlugin,
+        match_point::MatchPointPlugin,
+        instant_replay::InstantReplayPlugin,
+        snapshot::SnapshotPlugin,
+        savestate::SaveStatePlugin,
+        kiosk::KioskPlugin {
+            enabled: net_options.kiosk,
+        },
+    ))
+    .add_plugins((
+        orientation::OrientationPlugin,
+        minimap::MinimapPlugin,
+        split_screen::SplitScreenPlugin,
+        asset_check::AssetCheckPlugin,
+        synth::SynthPlugin,
+        audio_bus::AudioBusPlugin,
+        crowd::CrowdPlugin,
+        music::MusicPlugin,
+        mods::ModsPlugin,
+        registry::RegistryPlugin,
+        arena::ArenaPlugin,
+        serve::ServePlugin,
+        wall_fx::WallFxPlugin,
+        hit_stop::HitStopPlugin,
+    ))
+    .insert_resource(ClearColor(constants::BACKGROUND_COLOR))
+    .insert_resource(Scoreboard::default())
+    .init_resource::<rng::GameRng>()
+    .init_resource::<rng::SoundRng>()
+    .add_event::<CollisionEvent>()
+    .add_event::<GoalEvent>()
+    .add_event::<PointScored>()
+    .add_event::<PaddleWallHit>()
+    .add_systems(Startup, setup)
+    .add_systems(OnEnter(mutators::MatchState::Playing), start_game)
+    .add_systems(OnExit(mutators::MatchState::Playing), clear_balls)
+    // Add our gameplay simulation systems to the fixed timestep schedule
+    // which runs at 64 Hz by default
+    .configure_sets(
+        FixedUpdate,
+        (
+            TickSet::Input,
+            TickSet::Physics,
+            TickSet::Scoring,
+            TickSet::Effects,
+        )
+            .chain()
+            .run_if(in_state(mutators::MatchState::Playing))
+            .run_if(in_state(settings::MenuState::Closed))
+            .run_if(hit_stop::running)
+            .run_if(net::has_input_authority)
+            .run_if(net::not_waiting),
+    )
+    .add_systems(
+        FixedUpdate,
+        (
+            move_player_paddle.run_if(net::keyboard_is_local),
             (
-                move_player_paddle,
-                generate_ball_collide_events,
-                // move the ball after making events or we'll miss events
-                apply_velocity,
-                check_ball_bounce_collisions,
-                tally_score,
-                update_scoreboard,
-                enemy_paddle_ai,
-                handle_round_over,
-                play_collision_sound,
+                enemy_paddle_ai.run_if(not(replay::ghost_active)),
+                ghost_paddle.run_if(replay::ghost_active),
+            )
+                .run_if(net::computer_is_ai),
+        )
+            .in_set(TickSet::Input)
+            // volleyball paddles stay on the floor
+            .run_if(not(rules::variant_is(rules::Variant::Volleyball)))
+            // nothing to drive while the court's being set up or torn down
+            .run_if(any_with_component::<entities::Paddle>()),
+    )
+    .add_systems(
+        FixedUpdate,
+        (
+            detect_goals,
+            apply_gravity.run_if(
+                mutators::enabled(mutators::Mutator::Gravity)
+                    .or_else(rules::variant_is(rules::Variant::Volleyball)),
             ),
+            motion::apply_velocity,
+            portals::teleport_balls.run_if(mutators::enabled(mutators::Mutator::Portals)),
+            // make events between moving the balls and pushing them back out of what they've
+            // hit, or they'd never be seen overlapping
+            generate_ball_collide_events,
+            check_ball_bounce_collisions,
+            check_ball_ball_collisions,
+            unstick_stalled_balls,
+        )
+            .chain()
+            .in_set(TickSet::Physics)
+            // between a point's last ball going and the next serve
+            .run_if(any_with_component::<entities::Ball>()),
+    )
+    .add_systems(
+        FixedUpdate,
+        (rules::award_points, tally_score, update_scoreboard)
+            .chain()
+            .in_set(TickSet::Scoring),
+    )
+    .add_systems(
+        FixedUpdate,
+        (
+            handle_round_over,
+            play_collision_sound,
+            play_paddle_thud,
+            play_goal_sound,
         )
-        // .add_systems(Update, (update_scoreboard, bevy::window::close_on_esc))
-        .add_systems(Update, bevy::window::close_on_esc)
-        .run();
+            .in_set(TickSet::Effects),
+    )
+    .add_systems(
+        Update,
+        theme::apply_theme.run_if(resource_changed::<settings::Settings>()),
+    )
+    .add_systems(
+        Update,
+        place_paddles.run_if(resource_changed::<rules::Ends>()),
+    )
+    // .add_systems(Update, (update_scoreboard, bevy::window::close_on_esc))
+    // a kiosk only quits on its own key combination
+    .add_systems(
+        Update,
+        bevy::window::close_on_esc.run_if(not(kiosk::enabled)),
+    );
+
+    // each behind a feature, see `Cargo.toml`
+    #[cfg(feature = "post-processing")]
+    app.add_plugins((post_processing::PostProcessingPlugin, crt::CrtPlugin));
+    #[cfg(feature = "debug-tools")]
+    app.add_plugins(metrics::MetricsPlugin);
+    #[cfg(feature = "capture")]
+    app.add_plugins(export::ExportPlugin);
+    app
 }
@@ -0,0 +1,260 @@
+// Whoever's one point from winning the match gets a show made of it: the end of the court
+// they're attacking glows and pulses, a heartbeat thuds and "MATCH POINT" pulses over the
+// court. It all goes as soon as it isn't match point any more.
+use bevy::prelude::*;
+
+use crate::{
+    asset_check::SoundLoader,
+    audio_bus::{Bus, PlaySound},
+    constants::*,
+    entities::{self, Competitor, PerCompetitor},
+    mutators::MatchState,
+    rules::{self, Ends, Games, MatchMode, MatchRules},
+    PointScored, Scoreboard, TickSet,
+};
+
+// Who's at match point, if anybody. It's both of them when the next point decides it.
+#[derive(Resource, Debug, Default, PartialEq, Eq)]
+pub struct MatchPoint(pub PerCompetitor<bool>);
+
+impl MatchPoint {
+    pub fn active(&self) -> bool {
+        Competitor::ALL.iter().any(|competitor| self.0[*competitor])
+    }
+}
+
+// Whether each competitor would win the match with the next point
+fn at_match_point(
+    scores: PerCompetitor<usize>,
+    target: usize,
+    games: &Games,
+    rules: &MatchRules,
+) -> PerCompetitor<bool> {
+    let at = |competitor: Competitor| {
+        scores[competitor] + 1 >= target && games.won[competitor] + 1 >= rules.games_to_win()
+    };
+    PerCompetitor {
+        player: at(Competitor::Player),
+        computer: at(Competitor::Computer),
+    }
+}
+
+#[derive(Component)]
+struct MatchPointEdge;
+
+#[derive(Component)]
+struct MatchPointBanner;
+
+#[derive(Resource)]
+struct HeartbeatSound(Handle<AudioSource>);
+
+pub struct MatchPointPlugin;
+
+impl Plugin for MatchPointPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MatchPoint>()
+            .add_systems(Startup, load_heartbeat)
+            .add_systems(
+                FixedUpdate,
+                watch_match_point
+                    .after(crate::tally_score)
+                    .in_set(TickSet::Scoring)
+                    .run_if(rules::mode_is(MatchMode::Points)),
+            )
+            .add_systems(OnExit(MatchState::Playing), clear_match_point)
+            .add_systems(
+                Update,
+                (
+                    present_match_point.run_if(resource_changed::<MatchPoint>()),
+                    (pulse, heartbeat)
+                        .run_if(|match_point: Res<MatchPoint>| match_point.active())
+                        .run_if(in_state(MatchState::Playing)),
+                )
+                    .chain(),
+            );
+    }
+}
+
+fn load_heartbeat(mut commands: Commands, mut sounds: SoundLoader) {
+    commands.insert_resource(HeartbeatSound(sounds.load("thud_short.wav")));
+}
+
+fn watch_match_point(
+    mut points: EventReader<PointScored>,
+    scores: Res<Scoreboard>,
+    rules: Res<MatchRules>,
+    games: Res<Games>,
+    mut match_point: ResMut<MatchPoint>,
+) {
+    if points.read().count() == 0 {
+        return;
+    }
+    let Some(target) = rules.target_score else {
+        return;
+    };
+    let now = MatchPoint(at_match_point(**scores, target, &games, &rules));
+    // only touched when it changes, so it's only redrawn then
+    if *match_point != now {
+        *match_point = now;
+    }
+}
+
+fn clear_match_point(mut match_point: ResMut<MatchPoint>) {
+    if match_point.active() {
+        *match_point = MatchPoint::default();
+    }
+}
+
+// Puts the glow up at the threatened ends and the banner over the court, or takes them down
+fn present_match_point(
+    mut commands: Commands,
+    match_point: Res<MatchPoint>,
+    ends: Res<Ends>,
+    shown: Query<Entity, Or<(With<MatchPointEdge>, With<MatchPointBanner>)>>,
+) {
+    for entity in &shown {
+        commands.entity(entity).despawn_recursive();
+    }
+    if !match_point.active() {
+        return;
+    }
+
+    for competitor in Competitor::ALL {
+        if !match_point.0[competitor] {
+            continue;
+        }
+        // the end they're attacking
+        let side = ends.side_of(competitor).opponent();
+        let size = Vec2::new(
+            MATCH_POINT_EDGE_WIDTH,
+            entities::GoalZone::size().y - WALL_THICKNESS,
+        );
+        commands.spawn((
+            SpriteBundle {
+                transform: Transform::from_xyz(
+                    side.inset_x((WALL_THICKNESS + MATCH_POINT_EDGE_WIDTH) / 2.0),
+                    0.0,
+                    0.5,
+                )
+                .with_scale(size.extend(1.0)),
+                sprite: Sprite {
+                    color: MATCH_POINT_EDGE_COLOR,
+                    ..default()
+                },
+                ..default()
+            },
+            MatchPointEdge,
+        ));
+    }
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    top: Val::Px(MATCH_POINT_BANNER_TOP),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            MatchPointBanner,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "MATCH POINT",
+                TextStyle {
+                    font_size: MATCH_POINT_BANNER_FONT_SIZE,
+                    color: MATCH_POINT_BANNER_COLOR,
+                    ..default()
+                },
+            ));
+        });
+}
+
+// how bright the pulse is at `elapsed` seconds, from 0 to 1, peaking on each heartbeat
+fn pulse_at(elapsed: f32) -> f32 {
+    let t = (elapsed % MATCH_POINT_HEARTBEAT_PERIOD) / MATCH_POINT_HEARTBEAT_PERIOD;
+    1.0 - t
+}
+
+fn pulse(
+    mut edges: Query<&mut Sprite, With<MatchPointEdge>>,
+    banners: Query<&Children, With<MatchPointBanner>>,
+    mut texts: Query<&mut Text>,
+    time: Res<Time>,
+) {
+    let strength =
+        MATCH_POINT_MIN_ALPHA + (1.0 - MATCH_POINT_MIN_ALPHA) * pulse_at(time.elapsed_seconds());
+
+    for mut sprite in &mut edges {
+        sprite.color = MATCH_POINT_EDGE_COLOR.with_a(MATCH_POINT_EDGE_COLOR.a() * strength);
+    }
+    for children in &banners {
+        let mut texts = texts.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            for section in &mut text.sections {
+                section.style.color = MATCH_POINT_BANNER_COLOR.with_a(strength);
+            }
+        }
+    }
+}
+
+// A quiet thud-thud every period, in time with the pulse, on the music bus
+fn heartbeat(
+    mut play: EventWriter<PlaySound>,
+    sound: Res<HeartbeatSound>,
+    time: Res<Time>,
+    mut last: Local<f32>,
+) {
+    let now = time.elapsed_seconds() % MATCH_POINT_HEARTBEAT_PERIOD;
+    let beats = MATCH_POINT_HEARTBEAT_BEATS.iter().filter(|beat| {
+        // crossed since last frame, including wrapping round into a new period
+        if now >= *last {
+            **beat > *last && **beat <= now
+        } else {
+            **beat > *last || **beat <= now
+        }
+    });
+    for _ in beats {
+        play.send(
+            PlaySound::new(Bus::Music, sound.0.clone())
+                .with_volume(MATCH_POINT_HEARTBEAT_VOLUME)
+                .with_speed(MATCH_POINT_HEARTBEAT_SPEED),
+        );
+    }
+    *last = now;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn match_point_is_the_last_point_of_the_last_game() {
+        let rules = MatchRules {
+            games: 3,
+            ..default()
+        };
+        let mut games = Games::default();
+        let scores = PerCompetitor {
+            player: 10,
+            computer: 10,
+        };
+        // only game point, nobody's won a game yet
+        assert_eq!(
+            at_match_point(scores, 11, &games, &rules),
+            PerCompetitor::splat(false)
+        );
+
+        games.won.player = 1;
+        assert_eq!(
+            at_match_point(scores, 11, &games, &rules),
+            PerCompetitor {
+                player: true,
+                computer: false,
+            }
+        );
+    }
+}
@@ -0,0 +1,296 @@
+// Performance metrics, for when something's running slowly. F4 starts recording frame times,
+// simulation tick times and load (see `overload.rs`), entity and collision counts and shows
+// them on a graph; F4 again (or quitting) stops and writes everything recorded to a CSV report
+// next to the game's other saved files.
+use bevy::{app::AppExit, ecs::entity::Entities, prelude::*};
+use bevy_common::storage;
+
+use crate::{constants::*, overload::FixedLoad, CollisionEvent};
+
+// One frame's measurements
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Sample {
+    // since recording started, in seconds
+    pub time: f32,
+    // in milliseconds
+    pub frame_time: f32,
+    // all of this frame's `FixedUpdate` ticks together, in milliseconds
+    pub tick_time: f32,
+    pub ticks: usize,
+    // smoothed, and the match time dropped for being behind in milliseconds
+    pub load: f32,
+    pub behind: f32,
+    pub entities: u32,
+    pub collisions: usize,
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct Metrics {
+    pub recording: bool,
+    pub samples: Vec<Sample>,
+    started: f32,
+}
+
+impl Metrics {
+    fn start(&mut self, now: f32) {
+        *self = Self {
+            recording: true,
+            started: now,
+            ..default()
+        };
+    }
+
+    fn stop(&mut self) {
+        self.recording = false;
+        if self.samples.is_empty() {
+            return;
+        }
+        match storage::save(GAME_NAME, METRICS_FILE, &report(&self.samples)) {
+            Ok(()) => {
+                if let Some(path) = storage::path(GAME_NAME, METRICS_FILE) {
+                    info!("Wrote metrics to {}", path.display());
+                }
+            }
+            Err(err) => warn!("Couldn't write metrics: {err}"),
+        }
+        self.samples.clear();
+    }
+}
+
+pub fn report(samples: &[Sample]) -> String {
+    let mut report = "time,frame_ms,tick_ms,ticks,load,behind_ms,entities,collisions\n".to_string();
+    for sample in samples {
+        report.push_str(&format!(
+            "{:.3},{:.3},{:.3},{},{:.3},{:.3},{},{}\n",
+            sample.time,
+            sample.frame_time,
+            sample.tick_time,
+            sample.ticks,
+            sample.load,
+            sample.behind,
+            sample.entities,
+            sample.collisions
+        ));
+    }
+    report
+}
+
+#[derive(Component)]
+struct MetricsOverlay;
+
+#[derive(Component)]
+struct MetricsText;
+
+// One bar of the frame time graph, `MetricsGraphBar(0)` the newest
+#[derive(Component)]
+struct MetricsGraphBar(usize);
+
+pub struct MetricsPlugin;
+
+impl Plugin for MetricsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Metrics>()
+            .add_systems(Startup, spawn_overlay)
+            .add_systems(
+                Update,
+                (
+                    toggle_recording.run_if(crate::net::keyboard_is_local),
+                    update_overlay,
+                )
+                    .chain(),
+            )
+            .add_systems(Last, (record_sample.run_if(recording), stop_on_exit));
+    }
+}
+
+fn recording(metrics: Res<Metrics>) -> bool {
+    metrics.recording
+}
+
+fn record_sample(
+    mut metrics: ResMut<Metrics>,
+    mut collision_events: EventReader<CollisionEvent>,
+    entities: &Entities,
+    load: Res<FixedLoad>,
+    time: Res<Time<Real>>,
+) {
+    let sample = Sample {
+        time: time.elapsed_seconds() - metrics.started,
+        frame_time: time.delta_seconds() * 1000.0,
+        tick_time: load.tick_time,
+        ticks: load.ticks,
+        load: load.load,
+        behind: load.behind,
+        entities: entities.len(),
+        collisions: collision_events.read().count(),
+    };
+    metrics.samples.push(sample);
+}
+
+fn toggle_recording(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut metrics: ResMut<Metrics>,
+    mut overlays: Query<&mut Visibility, With<MetricsOverlay>>,
+    time: Res<Time<Real>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::F4) {
+        return;
+    }
+
+    if metrics.recording {
+        metrics.stop();
+    } else {
+        metrics.start(time.elapsed_seconds());
+    }
+    for mut visibility in &mut overlays {
+        *visibility = if metrics.recording {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+fn stop_on_exit(mut exits: EventReader<AppExit>, mut metrics: ResMut<Metrics>) {
+    if exits.read().next().is_some() && metrics.recording {
+        metrics.stop();
+    }
+}
+
+fn spawn_overlay(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(DIAGNOSTICS_PADDING),
+                    left: Val::Px(DIAGNOSTICS_PADDING),
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(DIAGNOSTICS_PADDING)),
+                    ..default()
+                },
+                background_color: SETTINGS_MENU_BACKDROP_COLOR.into(),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            MetricsOverlay,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: DIAGNOSTICS_FONT_SIZE,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                ),
+                MetricsText,
+            ));
+            // oldest on the left, bars grow up from the bottom
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        height: Val::Px(METRICS_GRAPH_HEIGHT),
+                        align_items: AlignItems::FlexEnd,
+                        margin: UiRect::top(Val::Px(DIAGNOSTICS_PADDING)),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|graph| {
+                    for age in (0..METRICS_GRAPH_SAMPLES).rev() {
+                        graph.spawn((
+                            NodeBundle {
+                                style: Style {
+                                    width: Val::Px(METRICS_GRAPH_BAR_WIDTH),
+                                    height: Val::Px(0.0),
+                                    ..default()
+                                },
+                                ..default()
+                            },
+                            MetricsGraphBar(age),
+                        ));
+                    }
+                });
+        });
+}
+
+// Green within a 60 Hz frame, yellow within two, red beyond
+fn frame_color(frame_time: f32) -> Color {
+    if frame_time <= METRICS_FRAME_BUDGET {
+        Color::GREEN
+    } else if frame_time <= METRICS_FRAME_BUDGET * 2.0 {
+        Color::YELLOW
+    } else {
+        Color::RED
+    }
+}
+
+fn update_overlay(
+    metrics: Res<Metrics>,
+    mut texts: Query<&mut Text, With<MetricsText>>,
+    mut bars: Query<(&mut Style, &mut BackgroundColor, &MetricsGraphBar)>,
+) {
+    if !metrics.recording {
+        return;
+    }
+
+    let recent = &metrics.samples[metrics.samples.len().saturating_sub(METRICS_GRAPH_SAMPLES)..];
+    let count = recent.len().max(1) as f32;
+    let average = |value: fn(&Sample) -> f32| recent.iter().map(value).sum::<f32>() / count;
+    let latest = recent.last().copied().unwrap_or_default();
+
+    for mut text in &mut texts {
+        text.sections[0].value = format!(
+            "recording metrics (F4 to stop)\n\
+             frame: {:.1} ms avg, {:.1} ms worst\n\
+             ticks: {:.2} ms/frame avg, {:.0}% load\n\
+             entities: {}\n\
+             collisions: {:.1}/frame avg",
+            average(|sample| sample.frame_time),
+            recent
+                .iter()
+                .map(|sample| sample.frame_time)
+                .fold(0.0, f32::max),
+            average(|sample| sample.tick_time),
+            latest.load * 100.0,
+            latest.entities,
+            average(|sample| sample.collisions as f32),
+        );
+    }
+
+    for (mut style, mut color, bar) in &mut bars {
+        let frame_time = recent
+            .len()
+            .checked_sub(bar.0 + 1)
+            .map_or(0.0, |index| recent[index].frame_time);
+        style.height = Val::Px((frame_time * METRICS_GRAPH_SCALE).min(METRICS_GRAPH_HEIGHT));
+        *color = frame_color(frame_time).into();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_a_row_per_sample() {
+        let report = report(&[
+            Sample {
+                time: 0.5,
+                frame_time: 16.0,
+                tick_time: 1.25,
+                ticks: 1,
+                load: 0.08,
+                behind: 0.0,
+                entities: 40,
+                collisions: 2,
+            },
+            Sample::default(),
+        ]);
+        let rows: Vec<_> = report.lines().collect();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[1], "0.500,16.000,1.250,1,0.080,0.000,40,2");
+    }
+}
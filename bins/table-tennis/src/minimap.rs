@@ -0,0 +1,174 @@
+// A picture-in-picture of the whole court in the top right corner, for whenever the main
+// camera can't see all of it: the camera zoomed in on somebody, or split screen. It's a second
+// camera looking at the same world, so it shows the balls, paddles and anything else on the
+// court just as they are, only smaller.
+use bevy::{
+    core_pipeline::clear_color::ClearColorConfig,
+    prelude::*,
+    render::camera::{ScalingMode, Viewport},
+    window::PrimaryWindow,
+};
+
+use crate::{
+    constants::*,
+    orientation::Orientation,
+    settings::Settings,
+    split_screen::{SplitCamera, SplitScreen},
+};
+
+// When the minimap's shown
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MinimapMode {
+    // only while some of the court's out of view
+    #[default]
+    Automatic,
+    Always,
+    Off,
+}
+
+impl MinimapMode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            MinimapMode::Automatic => "When needed",
+            MinimapMode::Always => "Always",
+            MinimapMode::Off => "Off",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            MinimapMode::Automatic => MinimapMode::Always,
+            MinimapMode::Always => MinimapMode::Off,
+            MinimapMode::Off => MinimapMode::Automatic,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct MinimapCamera;
+
+pub struct MinimapPlugin;
+
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_minimap)
+            .add_systems(Update, place_minimap);
+    }
+}
+
+// The court and its walls
+fn court() -> Rect {
+    Rect::new(LEFT_WALL, BOTTOM_WALL, RIGHT_WALL, TOP_WALL).inset(WALL_THICKNESS / 2.0)
+}
+
+// Whether a camera at `at` seeing `view` (its projection's area, already scaled) has all of
+// `court` in it
+fn court_fits(view: Rect, at: Vec2, court: Rect) -> bool {
+    let seen = Rect::from_center_size(at, view.size());
+    // a hair's leeway, the court fills a window its own size exactly
+    let seen = seen.inset(MINIMAP_LEEWAY);
+    seen.contains(court.min) && seen.contains(court.max)
+}
+
+fn spawn_minimap(mut commands: Commands) {
+    commands.spawn((
+        Camera2dBundle {
+            camera: Camera {
+                // drawn over the main camera
+                order: 1,
+                is_active: false,
+                ..default()
+            },
+            camera_2d: Camera2d {
+                clear_color: ClearColorConfig::Custom(MINIMAP_BACKGROUND),
+            },
+            ..default()
+        },
+        // the menus and overlays are only drawn once, by the main camera
+        UiCameraConfig { show_ui: false },
+        MinimapCamera,
+    ));
+}
+
+fn place_minimap(
+    settings: Res<Settings>,
+    orientation: Res<Orientation>,
+    split: Res<SplitScreen>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    main: Query<
+        (&Transform, &OrthographicProjection),
+        (With<Camera2d>, Without<MinimapCamera>, Without<SplitCamera>),
+    >,
+    mut minimaps: Query<
+        (&mut Camera, &mut Transform, &mut OrthographicProjection),
+        With<MinimapCamera>,
+    >,
+) {
+    let court = court();
+    let needed = match settings.minimap.mode {
+        MinimapMode::Off => false,
+        MinimapMode::Always => true,
+        MinimapMode::Automatic if split.active => true,
+        MinimapMode::Automatic => main.iter().any(|(transform, projection)| {
+            let at = transform.translation.truncate();
+            let mut view = projection.area;
+            if *orientation == Orientation::Portrait {
+                // the view's turned a quarter, so its width lies along the court's height
+                view = Rect::from_center_size(view.center(), view.size().yx());
+            }
+            !court_fits(view, at, court)
+        }),
+    };
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+
+    // as wide as the setting says, and as tall as the court is for that
+    let size = match *orientation {
+        Orientation::Landscape => court.size(),
+        Orientation::Portrait => court.size().yx(),
+    };
+    let width = window.physical_width() as f32 * settings.minimap.size;
+    let physical_size = UVec2::new(width as u32, (width * size.y / size.x) as u32);
+    let margin = (MINIMAP_MARGIN * window.scale_factor() as f32) as u32;
+    let fits = physical_size.x + 2 * margin <= window.physical_width()
+        && physical_size.y + 2 * margin <= window.physical_height()
+        && physical_size.min_element() > 0;
+
+    for (mut camera, mut transform, mut projection) in &mut minimaps {
+        camera.is_active = needed && fits;
+        if !camera.is_active {
+            continue;
+        }
+        camera.viewport = Some(Viewport {
+            physical_position: UVec2::new(
+                window.physical_width() - physical_size.x - margin,
+                margin,
+            ),
+            physical_size,
+            ..default()
+        });
+        transform.translation = court.center().extend(transform.translation.z);
+        transform.rotation = orientation.rotation();
+        projection.scaling_mode = ScalingMode::Fixed {
+            width: size.x,
+            height: size.y,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_court_fits_until_the_view_shrinks_or_moves() {
+        let court = Rect::new(-100.0, -50.0, 100.0, 50.0);
+        let view = Rect::new(-120.0, -60.0, 120.0, 60.0);
+        assert!(court_fits(view, Vec2::ZERO, court));
+        assert!(!court_fits(view, Vec2::new(40.0, 0.0), court));
+        // zoomed in
+        let zoomed = Rect::new(-60.0, -30.0, 60.0, 30.0);
+        assert!(!court_fits(zoomed, Vec2::ZERO, court));
+    }
+}
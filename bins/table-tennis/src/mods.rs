@@ -0,0 +1,248 @@
+// Content packs: every `.ron` file in `mods/` can add themes (to the settings menu), mutator
+// presets (to the pre-match screen), computer personalities and synthesized sounds. They're
+// read once, the first time anything asks. A pack that doesn't parse or doesn't make sense is
+// left out whole, and what's wrong with it is listed with any other loading problems (see
+// `asset_check.rs`). `assets/example_pack.ron` shows what goes in one.
+use std::{collections::HashMap, fs, path::Path, sync::OnceLock};
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    asset_check::AssetProblems,
+    constants::*,
+    mutators::{Mutator, Mutators},
+    personality::AiPersonality,
+    synth::Tone,
+    theme::Palette,
+};
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ContentPack {
+    pub name: String,
+    pub themes: Vec<ModTheme>,
+    pub mutator_presets: Vec<MutatorPreset>,
+    pub personalities: Vec<AiPersonality>,
+    // by the sound file they replace, or the synthesized sound's name
+    pub sounds: HashMap<String, Vec<Tone>>,
+}
+
+// Colors are red, green and blue. Above 1 only makes sense with `hdr`, they glow with bloom.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ModTheme {
+    pub name: String,
+    pub background: (f32, f32, f32),
+    pub paddle: (f32, f32, f32),
+    pub ball: (f32, f32, f32),
+    pub wall: (f32, f32, f32),
+    pub goal: (f32, f32, f32),
+    pub goal_flash: (f32, f32, f32),
+    pub scoreboard: (f32, f32, f32),
+    #[serde(default)]
+    pub hdr: bool,
+}
+
+impl ModTheme {
+    pub fn palette(&self) -> Palette {
+        let color = |(r, g, b): (f32, f32, f32)| Color::rgb(r, g, b);
+        Palette {
+            background: color(self.background),
+            paddle: color(self.paddle),
+            ball: color(self.ball),
+            wall: color(self.wall),
+            goal: color(self.goal),
+            goal_flash: color(self.goal_flash),
+            scoreboard: color(self.scoreboard),
+        }
+    }
+
+    fn colors(&self) -> [(f32, f32, f32); 7] {
+        [
+            self.background,
+            self.paddle,
+            self.ball,
+            self.wall,
+            self.goal,
+            self.goal_flash,
+            self.scoreboard,
+        ]
+    }
+}
+
+// Mutators that go well together, picked all at once
+#[derive(Debug, Clone, Deserialize)]
+pub struct MutatorPreset {
+    pub name: String,
+    pub mutators: Vec<Mutator>,
+}
+
+impl MutatorPreset {
+    pub fn mutators(&self) -> Mutators {
+        self.mutators.iter().copied().collect()
+    }
+}
+
+// Everything the packs that made it in add, in the order they were found
+#[derive(Debug, Default)]
+pub struct Mods {
+    pub themes: Vec<ModTheme>,
+    pub mutator_presets: Vec<MutatorPreset>,
+    pub personalities: Vec<AiPersonality>,
+    pub sounds: HashMap<String, Vec<Tone>>,
+    // for packs that were left out
+    pub problems: Vec<String>,
+}
+
+impl Mods {
+    fn add(&mut self, pack: ContentPack) {
+        self.themes.extend(pack.themes);
+        self.mutator_presets.extend(pack.mutator_presets);
+        self.personalities.extend(pack.personalities);
+        self.sounds.extend(pack.sounds);
+    }
+}
+
+pub fn loaded() -> &'static Mods {
+    static MODS: OnceLock<Mods> = OnceLock::new();
+    MODS.get_or_init(|| scan(Path::new(MODS_DIR)))
+}
+
+// Nothing at all if there's no such directory
+fn scan(directory: &Path) -> Mods {
+    let mut mods = Mods::default();
+    let Ok(entries) = fs::read_dir(directory) else {
+        return mods;
+    };
+    let mut paths: Vec<_> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|extension| extension == "ron"))
+        .collect();
+    // the same order everywhere, whatever the filesystem lists them in
+    paths.sort();
+
+    for path in paths {
+        let pack = fs::read_to_string(&path)
+            .map_err(|err| err.to_string())
+            .and_then(|contents| read_pack(&contents));
+        match pack {
+            Ok(pack) => mods.add(pack),
+            Err(err) => mods
+                .problems
+                .push(format!("{} was left out: {err}", path.display())),
+        }
+    }
+    mods
+}
+
+fn read_pack(contents: &str) -> Result<ContentPack, String> {
+    let pack: ContentPack = ron::from_str(contents).map_err(|err| err.to_string())?;
+    validate(&pack)?;
+    Ok(pack)
+}
+
+// What the game can't make sense of, the first thing found
+fn validate(pack: &ContentPack) -> Result<(), String> {
+    if pack.name.trim().is_empty() {
+        return Err("it needs a name".to_string());
+    }
+
+    for theme in &pack.themes {
+        if theme.name.trim().is_empty() {
+            return Err("a theme needs a name".to_string());
+        }
+        let most = if theme.hdr { MOD_MAX_HDR_CHANNEL } else { 1.0 };
+        let mut channels = theme.colors().into_iter().flat_map(|(r, g, b)| [r, g, b]);
+        if channels.any(|channel| !(0.0..=most).contains(&channel)) {
+            return Err(format!(
+                "{}'s colors need to be from 0 to {most}",
+                theme.name
+            ));
+        }
+    }
+
+    for preset in &pack.mutator_presets {
+        if preset.name.trim().is_empty() || preset.mutators.is_empty() {
+            return Err("a mutator preset needs a name and some mutators".to_string());
+        }
+    }
+
+    let fraction = 0.0..=1.0;
+    for personality in &pack.personalities {
+        if personality.name.trim().is_empty() {
+            return Err("a personality needs a name".to_string());
+        }
+        let fractions = [
+            personality.aggression,
+            personality.angle_preference,
+            personality.fatigue,
+            personality.taunt_chance,
+        ];
+        if !fractions.iter().all(|value| fraction.contains(value))
+            || !(0.0..90.0).contains(&personality.return_angle)
+        {
+            return Err(format!("{}'s traits are out of range", personality.name));
+        }
+    }
+
+    for (name, tones) in &pack.sounds {
+        let length: f32 = tones.iter().map(|tone| tone.length).sum();
+        let sensible = tones
+            .iter()
+            .all(|tone| tone.length > 0.0 && tone.pitch >= 0.0 && fraction.contains(&tone.volume));
+        if tones.is_empty() || !sensible || length > MOD_MAX_SOUND_LENGTH {
+            return Err(format!(
+                "{name} needs tones with lengths, pitches and volumes from 0 to 1, at most \
+                 {MOD_MAX_SOUND_LENGTH} seconds of them"
+            ));
+        }
+    }
+    Ok(())
+}
+
+pub struct ModsPlugin;
+
+impl Plugin for ModsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, report_problems);
+    }
+}
+
+fn report_problems(mut problems: ResMut<AssetProblems>) {
+    for problem in &loaded().problems {
+        warn!("{problem}");
+        problems.0.push(problem.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_example_pack_is_valid() {
+        let pack = read_pack(include_str!("../assets/example_pack.ron")).unwrap();
+        assert!(!pack.themes.is_empty());
+        assert!(!pack.mutator_presets.is_empty());
+        assert!(!pack.personalities.is_empty());
+        assert!(!pack.sounds.is_empty());
+    }
+
+    #[test]
+    fn malformed_packs_say_why() {
+        assert!(read_pack("(name: \"Broken\", themes: [(name: \"Half\")])").is_err());
+        assert!(read_pack("(themes: [])")
+            .unwrap_err()
+            .contains("needs a name"));
+        assert!(read_pack(
+            "(name: \"Wild\", mutator_presets: [(name: \"None\", mutators: [Teleporting])])"
+        )
+        .is_err());
+        let err = read_pack(
+            "(name: \"Loud\", sounds: { \"beep\": [(wave: Sine, pitch: 440.0, length: 0.1, \
+             volume: 3.0)] })",
+        )
+        .unwrap_err();
+        assert!(err.contains("beep"));
+    }
+}
@@ -0,0 +1,64 @@
+use crate::constants::*;
+
+// How quickly a paddle gets up to speed when it's told to move
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AccelerationCurve {
+    // the original arcade feel: full speed immediately, stop dead on release
+    Instant,
+    // constant acceleration all the way up to top speed
+    Linear,
+    // quick off the mark, tapering off as it approaches top speed
+    #[default]
+    Smooth,
+}
+
+impl AccelerationCurve {
+    pub fn name(&self) -> &'static str {
+        match self {
+            AccelerationCurve::Instant => "Instant",
+            AccelerationCurve::Linear => "Linear",
+            AccelerationCurve::Smooth => "Smooth",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            AccelerationCurve::Instant => AccelerationCurve::Linear,
+            AccelerationCurve::Linear => AccelerationCurve::Smooth,
+            AccelerationCurve::Smooth => AccelerationCurve::Instant,
+        }
+    }
+
+    // fraction of `PADDLE_ACCELERATION` available at a fraction of the paddle's top speed
+    fn falloff(&self, speed_fraction: f32) -> f32 {
+        match self {
+            AccelerationCurve::Instant | AccelerationCurve::Linear => 1.0,
+            AccelerationCurve::Smooth => {
+                (1.0 - speed_fraction * speed_fraction).max(SMOOTH_ACCELERATION_FLOOR)
+            }
+        }
+    }
+
+    // Move `velocity` towards `target` over `dt` seconds. `max_speed` is the paddle's top
+    // speed, which acceleration is scaled against. Slowing down or changing direction is
+    // driven by friction, speeding up by acceleration.
+    pub fn accelerate(&self, velocity: f32, target: f32, max_speed: f32, dt: f32) -> f32 {
+        if *self == AccelerationCurve::Instant {
+            return target;
+        }
+
+        let braking = target * velocity < 0.0 || target.abs() < velocity.abs();
+        let rate = if braking {
+            PADDLE_FRICTION
+        } else {
+            PADDLE_ACCELERATION * self.falloff(velocity.abs() / max_speed)
+        };
+
+        let step = rate * dt;
+        if (target - velocity).abs() <= step {
+            target
+        } else {
+            velocity + step * (target - velocity).signum()
+        }
+    }
+}
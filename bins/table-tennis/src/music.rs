@@ -0,0 +1,118 @@
+// The backing track: three synthesized loops of the same length (see `synth.rs`), started
+// together and kept together. The bass plays through every point, and the arpeggio and drums
+// fade in as the rally goes on and the ball gets quicker, with the whole thing speeding up a
+// little. It all drops back once the point's over. It's on the music bus, so goals duck it.
+use bevy::{audio::Volume, prelude::*};
+
+use crate::{
+    asset_check::SoundLoader,
+    audio_bus::{Bus, Mixer},
+    constants::*,
+    entities,
+    mutators::MatchState,
+    personality::Rally,
+    settings::Settings,
+    TickSet,
+};
+
+// How much is going on in the rally, from 0 to 1
+#[derive(Resource, Debug, Default)]
+pub struct MusicIntensity(pub f32);
+
+// One of the loops, which is all the way in once the intensity reaches `enters_at`
+#[derive(Component, Debug, Clone, Copy)]
+struct MusicLayer {
+    enters_at: f32,
+}
+
+impl MusicLayer {
+    // how far in it is at `intensity`, from 0 to 1
+    fn presence(self, intensity: f32) -> f32 {
+        ((intensity - self.enters_at) / MUSIC_LAYER_FADE + 1.0).clamp(0.0, 1.0)
+    }
+}
+
+pub struct MusicPlugin;
+
+impl Plugin for MusicPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MusicIntensity>()
+            .add_systems(Startup, start_music)
+            .add_systems(FixedUpdate, measure_intensity.in_set(TickSet::Effects))
+            .add_systems(Update, follow_intensity);
+    }
+}
+
+fn start_music(mut commands: Commands, mut sounds: SoundLoader) {
+    // spawned in the same frame, so they start on the same beat
+    for (name, enters_at) in [
+        ("music_bass", 0.0),
+        ("music_arp", MUSIC_ARP_ENTERS_AT),
+        ("music_drums", MUSIC_DRUMS_ENTER_AT),
+    ] {
+        commands.spawn((
+            AudioBundle {
+                source: sounds.synthesized(name),
+                settings: PlaybackSettings::LOOP.with_volume(Volume::new_relative(0.0)),
+            },
+            MusicLayer { enters_at },
+        ));
+    }
+}
+
+fn measure_intensity(
+    rally: Res<Rally>,
+    balls: Query<&entities::Velocity, With<entities::Ball>>,
+    mut intensity: ResMut<MusicIntensity>,
+) {
+    let returns = rally.returns as f32 / MUSIC_RALLY_RETURNS as f32;
+    let fastest = balls
+        .iter()
+        .map(|velocity| velocity.0.length())
+        .fold(0.0, f32::max);
+    let speed = (fastest - BALL_SPEED) / (MAX_RAMPED_BALL_SPEED - BALL_SPEED);
+    intensity.0 = returns.max(speed).clamp(0.0, 1.0);
+}
+
+// Eases every layer towards how loud it should be, and them all towards the same speed
+fn follow_intensity(
+    intensity: Res<MusicIntensity>,
+    settings: Res<Settings>,
+    state: Res<State<MatchState>>,
+    mixer: Res<Mixer>,
+    global: Res<GlobalVolume>,
+    layers: Query<(&MusicLayer, &AudioSink)>,
+    time: Res<Time<Real>>,
+) {
+    let playing = settings.music && *state.get() == MatchState::Playing;
+    let level = mixer.level(Bus::Music, time.elapsed_seconds()) * global.volume.get();
+    let ease = 1.0 - (-MUSIC_SMOOTHING * time.delta_seconds()).exp();
+    let speed = 1.0 + MUSIC_SPEED_UP * intensity.0;
+
+    for (layer, sink) in &layers {
+        let target = if playing {
+            MUSIC_VOLUME * layer.presence(intensity.0) * level
+        } else {
+            0.0
+        };
+        let volume = sink.volume();
+        sink.set_volume(volume + (target - volume) * ease);
+        // the same for every layer, or they'd drift apart
+        sink.set_speed(if playing { speed } else { 1.0 });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layers_fade_in_as_things_pick_up() {
+        let bass = MusicLayer { enters_at: 0.0 };
+        let drums = MusicLayer { enters_at: 0.8 };
+        assert_eq!(bass.presence(0.0), 1.0);
+        assert_eq!(drums.presence(0.0), 0.0);
+        assert!(drums.presence(0.8 - MUSIC_LAYER_FADE / 2.0) > 0.0);
+        assert_eq!(drums.presence(0.9), 1.0);
+    }
+}
@@ -0,0 +1,420 @@
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+use bevy_common::{
+    input,
+    screens::{self, AddScreen},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    arena::{self, ArenaPiece},
+    constants::*,
+    cosmetics::LockerState,
+    daily::ChallengeState,
+    devices::Joining,
+    entities, mods,
+    net::NetRole,
+    paddle_colors::{ColorItem, PaddleColors},
+    rating::Rating,
+    rules::{MatchRules, Opponent, RuleItem},
+    settings::MenuState,
+    settings::Settings,
+};
+
+// Optional rule changes picked before a match. Gameplay systems check `Mutators` for the
+// ones they implement, so any combination can be on at once.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Reflect, Serialize, Deserialize)]
+#[reflect(Hash, Serialize, Deserialize)]
+pub enum Mutator {
+    MultiBall,
+    Gravity,
+    BigPaddles,
+    SpeedRamp,
+    Obstacles,
+    FogOfWar,
+    Portals,
+}
+
+impl Mutator {
+    pub const ALL: [Mutator; 7] = [
+        Mutator::MultiBall,
+        Mutator::Gravity,
+        Mutator::BigPaddles,
+        Mutator::SpeedRamp,
+        Mutator::Obstacles,
+        Mutator::FogOfWar,
+        Mutator::Portals,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Mutator::MultiBall => "Multi-ball",
+            Mutator::Gravity => "Gravity",
+            Mutator::BigPaddles => "Big paddles",
+            Mutator::SpeedRamp => "Speed ramp",
+            Mutator::Obstacles => "Obstacles",
+            Mutator::FogOfWar => "Fog of war",
+            Mutator::Portals => "Portals",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Mutator::MultiBall => "serve several balls at once",
+            Mutator::Gravity => "balls fall towards the bottom wall",
+            Mutator::BigPaddles => "both paddles start bigger",
+            Mutator::SpeedRamp => "balls speed up every time they're returned",
+            Mutator::Obstacles => "blocks in the middle of the court",
+            Mutator::FogOfWar => "the middle of the court is hidden",
+            Mutator::Portals => "the top and bottom walls are linked",
+        }
+    }
+}
+
+#[derive(Resource, Debug, Clone, Default, PartialEq, Eq, Reflect, Serialize, Deserialize)]
+#[reflect(Resource, Serialize, Deserialize)]
+pub struct Mutators(HashSet<Mutator>);
+
+impl FromIterator<Mutator> for Mutators {
+    fn from_iter<I: IntoIterator<Item = Mutator>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl Mutators {
+    pub fn contains(&self, mutator: Mutator) -> bool {
+        self.0.contains(&mutator)
+    }
+
+    pub fn toggle(&mut self, mutator: Mutator) {
+        if !self.0.remove(&mutator) {
+            self.0.insert(mutator);
+        }
+    }
+
+    // how many balls go out on each serve
+    pub fn balls(&self, settings: &Settings) -> usize {
+        if self.contains(Mutator::MultiBall) {
+            settings.gameplay.multi_balls
+        } else {
+            1
+        }
+    }
+
+    // the paddle height everything else is scaled from, as a multiple of `PADDLE_SIZE.y`
+    pub fn paddle_scale(&self) -> f32 {
+        if self.contains(Mutator::BigPaddles) {
+            BIG_PADDLES_MULTIPLIER
+        } else {
+            1.0
+        }
+    }
+}
+
+// A run condition for systems that only apply with `mutator` on
+pub fn enabled(mutator: Mutator) -> impl FnMut(Res<Mutators>) -> bool + Clone {
+    move |mutators: Res<Mutators>| mutators.contains(mutator)
+}
+
+// The mutators screen is shown before the match starts
+#[derive(States, Debug, Clone, Copy, Default, Hash, PartialEq, Eq)]
+pub enum MatchState {
+    #[default]
+    PreMatch,
+    Playing,
+    // a break between games of a longer match, see `game_over.rs`
+    Intermission,
+    // somebody won, see `game_over.rs`
+    GameOver,
+}
+
+// Every row on the pre-match screen: the match rules, each side's colors, then the mutators
+// and any presets of them from mods (see `mods.rs`), by index
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PreMatchItem {
+    Rule(RuleItem),
+    Color(ColorItem),
+    Mutator(Mutator),
+    Preset(usize),
+}
+
+impl PreMatchItem {
+    // leaving out the rules that don't apply to the match being set up
+    fn all(rules: &MatchRules, role: &NetRole) -> Vec<PreMatchItem> {
+        RuleItem::ALL
+            .into_iter()
+            .filter(|item| item.shown(rules, role))
+            .map(PreMatchItem::Rule)
+            .chain(ColorItem::all().map(PreMatchItem::Color))
+            .chain(Mutator::ALL.into_iter().map(PreMatchItem::Mutator))
+            .chain((0..mods::loaded().mutator_presets.len()).map(PreMatchItem::Preset))
+            .collect()
+    }
+
+    fn header(&self) -> &'static str {
+        match self {
+            PreMatchItem::Rule(_) => "Rules",
+            PreMatchItem::Color(_) => "Colors",
+            PreMatchItem::Mutator(_) => "Mutators",
+            PreMatchItem::Preset(_) => "Presets",
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+struct MutatorsCursor(usize);
+
+#[derive(Component)]
+struct MutatorsScreen;
+
+#[derive(Component)]
+struct MutatorsScreenText;
+
+pub struct MutatorsPlugin;
+
+impl Plugin for MutatorsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<MatchState>()
+            .init_resource::<Mutators>()
+            .init_resource::<MutatorsCursor>()
+            .add_screen::<MutatorsScreen, _>(MatchState::PreMatch, spawn_mutators_screen)
+            .add_systems(
+                Update,
+                (navigate_mutators_screen, update_mutators_screen_text)
+                    .chain()
+                    .run_if(in_state(MatchState::PreMatch))
+                    // the settings menu uses the same keys
+                    .run_if(in_state(MenuState::Closed))
+                    .run_if(in_state(Joining::Closed))
+                    .run_if(in_state(LockerState::Closed))
+                    .run_if(in_state(ChallengeState::Off))
+                    .run_if(crate::net::has_input_authority),
+            )
+            .add_systems(OnEnter(MatchState::Playing), spawn_obstacles)
+            .add_systems(OnExit(MatchState::Playing), despawn_obstacles);
+    }
+}
+
+fn navigate_mutators_screen(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut cursor: ResMut<MutatorsCursor>,
+    mut mutators: ResMut<Mutators>,
+    mut rules: ResMut<MatchRules>,
+    mut colors: ResMut<PaddleColors>,
+    mut next_state: ResMut<NextState<MatchState>>,
+    mut next_joining: ResMut<NextState<Joining>>,
+    role: Res<NetRole>,
+) {
+    let all = PreMatchItem::all(&rules, &role);
+    let items = all.len();
+    // rows come and go with the rules
+    cursor.0 = cursor.0.min(items - 1);
+
+    if keyboard_input.any_just_pressed(input::UP) {
+        cursor.0 = (cursor.0 + items - 1) % items;
+    }
+    if keyboard_input.any_just_pressed(input::DOWN) {
+        cursor.0 = (cursor.0 + 1) % items;
+    }
+
+    let step = if keyboard_input.any_just_pressed(input::LEFT) {
+        Some(-1.0)
+    } else if keyboard_input.any_just_pressed(input::RIGHT)
+        || keyboard_input.just_pressed(KeyCode::Space)
+    {
+        Some(1.0)
+    } else {
+        None
+    };
+
+    if let Some(step) = step {
+        match all[cursor.0] {
+            PreMatchItem::Rule(item) => item.adjust(&mut rules, step),
+            PreMatchItem::Color(item) => item.adjust(&mut colors, step),
+            PreMatchItem::Mutator(mutator) => mutators.toggle(mutator),
+            PreMatchItem::Preset(preset) => {
+                *mutators = mods::loaded().mutator_presets[preset].mutators()
+            }
+        }
+    }
+
+    // two players here pick who's on which paddle first
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        if rules.opponent == Opponent::Local {
+            next_joining.set(Joining::Open);
+        } else {
+            next_state.set(MatchState::Playing);
+        }
+    }
+}
+
+fn screen_sections(
+    mutators: &Mutators,
+    rules: &MatchRules,
+    colors: &PaddleColors,
+    rating: &Rating,
+    role: &NetRole,
+    cursor: usize,
+) -> Vec<TextSection> {
+    let style = |color| TextStyle {
+        font_size: SETTINGS_MENU_FONT_SIZE,
+        color,
+        ..default()
+    };
+
+    let mut sections = vec![
+        TextSection::new("New match", style(Color::WHITE)),
+        TextSection::new(
+            format!("    rating {:.0}\n", rating.rating),
+            style(Color::GRAY),
+        ),
+    ];
+    let mut in_header = None;
+
+    for (i, item) in PreMatchItem::all(rules, role).into_iter().enumerate() {
+        let header = item.header();
+        if in_header != Some(header) {
+            in_header = Some(header);
+            sections.push(TextSection::new(
+                format!("\n{header}\n"),
+                style(Color::GRAY),
+            ));
+        }
+
+        let (marker, color) = if i == cursor {
+            ("> ", Color::YELLOW)
+        } else {
+            ("  ", Color::WHITE)
+        };
+
+        match item {
+            PreMatchItem::Rule(item) => sections.push(TextSection::new(
+                format!("{marker}{}: < {} >\n", item.label(), item.value(rules)),
+                style(color),
+            )),
+            PreMatchItem::Color(item) => sections.push(TextSection::new(
+                format!("{marker}{}: < {} >\n", item.label(), item.value(colors)),
+                style(color),
+            )),
+            PreMatchItem::Mutator(mutator) => {
+                let check = if mutators.contains(mutator) { "x" } else { " " };
+
+                sections.push(TextSection::new(
+                    format!("{marker}[{check}] {}", mutator.name()),
+                    style(color),
+                ));
+                sections.push(TextSection::new(
+                    format!(" - {}\n", mutator.description()),
+                    style(Color::GRAY),
+                ));
+            }
+            PreMatchItem::Preset(preset) => {
+                let preset = &mods::loaded().mutator_presets[preset];
+                let check = if *mutators == preset.mutators() {
+                    "x"
+                } else {
+                    " "
+                };
+                let names: Vec<_> = preset.mutators.iter().map(Mutator::name).collect();
+
+                sections.push(TextSection::new(
+                    format!("{marker}[{check}] {}", preset.name),
+                    style(color),
+                ));
+                sections.push(TextSection::new(
+                    format!(" - {}\n", names.join(", ")),
+                    style(Color::GRAY),
+                ));
+            }
+        }
+    }
+
+    sections.push(TextSection::new(
+        "\nup/down: select  left/right: change  enter: start  \
+         t: tutorial  d: daily  l: locker  tab: settings",
+        style(Color::GRAY),
+    ));
+
+    sections
+}
+
+fn spawn_mutators_screen(
+    mut commands: Commands,
+    mutators: Res<Mutators>,
+    rules: Res<MatchRules>,
+    colors: Res<PaddleColors>,
+    rating: Res<Rating>,
+    role: Res<NetRole>,
+    cursor: Res<MutatorsCursor>,
+) {
+    commands
+        .spawn((
+            // under the settings menu so it can be opened from here
+            screens::overlay(SETTINGS_MENU_BACKDROP_COLOR, SETTINGS_MENU_Z_INDEX - 1),
+            MutatorsScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_sections(screen_sections(
+                    &mutators, &rules, &colors, &rating, &role, cursor.0,
+                )),
+                MutatorsScreenText,
+            ));
+            arena::spawn_preview(parent);
+        });
+}
+
+fn update_mutators_screen_text(
+    mutators: Res<Mutators>,
+    rules: Res<MatchRules>,
+    colors: Res<PaddleColors>,
+    rating: Res<Rating>,
+    role: Res<NetRole>,
+    cursor: Res<MutatorsCursor>,
+    mut text: Query<&mut Text, With<MutatorsScreenText>>,
+) {
+    if !mutators.is_changed() && !rules.is_changed() && !colors.is_changed() && !cursor.is_changed()
+    {
+        return;
+    }
+
+    for mut text in &mut text {
+        text.sections = screen_sections(&mutators, &rules, &colors, &rating, &role, cursor.0);
+    }
+}
+
+fn spawn_obstacles(mut commands: Commands, mutators: Res<Mutators>, settings: Res<Settings>) {
+    if !mutators.contains(Mutator::Obstacles) {
+        return;
+    }
+
+    for position in OBSTACLE_POSITIONS {
+        commands.spawn((
+            SpriteBundle {
+                transform: Transform {
+                    translation: position.extend(0.0),
+                    scale: OBSTACLE_SIZE,
+                    ..default()
+                },
+                sprite: Sprite {
+                    color: settings.theme.palette().wall,
+                    ..default()
+                },
+                ..default()
+            },
+            entities::Obstacle,
+            entities::Collider,
+        ));
+    }
+}
+
+// the arena's own obstacles stay, see `arena.rs`
+fn despawn_obstacles(
+    mut commands: Commands,
+    obstacles: Query<Entity, (With<entities::Obstacle>, Without<ArenaPiece>)>,
+) {
+    for obstacle in &obstacles {
+        commands.entity(obstacle).despawn();
+    }
+}
@@ -0,0 +1,639 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use super::{
+    desync::{self, SnapshotHistory},
+    diagnostics::NetStats,
+    emotes::{Emote, EmoteEvent},
+    protocol::{Message, Snapshot},
+    reconnect::Waiting,
+    reliable::Reliable,
+    transport::{self, PeerId, Transport},
+    NetOptions, Spectators,
+};
+use crate::{
+    constants::*,
+    entities::{self, Competitor},
+    mutators::{MatchState, Mutators},
+    rules::{Ends, MatchRules, MatchWinner},
+    settings::{MenuState, Settings},
+    Scoreboard,
+};
+
+// A snapshot and when it arrived, by the real clock
+#[derive(Debug)]
+struct Received {
+    snapshot: Snapshot,
+    at: f32,
+}
+
+// A client's connection to a host
+#[derive(Resource)]
+struct Connection {
+    transport: Box<dyn Transport>,
+    // whether we asked for a paddle, or are only watching
+    join: bool,
+    keepalive: Timer,
+    // `None` until the host answers a join, then the paddle it gave us (if any)
+    seat: Option<Option<Competitor>>,
+    // our inputs the host hasn't included in a snapshot yet, replayed on top of each one
+    sequence: u32,
+    unacknowledged: VecDeque<(u32, f32)>,
+    // when each input went out, by the real clock, to time the round trip
+    sent_at: VecDeque<(u32, f32)>,
+    // everything but our own paddle is drawn between the last two snapshots, a snapshot
+    // behind, so it moves smoothly however the packets arrive
+    previous: Option<Received>,
+    latest: Option<Received>,
+    // the latest snapshot hasn't been applied yet
+    fresh: bool,
+    history: SnapshotHistory,
+    reliable: Reliable,
+}
+
+impl Connection {
+    fn paddle(&self) -> Option<Competitor> {
+        self.seat.flatten()
+    }
+
+    fn send(&mut self, message: &Message, stats: &mut NetStats) {
+        self.transport.send(PeerId::HOST, &message.encode());
+        stats.record_sent();
+    }
+}
+
+pub struct ClientPlugin {
+    pub address: String,
+    pub join: bool,
+    pub options: NetOptions,
+}
+
+impl Plugin for ClientPlugin {
+    fn build(&self, app: &mut App) {
+        let transport = match transport::connect(&self.address) {
+            Ok(transport) => self.options.condition(transport),
+            Err(err) => {
+                super::play_locally(app, &self.options, err);
+                return;
+            }
+        };
+        info!("Connecting to {}", self.address);
+
+        // fires straight away so the host hears from us on the first frame
+        let mut keepalive = Timer::from_seconds(KEEPALIVE_INTERVAL, TimerMode::Repeating);
+        keepalive.tick(keepalive.duration());
+
+        app.insert_resource(Connection {
+            transport,
+            join: self.join,
+            keepalive,
+            seat: None,
+            sequence: 0,
+            unacknowledged: VecDeque::new(),
+            sent_at: VecDeque::new(),
+            previous: None,
+            latest: None,
+            fresh: false,
+            history: SnapshotHistory::default(),
+            reliable: Reliable::default(),
+        })
+        .add_systems(
+            Update,
+            (
+                keep_connected,
+                receive_messages,
+                apply_snapshot,
+                interpolate.after(crate::place_paddles),
+                crate::update_scoreboard.run_if(resource_changed::<Scoreboard>()),
+            )
+                .chain(),
+        )
+        .add_systems(Update, send_emotes)
+        .add_systems(FixedUpdate, predict_own_paddle);
+
+        if !self.join {
+            app.add_systems(Startup, spawn_spectating_indicator);
+        }
+    }
+}
+
+fn keep_connected(
+    mut connection: ResMut<Connection>,
+    mut stats: ResMut<NetStats>,
+    time: Res<Time<Real>>,
+) {
+    // the host forgets about us if it doesn't hear from us for long enough, so start over
+    // and join again. It keeps our paddle for a while.
+    let now = time.elapsed_seconds();
+    let lost = connection.seat.is_some()
+        && connection
+            .latest
+            .as_ref()
+            .is_some_and(|latest| now - latest.at > PEER_TIMEOUT);
+    if lost {
+        warn!("Lost the host, reconnecting");
+        let connection = &mut *connection;
+        connection.seat = None;
+        connection.previous = None;
+        connection.latest = None;
+        connection.unacknowledged.clear();
+        connection.sent_at.clear();
+    }
+
+    if !connection.keepalive.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    // players keep asking until they're welcomed, after that their inputs keep them connected
+    let message = match (connection.join, connection.seat) {
+        (true, None) => Message::Join,
+        (true, Some(Some(_))) => return,
+        _ => Message::Watch,
+    };
+    // the host just stops sending if these stop arriving
+    connection.send(&message, &mut stats);
+}
+
+fn receive_messages(
+    mut connection: ResMut<Connection>,
+    mut stats: ResMut<NetStats>,
+    mut emotes: EventWriter<EmoteEvent>,
+    time: Res<Time<Real>>,
+) {
+    while let Some((_, bytes)) = connection.transport.receive() {
+        stats.record_received();
+        match Message::decode(&bytes) {
+            Some(Message::Welcome(competitor)) if connection.seat.is_none() => {
+                match competitor {
+                    Some(competitor) => info!("Playing as {competitor:?}"),
+                    None => info!("No paddles free, spectating"),
+                }
+                connection.seat = Some(competitor);
+            }
+            Some(Message::Snapshot(snapshot)) => {
+                // datagrams can arrive out of order, anything older is already out of date
+                let stale = connection
+                    .latest
+                    .as_ref()
+                    .is_some_and(|latest| latest.snapshot.tick >= snapshot.tick);
+                if !stale {
+                    connection.history.push(snapshot.clone());
+                    connection.previous = connection.latest.replace(Received {
+                        snapshot,
+                        at: time.elapsed_seconds(),
+                    });
+                    connection.fresh = true;
+                }
+            }
+            Some(Message::Emote {
+                sequence,
+                competitor,
+                emote,
+            }) => {
+                if connection.reliable.deliver(sequence) {
+                    emotes.send(EmoteEvent { competitor, emote });
+                }
+                connection.send(&Message::Ack(sequence), &mut stats);
+            }
+            Some(Message::Ack(sequence)) => connection.reliable.acknowledge(sequence),
+            Some(Message::Checksum { tick, checksum }) => {
+                let desynced = connection
+                    .history
+                    .mismatch(tick, checksum)
+                    .map(|snapshot| desync::dump(snapshot, checksum))
+                    .is_some();
+                if desynced {
+                    stats.desyncs += 1;
+                    // jump straight to the next snapshot
+                    connection.previous = None;
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+// Copies over everything in the newest snapshot that isn't drawn between snapshots, and
+// corrects our own paddle's prediction
+fn apply_snapshot(
+    mut connection: ResMut<Connection>,
+    state: Res<State<MatchState>>,
+    mut next_state: ResMut<NextState<MatchState>>,
+    mut winner: ResMut<MatchWinner>,
+    mut scores: ResMut<Scoreboard>,
+    mut ends: ResMut<Ends>,
+    mut mutators: ResMut<Mutators>,
+    mut rules: ResMut<MatchRules>,
+    mut spectators: ResMut<Spectators>,
+    mut waiting: ResMut<Waiting>,
+    mut stats: ResMut<NetStats>,
+    mut paddles: Query<
+        (&mut Transform, &mut entities::PaddleVelocity, &Competitor),
+        With<entities::Paddle>,
+    >,
+    walls: Query<&Transform, (With<entities::WallSide>, Without<entities::Paddle>)>,
+    settings: Res<Settings>,
+    fixed_time: Res<Time<Fixed>>,
+) {
+    if !connection.fresh {
+        return;
+    }
+    let connection = &mut *connection;
+    connection.fresh = false;
+    let Some(Received { snapshot, at }) = &connection.latest else {
+        return;
+    };
+
+    // only touch what changed, plenty of systems wait for these to change
+    if *state.get() != snapshot.state {
+        next_state.set(snapshot.state);
+    }
+    if winner.0 != snapshot.winner {
+        winner.0 = snapshot.winner;
+    }
+    if **scores != snapshot.scores {
+        **scores = snapshot.scores;
+    }
+    let snapshot_ends = Ends::new(snapshot.player_side);
+    if *ends != snapshot_ends {
+        *ends = snapshot_ends;
+    }
+    let snapshot_mutators = snapshot.mutators.iter().copied().collect();
+    if *mutators != snapshot_mutators {
+        *mutators = snapshot_mutators;
+    }
+    if rules.variant != snapshot.variant {
+        rules.variant = snapshot.variant;
+    }
+    if spectators.0 != snapshot.spectators {
+        spectators.0 = snapshot.spectators;
+    }
+    if waiting.0 != snapshot.waiting {
+        waiting.0 = snapshot.waiting;
+    }
+
+    let Some(own) = connection.paddle() else {
+        return;
+    };
+    connection
+        .unacknowledged
+        .retain(|(sequence, _)| *sequence > snapshot.last_input);
+    if let Some((_, sent_at)) = connection
+        .sent_at
+        .iter()
+        .find(|(sequence, _)| *sequence == snapshot.last_input)
+    {
+        stats.record_round_trip(at - sent_at);
+    }
+    connection
+        .sent_at
+        .retain(|(sequence, _)| *sequence > snapshot.last_input);
+
+    // start again from where the server has us, then redo everything it hasn't seen yet
+    for (mut transform, mut velocity, competitor) in &mut paddles {
+        if *competitor != own {
+            continue;
+        }
+
+        let predicted = transform.translation.y;
+        let server = snapshot.paddles[own];
+        transform.translation.x = server.x;
+        transform.translation.y = server.y;
+        transform.scale.y = server.height;
+        velocity.0 = server.velocity;
+
+        if snapshot.state != MatchState::Playing || snapshot.waiting.is_some() {
+            continue;
+        }
+        for (_, direction) in &connection.unacknowledged {
+            crate::drive_paddle(
+                &mut transform,
+                &mut velocity,
+                direction * PADDLE_SPEED,
+                PADDLE_SPEED,
+                &walls,
+                &settings,
+                fixed_time.timestep().as_secs_f32(),
+            );
+        }
+        stats.prediction_error = (transform.translation.y - predicted).abs();
+        stats.replayed_inputs = connection.unacknowledged.len();
+    }
+}
+
+// Draws everything we don't predict part of the way from the previous snapshot to the latest
+fn interpolate(
+    mut commands: Commands,
+    connection: Res<Connection>,
+    mut paddles: Query<(&mut Transform, &Competitor), With<entities::Paddle>>,
+    mut balls: Query<
+        (Entity, &mut Transform, &mut entities::Velocity),
+        (With<entities::Ball>, Without<entities::Paddle>),
+    >,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    settings: Res<Settings>,
+    time: Res<Time<Real>>,
+) {
+    let Some(latest) = &connection.latest else {
+        return;
+    };
+    let (from, t) = match &connection.previous {
+        Some(previous) if latest.at > previous.at => (
+            &previous.snapshot,
+            ((time.elapsed_seconds() - latest.at) / (latest.at - previous.at)).min(1.0),
+        ),
+        _ => (&latest.snapshot, 1.0),
+    };
+    let to = &latest.snapshot;
+
+    for (mut transform, competitor) in &mut paddles {
+        if Some(*competitor) == connection.paddle() {
+            continue;
+        }
+        let (from, to) = (from.paddles[*competitor], to.paddles[*competitor]);
+        transform.translation.x = from.x + (to.x - from.x) * t;
+        transform.translation.y = from.y + (to.y - from.y) * t;
+        transform.scale.y = from.height + (to.height - from.height) * t;
+    }
+
+    // new and scored balls don't have anything to be drawn between, so they jump straight in
+    let from_balls = if from.balls.len() == to.balls.len() {
+        &from.balls
+    } else {
+        &to.balls
+    };
+    let mut snapshot_balls = from_balls.iter().zip(&to.balls);
+    for (entity, mut transform, mut velocity) in &mut balls {
+        match snapshot_balls.next() {
+            Some((from, to)) => {
+                transform.translation = from
+                    .position
+                    .lerp(to.position, t)
+                    .extend(transform.translation.z);
+                velocity.0 = to.velocity;
+            }
+            None => commands.entity(entity).despawn(),
+        }
+    }
+    for (_, ball) in snapshot_balls {
+        let (mesh, marker, _, stall_timer) = crate::spawn_ball(
+            &mut materials,
+            &mut meshes,
+            settings.theme.palette().ball,
+            ball.position.extend(BALL_STARTING_POSITION.z),
+            Vec2::X,
+        );
+        commands.spawn((mesh, marker, entities::Velocity(ball.velocity), stall_timer));
+    }
+}
+
+// Moves our paddle straight away rather than waiting to hear back from the server, and sends
+// the input that moved it
+fn predict_own_paddle(
+    mut connection: ResMut<Connection>,
+    mut stats: ResMut<NetStats>,
+    real_time: Res<Time<Real>>,
+    actions: Res<crate::actions::ActionState>,
+    state: Res<State<MatchState>>,
+    waiting: Res<Waiting>,
+    menu: Res<State<MenuState>>,
+    mut paddles: Query<
+        (&mut Transform, &mut entities::PaddleVelocity, &Competitor),
+        With<entities::Paddle>,
+    >,
+    walls: Query<&Transform, (With<entities::WallSide>, Without<entities::Paddle>)>,
+    settings: Res<Settings>,
+    time: Res<Time>,
+) {
+    let Some(own) = connection.paddle() else {
+        return;
+    };
+
+    // the settings menu uses the same keys
+    let direction = if *menu.get() == MenuState::Closed {
+        actions.move_axis.y
+    } else {
+        0.0
+    };
+
+    connection.sequence += 1;
+    let sequence = connection.sequence;
+    connection.send(
+        &Message::Input {
+            sequence,
+            direction,
+        },
+        &mut stats,
+    );
+
+    // anything older has been lost on the way, or its snapshot has
+    let now = real_time.elapsed_seconds();
+    connection
+        .sent_at
+        .retain(|(_, sent_at)| now - sent_at < PEER_TIMEOUT);
+    connection.sent_at.push_back((sequence, now));
+
+    // the server isn't moving anything while it's paused
+    if *state.get() != MatchState::Playing || waiting.0.is_some() {
+        return;
+    }
+    connection.unacknowledged.push_back((sequence, direction));
+
+    for (mut transform, mut velocity, competitor) in &mut paddles {
+        if *competitor == own {
+            crate::drive_paddle(
+                &mut transform,
+                &mut velocity,
+                direction * PADDLE_SPEED,
+                PADDLE_SPEED,
+                &walls,
+                &settings,
+                time.delta_seconds(),
+            );
+        }
+    }
+}
+
+// Sends our emotes to the host, which passes them on, and shows them straight away
+fn send_emotes(
+    mut connection: ResMut<Connection>,
+    mut stats: ResMut<NetStats>,
+    mut emotes: EventWriter<EmoteEvent>,
+    keyboard_input: Res<Input<KeyCode>>,
+    time: Res<Time<Real>>,
+) {
+    let now = time.elapsed_seconds();
+
+    if let (Some(competitor), Some(emote)) = (connection.paddle(), Emote::pressed(&keyboard_input))
+    {
+        let bytes = connection.reliable.send(
+            |sequence| Message::Emote {
+                sequence,
+                competitor,
+                emote,
+            },
+            now,
+        );
+        connection.transport.send(PeerId::HOST, &bytes);
+        stats.record_sent();
+        emotes.send(EmoteEvent { competitor, emote });
+    }
+
+    for bytes in connection.reliable.resends(now) {
+        connection.transport.send(PeerId::HOST, &bytes);
+        stats.record_sent();
+    }
+}
+
+fn spawn_spectating_indicator(mut commands: Commands) {
+    commands.spawn(Text2dBundle {
+        text: Text::from_section(
+            "SPECTATING",
+            TextStyle {
+                font_size: LIVE_FONT_SIZE,
+                color: SCOREBOARD_COLOR,
+                ..default()
+            },
+        ),
+        transform: Transform::from_translation(Vec3::new(0.0, BOTTOM_WALL + LIVE_PADDING_Y, 1.0)),
+        ..default()
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        entities::{PerCompetitor, Side},
+        net::protocol::PaddleState,
+        rules::Variant,
+    };
+
+    // snapshots are handed straight to the connection instead
+    struct Unplugged;
+
+    impl Transport for Unplugged {
+        fn send(&mut self, _: PeerId, _: &[u8]) {}
+
+        fn receive(&mut self) -> Option<(PeerId, Vec<u8>)> {
+            None
+        }
+    }
+
+    fn snapshot(last_input: u32, y: f32) -> Snapshot {
+        Snapshot {
+            tick: last_input,
+            last_input,
+            spectators: 0,
+            state: MatchState::Playing,
+            winner: None,
+            waiting: None,
+            scores: default(),
+            player_side: Side::Left,
+            mutators: Vec::new(),
+            variant: Variant::TableTennis,
+            paddles: PerCompetitor {
+                player: PaddleState {
+                    x: -390.0,
+                    y,
+                    velocity: 0.0,
+                    height: PADDLE_SIZE.y,
+                },
+                computer: default(),
+            },
+            balls: Vec::new(),
+        }
+    }
+
+    fn receive(app: &mut App, snapshot: Snapshot) {
+        let mut connection = app.world.resource_mut::<Connection>();
+        connection.latest = Some(Received { snapshot, at: 0.0 });
+        connection.fresh = true;
+        app.update();
+    }
+
+    #[test]
+    fn inputs_the_host_hasnt_seen_are_replayed_on_its_snapshot() {
+        let mut app = App::new();
+        app.insert_resource(State::new(MatchState::Playing))
+            .init_resource::<NextState<MatchState>>()
+            .init_resource::<MatchWinner>()
+            .init_resource::<Scoreboard>()
+            .init_resource::<Ends>()
+            .init_resource::<Mutators>()
+            .init_resource::<MatchRules>()
+            .init_resource::<Spectators>()
+            .init_resource::<Waiting>()
+            .init_resource::<NetStats>()
+            .init_resource::<Settings>()
+            .init_resource::<Time<Fixed>>()
+            .insert_resource(Connection {
+                transport: Box::new(Unplugged),
+                join: true,
+                keepalive: Timer::from_seconds(KEEPALIVE_INTERVAL, TimerMode::Repeating),
+                seat: Some(Some(Competitor::Player)),
+                sequence: 3,
+                unacknowledged: [(1, 1.0), (2, 1.0), (3, 1.0)].into(),
+                sent_at: VecDeque::new(),
+                previous: None,
+                latest: None,
+                fresh: false,
+                history: SnapshotHistory::default(),
+                reliable: Reliable::default(),
+            })
+            .add_systems(Update, apply_snapshot);
+        // well ahead of where the host has it
+        let paddle = app
+            .world
+            .spawn((
+                entities::Paddle,
+                Competitor::Player,
+                Transform::from_xyz(-390.0, 250.0, 0.0).with_scale(PADDLE_SIZE),
+                entities::PaddleVelocity(400.0),
+            ))
+            .id();
+
+        // the host's seen the first input, so the other two go again from where it has us
+        receive(&mut app, snapshot(1, 100.0));
+        let mut expected = Transform::from_xyz(-390.0, 100.0, 0.0).with_scale(PADDLE_SIZE);
+        let mut velocity = entities::PaddleVelocity(0.0);
+        let dt = Time::<Fixed>::default().timestep().as_secs_f32();
+        for _ in 0..2 {
+            crate::drive_paddle(
+                &mut expected,
+                &mut velocity,
+                PADDLE_SPEED,
+                PADDLE_SPEED,
+                std::iter::empty(),
+                &Settings::default(),
+                dt,
+            );
+        }
+        let y = app.world.get::<Transform>(paddle).unwrap().translation.y;
+        assert!(y > 100.0);
+        assert_eq!(y, expected.translation.y);
+        assert_eq!(
+            app.world.get::<entities::PaddleVelocity>(paddle).unwrap().0,
+            velocity.0
+        );
+        assert_eq!(app.world.resource::<NetStats>().replayed_inputs, 2);
+
+        // and once it's caught up there's nothing left to redo
+        receive(&mut app, snapshot(3, -40.0));
+        assert!(app.world.resource::<Connection>().unacknowledged.is_empty());
+        assert_eq!(
+            app.world.get::<Transform>(paddle).unwrap().translation.y,
+            -40.0
+        );
+
+        // nor is there while a snapshot isn't fresh
+        app.world.resource_mut::<Connection>().unacknowledged = [(4, 1.0)].into();
+        app.update();
+        assert_eq!(
+            app.world.get::<Transform>(paddle).unwrap().translation.y,
+            -40.0
+        );
+    }
+}
@@ -0,0 +1,150 @@
+// Catching a client that's showing a different match to the one the host is running. Every
+// so often the host sends a checksum of its match as it was on that tick (the balls, the
+// paddles and the score), worked out from its world, and the client checks it against one
+// worked out from the snapshot it was sent for the same tick. Snapshots carry the whole
+// match, so they should always agree; when they don't, something between the host's world
+// and the client's copy of it has gone wrong. The client writes what it had for that tick
+// beside the game's other saved files and takes the next snapshot as it comes, rather than
+// easing towards it from the one that was wrong.
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use bevy_common::storage;
+
+use super::protocol::Snapshot;
+use crate::{
+    constants::*,
+    entities::{Competitor, PerCompetitor},
+};
+
+// FNV-1a over everything's bits, so it comes out the same on every platform
+struct Hasher(u64);
+
+impl Hasher {
+    fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn u32(&mut self, value: u32) {
+        for byte in value.to_le_bytes() {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn vec2(&mut self, value: Vec2) {
+        self.u32(value.x.to_bits());
+        self.u32(value.y.to_bits());
+    }
+}
+
+// The match on one tick: the scores, where each paddle is, and each ball's position and
+// velocity in the order they're sent
+pub fn checksum(
+    scores: PerCompetitor<usize>,
+    paddles: PerCompetitor<Vec2>,
+    balls: impl IntoIterator<Item = (Vec2, Vec2)>,
+) -> u64 {
+    let mut hasher = Hasher::new();
+    for competitor in Competitor::ALL {
+        hasher.u32(scores[competitor] as u32);
+        hasher.vec2(paddles[competitor]);
+    }
+    for (position, velocity) in balls {
+        hasher.vec2(position);
+        hasher.vec2(velocity);
+    }
+    hasher.0
+}
+
+impl Snapshot {
+    pub fn checksum(&self) -> u64 {
+        checksum(
+            self.scores,
+            PerCompetitor {
+                player: Vec2::new(self.paddles.player.x, self.paddles.player.y),
+                computer: Vec2::new(self.paddles.computer.x, self.paddles.computer.y),
+            },
+            self.balls.iter().map(|ball| (ball.position, ball.velocity)),
+        )
+    }
+}
+
+// The snapshots a client's had lately, for the host's checksums to be checked against
+#[derive(Debug, Default)]
+pub struct SnapshotHistory(VecDeque<Snapshot>);
+
+impl SnapshotHistory {
+    pub fn push(&mut self, snapshot: Snapshot) {
+        if self.0.len() >= DESYNC_HISTORY {
+            self.0.pop_front();
+        }
+        self.0.push_back(snapshot);
+    }
+
+    // The snapshot for `tick` if it doesn't match the host's `checksum`. One that never
+    // arrived, or was too long ago, can't be checked.
+    pub fn mismatch(&self, tick: u32, checksum: u64) -> Option<&Snapshot> {
+        self.0
+            .iter()
+            .find(|snapshot| snapshot.tick == tick)
+            .filter(|snapshot| snapshot.checksum() != checksum)
+    }
+}
+
+// Writes out what the client had for a tick the host disagrees with
+pub fn dump(snapshot: &Snapshot, checksum: u64) {
+    warn!(
+        "Out of sync with the host on tick {}: it has {checksum:016x}, we have {:016x}",
+        snapshot.tick,
+        snapshot.checksum()
+    );
+    let report = format!(
+        "tick {}\nhost's checksum {checksum:016x}\nours {:016x}\n\n{snapshot:#?}\n",
+        snapshot.tick,
+        snapshot.checksum()
+    );
+    if let Err(err) = storage::save(GAME_NAME, DESYNC_FILE, &report) {
+        warn!("Couldn't save the desync report: {err}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{entities::Side, mutators::MatchState, net::protocol::BallState, rules::Variant};
+
+    #[test]
+    fn only_the_right_snapshot_with_the_wrong_checksum_mismatches() {
+        let mut history = SnapshotHistory::default();
+        for tick in 1..=3 {
+            history.push(Snapshot {
+                tick,
+                last_input: 0,
+                spectators: 0,
+                state: MatchState::Playing,
+                winner: None,
+                waiting: None,
+                scores: default(),
+                player_side: Side::Right,
+                mutators: Vec::new(),
+                variant: Variant::TableTennis,
+                paddles: default(),
+                balls: vec![BallState {
+                    position: Vec2::new(tick as f32, 0.0),
+                    velocity: Vec2::X,
+                }],
+            });
+        }
+        let sum = history.0[1].checksum();
+        assert_ne!(sum, history.0[0].checksum());
+
+        assert!(history.mismatch(2, sum).is_none());
+        assert_eq!(
+            history.mismatch(2, sum ^ 1).map(|snapshot| snapshot.tick),
+            Some(2)
+        );
+        // nothing to check tick 9 against
+        assert!(history.mismatch(9, sum ^ 1).is_none());
+    }
+}
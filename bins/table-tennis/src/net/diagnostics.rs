@@ -0,0 +1,162 @@
+use bevy::prelude::*;
+
+use super::{transport::LinkConditions, NetOptions, NetRole};
+use crate::constants::*;
+
+// What the netcode measures about itself, shown on the overlay
+#[derive(Resource, Debug, Default)]
+pub struct NetStats {
+    // smoothed, clients only
+    pub round_trip: Option<f32>,
+    // how far our paddle was from where the server put it, once our unacknowledged inputs
+    // were replayed on top
+    pub prediction_error: f32,
+    pub replayed_inputs: usize,
+    // ticks a client's snapshot disagreed with the host's checksum, see `desync.rs`
+    pub desyncs: usize,
+    pub sent_per_second: f32,
+    pub received_per_second: f32,
+    sent: usize,
+    received: usize,
+    window: f32,
+}
+
+#[cfg(feature = "networking")]
+impl NetStats {
+    pub fn record_sent(&mut self) {
+        self.sent += 1;
+    }
+
+    pub fn record_received(&mut self) {
+        self.received += 1;
+    }
+
+    pub fn record_round_trip(&mut self, round_trip: f32) {
+        self.round_trip = Some(match self.round_trip {
+            Some(smoothed) => smoothed + (round_trip - smoothed) * ROUND_TRIP_SMOOTHING,
+            None => round_trip,
+        });
+    }
+}
+
+#[derive(Component)]
+struct DiagnosticsOverlay;
+
+pub struct DiagnosticsPlugin;
+
+impl Plugin for DiagnosticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NetStats>()
+            .add_systems(Startup, spawn_overlay)
+            .add_systems(
+                Update,
+                (count_packets, toggle_overlay, update_overlay).chain(),
+            );
+    }
+}
+
+fn count_packets(mut stats: ResMut<NetStats>, time: Res<Time<Real>>) {
+    stats.window += time.delta_seconds();
+    if stats.window < 1.0 {
+        return;
+    }
+
+    let window = stats.window;
+    stats.sent_per_second = stats.sent as f32 / window;
+    stats.received_per_second = stats.received as f32 / window;
+    stats.sent = 0;
+    stats.received = 0;
+    stats.window = 0.0;
+}
+
+fn spawn_overlay(mut commands: Commands) {
+    let mut overlay = TextBundle::from_section(
+        "",
+        TextStyle {
+            font_size: DIAGNOSTICS_FONT_SIZE,
+            color: Color::WHITE,
+            ..default()
+        },
+    )
+    .with_style(Style {
+        position_type: PositionType::Absolute,
+        top: Val::Px(DIAGNOSTICS_PADDING),
+        left: Val::Px(DIAGNOSTICS_PADDING),
+        ..default()
+    })
+    .with_background_color(SETTINGS_MENU_BACKDROP_COLOR);
+    overlay.visibility = Visibility::Hidden;
+
+    commands.spawn((overlay, DiagnosticsOverlay));
+}
+
+fn toggle_overlay(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut overlays: Query<&mut Visibility, With<DiagnosticsOverlay>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::F3) {
+        return;
+    }
+
+    for mut visibility in &mut overlays {
+        *visibility = match *visibility {
+            Visibility::Hidden => Visibility::Visible,
+            _ => Visibility::Hidden,
+        };
+    }
+}
+
+fn describe_role(role: &NetRole) -> String {
+    match role {
+        NetRole::Local => "local".to_string(),
+        NetRole::Broadcast(port) => format!("broadcasting on {port}"),
+        NetRole::Spectator(address) => format!("spectating {address}"),
+        NetRole::Server(port) => format!("serving on {port}"),
+        NetRole::Client(address) => format!("playing on {address}"),
+    }
+}
+
+fn describe_conditions(conditions: &LinkConditions) -> String {
+    if conditions.is_perfect() {
+        return "none".to_string();
+    }
+
+    format!(
+        "+{:.0} ms, jitter {:.0} ms, {:.0}% loss",
+        conditions.latency * 1000.0,
+        conditions.jitter * 1000.0,
+        conditions.loss * 100.0
+    )
+}
+
+fn update_overlay(
+    options: Res<NetOptions>,
+    stats: Res<NetStats>,
+    mut overlays: Query<(&mut Text, &Visibility), With<DiagnosticsOverlay>>,
+) {
+    for (mut text, visibility) in &mut overlays {
+        if *visibility == Visibility::Hidden {
+            continue;
+        }
+
+        let round_trip = match stats.round_trip {
+            Some(round_trip) => format!("{:.0} ms", round_trip * 1000.0),
+            None => "-".to_string(),
+        };
+        text.sections[0].value = format!(
+            "net: {}\n\
+             round trip: {round_trip}\n\
+             prediction error: {:.1} ({} inputs replayed)\n\
+             desyncs: {}\n\
+             packets/s: {:.0} in, {:.0} out\n\
+             simulated: {}",
+            describe_role(&options.role),
+            stats.prediction_error,
+            stats.replayed_inputs,
+            stats.desyncs,
+            stats.received_per_second,
+            stats.sent_per_second,
+            describe_conditions(&options.conditions),
+        );
+    }
+}
@@ -0,0 +1,171 @@
+use bevy::{prelude::*, sprite::Anchor};
+use serde::Deserialize;
+
+use crate::{
+    constants::*,
+    entities::{self, Competitor},
+};
+
+// Quick-chat for online matches, on the number keys. Computer personalities taunt with them
+// too, see `personality.rs`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum Emote {
+    NiceShot,
+    Unlucky,
+    Oops,
+    Wow,
+    GoodGame,
+}
+
+impl Emote {
+    #[cfg(feature = "networking")]
+    pub const ALL: [Emote; 5] = [
+        Emote::NiceShot,
+        Emote::Unlucky,
+        Emote::Oops,
+        Emote::Wow,
+        Emote::GoodGame,
+    ];
+
+    #[cfg(feature = "networking")]
+    const KEYS: [KeyCode; 5] = [
+        KeyCode::Key1,
+        KeyCode::Key2,
+        KeyCode::Key3,
+        KeyCode::Key4,
+        KeyCode::Key5,
+    ];
+
+    pub fn text(&self) -> &'static str {
+        match self {
+            Emote::NiceShot => "Nice shot!",
+            Emote::Unlucky => "Unlucky!",
+            Emote::Oops => "Oops",
+            Emote::Wow => "Wow!",
+            Emote::GoodGame => "Good game",
+        }
+    }
+
+    // where it is in `ALL`, which is also how it goes over the wire
+    #[cfg(feature = "networking")]
+    pub fn index(&self) -> usize {
+        Emote::ALL.iter().position(|emote| emote == self).unwrap()
+    }
+
+    // the emote whose key was just pressed, if any
+    #[cfg(feature = "networking")]
+    pub fn pressed(keyboard_input: &Input<KeyCode>) -> Option<Emote> {
+        Emote::KEYS
+            .into_iter()
+            .zip(Emote::ALL)
+            .find(|(key, _)| keyboard_input.just_pressed(*key))
+            .map(|(_, emote)| emote)
+    }
+}
+
+// Somebody sent an emote, shown next to their paddle. Sent by whichever end heard about it,
+// see `host.rs` and `client.rs`.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct EmoteEvent {
+    pub competitor: Competitor,
+    pub emote: Emote,
+}
+
+// An emote on screen, following its sender's paddle until it fades out
+#[derive(Component, Debug)]
+struct EmoteBubble {
+    competitor: Competitor,
+    timer: Timer,
+}
+
+pub struct EmotesPlugin;
+
+impl Plugin for EmotesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<EmoteEvent>().add_systems(
+            Update,
+            (show_emotes, move_emote_bubbles)
+                .chain()
+                .after(crate::place_paddles),
+        );
+    }
+}
+
+fn show_emotes(
+    mut commands: Commands,
+    mut emotes: EventReader<EmoteEvent>,
+    bubbles: Query<(Entity, &EmoteBubble)>,
+) {
+    for event in emotes.read() {
+        // a new emote replaces whatever its sender said last
+        for (entity, bubble) in &bubbles {
+            if bubble.competitor == event.competitor {
+                commands.entity(entity).despawn();
+            }
+        }
+
+        commands.spawn((
+            Text2dBundle {
+                text: Text::from_section(
+                    event.emote.text(),
+                    TextStyle {
+                        font_size: EMOTE_FONT_SIZE,
+                        color: EMOTE_COLOR,
+                        ..default()
+                    },
+                ),
+                text_anchor: Anchor::BottomCenter,
+                // placed properly once it's found its paddle
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            EmoteBubble {
+                competitor: event.competitor,
+                timer: Timer::from_seconds(EMOTE_DURATION, TimerMode::Once),
+            },
+        ));
+    }
+}
+
+fn move_emote_bubbles(
+    mut commands: Commands,
+    mut bubbles: Query<(
+        Entity,
+        &mut EmoteBubble,
+        &mut Transform,
+        &mut Text,
+        &mut Visibility,
+    )>,
+    paddles: Query<(&Transform, &Competitor), (With<entities::Paddle>, Without<EmoteBubble>)>,
+    time: Res<Time>,
+) {
+    for (entity, mut bubble, mut transform, mut text, mut visibility) in &mut bubbles {
+        if bubble.timer.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        let Some((paddle, _)) = paddles
+            .iter()
+            .find(|(_, competitor)| **competitor == bubble.competitor)
+        else {
+            continue;
+        };
+
+        // above the paddle, pulled in towards the middle so it stays on the court
+        transform.translation = Vec3::new(
+            paddle.translation.x - paddle.translation.x.signum() * EMOTE_INSET_X,
+            paddle.translation.y + paddle.scale.y / 2.0 + EMOTE_PADDING_Y,
+            1.0,
+        );
+        *visibility = Visibility::Visible;
+
+        let remaining = bubble.timer.remaining_secs();
+        for section in &mut text.sections {
+            section
+                .style
+                .color
+                .set_a((remaining / EMOTE_FADE_DURATION).min(1.0));
+        }
+    }
+}
@@ -0,0 +1,466 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use super::{
+    desync,
+    diagnostics::NetStats,
+    emotes::{Emote, EmoteEvent},
+    protocol::{BallState, Message, PaddleState, Snapshot},
+    reconnect::{Dropout, Waiting},
+    reliable::Reliable,
+    transport::{self, PeerId, Transport},
+    NetOptions, Spectators,
+};
+use crate::{
+    constants::*,
+    entities::{self, Competitor, PerCompetitor},
+    mutators::{MatchState, Mutator, Mutators},
+    rules::{Ends, MatchRules, MatchWinner, Opponent},
+    settings::Settings,
+    PaddleWallHit, Scoreboard, TickSet,
+};
+
+// Somebody we're sending snapshots to
+#[derive(Debug)]
+struct Peer {
+    // the paddle they're driving, `None` when they're only watching
+    competitor: Option<Competitor>,
+    last_seen: f32,
+    // their newest input, by sequence number
+    last_input: u32,
+    direction: f32,
+    reliable: Reliable,
+}
+
+impl Peer {
+    fn new(competitor: Option<Competitor>, now: f32) -> Self {
+        Self {
+            competitor,
+            last_seen: now,
+            last_input: 0,
+            direction: 0.0,
+            reliable: Reliable::default(),
+        }
+    }
+}
+
+// The authoritative end of a networked match: a broadcast, or a server handing out paddles
+// when `seats` is set
+#[derive(Resource)]
+pub struct Host {
+    transport: Box<dyn Transport>,
+    seats: bool,
+    peers: HashMap<PeerId, Peer>,
+    tick: u32,
+}
+
+impl Host {
+    fn seated(&self, competitor: Competitor) -> Option<&Peer> {
+        self.peers
+            .values()
+            .find(|peer| peer.competitor == Some(competitor))
+    }
+
+    fn spectators(&self) -> usize {
+        self.peers
+            .values()
+            .filter(|peer| peer.competitor.is_none())
+            .count()
+    }
+}
+
+// A server starts a rematch by itself once a match has been over for a while
+#[derive(Resource, Debug, Deref, DerefMut)]
+struct RematchTimer(Timer);
+
+// A run condition for the computer driving its own paddle, unless a client or somebody here
+// has taken it
+pub fn computer_is_ai(host: Option<Res<Host>>, rules: Res<MatchRules>) -> bool {
+    rules.opponent != Opponent::Local
+        && host.is_none_or(|host| host.seated(Competitor::Computer).is_none())
+}
+
+pub struct HostPlugin {
+    pub port: u16,
+    pub seats: bool,
+    pub options: NetOptions,
+}
+
+impl Plugin for HostPlugin {
+    fn build(&self, app: &mut App) {
+        let transport = match transport::listen(self.port) {
+            Ok(transport) => self.options.condition(transport),
+            Err(err) => {
+                super::play_locally(app, &self.options, err);
+                return;
+            }
+        };
+        if self.seats {
+            info!("Serving");
+        } else {
+            info!("Broadcasting");
+        }
+
+        app.insert_resource(Host {
+            transport,
+            seats: self.seats,
+            peers: HashMap::new(),
+            tick: 0,
+        })
+        .add_systems(
+            FixedUpdate,
+            (
+                receive_messages.before(TickSet::Input),
+                send_snapshots.after(TickSet::Effects),
+            ),
+        )
+        .add_systems(Update, send_emotes);
+
+        if self.seats {
+            app.insert_resource(RematchTimer(Timer::from_seconds(
+                SERVER_REMATCH_DELAY,
+                TimerMode::Once,
+            )))
+            .add_systems(FixedUpdate, drive_remote_paddles.in_set(TickSet::Input))
+            .add_systems(OnEnter(MatchState::GameOver), reset_rematch_timer)
+            .add_systems(Update, (run_server_match, wait_for_dropouts));
+        } else {
+            app.add_systems(Update, send_local_emotes.before(send_emotes));
+        }
+    }
+}
+
+fn receive_messages(
+    mut host: ResMut<Host>,
+    mut spectators: ResMut<Spectators>,
+    mut stats: ResMut<NetStats>,
+    mut emotes: EventWriter<EmoteEvent>,
+    mut waiting: ResMut<Waiting>,
+    state: Res<State<MatchState>>,
+    time: Res<Time<Real>>,
+) {
+    let now = time.elapsed_seconds();
+
+    while let Some((from, bytes)) = host.transport.receive() {
+        stats.record_received();
+        let Some(message) = Message::decode(&bytes) else {
+            continue;
+        };
+
+        match message {
+            Message::Watch => {
+                host.peers
+                    .entry(from)
+                    .or_insert_with(|| Peer::new(None, now))
+                    .last_seen = now;
+            }
+            Message::Join => {
+                // whoever dropped out gets their paddle back first
+                let dropped = waiting.0.map(|dropout| dropout.competitor);
+                let competitor = match host.peers.get(&from) {
+                    Some(peer) => peer.competitor,
+                    None if host.seats => dropped
+                        .into_iter()
+                        .chain(Competitor::ALL)
+                        .find(|competitor| host.seated(*competitor).is_none()),
+                    None => None,
+                };
+                let peer = host.peers.entry(from).or_insert_with(|| {
+                    match competitor {
+                        Some(competitor) if dropped == Some(competitor) => {
+                            info!("{from} rejoined as {competitor:?}");
+                            waiting.0 = None;
+                        }
+                        Some(competitor) => info!("{from} joined as {competitor:?}"),
+                        None => info!("{from} is spectating"),
+                    }
+                    Peer::new(competitor, now)
+                });
+                peer.last_seen = now;
+                // sent for every join, the client keeps asking until it hears back
+                host.transport
+                    .send(from, &Message::Welcome(competitor).encode());
+                stats.record_sent();
+            }
+            Message::Input {
+                sequence,
+                direction,
+            } => {
+                if let Some(peer) = host.peers.get_mut(&from) {
+                    peer.last_seen = now;
+                    // datagrams can arrive out of order
+                    if sequence > peer.last_input {
+                        peer.last_input = sequence;
+                        peer.direction = direction.clamp(-1.0, 1.0);
+                    }
+                }
+            }
+            Message::Emote {
+                sequence, emote, ..
+            } => {
+                // only players get a say, and as whoever they're playing as
+                let Some(peer) = host.peers.get_mut(&from) else {
+                    continue;
+                };
+                let Some(competitor) = peer.competitor else {
+                    continue;
+                };
+                peer.last_seen = now;
+                if peer.reliable.deliver(sequence) {
+                    emotes.send(EmoteEvent { competitor, emote });
+                }
+                host.transport.send(from, &Message::Ack(sequence).encode());
+                stats.record_sent();
+            }
+            Message::Ack(sequence) => {
+                if let Some(peer) = host.peers.get_mut(&from) {
+                    peer.reliable.acknowledge(sequence);
+                }
+            }
+            // only ever sent by hosts
+            Message::Welcome(_) | Message::Snapshot(_) | Message::Checksum { .. } => (),
+        }
+    }
+
+    let mid_match = matches!(state.get(), MatchState::Playing | MatchState::Intermission);
+    host.peers.retain(|id, peer| {
+        let connected = now - peer.last_seen < PEER_TIMEOUT;
+        match peer.competitor {
+            Some(competitor) if !connected && mid_match && waiting.0.is_none() => {
+                info!("{id} dropped out, waiting {RECONNECT_TIMEOUT}s for them");
+                waiting.0 = Some(Dropout {
+                    competitor,
+                    remaining: RECONNECT_TIMEOUT,
+                });
+            }
+            Some(_) if !connected => info!("{id} left"),
+            _ => (),
+        }
+        connected
+    });
+
+    let watching = host.spectators();
+    if spectators.0 != watching {
+        spectators.0 = watching;
+    }
+}
+
+// A broadcaster's keyboard is the player's paddle
+fn send_local_emotes(keyboard_input: Res<Input<KeyCode>>, mut emotes: EventWriter<EmoteEvent>) {
+    if let Some(emote) = Emote::pressed(&keyboard_input) {
+        emotes.send(EmoteEvent {
+            competitor: Competitor::Player,
+            emote,
+        });
+    }
+}
+
+// Passes every emote on to everybody but its sender, and resends any that haven't been
+// acknowledged
+fn send_emotes(
+    mut host: ResMut<Host>,
+    mut stats: ResMut<NetStats>,
+    mut emotes: EventReader<EmoteEvent>,
+    time: Res<Time<Real>>,
+) {
+    let now = time.elapsed_seconds();
+    let host = &mut *host;
+
+    for event in emotes.read() {
+        for (id, peer) in &mut host.peers {
+            if peer.competitor == Some(event.competitor) {
+                continue;
+            }
+            let bytes = peer.reliable.send(
+                |sequence| Message::Emote {
+                    sequence,
+                    competitor: event.competitor,
+                    emote: event.emote,
+                },
+                now,
+            );
+            host.transport.send(*id, &bytes);
+            stats.record_sent();
+        }
+    }
+
+    for (id, peer) in &mut host.peers {
+        for bytes in peer.reliable.resends(now) {
+            host.transport.send(*id, &bytes);
+            stats.record_sent();
+        }
+    }
+}
+
+fn drive_remote_paddles(
+    host: Res<Host>,
+    mut paddles: Query<
+        (
+            &mut Transform,
+            &mut entities::PaddleVelocity,
+            &crate::powerups::PaddleBoost,
+            &Competitor,
+        ),
+        With<entities::Paddle>,
+    >,
+    walls: Query<&Transform, (With<entities::WallSide>, Without<entities::Paddle>)>,
+    settings: Res<Settings>,
+    mut wall_hits: EventWriter<PaddleWallHit>,
+    time: Res<Time>,
+) {
+    for (mut transform, mut velocity, boost, competitor) in &mut paddles {
+        let Some(peer) = host.seated(*competitor) else {
+            continue;
+        };
+
+        let max_speed = PADDLE_SPEED * boost.multiplier();
+        if let Some(speed) = crate::drive_paddle(
+            &mut transform,
+            &mut velocity,
+            peer.direction * max_speed,
+            max_speed,
+            &walls,
+            &settings,
+            time.delta_seconds(),
+        ) {
+            wall_hits.send(PaddleWallHit { speed });
+        }
+    }
+}
+
+// Nobody's at the server to pick rules or press enter, so it plays with its defaults as soon
+// as somebody has joined
+fn run_server_match(
+    host: Res<Host>,
+    state: Res<State<MatchState>>,
+    mut next_state: ResMut<NextState<MatchState>>,
+    mut rematch: ResMut<RematchTimer>,
+    time: Res<Time>,
+) {
+    let anyone_playing = host.peers.values().any(|peer| peer.competitor.is_some());
+
+    match state.get() {
+        MatchState::PreMatch if anyone_playing => next_state.set(MatchState::Playing),
+        MatchState::GameOver if rematch.tick(time.delta()).just_finished() => {
+            next_state.set(if anyone_playing {
+                MatchState::Playing
+            } else {
+                MatchState::PreMatch
+            });
+        }
+        _ => (),
+    }
+}
+
+// Counts down while a player is missing, and gives the match to their opponent if they
+// don't make it back
+fn wait_for_dropouts(
+    mut waiting: ResMut<Waiting>,
+    mut winner: ResMut<MatchWinner>,
+    state: Res<State<MatchState>>,
+    mut next_state: ResMut<NextState<MatchState>>,
+    time: Res<Time<Real>>,
+) {
+    let Some(dropout) = &mut waiting.0 else {
+        return;
+    };
+
+    if !matches!(state.get(), MatchState::Playing | MatchState::Intermission) {
+        waiting.0 = None;
+        return;
+    }
+
+    dropout.remaining -= time.delta_seconds();
+    if dropout.remaining <= 0.0 {
+        let stayed = dropout.competitor.opponent();
+        info!("{:?} forfeits, {stayed:?} wins", dropout.competitor);
+        winner.0 = Some(stayed);
+        next_state.set(MatchState::GameOver);
+        waiting.0 = None;
+    }
+}
+
+fn reset_rematch_timer(mut rematch: ResMut<RematchTimer>) {
+    rematch.reset();
+}
+
+fn send_snapshots(
+    mut host: ResMut<Host>,
+    mut stats: ResMut<NetStats>,
+    state: Res<State<MatchState>>,
+    winner: Res<MatchWinner>,
+    waiting: Res<Waiting>,
+    scores: Res<Scoreboard>,
+    ends: Res<Ends>,
+    mutators: Res<Mutators>,
+    rules: Res<MatchRules>,
+    paddles: Query<(&Transform, &entities::PaddleVelocity, &Competitor), With<entities::Paddle>>,
+    balls: Query<(&Transform, &entities::Velocity), With<entities::Ball>>,
+) {
+    if host.peers.is_empty() {
+        return;
+    }
+
+    host.tick += 1;
+    let mut snapshot = Snapshot {
+        tick: host.tick,
+        last_input: 0,
+        spectators: host.spectators(),
+        state: *state.get(),
+        winner: winner.0,
+        waiting: waiting.0,
+        scores: **scores,
+        player_side: ends.side_of(Competitor::Player),
+        mutators: Mutator::ALL
+            .into_iter()
+            .filter(|mutator| mutators.contains(*mutator))
+            .collect(),
+        variant: rules.variant,
+        paddles: default(),
+        balls: balls
+            .iter()
+            .map(|(transform, velocity)| BallState {
+                position: transform.translation.truncate(),
+                velocity: velocity.0,
+            })
+            .collect(),
+    };
+    for (transform, velocity, competitor) in &paddles {
+        snapshot.paddles[*competitor] = PaddleState {
+            x: transform.translation.x,
+            y: transform.translation.y,
+            velocity: velocity.0,
+            height: transform.scale.y,
+        };
+    }
+
+    // from the world rather than the snapshot, so anything the snapshot gets wrong shows up
+    let checksum = host.tick.is_multiple_of(DESYNC_CHECK_INTERVAL).then(|| {
+        let mut positions = PerCompetitor::<Vec2>::default();
+        for (transform, _, competitor) in &paddles {
+            positions[*competitor] = transform.translation.truncate();
+        }
+        let balls = balls
+            .iter()
+            .map(|(transform, velocity)| (transform.translation.truncate(), velocity.0));
+        desync::checksum(**scores, positions, balls)
+    });
+
+    let host = &mut *host;
+    for (id, peer) in &host.peers {
+        snapshot.last_input = peer.last_input;
+        // a dropped snapshot is replaced by the next one
+        host.transport
+            .send(*id, &Message::Snapshot(snapshot.clone()).encode());
+        stats.record_sent();
+        if let Some(checksum) = checksum {
+            let message = Message::Checksum {
+                tick: host.tick,
+                checksum,
+            };
+            host.transport.send(*id, &message.encode());
+            stats.record_sent();
+        }
+    }
+}
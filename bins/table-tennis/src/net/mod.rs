@@ -0,0 +1,452 @@
+// Networked play, picked with command line flags:
+//
+//   table-tennis --broadcast [port]    play locally and stream the match to spectators
+//   table-tennis --spectate host:port  watch somebody else's match
+//   table-tennis --server [port]       run a headless, authoritative match for clients
+//   table-tennis --connect host:port   play on a server
+//
+// Any of them can add --lag <ms>, --jitter <ms> and --loss <percent> to simulate a bad
+// connection. F3 shows what the netcode is seeing. Anyone with a paddle can send a quick-chat
+// emote with the number keys. --kiosk runs the game on a demo machine instead, see
+// `kiosk.rs`.
+//
+// Whoever runs the simulation (a broadcaster or a server) is the only one with authority
+// over the match. Everyone else is sent a snapshot of it every tick; server clients send
+// their inputs back and predict their own paddle so it responds without waiting a round
+// trip. Now and then a checksum goes out too, to make sure everyone's seeing the same match,
+// see `desync.rs`. A player who drops out of a server match has a while to reconnect (from
+// anywhere, the first client to join takes the empty paddle) before they forfeit it.
+//
+// Built without the `networking` feature there's only ever a local match; the role flags
+// say so, and the transports, host, client and reconnecting are left out.
+
+#[cfg(feature = "networking")]
+mod client;
+#[cfg(feature = "networking")]
+mod desync;
+mod diagnostics;
+mod emotes;
+#[cfg(feature = "networking")]
+mod host;
+#[cfg(feature = "networking")]
+mod protocol;
+#[cfg(feature = "networking")]
+mod reconnect;
+#[cfg(feature = "networking")]
+mod reliable;
+pub mod transport;
+
+use std::time::Duration;
+
+use bevy::{
+    app::{PluginGroupBuilder, ScheduleRunnerPlugin},
+    log::{Level, LogPlugin},
+    prelude::*,
+    render::{settings::WgpuSettings, RenderPlugin},
+    window::{ExitCondition, WindowMode},
+    winit::WinitPlugin,
+};
+
+pub use emotes::{Emote, EmoteEvent};
+#[cfg(feature = "networking")]
+pub use host::computer_is_ai;
+#[cfg(feature = "networking")]
+pub use reconnect::{not_waiting, Waiting};
+
+use self::transport::LinkConditions;
+#[cfg(feature = "networking")]
+use self::transport::{ConditionedTransport, Transport};
+use crate::constants::*;
+
+#[derive(Resource, Debug, Clone, Default, PartialEq, Eq)]
+pub enum NetRole {
+    #[default]
+    Local,
+    Broadcast(u16),
+    Spectator(String),
+    Server(u16),
+    Client(String),
+}
+
+// Everything picked on the command line
+#[derive(Resource, Debug, Clone, Default, PartialEq)]
+pub struct NetOptions {
+    pub role: NetRole,
+    pub conditions: LinkConditions,
+    // fullscreen, showing off by itself between matches
+    pub kiosk: bool,
+    // the least severe logs shown, info when it's not given, see `logging.rs`
+    pub log_level: Option<Level>,
+}
+
+impl NetOptions {
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut options = NetOptions::default();
+        let mut args = args.into_iter().peekable();
+
+        while let Some(flag) = args.next() {
+            // the value after a flag, unless it's the next flag
+            let value = args.next_if(|arg| !arg.starts_with("--"));
+            let number = |value: Option<String>| {
+                let value = value.ok_or_else(|| format!("{flag} needs a number"))?;
+                value
+                    .parse::<f32>()
+                    .map_err(|_| format!("{flag} needs a number, not {value}"))
+            };
+            let port = |value: Option<String>| match value {
+                None => Ok(DEFAULT_PORT),
+                Some(port) => port.parse().map_err(|_| format!("not a port: {port}")),
+            };
+            let address = |value: Option<String>| {
+                value.ok_or_else(|| {
+                    format!("{flag} needs an address, e.g. 127.0.0.1:{DEFAULT_PORT}")
+                })
+            };
+
+            let role = match flag.as_str() {
+                "--broadcast" => NetRole::Broadcast(port(value)?),
+                "--server" => NetRole::Server(port(value)?),
+                "--spectate" => NetRole::Spectator(address(value)?),
+                "--connect" => NetRole::Client(address(value)?),
+                "--lag" => {
+                    options.conditions.latency = number(value)? / 1000.0;
+                    continue;
+                }
+                "--jitter" => {
+                    options.conditions.jitter = number(value)? / 1000.0;
+                    continue;
+                }
+                "--loss" => {
+                    options.conditions.loss = number(value)? / 100.0;
+                    continue;
+                }
+                "--log-level" => {
+                    let value = value.unwrap_or_default();
+                    options.log_level = Some(value.parse().map_err(|_| {
+                        format!(
+                            "--log-level needs one of error, warn, info, debug or trace, \
+                             not {value:?}"
+                        )
+                    })?);
+                    continue;
+                }
+                "--kiosk" => {
+                    if let Some(value) = value {
+                        return Err(format!("--kiosk doesn't take a value, not {value}"));
+                    }
+                    options.kiosk = true;
+                    continue;
+                }
+                _ => return Err(format!("unknown flag: {flag}")),
+            };
+
+            if cfg!(not(feature = "networking")) {
+                return Err(format!("{flag} needs a build with networking"));
+            }
+            if options.role != NetRole::Local {
+                return Err("pick one of --broadcast, --spectate, --server or --connect".into());
+            }
+            options.role = role;
+        }
+
+        Ok(options)
+    }
+
+    pub fn from_env() -> Self {
+        Self::from_args(args()).unwrap_or_else(|err| {
+            eprintln!("{err}, playing locally");
+            NetOptions::default()
+        })
+    }
+
+    // `transport`, with whatever trouble was asked for
+    #[cfg(feature = "networking")]
+    fn condition(&self, transport: Box<dyn Transport>) -> Box<dyn Transport> {
+        if self.conditions.is_perfect() {
+            transport
+        } else {
+            Box::new(ConditionedTransport::new(transport, self.conditions))
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn args() -> Vec<String> {
+    std::env::args().skip(1).collect()
+}
+
+// Browsers don't have a command line, so the flags come from the page's query string instead,
+// e.g. `index.html?connect=example.com:7373`
+#[cfg(target_arch = "wasm32")]
+fn args() -> Vec<String> {
+    let query = web_sys::window()
+        .and_then(|window| window.location().search().ok())
+        .unwrap_or_default();
+
+    query
+        .trim_start_matches('?')
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .flat_map(|(flag, value)| [format!("--{flag}"), value.to_string()])
+        .collect()
+}
+
+// Bevy's defaults, with the window where it was last time (or filling the screen in a
+// kiosk), minus the window and GPU when running a server, and logging as asked
+pub fn default_plugins(options: &NetOptions) -> PluginGroupBuilder {
+    let level = options.log_level.unwrap_or(Level::INFO);
+    // browsers log to the console, which only bevy's own logging knows how to do
+    #[cfg(target_arch = "wasm32")]
+    let logging = |plugins: PluginGroupBuilder| plugins.set(LogPlugin { level, ..default() });
+    #[cfg(not(target_arch = "wasm32"))]
+    let logging = |plugins: PluginGroupBuilder| {
+        plugins
+            .disable::<LogPlugin>()
+            .add_before::<LogPlugin, _>(crate::logging::LoggingPlugin { level })
+    };
+
+    if !matches!(options.role, NetRole::Server(_)) {
+        let mut window = bevy_common::window::window_plugin(GAME_NAME);
+        if options.kiosk {
+            window.close_when_requested = false;
+            if let Some(primary) = &mut window.primary_window {
+                primary.mode = WindowMode::BorderlessFullscreen;
+                primary.cursor.visible = false;
+            }
+        }
+        return logging(DefaultPlugins.build().set(window));
+    }
+
+    logging(
+        headless_plugins().add(ScheduleRunnerPlugin::run_loop(Duration::from_secs_f64(
+            1.0 / SERVER_FRAME_RATE,
+        ))),
+    )
+}
+
+// Bevy's defaults without a window or GPU, for whoever drives the app's updates
+pub fn headless_plugins() -> PluginGroupBuilder {
+    DefaultPlugins
+        .build()
+        .set(WindowPlugin {
+            primary_window: None,
+            exit_condition: ExitCondition::DontExit,
+            close_when_requested: false,
+        })
+        .set(RenderPlugin {
+            render_creation: WgpuSettings {
+                backends: None,
+                ..default()
+            }
+            .into(),
+        })
+        .disable::<WinitPlugin>()
+}
+
+// Without networking nobody can sit down at the computer's paddle
+#[cfg(not(feature = "networking"))]
+pub fn computer_is_ai(rules: Res<crate::rules::MatchRules>) -> bool {
+    rules.opponent != crate::rules::Opponent::Local
+}
+
+// Without networking nobody can drop out and keep the match waiting
+#[cfg(not(feature = "networking"))]
+pub fn not_waiting() -> bool {
+    true
+}
+
+// A run condition for anything that changes the match. Clients only ever mirror the
+// snapshots they're sent.
+pub fn has_input_authority(role: Res<NetRole>) -> bool {
+    matches!(
+        *role,
+        NetRole::Local | NetRole::Broadcast(_) | NetRole::Server(_)
+    )
+}
+
+// A run condition for the keyboard driving the player's paddle directly. On a server it's
+// driven by a client instead.
+pub fn keyboard_is_local(role: Res<NetRole>) -> bool {
+    !matches!(*role, NetRole::Server(_))
+}
+
+// How many people are watching without a paddle
+#[derive(Resource, Debug, Default)]
+pub struct Spectators(pub usize);
+
+#[derive(Component)]
+struct LiveIndicator;
+
+pub struct NetPlugin {
+    pub options: NetOptions,
+}
+
+// When the port won't open or the host can't be found, the match goes on without the network
+#[cfg(feature = "networking")]
+fn play_locally(app: &mut App, options: &NetOptions, err: std::io::Error) {
+    warn!("{err}, playing locally");
+    app.insert_resource(NetRole::Local)
+        .insert_resource(NetOptions {
+            role: NetRole::Local,
+            ..options.clone()
+        });
+}
+
+impl Plugin for NetPlugin {
+    fn build(&self, app: &mut App) {
+        let options = &self.options;
+        // before the host or client, which fall back to a local match if they can't start
+        app.insert_resource(options.role.clone())
+            .insert_resource(options.clone());
+        #[cfg(feature = "networking")]
+        match &options.role {
+            NetRole::Local => (),
+            NetRole::Broadcast(port) => {
+                app.add_plugins(host::HostPlugin {
+                    port: *port,
+                    seats: false,
+                    options: options.clone(),
+                });
+            }
+            NetRole::Server(port) => {
+                app.add_plugins(host::HostPlugin {
+                    port: *port,
+                    seats: true,
+                    options: options.clone(),
+                });
+            }
+            NetRole::Spectator(address) => {
+                app.add_plugins(client::ClientPlugin {
+                    address: address.clone(),
+                    join: false,
+                    options: options.clone(),
+                });
+            }
+            NetRole::Client(address) => {
+                app.add_plugins(client::ClientPlugin {
+                    address: address.clone(),
+                    join: true,
+                    options: options.clone(),
+                });
+            }
+        }
+        #[cfg(feature = "networking")]
+        app.add_plugins(reconnect::ReconnectPlugin);
+
+        app.add_plugins((diagnostics::DiagnosticsPlugin, emotes::EmotesPlugin))
+            .init_resource::<Spectators>()
+            .add_systems(Startup, spawn_live_indicator)
+            .add_systems(
+                Update,
+                update_live_indicator.run_if(resource_changed::<Spectators>()),
+            );
+    }
+}
+
+fn spawn_live_indicator(mut commands: Commands) {
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_sections([
+                TextSection::new(
+                    "LIVE",
+                    TextStyle {
+                        font_size: LIVE_FONT_SIZE,
+                        color: LIVE_COLOR,
+                        ..default()
+                    },
+                ),
+                TextSection::from_style(TextStyle {
+                    font_size: LIVE_FONT_SIZE,
+                    color: SCOREBOARD_COLOR,
+                    ..default()
+                }),
+            ]),
+            // centered under the court
+            transform: Transform::from_translation(Vec3::new(
+                0.0,
+                BOTTOM_WALL + LIVE_PADDING_Y,
+                1.0,
+            )),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        LiveIndicator,
+    ));
+}
+
+fn update_live_indicator(
+    role: Res<NetRole>,
+    spectators: Res<Spectators>,
+    mut indicators: Query<(&mut Text, &mut Visibility), With<LiveIndicator>>,
+) {
+    // spectators get their own label instead, see `client.rs`
+    let shown = spectators.0 > 0 && !matches!(*role, NetRole::Spectator(_));
+
+    for (mut text, mut visibility) in &mut indicators {
+        *visibility = if shown {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+        text.sections[1].value = format!("  {} watching", spectators.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> Result<NetOptions, String> {
+        NetOptions::from_args(args.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn no_flags_is_a_local_match() {
+        assert_eq!(parse(""), Ok(NetOptions::default()));
+    }
+
+    #[test]
+    #[cfg(feature = "networking")]
+    fn ports_are_optional() {
+        assert_eq!(
+            parse("--server").unwrap().role,
+            NetRole::Server(DEFAULT_PORT)
+        );
+        assert_eq!(parse("--server 9000").unwrap().role, NetRole::Server(9000));
+        assert_eq!(
+            parse("--broadcast --lag 100").unwrap().role,
+            NetRole::Broadcast(DEFAULT_PORT)
+        );
+        assert!(parse("--kiosk --broadcast").unwrap().kiosk);
+        assert_eq!(
+            parse("--log-level debug").unwrap().log_level,
+            Some(Level::DEBUG)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "networking")]
+    fn link_conditions_come_in_milliseconds_and_percent() {
+        let options = parse("--connect 10.0.0.1:7373 --lag 120 --jitter 30 --loss 5").unwrap();
+        assert_eq!(options.role, NetRole::Client("10.0.0.1:7373".to_string()));
+        assert_eq!(
+            options.conditions,
+            LinkConditions {
+                latency: 0.12,
+                jitter: 0.03,
+                loss: 0.05,
+            }
+        );
+    }
+
+    #[test]
+    fn bad_flags_are_rejected() {
+        assert!(parse("--connect").is_err());
+        assert!(parse("--server nope").is_err());
+        assert!(parse("--lag").is_err());
+        assert!(parse("--server --connect 10.0.0.1:7373").is_err());
+        assert!(parse("--fast").is_err());
+        assert!(parse("--log-level loud").is_err());
+        assert!(parse("--kiosk yes").is_err());
+    }
+}
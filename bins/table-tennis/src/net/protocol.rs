@@ -0,0 +1,463 @@
+use bevy::prelude::*;
+
+use super::{emotes::Emote, reconnect::Dropout};
+use crate::{
+    entities::{Competitor, PerCompetitor, Side},
+    mutators::{MatchState, Mutator},
+    rules::Variant,
+};
+
+// Everything a client needs to draw one tick of the match
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    // counts up every host tick, to spot snapshots arriving out of order
+    pub tick: u32,
+    // the newest `Message::Input` from the recipient that this tick includes, 0 for spectators
+    pub last_input: u32,
+    pub spectators: usize,
+    pub state: MatchState,
+    pub winner: Option<Competitor>,
+    // paused for a player who dropped out, see `reconnect.rs`
+    pub waiting: Option<Dropout>,
+    pub scores: PerCompetitor<usize>,
+    pub player_side: Side,
+    pub mutators: Vec<Mutator>,
+    pub variant: Variant,
+    pub paddles: PerCompetitor<PaddleState>,
+    pub balls: Vec<BallState>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PaddleState {
+    // only ever moves in air hockey
+    pub x: f32,
+    pub y: f32,
+    pub velocity: f32,
+    pub height: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BallState {
+    pub position: Vec2,
+    pub velocity: Vec2,
+}
+
+// What goes over the wire, one per datagram
+#[derive(Debug, Clone, PartialEq)]
+pub enum Message {
+    // a spectator asking for (or keeping up) the snapshot stream
+    Watch,
+    // a client asking for a paddle
+    Join,
+    // the paddle a client was given, if any were free. Otherwise it's spectating.
+    Welcome(Option<Competitor>),
+    // a client's paddle direction for one of its ticks, numbered from 1
+    Input {
+        sequence: u32,
+        direction: f32,
+    },
+    Snapshot(Snapshot),
+    // a quick-chat message from whoever drives `competitor`, sent reliably, see
+    // `reliable.rs`. The host fills in the competitor for its clients' emotes.
+    Emote {
+        sequence: u32,
+        competitor: Competitor,
+        emote: Emote,
+    },
+    // the reliable message numbered this arrived
+    Ack(u32),
+    // the host's match on one of its ticks, see `desync.rs`
+    Checksum {
+        tick: u32,
+        checksum: u64,
+    },
+}
+
+impl Message {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut writer = Writer::default();
+        match self {
+            Message::Watch => writer.u8(0),
+            Message::Snapshot(snapshot) => {
+                writer.u8(1);
+                writer.snapshot(snapshot);
+            }
+            Message::Join => writer.u8(2),
+            Message::Welcome(competitor) => {
+                writer.u8(3);
+                writer.competitor(*competitor);
+            }
+            Message::Input {
+                sequence,
+                direction,
+            } => {
+                writer.u8(4);
+                writer.u32(*sequence);
+                writer.f32(*direction);
+            }
+            Message::Emote {
+                sequence,
+                competitor,
+                emote,
+            } => {
+                writer.u8(5);
+                writer.u32(*sequence);
+                writer.competitor(Some(*competitor));
+                writer.u8(emote.index() as u8);
+            }
+            Message::Ack(sequence) => {
+                writer.u8(6);
+                writer.u32(*sequence);
+            }
+            Message::Checksum { tick, checksum } => {
+                writer.u8(7);
+                writer.u32(*tick);
+                writer.u64(*checksum);
+            }
+        }
+        writer.0
+    }
+
+    // `None` for anything malformed, which is dropped like a lost packet
+    pub fn decode(bytes: &[u8]) -> Option<Self> {
+        let mut reader = Reader { bytes, at: 0 };
+        let message = match reader.u8()? {
+            0 => Message::Watch,
+            1 => Message::Snapshot(reader.snapshot()?),
+            2 => Message::Join,
+            3 => Message::Welcome(reader.competitor()?),
+            4 => Message::Input {
+                sequence: reader.u32()?,
+                direction: reader.f32()?,
+            },
+            5 => Message::Emote {
+                sequence: reader.u32()?,
+                competitor: reader.competitor()??,
+                emote: *Emote::ALL.get(reader.u8()? as usize)?,
+            },
+            6 => Message::Ack(reader.u32()?),
+            7 => Message::Checksum {
+                tick: reader.u32()?,
+                checksum: reader.u64()?,
+            },
+            _ => return None,
+        };
+        reader.finished().then_some(message)
+    }
+}
+
+fn state_tag(state: MatchState) -> u8 {
+    match state {
+        MatchState::PreMatch => 0,
+        MatchState::Playing => 1,
+        MatchState::Intermission => 2,
+        MatchState::GameOver => 3,
+    }
+}
+
+fn competitor_tag(competitor: Competitor) -> u8 {
+    match competitor {
+        Competitor::Player => 0,
+        Competitor::Computer => 1,
+    }
+}
+
+// Little-endian, with no padding or versioning: both ends are always the same build
+#[derive(Default)]
+struct Writer(Vec<u8>);
+
+impl Writer {
+    fn u8(&mut self, value: u8) {
+        self.0.push(value);
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn u64(&mut self, value: u64) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn f32(&mut self, value: f32) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn vec2(&mut self, value: Vec2) {
+        self.f32(value.x);
+        self.f32(value.y);
+    }
+
+    fn competitor(&mut self, competitor: Option<Competitor>) {
+        self.u8(match competitor {
+            None => 0,
+            Some(competitor) => competitor_tag(competitor) + 1,
+        });
+    }
+
+    fn snapshot(&mut self, snapshot: &Snapshot) {
+        self.u32(snapshot.tick);
+        self.u32(snapshot.last_input);
+        self.u32(snapshot.spectators as u32);
+        self.u8(state_tag(snapshot.state));
+        self.competitor(snapshot.winner);
+        self.competitor(snapshot.waiting.map(|dropout| dropout.competitor));
+        if let Some(dropout) = snapshot.waiting {
+            self.f32(dropout.remaining);
+        }
+        for competitor in Competitor::ALL {
+            self.u32(snapshot.scores[competitor] as u32);
+        }
+        self.u8(match snapshot.player_side {
+            Side::Left => 0,
+            Side::Right => 1,
+        });
+
+        let mut mutators = 0;
+        for (bit, mutator) in Mutator::ALL.into_iter().enumerate() {
+            if snapshot.mutators.contains(&mutator) {
+                mutators |= 1 << bit;
+            }
+        }
+        self.u8(mutators);
+        self.u8(match snapshot.variant {
+            Variant::TableTennis => 0,
+            Variant::AirHockey => 1,
+            Variant::Volleyball => 2,
+        });
+
+        for competitor in Competitor::ALL {
+            self.f32(snapshot.paddles[competitor].x);
+            self.f32(snapshot.paddles[competitor].y);
+            self.f32(snapshot.paddles[competitor].velocity);
+            self.f32(snapshot.paddles[competitor].height);
+        }
+
+        self.u8(snapshot.balls.len() as u8);
+        for ball in &snapshot.balls {
+            self.vec2(ball.position);
+            self.vec2(ball.velocity);
+        }
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    at: usize,
+}
+
+impl Reader<'_> {
+    fn take<const N: usize>(&mut self) -> Option<[u8; N]> {
+        let bytes = self.bytes.get(self.at..self.at + N)?;
+        self.at += N;
+        bytes.try_into().ok()
+    }
+
+    fn finished(&self) -> bool {
+        self.at == self.bytes.len()
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.take::<1>().map(|[value]| value)
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.take().map(u32::from_le_bytes)
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        self.take().map(u64::from_le_bytes)
+    }
+
+    fn f32(&mut self) -> Option<f32> {
+        self.take().map(f32::from_le_bytes)
+    }
+
+    fn vec2(&mut self) -> Option<Vec2> {
+        Some(Vec2::new(self.f32()?, self.f32()?))
+    }
+
+    fn competitor(&mut self) -> Option<Option<Competitor>> {
+        match self.u8()? {
+            0 => Some(None),
+            1 => Some(Some(Competitor::Player)),
+            2 => Some(Some(Competitor::Computer)),
+            _ => None,
+        }
+    }
+
+    fn snapshot(&mut self) -> Option<Snapshot> {
+        let tick = self.u32()?;
+        let last_input = self.u32()?;
+        let spectators = self.u32()? as usize;
+        let state = match self.u8()? {
+            0 => MatchState::PreMatch,
+            1 => MatchState::Playing,
+            2 => MatchState::Intermission,
+            3 => MatchState::GameOver,
+            _ => return None,
+        };
+        let winner = self.competitor()?;
+        let waiting = match self.competitor()? {
+            Some(competitor) => Some(Dropout {
+                competitor,
+                remaining: self.f32()?,
+            }),
+            None => None,
+        };
+        let scores = PerCompetitor {
+            player: self.u32()? as usize,
+            computer: self.u32()? as usize,
+        };
+        let player_side = match self.u8()? {
+            0 => Side::Left,
+            1 => Side::Right,
+            _ => return None,
+        };
+
+        let bits = self.u8()?;
+        let mutators = Mutator::ALL
+            .into_iter()
+            .enumerate()
+            .filter(|(bit, _)| bits & (1 << bit) != 0)
+            .map(|(_, mutator)| mutator)
+            .collect();
+        let variant = match self.u8()? {
+            0 => Variant::TableTennis,
+            1 => Variant::AirHockey,
+            2 => Variant::Volleyball,
+            _ => return None,
+        };
+
+        let mut paddle = || {
+            Some(PaddleState {
+                x: self.f32()?,
+                y: self.f32()?,
+                velocity: self.f32()?,
+                height: self.f32()?,
+            })
+        };
+        let paddles = PerCompetitor {
+            player: paddle()?,
+            computer: paddle()?,
+        };
+
+        let balls = (0..self.u8()?)
+            .map(|_| {
+                Some(BallState {
+                    position: self.vec2()?,
+                    velocity: self.vec2()?,
+                })
+            })
+            .collect::<Option<_>>()?;
+
+        Some(Snapshot {
+            tick,
+            last_input,
+            spectators,
+            state,
+            winner,
+            waiting,
+            scores,
+            player_side,
+            mutators,
+            variant,
+            paddles,
+            balls,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot() -> Snapshot {
+        Snapshot {
+            tick: 42,
+            last_input: 40,
+            spectators: 3,
+            state: MatchState::Playing,
+            winner: Some(Competitor::Computer),
+            waiting: Some(Dropout {
+                competitor: Competitor::Player,
+                remaining: 12.5,
+            }),
+            scores: PerCompetitor {
+                player: 4,
+                computer: 7,
+            },
+            player_side: Side::Left,
+            mutators: vec![Mutator::Gravity, Mutator::Portals],
+            variant: Variant::AirHockey,
+            paddles: PerCompetitor {
+                player: PaddleState {
+                    x: 390.0,
+                    y: 12.5,
+                    velocity: -300.0,
+                    height: 120.0,
+                },
+                computer: PaddleState {
+                    x: -200.0,
+                    y: -80.0,
+                    velocity: 0.0,
+                    height: 72.0,
+                },
+            },
+            balls: vec![
+                BallState {
+                    position: Vec2::new(10.0, -20.0),
+                    velocity: Vec2::new(400.0, 30.0),
+                },
+                BallState {
+                    position: Vec2::ZERO,
+                    velocity: Vec2::new(-350.0, -90.0),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn every_message_survives_a_round_trip() {
+        for message in [
+            Message::Watch,
+            Message::Join,
+            Message::Welcome(None),
+            Message::Welcome(Some(Competitor::Player)),
+            Message::Input {
+                sequence: 7,
+                direction: -1.0,
+            },
+            Message::Snapshot(snapshot()),
+            Message::Emote {
+                sequence: 3,
+                competitor: Competitor::Computer,
+                emote: Emote::ALL[1],
+            },
+            Message::Ack(3),
+            Message::Checksum {
+                tick: 42,
+                checksum: 0x0123_4567_89ab_cdef,
+            },
+        ] {
+            assert_eq!(Message::decode(&message.encode()), Some(message));
+        }
+    }
+
+    #[test]
+    fn snapshots_fit_in_a_datagram() {
+        let mut snapshot = snapshot();
+        snapshot.balls = vec![snapshot.balls[0]; crate::constants::MAX_BALLS];
+        assert!(Message::Snapshot(snapshot).encode().len() <= crate::constants::MAX_DATAGRAM_SIZE);
+    }
+
+    #[test]
+    fn malformed_datagrams_are_dropped() {
+        let bytes = Message::Snapshot(snapshot()).encode();
+
+        assert_eq!(Message::decode(&[]), None);
+        assert_eq!(Message::decode(&[255]), None);
+        assert_eq!(Message::decode(&bytes[..bytes.len() - 1]), None);
+        assert_eq!(Message::decode(&[bytes.as_slice(), &[0]].concat()), None);
+    }
+}
@@ -0,0 +1,89 @@
+use bevy::prelude::*;
+use bevy_common::screens;
+
+use crate::{constants::*, entities::Competitor};
+
+// A player who dropped out of a server match, and how long they've got left to come back
+// before they forfeit it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Dropout {
+    pub competitor: Competitor,
+    pub remaining: f32,
+}
+
+// Set by the server, and copied to its clients from snapshots. The match is paused while
+// it's waiting.
+#[derive(Resource, Debug, Default)]
+pub struct Waiting(pub Option<Dropout>);
+
+// A run condition for the match carrying on, nobody is missing
+pub fn not_waiting(waiting: Res<Waiting>) -> bool {
+    waiting.0.is_none()
+}
+
+#[derive(Component)]
+struct WaitingScreen;
+
+pub struct ReconnectPlugin;
+
+impl Plugin for ReconnectPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Waiting>()
+            .add_systems(Startup, spawn_waiting_screen)
+            .add_systems(
+                Update,
+                update_waiting_screen.run_if(resource_changed::<Waiting>()),
+            );
+    }
+}
+
+fn spawn_waiting_screen(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                visibility: Visibility::Hidden,
+                ..screens::overlay(SETTINGS_MENU_BACKDROP_COLOR, SETTINGS_MENU_Z_INDEX - 1)
+            },
+            WaitingScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_sections([
+                TextSection::from_style(TextStyle {
+                    font_size: GAME_OVER_FONT_SIZE,
+                    color: Color::WHITE,
+                    ..default()
+                }),
+                TextSection::new(
+                    "\n\nthe match is paused",
+                    TextStyle {
+                        font_size: SETTINGS_MENU_FONT_SIZE,
+                        color: Color::GRAY,
+                        ..default()
+                    },
+                ),
+            ]));
+        });
+}
+
+fn update_waiting_screen(
+    waiting: Res<Waiting>,
+    mut screens: Query<(&mut Visibility, &Children), With<WaitingScreen>>,
+    mut texts: Query<&mut Text>,
+) {
+    for (mut visibility, children) in &mut screens {
+        let Some(dropout) = waiting.0 else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        *visibility = Visibility::Visible;
+
+        for child in children {
+            if let Ok(mut text) = texts.get_mut(*child) {
+                text.sections[0].value = format!(
+                    "Waiting for opponent ({}s)",
+                    dropout.remaining.max(0.0).ceil()
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use std::collections::VecDeque;
+
+use super::protocol::Message;
+use crate::constants::*;
+
+// A message that has to arrive, kept until the other end acknowledges it
+#[derive(Debug)]
+struct Unacknowledged {
+    sequence: u32,
+    bytes: Vec<u8>,
+    first_sent: f32,
+    last_sent: f32,
+}
+
+// The rare messages that can't just be replaced by the next snapshot (emotes) go out on
+// top of the lossy transport with their own sequence numbers. They're resent until
+// acknowledged, and anything that turns up twice is only delivered once.
+#[derive(Debug, Default)]
+pub struct Reliable {
+    sequence: u32,
+    unacknowledged: Vec<Unacknowledged>,
+    // the sequence numbers most recently delivered from the other end
+    delivered: VecDeque<u32>,
+}
+
+impl Reliable {
+    // numbers the message `make` builds and returns it encoded, ready to send
+    pub fn send(&mut self, make: impl FnOnce(u32) -> Message, now: f32) -> Vec<u8> {
+        self.sequence += 1;
+        let bytes = make(self.sequence).encode();
+        self.unacknowledged.push(Unacknowledged {
+            sequence: self.sequence,
+            bytes: bytes.clone(),
+            first_sent: now,
+            last_sent: now,
+        });
+        bytes
+    }
+
+    pub fn acknowledge(&mut self, sequence: u32) {
+        self.unacknowledged
+            .retain(|message| message.sequence != sequence);
+    }
+
+    // everything that's gone unacknowledged long enough to send again. Messages still
+    // unacknowledged after the peer timeout are given up on, the peer is gone.
+    pub fn resends(&mut self, now: f32) -> Vec<Vec<u8>> {
+        self.unacknowledged
+            .retain(|message| now - message.first_sent < PEER_TIMEOUT);
+
+        self.unacknowledged
+            .iter_mut()
+            .filter(|message| now - message.last_sent >= RELIABLE_RESEND_INTERVAL)
+            .map(|message| {
+                message.last_sent = now;
+                message.bytes.clone()
+            })
+            .collect()
+    }
+
+    // whether a message with `sequence` is new. It's acknowledged either way, the
+    // acknowledgement for an earlier copy may have been lost.
+    pub fn deliver(&mut self, sequence: u32) -> bool {
+        if self.delivered.contains(&sequence) {
+            return false;
+        }
+        if self.delivered.len() == RELIABLE_HISTORY {
+            self.delivered.pop_front();
+        }
+        self.delivered.push_back(sequence);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ack(sequence: u32) -> Message {
+        Message::Ack(sequence)
+    }
+
+    #[test]
+    fn messages_are_resent_until_acknowledged() {
+        let mut reliable = Reliable::default();
+        let bytes = reliable.send(ack, 0.0);
+
+        assert!(reliable.resends(0.0).is_empty());
+        assert_eq!(reliable.resends(RELIABLE_RESEND_INTERVAL), vec![bytes]);
+        assert!(reliable.resends(RELIABLE_RESEND_INTERVAL).is_empty());
+
+        reliable.acknowledge(1);
+        assert!(reliable.resends(RELIABLE_RESEND_INTERVAL * 3.0).is_empty());
+    }
+
+    #[test]
+    fn messages_are_given_up_on_with_the_peer() {
+        let mut reliable = Reliable::default();
+        reliable.send(ack, 0.0);
+
+        assert!(reliable.resends(PEER_TIMEOUT).is_empty());
+    }
+
+    #[test]
+    fn duplicates_are_only_delivered_once() {
+        let mut reliable = Reliable::default();
+
+        assert!(reliable.deliver(2));
+        assert!(reliable.deliver(1));
+        assert!(!reliable.deliver(2));
+        assert!(!reliable.deliver(1));
+    }
+}
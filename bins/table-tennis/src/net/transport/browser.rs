@@ -0,0 +1,57 @@
+use std::{
+    collections::VecDeque,
+    io,
+    sync::{Arc, Mutex},
+};
+
+use wasm_bindgen::{prelude::*, JsCast};
+use web_sys::{BinaryType, MessageEvent, WebSocket};
+
+use super::{PeerId, Transport};
+
+// A browser client's link to its host's `WebSocketListener`
+pub struct BrowserWebSocket {
+    socket: WebSocket,
+    inbox: Arc<Mutex<VecDeque<Vec<u8>>>>,
+}
+
+// wasm32 builds are single threaded, so the socket never actually crosses threads
+unsafe impl Send for BrowserWebSocket {}
+unsafe impl Sync for BrowserWebSocket {}
+
+impl BrowserWebSocket {
+    pub fn connect(url: &str) -> io::Result<Self> {
+        let socket = WebSocket::new(url).map_err(|err| io::Error::other(format!("{err:?}")))?;
+        socket.set_binary_type(BinaryType::Arraybuffer);
+
+        let inbox = Arc::new(Mutex::new(VecDeque::new()));
+        let queue = inbox.clone();
+        let on_message = Closure::<dyn FnMut(MessageEvent)>::new(move |event: MessageEvent| {
+            if let Ok(buffer) = event.data().dyn_into::<js_sys::ArrayBuffer>() {
+                queue
+                    .lock()
+                    .unwrap()
+                    .push_back(js_sys::Uint8Array::new(&buffer).to_vec());
+            }
+        });
+        socket.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+        // lives as long as the page does
+        on_message.forget();
+
+        Ok(Self { socket, inbox })
+    }
+}
+
+impl Transport for BrowserWebSocket {
+    fn send(&mut self, to: PeerId, bytes: &[u8]) {
+        // anything sent while still connecting is dropped, like a lost datagram
+        if to == PeerId::HOST && self.socket.ready_state() == WebSocket::OPEN {
+            let _ = self.socket.send_with_u8_array(bytes);
+        }
+    }
+
+    fn receive(&mut self) -> Option<(PeerId, Vec<u8>)> {
+        let bytes = self.inbox.lock().unwrap().pop_front()?;
+        Some((PeerId::HOST, bytes))
+    }
+}
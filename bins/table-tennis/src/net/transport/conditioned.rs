@@ -0,0 +1,180 @@
+#[cfg(feature = "networking")]
+use bevy::utils::{Duration, Instant};
+#[cfg(feature = "networking")]
+use rand::Rng;
+
+#[cfg(feature = "networking")]
+use super::{PeerId, Transport};
+
+// Artificial network trouble, so netcode can be tuned without a second machine
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LinkConditions {
+    // added to the round trip, in seconds, half on the way out and half on the way back
+    pub latency: f32,
+    // up to this much more again, picked per datagram, so they can arrive out of order
+    pub jitter: f32,
+    // the chance of each datagram being dropped, in each direction
+    pub loss: f32,
+}
+
+impl LinkConditions {
+    pub fn is_perfect(&self) -> bool {
+        self.latency <= 0.0 && self.jitter <= 0.0 && self.loss <= 0.0
+    }
+
+    // how long one datagram spends on its way, one way
+    #[cfg(feature = "networking")]
+    fn delay(&self) -> Duration {
+        let jitter = if self.jitter > 0.0 {
+            rand::thread_rng().gen_range(0.0..self.jitter)
+        } else {
+            0.0
+        };
+        Duration::from_secs_f32((self.latency + jitter).max(0.0) / 2.0)
+    }
+
+    #[cfg(feature = "networking")]
+    fn lost(&self) -> bool {
+        // not the game's `GameRng`, network trouble shouldn't change how a seeded match plays
+        self.loss > 0.0 && rand::thread_rng().gen_bool(self.loss.min(1.0) as f64)
+    }
+}
+
+#[cfg(feature = "networking")]
+struct Delayed {
+    due: Instant,
+    peer: PeerId,
+    bytes: Vec<u8>,
+}
+
+// Wraps another transport, holding on to (or dropping) everything going through it
+#[cfg(feature = "networking")]
+pub struct ConditionedTransport {
+    inner: Box<dyn Transport>,
+    conditions: LinkConditions,
+    outgoing: Vec<Delayed>,
+    incoming: Vec<Delayed>,
+}
+
+#[cfg(feature = "networking")]
+impl ConditionedTransport {
+    pub fn new(inner: Box<dyn Transport>, conditions: LinkConditions) -> Self {
+        Self {
+            inner,
+            conditions,
+            outgoing: Vec::new(),
+            incoming: Vec::new(),
+        }
+    }
+
+    fn send_due(&mut self, now: Instant) {
+        let (mut due, waiting): (Vec<_>, Vec<_>) = std::mem::take(&mut self.outgoing)
+            .into_iter()
+            .partition(|datagram| datagram.due <= now);
+        self.outgoing = waiting;
+
+        due.sort_by_key(|datagram| datagram.due);
+        for datagram in due {
+            self.inner.send(datagram.peer, &datagram.bytes);
+        }
+    }
+}
+
+#[cfg(feature = "networking")]
+impl Transport for ConditionedTransport {
+    fn send(&mut self, to: PeerId, bytes: &[u8]) {
+        let now = Instant::now();
+        if !self.conditions.lost() {
+            self.outgoing.push(Delayed {
+                due: now + self.conditions.delay(),
+                peer: to,
+                bytes: bytes.to_vec(),
+            });
+        }
+        self.send_due(now);
+    }
+
+    fn receive(&mut self) -> Option<(PeerId, Vec<u8>)> {
+        let now = Instant::now();
+        // hosts and clients receive every tick, even when they've nothing to send
+        self.send_due(now);
+
+        while let Some((peer, bytes)) = self.inner.receive() {
+            if !self.conditions.lost() {
+                self.incoming.push(Delayed {
+                    due: now + self.conditions.delay(),
+                    peer,
+                    bytes,
+                });
+            }
+        }
+
+        let next = self
+            .incoming
+            .iter()
+            .enumerate()
+            .filter(|(_, datagram)| datagram.due <= now)
+            .min_by_key(|(_, datagram)| datagram.due)
+            .map(|(i, _)| i)?;
+        let datagram = self.incoming.swap_remove(next);
+        Some((datagram.peer, datagram.bytes))
+    }
+}
+
+#[cfg(all(test, feature = "networking"))]
+mod tests {
+    use std::thread;
+
+    use super::{super::memory::MemoryTransport, *};
+
+    #[test]
+    fn perfect_conditions_pass_everything_straight_through() {
+        let (host, mut client) = MemoryTransport::pair();
+        let mut host = ConditionedTransport::new(Box::new(host), LinkConditions::default());
+
+        client.send(PeerId::HOST, b"now");
+        assert_eq!(host.receive().unwrap().1, b"now");
+    }
+
+    #[test]
+    fn latency_holds_datagrams_back() {
+        let (host, mut client) = MemoryTransport::pair();
+        let mut host = ConditionedTransport::new(
+            Box::new(host),
+            LinkConditions {
+                latency: 0.1,
+                ..LinkConditions::default()
+            },
+        );
+
+        client.send(PeerId::HOST, b"later");
+        assert!(host.receive().is_none());
+        thread::sleep(Duration::from_millis(60));
+        let (peer, bytes) = host.receive().unwrap();
+        assert_eq!(bytes, b"later");
+
+        host.send(peer, b"back");
+        assert!(client.receive().is_none());
+        thread::sleep(Duration::from_millis(60));
+        // sent on the host's next receive after it's due
+        assert!(host.receive().is_none());
+        assert_eq!(client.receive().unwrap().1, b"back");
+    }
+
+    #[test]
+    fn total_loss_drops_everything() {
+        let (host, mut client) = MemoryTransport::pair();
+        let mut host = ConditionedTransport::new(
+            Box::new(host),
+            LinkConditions {
+                loss: 1.0,
+                ..LinkConditions::default()
+            },
+        );
+
+        for _ in 0..10 {
+            client.send(PeerId::HOST, b"lost");
+        }
+        assert!(host.receive().is_none());
+    }
+}
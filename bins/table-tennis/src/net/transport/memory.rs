@@ -0,0 +1,96 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+
+use super::{PeerId, Transport};
+
+type Queue = Arc<Mutex<VecDeque<Vec<u8>>>>;
+
+// One end of an in-memory link. Nothing is ever lost, which makes it the transport for tests.
+pub struct MemoryTransport {
+    inbox: Queue,
+    outbox: Queue,
+    // whoever is on the other end
+    peer: PeerId,
+}
+
+impl MemoryTransport {
+    // a host's end and a client's end, linked to each other
+    pub fn pair() -> (Self, Self) {
+        let to_host = Queue::default();
+        let to_client = Queue::default();
+
+        let host = Self {
+            inbox: to_host.clone(),
+            outbox: to_client.clone(),
+            peer: PeerId::next(),
+        };
+        let client = Self {
+            inbox: to_client,
+            outbox: to_host,
+            peer: PeerId::HOST,
+        };
+        (host, client)
+    }
+}
+
+impl Transport for MemoryTransport {
+    fn send(&mut self, to: PeerId, bytes: &[u8]) {
+        if to == self.peer {
+            self.outbox.lock().unwrap().push_back(bytes.to_vec());
+        }
+    }
+
+    fn receive(&mut self) -> Option<(PeerId, Vec<u8>)> {
+        let bytes = self.inbox.lock().unwrap().pop_front()?;
+        Some((self.peer, bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delivers_in_order_both_ways() {
+        let (mut host, mut client) = MemoryTransport::pair();
+
+        client.send(PeerId::HOST, b"one");
+        client.send(PeerId::HOST, b"two");
+        let (from, bytes) = host.receive().unwrap();
+        assert_eq!(bytes, b"one");
+        assert_eq!(host.receive().unwrap().1, b"two");
+        assert!(host.receive().is_none());
+
+        host.send(from, b"three");
+        assert_eq!(client.receive(), Some((PeerId::HOST, b"three".to_vec())));
+    }
+
+    #[test]
+    fn drops_datagrams_for_strangers() {
+        let (mut host, mut client) = MemoryTransport::pair();
+        let (_, mut other_client) = MemoryTransport::pair();
+
+        host.send(PeerId::next(), b"lost");
+        assert!(client.receive().is_none());
+        assert!(other_client.receive().is_none());
+    }
+
+    #[test]
+    fn several_transports_act_as_one() {
+        let (first_host, mut first) = MemoryTransport::pair();
+        let (second_host, mut second) = MemoryTransport::pair();
+        let mut host: Vec<Box<dyn Transport>> = vec![Box::new(first_host), Box::new(second_host)];
+
+        first.send(PeerId::HOST, b"first");
+        second.send(PeerId::HOST, b"second");
+        let (first_peer, _) = host.receive().unwrap();
+        let (second_peer, _) = host.receive().unwrap();
+        assert_ne!(first_peer, second_peer);
+
+        host.send(second_peer, b"only second");
+        assert!(first.receive().is_none());
+        assert_eq!(second.receive().unwrap().1, b"only second");
+    }
+}
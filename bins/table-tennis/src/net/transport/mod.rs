@@ -0,0 +1,116 @@
+// How the netcode exchanges datagrams, so the same host and client logic runs over UDP
+// natively, WebSockets in the browser, and in-memory queues in tests.
+//
+// Delivery is best effort, like UDP: datagrams can be dropped or arrive out of order, and
+// nothing above this layer relies on them arriving.
+mod conditioned;
+#[cfg(all(test, feature = "networking"))]
+mod memory;
+#[cfg(feature = "networking")]
+mod udp;
+#[cfg(feature = "networking")]
+mod websocket;
+
+#[cfg(all(feature = "networking", target_arch = "wasm32"))]
+mod browser;
+
+#[cfg(feature = "networking")]
+use std::{
+    fmt, io,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+#[cfg(all(feature = "networking", target_arch = "wasm32"))]
+pub use browser::BrowserWebSocket;
+#[cfg(feature = "networking")]
+pub use conditioned::ConditionedTransport;
+pub use conditioned::LinkConditions;
+#[cfg(feature = "networking")]
+pub use udp::UdpTransport;
+#[cfg(feature = "networking")]
+pub use websocket::WebSocketListener;
+
+#[cfg(feature = "networking")]
+use crate::constants::*;
+
+// Somebody on the other end of a transport
+#[cfg(feature = "networking")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PeerId(u64);
+
+#[cfg(feature = "networking")]
+impl PeerId {
+    // how a client addresses the host it connected to
+    pub const HOST: PeerId = PeerId(0);
+
+    // unique across every transport, so a host can listen on several at once
+    fn next() -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(1);
+        PeerId(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+#[cfg(feature = "networking")]
+impl fmt::Display for PeerId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "peer {}", self.0)
+    }
+}
+
+#[cfg(feature = "networking")]
+pub trait Transport: Send + Sync + 'static {
+    // anything sent to a peer this transport doesn't know is dropped
+    fn send(&mut self, to: PeerId, bytes: &[u8]);
+
+    // the next datagram that's arrived, if there is one
+    fn receive(&mut self) -> Option<(PeerId, Vec<u8>)>;
+}
+
+// Several transports as one, e.g. a host taking both native and browser clients
+#[cfg(feature = "networking")]
+impl Transport for Vec<Box<dyn Transport>> {
+    fn send(&mut self, to: PeerId, bytes: &[u8]) {
+        for transport in self {
+            transport.send(to, bytes);
+        }
+    }
+
+    fn receive(&mut self) -> Option<(PeerId, Vec<u8>)> {
+        self.iter_mut().find_map(|transport| transport.receive())
+    }
+}
+
+// What a host listens on: UDP on `port`, and WebSockets for browsers on the port after
+#[cfg(feature = "networking")]
+pub fn listen(port: u16) -> io::Result<Box<dyn Transport>> {
+    let udp = UdpTransport::bind(port)?;
+    let websocket = WebSocketListener::bind(port + WEBSOCKET_PORT_OFFSET)?;
+    bevy::log::info!(
+        "Listening on {} (UDP) and {} (WebSockets)",
+        udp.local_addr()?,
+        websocket.local_addr()?
+    );
+
+    let transports: Vec<Box<dyn Transport>> = vec![Box::new(udp), Box::new(websocket)];
+    Ok(Box::new(transports))
+}
+
+// A client's link to the host at `address`, e.g. "127.0.0.1:7373"
+#[cfg(all(feature = "networking", not(target_arch = "wasm32")))]
+pub fn connect(address: &str) -> io::Result<Box<dyn Transport>> {
+    Ok(Box::new(UdpTransport::connect(address)?))
+}
+
+// Browsers can't send UDP, so they use the host's WebSocket port instead
+#[cfg(all(feature = "networking", target_arch = "wasm32"))]
+pub fn connect(address: &str) -> io::Result<Box<dyn Transport>> {
+    let (host, port) = address
+        .rsplit_once(':')
+        .and_then(|(host, port)| Some((host, port.parse::<u16>().ok()?)))
+        .ok_or_else(|| io::Error::other(format!("not an address: {address}")))?;
+
+    Ok(Box::new(BrowserWebSocket::connect(&format!(
+        "ws://{host}:{}",
+        port + WEBSOCKET_PORT_OFFSET
+    ))?))
+}
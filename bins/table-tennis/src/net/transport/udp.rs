@@ -0,0 +1,119 @@
+use std::{
+    collections::HashMap,
+    io::{self, ErrorKind},
+    net::{SocketAddr, ToSocketAddrs, UdpSocket},
+};
+
+use super::{PeerId, Transport};
+use crate::constants::*;
+
+pub struct UdpTransport {
+    socket: UdpSocket,
+    peers: HashMap<SocketAddr, PeerId>,
+    addresses: HashMap<PeerId, SocketAddr>,
+    // hosts meet new peers by hearing from them, clients only listen to their host
+    accepts_peers: bool,
+}
+
+impl UdpTransport {
+    fn new(socket: UdpSocket, accepts_peers: bool) -> io::Result<Self> {
+        socket.set_nonblocking(true)?;
+        Ok(Self {
+            socket,
+            peers: HashMap::new(),
+            addresses: HashMap::new(),
+            accepts_peers,
+        })
+    }
+
+    // a host on `port`, on every interface
+    pub fn bind(port: u16) -> io::Result<Self> {
+        Self::new(UdpSocket::bind(("0.0.0.0", port))?, true)
+    }
+
+    // a client of the host at `address`, which it knows as `PeerId::HOST`
+    pub fn connect(address: impl ToSocketAddrs) -> io::Result<Self> {
+        let address = address
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::new(ErrorKind::NotFound, "couldn't find the host"))?;
+
+        let mut transport = Self::new(UdpSocket::bind(("0.0.0.0", 0))?, false)?;
+        transport.peers.insert(address, PeerId::HOST);
+        transport.addresses.insert(PeerId::HOST, address);
+        Ok(transport)
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+}
+
+impl Transport for UdpTransport {
+    fn send(&mut self, to: PeerId, bytes: &[u8]) {
+        if let Some(address) = self.addresses.get(&to) {
+            // best effort, same as if it had been lost on the way
+            let _ = self.socket.send_to(bytes, address);
+        }
+    }
+
+    fn receive(&mut self) -> Option<(PeerId, Vec<u8>)> {
+        let mut buffer = [0; MAX_DATAGRAM_SIZE];
+
+        loop {
+            let (len, from) = match self.socket.recv_from(&mut buffer) {
+                Ok(received) => received,
+                Err(err) if err.kind() == ErrorKind::WouldBlock => return None,
+                // e.g. a peer that went away without saying so
+                Err(_) => continue,
+            };
+
+            let peer = match self.peers.get(&from) {
+                Some(peer) => *peer,
+                None if self.accepts_peers => {
+                    let peer = PeerId::next();
+                    self.peers.insert(from, peer);
+                    self.addresses.insert(peer, from);
+                    peer
+                }
+                None => continue,
+            };
+            return Some((peer, buffer[..len].to_vec()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{thread, time::Duration};
+
+    use super::*;
+
+    // polls for a bit, the datagram can take a moment even over loopback
+    fn receive(transport: &mut UdpTransport) -> Option<(PeerId, Vec<u8>)> {
+        for _ in 0..100 {
+            if let Some(received) = transport.receive() {
+                return Some(received);
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+        None
+    }
+
+    #[test]
+    fn round_trip_over_loopback() {
+        let mut host = UdpTransport::bind(0).unwrap();
+        let port = host.local_addr().unwrap().port();
+        let mut client = UdpTransport::connect(("127.0.0.1", port)).unwrap();
+
+        client.send(PeerId::HOST, b"hello");
+        let (peer, bytes) = receive(&mut host).unwrap();
+        assert_eq!(bytes, b"hello");
+
+        host.send(peer, b"welcome");
+        assert_eq!(
+            receive(&mut client),
+            Some((PeerId::HOST, b"welcome".to_vec()))
+        );
+    }
+}
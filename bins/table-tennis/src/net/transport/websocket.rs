@@ -0,0 +1,400 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    io::{self, ErrorKind, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+};
+
+use super::{PeerId, Transport};
+use crate::constants::*;
+
+// RFC 6455 section 1.3
+const HANDSHAKE_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+// opcodes this end understands, anything else is ignored
+const OPCODE_BINARY: u8 = 0x2;
+const OPCODE_CLOSE: u8 = 0x8;
+const OPCODE_PING: u8 = 0x9;
+const OPCODE_PONG: u8 = 0xA;
+
+// A host's side of browser clients, who can't send UDP. Only the bits of the protocol a
+// browser's `WebSocket` needs are here: no extensions, subprotocols or fragmented messages,
+// and every message is binary.
+pub struct WebSocketListener {
+    listener: TcpListener,
+    connections: HashMap<PeerId, Connection>,
+    received: VecDeque<(PeerId, Vec<u8>)>,
+}
+
+struct Connection {
+    stream: TcpStream,
+    incoming: Vec<u8>,
+    outgoing: Vec<u8>,
+    // the HTTP upgrade is done and frames are flowing
+    upgraded: bool,
+    closed: bool,
+}
+
+impl WebSocketListener {
+    pub fn bind(port: u16) -> io::Result<Self> {
+        let listener = TcpListener::bind(("0.0.0.0", port))?;
+        listener.set_nonblocking(true)?;
+        Ok(Self {
+            listener,
+            connections: HashMap::new(),
+            received: VecDeque::new(),
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    fn poll(&mut self) {
+        while let Ok((stream, _)) = self.listener.accept() {
+            if stream.set_nonblocking(true).is_ok() {
+                self.connections.insert(
+                    PeerId::next(),
+                    Connection {
+                        stream,
+                        incoming: Vec::new(),
+                        outgoing: Vec::new(),
+                        upgraded: false,
+                        closed: false,
+                    },
+                );
+            }
+        }
+
+        for (peer, connection) in &mut self.connections {
+            connection.read();
+            if !connection.upgraded {
+                connection.upgrade();
+            }
+            while connection.upgraded {
+                match connection.next_frame() {
+                    Some((OPCODE_BINARY, payload)) => self.received.push_back((*peer, payload)),
+                    Some((OPCODE_PING, payload)) => connection.queue_frame(OPCODE_PONG, &payload),
+                    Some((OPCODE_CLOSE, _)) => connection.closed = true,
+                    Some(_) => (),
+                    None => break,
+                }
+            }
+            connection.flush();
+        }
+
+        self.connections.retain(|_, connection| !connection.closed);
+    }
+}
+
+impl Connection {
+    fn read(&mut self) {
+        let mut buffer = [0; MAX_DATAGRAM_SIZE];
+        loop {
+            match self.stream.read(&mut buffer) {
+                Ok(0) => {
+                    self.closed = true;
+                    return;
+                }
+                Ok(len) => self.incoming.extend_from_slice(&buffer[..len]),
+                Err(err) if err.kind() == ErrorKind::WouldBlock => return,
+                Err(_) => {
+                    self.closed = true;
+                    return;
+                }
+            }
+        }
+    }
+
+    fn flush(&mut self) {
+        while !self.outgoing.is_empty() {
+            match self.stream.write(&self.outgoing) {
+                Ok(0) => {
+                    self.closed = true;
+                    return;
+                }
+                Ok(len) => {
+                    self.outgoing.drain(..len);
+                }
+                Err(err) if err.kind() == ErrorKind::WouldBlock => return,
+                Err(_) => {
+                    self.closed = true;
+                    return;
+                }
+            }
+        }
+    }
+
+    // answers the browser's HTTP upgrade request once all of it has arrived
+    fn upgrade(&mut self) {
+        let Some(end) = self
+            .incoming
+            .windows(4)
+            .position(|window| window == b"\r\n\r\n")
+        else {
+            return;
+        };
+        let request = String::from_utf8_lossy(&self.incoming[..end]).into_owned();
+        self.incoming.drain(..end + 4);
+
+        let Some(key) = request.lines().find_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            name.trim()
+                .eq_ignore_ascii_case("sec-websocket-key")
+                .then(|| value.trim().to_string())
+        }) else {
+            self.closed = true;
+            return;
+        };
+
+        self.outgoing.extend_from_slice(
+            format!(
+                "HTTP/1.1 101 Switching Protocols\r\n\
+                 Upgrade: websocket\r\n\
+                 Connection: Upgrade\r\n\
+                 Sec-WebSocket-Accept: {}\r\n\r\n",
+                accept_key(&key)
+            )
+            .as_bytes(),
+        );
+        self.upgraded = true;
+    }
+
+    // the opcode and payload of the next complete frame, if one has arrived
+    fn next_frame(&mut self) -> Option<(u8, Vec<u8>)> {
+        let bytes = &self.incoming;
+        let (&first, &second) = (bytes.first()?, bytes.get(1)?);
+
+        let (len, mut at) = match second & 0x7F {
+            126 => (
+                u16::from_be_bytes(bytes.get(2..4)?.try_into().ok()?) as usize,
+                4,
+            ),
+            127 => (
+                u64::from_be_bytes(bytes.get(2..10)?.try_into().ok()?) as usize,
+                10,
+            ),
+            len => (len as usize, 2),
+        };
+        if len > MAX_DATAGRAM_SIZE || first & 0x80 == 0 {
+            // way bigger than anything we send, or fragmented: not one of ours
+            self.closed = true;
+            return None;
+        }
+
+        // browsers always mask what they send
+        let mask: [u8; 4] = if second & 0x80 != 0 {
+            let mask = bytes.get(at..at + 4)?.try_into().ok()?;
+            at += 4;
+            mask
+        } else {
+            [0; 4]
+        };
+
+        let payload = bytes
+            .get(at..at + len)?
+            .iter()
+            .enumerate()
+            .map(|(i, byte)| byte ^ mask[i % 4])
+            .collect();
+        self.incoming.drain(..at + len);
+
+        Some((first & 0x0F, payload))
+    }
+
+    // a single, unmasked frame, as servers send them
+    fn queue_frame(&mut self, opcode: u8, payload: &[u8]) {
+        self.outgoing.push(0x80 | opcode);
+        match payload.len() {
+            len @ 0..=125 => self.outgoing.push(len as u8),
+            len @ 126..=0xFFFF => {
+                self.outgoing.push(126);
+                self.outgoing.extend_from_slice(&(len as u16).to_be_bytes());
+            }
+            len => {
+                self.outgoing.push(127);
+                self.outgoing.extend_from_slice(&(len as u64).to_be_bytes());
+            }
+        }
+        self.outgoing.extend_from_slice(payload);
+    }
+}
+
+impl Transport for WebSocketListener {
+    fn send(&mut self, to: PeerId, bytes: &[u8]) {
+        if let Some(connection) = self.connections.get_mut(&to) {
+            if connection.upgraded {
+                connection.queue_frame(OPCODE_BINARY, bytes);
+                connection.flush();
+            }
+        }
+    }
+
+    fn receive(&mut self) -> Option<(PeerId, Vec<u8>)> {
+        if self.received.is_empty() {
+            self.poll();
+        }
+        self.received.pop_front()
+    }
+}
+
+// `Sec-WebSocket-Accept` for a client's `Sec-WebSocket-Key`
+fn accept_key(key: &str) -> String {
+    base64(&sha1(format!("{key}{HANDSHAKE_GUID}").as_bytes()))
+}
+
+// Only used for the handshake above, so it doesn't have to be fast
+fn sha1(message: &[u8]) -> [u8; 20] {
+    let mut state: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+
+    let mut padded = message.to_vec();
+    padded.push(0x80);
+    while padded.len() % 64 != 56 {
+        padded.push(0);
+    }
+    padded.extend_from_slice(&((message.len() as u64) * 8).to_be_bytes());
+
+    for block in padded.chunks(64) {
+        let mut words = [0u32; 80];
+        for (i, word) in block.chunks(4).enumerate() {
+            words[i] = u32::from_be_bytes(word.try_into().unwrap());
+        }
+        for i in 16..80 {
+            words[i] = (words[i - 3] ^ words[i - 8] ^ words[i - 14] ^ words[i - 16]).rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = state;
+        for (i, word) in words.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5A827999),
+                20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6),
+            };
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(*word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+
+        for (value, add) in state.iter_mut().zip([a, b, c, d, e]) {
+            *value = value.wrapping_add(add);
+        }
+    }
+
+    let mut digest = [0; 20];
+    for (bytes, value) in digest.chunks_mut(4).zip(state) {
+        bytes.copy_from_slice(&value.to_be_bytes());
+    }
+    digest
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::new();
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, byte)| n | (*byte as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - 6 * i) & 0x3F) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{thread, time::Duration};
+
+    use super::*;
+
+    #[test]
+    fn sha1_matches_known_digests() {
+        let hex = |digest: [u8; 20]| {
+            digest
+                .iter()
+                .map(|byte| format!("{byte:02x}"))
+                .collect::<String>()
+        };
+        assert_eq!(
+            hex(sha1(b"abc")),
+            "a9993e364706816aba3e25717850c26c9cd0d89d"
+        );
+        assert_eq!(hex(sha1(b"")), "da39a3ee5e6b4b0d3255bfef95601890afd80709");
+    }
+
+    #[test]
+    fn accept_key_matches_the_rfc_example() {
+        assert_eq!(
+            accept_key("dGhlIHNhbXBsZSBub25jZQ=="),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+    }
+
+    // polls for a bit, the connection can take a moment even over loopback
+    fn receive(listener: &mut WebSocketListener) -> Option<(PeerId, Vec<u8>)> {
+        for _ in 0..100 {
+            if let Some(received) = listener.receive() {
+                return Some(received);
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+        None
+    }
+
+    #[test]
+    fn talks_to_a_browser_style_client() {
+        let mut listener = WebSocketListener::bind(0).unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+
+        client
+            .write_all(
+                b"GET / HTTP/1.1\r\n\
+                  Host: localhost\r\n\
+                  Upgrade: websocket\r\n\
+                  Connection: Upgrade\r\n\
+                  Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\
+                  Sec-WebSocket-Version: 13\r\n\r\n",
+            )
+            .unwrap();
+
+        // a masked binary frame holding "hi"
+        let mask = [1, 2, 3, 4];
+        let mut frame = vec![0x80 | OPCODE_BINARY, 0x80 | 2];
+        frame.extend_from_slice(&mask);
+        frame.extend(b"hi".iter().zip(mask).map(|(byte, mask)| byte ^ mask));
+        client.write_all(&frame).unwrap();
+
+        let (peer, bytes) = receive(&mut listener).unwrap();
+        assert_eq!(bytes, b"hi");
+
+        listener.send(peer, b"hello");
+
+        client
+            .set_read_timeout(Some(Duration::from_secs(1)))
+            .unwrap();
+        let mut response = Vec::new();
+        let mut buffer = [0; 256];
+        while !response.ends_with(b"hello") {
+            let len = client.read(&mut buffer).unwrap();
+            assert_ne!(len, 0);
+            response.extend_from_slice(&buffer[..len]);
+        }
+        let response = String::from_utf8_lossy(&response);
+        assert!(response.starts_with("HTTP/1.1 101"));
+        assert!(response.contains("Sec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo="));
+    }
+}
@@ -0,0 +1,175 @@
+// Which way up the court is drawn. In portrait the camera's turned a quarter so the court
+// stands on end, the player's end at the bottom, and either way it's scaled to fit the window
+// (see `units.rs`). Only the view turns: the simulation still runs left to right, so
+// directions pressed are turned to match before anything reads them, and the scoreboards are
+// turned back upright.
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::{prelude::*, text::Text2dBounds, window::PrimaryWindow};
+
+use crate::{
+    actions::{ActionSet, ActionState, OpponentActions},
+    constants::*,
+    minimap::MinimapCamera,
+    settings::Settings,
+    split_screen::SplitCamera,
+    units,
+};
+
+// The court layout picked in the settings
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Layout {
+    // portrait when the window's taller than it is wide
+    #[default]
+    Automatic,
+    Landscape,
+    Portrait,
+}
+
+impl Layout {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Layout::Automatic => "Automatic",
+            Layout::Landscape => "Landscape",
+            Layout::Portrait => "Portrait",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            Layout::Automatic => Layout::Landscape,
+            Layout::Landscape => Layout::Portrait,
+            Layout::Portrait => Layout::Automatic,
+        }
+    }
+
+    // `window` is its size, when there is one
+    fn orientation(&self, window: Option<Vec2>) -> Orientation {
+        match self {
+            Layout::Landscape => Orientation::Landscape,
+            Layout::Portrait => Orientation::Portrait,
+            Layout::Automatic => match window {
+                Some(size) if size.y > size.x => Orientation::Portrait,
+                _ => Orientation::Landscape,
+            },
+        }
+    }
+}
+
+// How the court is drawn right now
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Orientation {
+    #[default]
+    Landscape,
+    Portrait,
+}
+
+impl Orientation {
+    // the camera's turn, a quarter anticlockwise puts the right end at the bottom
+    pub fn rotation(&self) -> Quat {
+        match self {
+            Orientation::Landscape => Quat::IDENTITY,
+            Orientation::Portrait => Quat::from_rotation_z(FRAC_PI_2),
+        }
+    }
+
+    // a direction on screen, up and right, as a direction on the court
+    pub fn onto_court(&self, direction: Vec2) -> Vec2 {
+        self.rotation().mul_vec3(direction.extend(0.0)).truncate()
+    }
+}
+
+pub struct OrientationPlugin;
+
+impl Plugin for OrientationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Orientation>()
+            .add_systems(
+                PreUpdate,
+                orient_actions
+                    .in_set(ActionSet::Orient)
+                    .run_if(|orientation: Res<Orientation>| *orientation != Orientation::Landscape),
+            )
+            .add_systems(
+                Update,
+                (
+                    pick_orientation,
+                    turn_camera.run_if(resource_changed::<Orientation>()),
+                    upright_text,
+                )
+                    .chain(),
+            );
+    }
+}
+
+fn pick_orientation(
+    settings: Res<Settings>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut orientation: ResMut<Orientation>,
+) {
+    let window = windows
+        .get_single()
+        .ok()
+        .map(|window| Vec2::new(window.width(), window.height()));
+    let picked = settings.layout.orientation(window);
+    // only touched when it changes, so the camera's only turned then
+    if *orientation != picked {
+        *orientation = picked;
+    }
+}
+
+fn turn_camera(
+    orientation: Res<Orientation>,
+    mut cameras: Query<
+        (&mut Transform, &mut OrthographicProjection),
+        (With<Camera2d>, Without<MinimapCamera>, Without<SplitCamera>),
+    >,
+) {
+    for (mut transform, mut projection) in &mut cameras {
+        transform.rotation = orientation.rotation();
+        projection.scaling_mode = units::fit(match *orientation {
+            Orientation::Landscape => COURT_VIEW,
+            Orientation::Portrait => PORTRAIT_VIEW,
+        });
+    }
+}
+
+// Text drawn on the court turns the other way to the camera, so it still reads level
+fn upright_text(
+    orientation: Res<Orientation>,
+    mut texts: Query<(&mut Transform, Ref<Text2dBounds>)>,
+) {
+    let rotation = orientation.rotation();
+    for (mut transform, bounds) in &mut texts {
+        if orientation.is_changed() || bounds.is_added() {
+            transform.rotation = rotation;
+        }
+    }
+}
+
+fn orient_actions(
+    orientation: Res<Orientation>,
+    mut actions: ResMut<ActionState>,
+    mut opponent: ResMut<OpponentActions>,
+) {
+    actions.move_axis = orientation.onto_court(actions.move_axis);
+    opponent.0.move_axis = orientation.onto_court(opponent.0.move_axis);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn portrait_turns_screen_directions_onto_the_court() {
+        let tall = Some(Vec2::new(720.0, 1280.0));
+        assert_eq!(Layout::Automatic.orientation(tall), Orientation::Portrait);
+        assert_eq!(Layout::Automatic.orientation(None), Orientation::Landscape);
+        assert_eq!(Layout::Landscape.orientation(tall), Orientation::Landscape);
+
+        // pressing right moves the paddle up the court, towards the screen's right
+        let right = Orientation::Portrait.onto_court(Vec2::X);
+        assert!((right - Vec2::Y).length() < 1e-6);
+        assert_eq!(Orientation::Landscape.onto_court(Vec2::X), Vec2::X);
+    }
+}
@@ -0,0 +1,200 @@
+// Noticing when the machine can't keep up with the fixed timestep. Every frame the fixed ticks
+// are timed, and how long they took against how much match time they stood for is the load:
+// at 1 or more each tick costs more than it simulates, so there are more of them to run every
+// frame and the game spirals. bevy drops whatever's past a quarter of a second behind rather
+// than run it, and that's measured too. While overloaded a warning shows at the top of the
+// screen, and with the setting on, local matches tick half as often until the load's back down.
+use std::time::Duration;
+
+use bevy::{app::RunFixedUpdateLoop, prelude::*, utils::Instant};
+
+use crate::{constants::*, net::NetRole, settings::Settings};
+
+#[derive(Resource, Debug, Default)]
+pub struct FixedLoad {
+    // this frame's fixed ticks, how many and how long they took together in milliseconds
+    pub ticks: usize,
+    pub tick_time: f32,
+    // smoothed, the real time the ticks took over the match time they simulated
+    pub load: f32,
+    // the match time bevy dropped this frame for being too far behind, in milliseconds
+    pub behind: f32,
+    pub overloaded: bool,
+    // the timestep before it was lengthened, while it is
+    eased_from: Option<Duration>,
+    ticks_started: Option<Instant>,
+}
+
+impl FixedLoad {
+    pub fn eased(&self) -> bool {
+        self.eased_from.is_some()
+    }
+}
+
+// Overloaded from a load of OVERLOAD_LOAD until it's down to OVERLOAD_CLEARED, so it doesn't
+// flicker on and off around the one number. A single dropped frame is a hitch, not overload.
+fn overloaded(was: bool, load: f32) -> bool {
+    if was {
+        load > OVERLOAD_CLEARED
+    } else {
+        load >= OVERLOAD_LOAD
+    }
+}
+
+#[derive(Component)]
+struct OverloadWarning;
+
+#[derive(Component)]
+struct OverloadWarningText;
+
+pub struct OverloadPlugin;
+
+impl Plugin for OverloadPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FixedLoad>()
+            .add_systems(Startup, spawn_warning)
+            // around every fixed tick this frame, however many there are
+            .add_systems(
+                RunFixedUpdateLoop,
+                (
+                    start_ticks.before(bevy::time::run_fixed_update_schedule),
+                    end_ticks.after(bevy::time::run_fixed_update_schedule),
+                ),
+            )
+            .add_systems(FixedUpdate, count_tick)
+            .add_systems(
+                Update,
+                (measure_load, ease_timestep, update_warning).chain(),
+            );
+    }
+}
+
+fn start_ticks(mut load: ResMut<FixedLoad>) {
+    load.ticks_started = Some(Instant::now());
+    load.ticks = 0;
+    load.tick_time = 0.0;
+}
+
+fn count_tick(mut load: ResMut<FixedLoad>) {
+    load.ticks += 1;
+}
+
+fn end_ticks(mut load: ResMut<FixedLoad>) {
+    if let Some(started) = load.ticks_started.take() {
+        load.tick_time = started.elapsed().as_secs_f32() * 1000.0;
+    }
+}
+
+fn measure_load(
+    mut load: ResMut<FixedLoad>,
+    fixed: Res<Time<Fixed>>,
+    real: Res<Time<Real>>,
+    virtual_time: Res<Time<Virtual>>,
+) {
+    load.behind = if virtual_time.is_paused() {
+        0.0
+    } else {
+        let wanted = real.delta_seconds() * virtual_time.effective_speed();
+        (wanted - virtual_time.delta_seconds()).max(0.0) * 1000.0
+    };
+    if load.ticks > 0 {
+        let simulated = load.ticks as f32 * fixed.timestep().as_secs_f32() * 1000.0;
+        let frame = load.tick_time / simulated;
+        load.load += (frame - load.load) * OVERLOAD_SMOOTHING;
+    }
+
+    let was = load.overloaded;
+    load.overloaded = overloaded(was, load.load);
+    if load.overloaded != was {
+        if load.overloaded {
+            warn!(
+                "The simulation can't keep up, its ticks take {:.0}% of the time they simulate",
+                load.load * 100.0
+            );
+        } else {
+            info!("The simulation has caught up");
+        }
+    }
+}
+
+// Only when the match is ours alone, a networked one has to tick with everyone else
+fn ease_timestep(
+    mut load: ResMut<FixedLoad>,
+    mut fixed: ResMut<Time<Fixed>>,
+    settings: Res<Settings>,
+    role: Res<NetRole>,
+) {
+    let allowed = settings.pacing.ease_overload && *role == NetRole::Local;
+    match load.eased_from {
+        None if allowed && load.overloaded => {
+            let timestep = fixed.timestep();
+            info!("Ticking half as often until the simulation catches up");
+            fixed.set_timestep(timestep * 2);
+            load.eased_from = Some(timestep);
+        }
+        Some(timestep) if !allowed || !load.overloaded => {
+            fixed.set_timestep(timestep);
+            load.eased_from = None;
+        }
+        _ => (),
+    }
+}
+
+fn spawn_warning(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    top: Val::Px(DIAGNOSTICS_PADDING),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            OverloadWarning,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: DIAGNOSTICS_FONT_SIZE,
+                        color: OVERLOAD_WARNING_COLOR,
+                        ..default()
+                    },
+                )
+                .with_background_color(SETTINGS_MENU_BACKDROP_COLOR),
+                OverloadWarningText,
+            ));
+        });
+}
+
+fn update_warning(
+    load: Res<FixedLoad>,
+    fixed: Res<Time<Fixed>>,
+    mut warnings: Query<&mut Visibility, With<OverloadWarning>>,
+    mut texts: Query<&mut Text, With<OverloadWarningText>>,
+) {
+    for mut visibility in &mut warnings {
+        *visibility = if load.overloaded {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+    }
+    if !load.overloaded {
+        return;
+    }
+
+    let rate = 1.0 / fixed.timestep().as_secs_f32();
+    for mut text in &mut texts {
+        text.sections[0].value = if load.eased() {
+            format!("Can't keep up, ticking at {rate:.0} Hz")
+        } else {
+            format!("Can't keep up, {:.0}% load", load.load * 100.0)
+        };
+    }
+}
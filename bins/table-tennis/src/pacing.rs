@@ -0,0 +1,76 @@
+// Keeps a pong game from burning through a laptop's battery: frames can be capped at a
+// rate from the settings, and with power saving on, menus and pauses only redraw a few times
+// a second (or straight away for input).
+use std::time::Duration;
+
+use bevy::{
+    prelude::*,
+    utils::Instant,
+    winit::{UpdateMode, WinitSettings},
+};
+
+use crate::{
+    constants::*,
+    mutators::MatchState,
+    settings::{MenuState, Settings},
+};
+
+pub struct PacingPlugin;
+
+impl Plugin for PacingPlugin {
+    fn build(&self, app: &mut App) {
+        // a server runs headless at its own rate
+        app.add_systems(
+            Update,
+            pick_update_mode.run_if(crate::net::keyboard_is_local),
+        );
+        #[cfg(not(target_arch = "wasm32"))]
+        app.add_systems(Last, cap_frame_rate.run_if(crate::net::keyboard_is_local));
+    }
+}
+
+// Whether anything's moving on screen that needs drawing every frame
+fn in_menu(match_state: MatchState, menu: MenuState) -> bool {
+    menu != MenuState::Closed || matches!(match_state, MatchState::PreMatch | MatchState::GameOver)
+}
+
+fn pick_update_mode(
+    settings: Res<Settings>,
+    match_state: Res<State<MatchState>>,
+    menu: Res<State<MenuState>>,
+    winit: Option<ResMut<WinitSettings>>,
+    // only changed when it needs to be, so bevy doesn't see it change every frame
+    mut saving: Local<Option<bool>>,
+) {
+    let Some(mut winit) = winit else {
+        return;
+    };
+
+    let save = settings.pacing.power_saving && in_menu(*match_state.get(), *menu.get());
+    if *saving == Some(save) {
+        return;
+    }
+    *saving = Some(save);
+    winit.focused_mode = if save {
+        UpdateMode::Reactive {
+            wait: Duration::from_secs_f32(POWER_SAVING_FRAME_TIME),
+        }
+    } else {
+        UpdateMode::Continuous
+    };
+}
+
+// Sleeps off whatever's left of the frame's share of a second
+#[cfg(not(target_arch = "wasm32"))]
+fn cap_frame_rate(settings: Res<Settings>, mut last: Local<Option<Instant>>) {
+    let cap = settings.pacing.frame_cap;
+    if cap > 0 {
+        if let Some(last) = *last {
+            let frame = Duration::from_secs_f64(1.0 / cap as f64);
+            if let Some(left) = frame.checked_sub(last.elapsed()) {
+                std::thread::sleep(left);
+            }
+        }
+    }
+    *last = Some(Instant::now());
+}
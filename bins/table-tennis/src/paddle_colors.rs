@@ -0,0 +1,346 @@
+// Each side picks its paddle's color, and the color of the trail it leaves when it moves,
+// from the pre-match screen. The scoreboard at each end is tinted to match whoever's playing
+// from it. The picks are kept between runs, one pair for each seat.
+use bevy::prelude::*;
+use bevy_common::storage;
+
+use crate::{
+    constants::*,
+    cosmetics::Cosmetics,
+    entities::{self, Competitor, PerCompetitor},
+    mutators::MatchState,
+    rules::Ends,
+    settings::Settings,
+    theme::Theme,
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Swatch {
+    // the theme's paddle color, or for a trail whatever the paddle is
+    #[default]
+    Default,
+    Red,
+    Orange,
+    Yellow,
+    Green,
+    Cyan,
+    Blue,
+    Magenta,
+}
+
+impl Swatch {
+    pub const ALL: [Swatch; 8] = [
+        Swatch::Default,
+        Swatch::Red,
+        Swatch::Orange,
+        Swatch::Yellow,
+        Swatch::Green,
+        Swatch::Cyan,
+        Swatch::Blue,
+        Swatch::Magenta,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Swatch::Default => "Default",
+            Swatch::Red => "Red",
+            Swatch::Orange => "Orange",
+            Swatch::Yellow => "Yellow",
+            Swatch::Green => "Green",
+            Swatch::Cyan => "Cyan",
+            Swatch::Blue => "Blue",
+            Swatch::Magenta => "Magenta",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Swatch::ALL.into_iter().find(|swatch| swatch.name() == name)
+    }
+
+    fn step(&self, step: f32) -> Self {
+        let i = Swatch::ALL
+            .iter()
+            .position(|swatch| swatch == self)
+            .unwrap_or(0);
+        let len = Swatch::ALL.len();
+        let i = if step < 0.0 { i + len - 1 } else { i + 1 };
+        Swatch::ALL[i % len]
+    }
+
+    // `None` for the default, which depends on what it's for
+    fn color(&self, theme: Theme) -> Option<Color> {
+        let color = match self {
+            Swatch::Default => return None,
+            Swatch::Red => Color::rgb(0.95, 0.2, 0.2),
+            Swatch::Orange => Color::rgb(1.0, 0.55, 0.1),
+            Swatch::Yellow => Color::rgb(1.0, 0.9, 0.2),
+            Swatch::Green => Color::rgb(0.3, 0.9, 0.3),
+            Swatch::Cyan => Color::rgb(0.2, 0.9, 0.95),
+            Swatch::Blue => Color::rgb(0.3, 0.45, 1.0),
+            Swatch::Magenta => Color::rgb(0.95, 0.3, 0.9),
+        };
+        // bright enough to glow like the rest of the neon theme
+        Some(if theme.is_hdr() {
+            color * NEON_SWATCH_BRIGHTNESS
+        } else {
+            color
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SideColors {
+    pub paddle: Swatch,
+    pub trail: Swatch,
+}
+
+#[derive(Resource, Debug, Default, PartialEq, Eq)]
+pub struct PaddleColors(pub PerCompetitor<SideColors>);
+
+impl PaddleColors {
+    fn load() -> Self {
+        storage::load(GAME_NAME, PADDLE_COLORS_FILE)
+            .map(|contents| Self::parse(&contents))
+            .unwrap_or_default()
+    }
+
+    fn save(&self) {
+        if let Err(err) = storage::save(GAME_NAME, PADDLE_COLORS_FILE, &self.to_text()) {
+            warn!("Couldn't save the paddle colors: {err}");
+        }
+    }
+
+    // a line for each seat with its paddle's and trail's colors, anything unreadable left at
+    // the default
+    fn parse(contents: &str) -> Self {
+        let mut colors = Self::default();
+        for (competitor, line) in Competitor::ALL.into_iter().zip(contents.lines()) {
+            let mut names = line.split_whitespace().map(Swatch::from_name);
+            colors.0[competitor] = SideColors {
+                paddle: names.next().flatten().unwrap_or_default(),
+                trail: names.next().flatten().unwrap_or_default(),
+            };
+        }
+        colors
+    }
+
+    fn to_text(&self) -> String {
+        Competitor::ALL
+            .iter()
+            .map(|competitor| {
+                let side = self.0[*competitor];
+                format!("{} {}\n", side.paddle.name(), side.trail.name())
+            })
+            .collect()
+    }
+
+    pub fn paddle(&self, competitor: Competitor, theme: Theme) -> Color {
+        self.0[competitor]
+            .paddle
+            .color(theme)
+            .unwrap_or(theme.palette().paddle)
+    }
+
+    fn trail(&self, competitor: Competitor, theme: Theme) -> Color {
+        self.0[competitor]
+            .trail
+            .color(theme)
+            .unwrap_or_else(|| self.paddle(competitor, theme))
+            .with_a(PADDLE_TRAIL_ALPHA)
+    }
+
+    pub fn scoreboard(&self, competitor: Competitor, theme: Theme) -> Color {
+        self.0[competitor]
+            .paddle
+            .color(theme)
+            .unwrap_or(theme.palette().scoreboard)
+    }
+}
+
+// The color rows on the pre-match screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColorItem {
+    competitor: Competitor,
+    trail: bool,
+}
+
+impl ColorItem {
+    pub fn all() -> impl Iterator<Item = ColorItem> {
+        Competitor::ALL.into_iter().flat_map(|competitor| {
+            [false, true]
+                .into_iter()
+                .map(move |trail| ColorItem { competitor, trail })
+        })
+    }
+
+    pub fn label(&self) -> &'static str {
+        match (self.competitor, self.trail) {
+            (Competitor::Player, false) => "Player 1 paddle",
+            (Competitor::Player, true) => "Player 1 trail",
+            (Competitor::Computer, false) => "Player 2 paddle",
+            (Competitor::Computer, true) => "Player 2 trail",
+        }
+    }
+
+    fn swatch<'a>(&self, colors: &'a mut PaddleColors) -> &'a mut Swatch {
+        let side = &mut colors.0[self.competitor];
+        if self.trail {
+            &mut side.trail
+        } else {
+            &mut side.paddle
+        }
+    }
+
+    pub fn value(&self, colors: &PaddleColors) -> &'static str {
+        let side = colors.0[self.competitor];
+        if self.trail {
+            side.trail.name()
+        } else {
+            side.paddle.name()
+        }
+    }
+
+    pub fn adjust(&self, colors: &mut PaddleColors, step: f32) {
+        let swatch = self.swatch(colors);
+        *swatch = swatch.step(step);
+    }
+}
+
+// A fading copy of a paddle where it just was
+#[derive(Component, Debug)]
+struct TrailGhost {
+    color: Color,
+    lifetime: Timer,
+}
+
+pub struct PaddleColorsPlugin;
+
+impl Plugin for PaddleColorsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(PaddleColors::load())
+            .add_systems(OnExit(MatchState::PreMatch), save_colors)
+            .add_systems(
+                Update,
+                (
+                    color_sides,
+                    leave_trails.run_if(in_state(MatchState::Playing)),
+                    fade_trails,
+                ),
+            );
+    }
+}
+
+fn save_colors(colors: Res<PaddleColors>) {
+    colors.save();
+}
+
+// Paddles and scoreboards, whenever the picks, the theme or the ends change
+fn color_sides(
+    colors: Res<PaddleColors>,
+    settings: Res<Settings>,
+    ends: Res<Ends>,
+    mut paddles: Query<(&mut Sprite, Option<&Competitor>, &entities::Side), With<entities::Paddle>>,
+    mut scoreboards: Query<(&mut Text, &entities::Side), With<entities::ScoreboardText>>,
+) {
+    if !colors.is_changed() && !settings.is_changed() && !ends.is_changed() {
+        return;
+    }
+    for (mut sprite, competitor, side) in &mut paddles {
+        // a partner in doubles plays for whoever's at its end
+        let competitor = competitor.copied().unwrap_or_else(|| ends.at(*side));
+        sprite.color = colors.paddle(competitor, settings.theme);
+    }
+    for (mut text, side) in &mut scoreboards {
+        let color = colors.scoreboard(ends.at(*side), settings.theme);
+        for section in &mut text.sections {
+            section.style.color = color;
+        }
+    }
+}
+
+// Drops a ghost every so often behind a paddle that's moved since the last one, shaped by its
+// trail style, see `cosmetics.rs`
+fn leave_trails(
+    mut commands: Commands,
+    colors: Res<PaddleColors>,
+    cosmetics: Res<Cosmetics>,
+    settings: Res<Settings>,
+    paddles: Query<(Entity, &Transform, &Competitor), With<entities::Paddle>>,
+    mut last: Local<Vec<(Entity, Vec2)>>,
+    mut since: Local<f32>,
+    time: Res<Time>,
+) {
+    *since += time.delta_seconds();
+    if *since < PADDLE_TRAIL_INTERVAL {
+        return;
+    }
+    *since = 0.0;
+
+    for (entity, transform, competitor) in &paddles {
+        let position = transform.translation.truncate();
+        let moved = last
+            .iter()
+            .find(|(paddle, _)| *paddle == entity)
+            .is_some_and(|(_, at)| at.distance(position) >= PADDLE_TRAIL_MIN_DISTANCE);
+        if moved {
+            let color = colors.trail(*competitor, settings.theme);
+            let style = cosmetics.trails[*competitor];
+            let mut ghost = transform.with_translation(position.extend(-0.1));
+            ghost.scale.x *= style.width();
+            commands.spawn((
+                SpriteBundle {
+                    // just behind the paddles
+                    transform: ghost,
+                    sprite: Sprite { color, ..default() },
+                    ..default()
+                },
+                TrailGhost {
+                    color,
+                    lifetime: Timer::from_seconds(style.lifetime(), TimerMode::Once),
+                },
+            ));
+        }
+    }
+    *last = paddles
+        .iter()
+        .map(|(entity, transform, _)| (entity, transform.translation.truncate()))
+        .collect();
+}
+
+fn fade_trails(
+    mut commands: Commands,
+    mut ghosts: Query<(Entity, &mut Sprite, &mut TrailGhost)>,
+    time: Res<Time>,
+) {
+    for (entity, mut sprite, mut ghost) in &mut ghosts {
+        ghost.lifetime.tick(time.delta());
+        if ghost.lifetime.finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let left = 1.0 - ghost.lifetime.percent();
+        sprite.color = ghost.color.with_a(ghost.color.a() * left);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colors_survive_saving() {
+        let mut colors = PaddleColors::default();
+        colors.0.player.trail = Swatch::Cyan;
+        colors.0.computer.paddle = Swatch::Magenta;
+        assert_eq!(PaddleColors::parse(&colors.to_text()), colors);
+
+        // anything that isn't a color is left at the default
+        assert_eq!(
+            PaddleColors::parse("Blue Teal\n").0.player,
+            SideColors {
+                paddle: Swatch::Blue,
+                trail: Swatch::Default,
+            }
+        );
+    }
+}
@@ -0,0 +1,205 @@
+use std::sync::OnceLock;
+
+use bevy::prelude::*;
+use bevy_common::collision;
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::{
+    constants::*,
+    entities::{self, Competitor},
+    net::{Emote, EmoteEvent},
+    rng::GameRng,
+    rules::{self, Ends, MatchRules},
+    CollisionEvent, PointScored, TickSet,
+};
+
+// How the computer likes to play, beyond how fast it is. They're in
+// `assets/personalities.ron`, and content packs can add more (see `mods.rs`).
+#[derive(Debug, Clone, Deserialize)]
+pub struct AiPersonality {
+    pub name: String,
+    // the chance each return is smashed, from 0 to 1
+    pub aggression: f32,
+    // the angle returns lean towards, in degrees from horizontal, and how far towards it
+    // they're pulled, from 0 to 1
+    pub return_angle: f32,
+    pub angle_preference: f32,
+    // how much of its top speed it loses with each return of a rally
+    pub fatigue: f32,
+    // said after winning a point, with `taunt_chance`
+    pub taunts: Vec<Emote>,
+    pub taunt_chance: f32,
+}
+
+impl AiPersonality {
+    // the direction a return leaves at, given the one it bounced off at
+    fn steer(&self, direction: Vec2) -> Vec2 {
+        let angle = self.return_angle.to_radians();
+        let preferred = Vec2::new(
+            direction.x.signum() * angle.cos(),
+            direction.y.signum() * angle.sin(),
+        );
+        direction
+            .normalize_or_zero()
+            .lerp(preferred, self.angle_preference)
+            .normalize_or_zero()
+    }
+
+    // the fraction of its top speed that's left this far into a rally
+    pub fn stamina(&self, returns: usize) -> f32 {
+        (1.0 - self.fatigue * returns as f32).max(MIN_AI_STAMINA)
+    }
+}
+
+pub fn all() -> &'static [AiPersonality] {
+    static PERSONALITIES: OnceLock<Vec<AiPersonality>> = OnceLock::new();
+    PERSONALITIES.get_or_init(|| {
+        let mut personalities: Vec<AiPersonality> =
+            ron::from_str(include_str!("../assets/personalities.ron"))
+                .expect("assets/personalities.ron should be valid");
+        personalities.extend(crate::mods::loaded().personalities.iter().cloned());
+        personalities
+    })
+}
+
+// The personality chosen for this match
+pub fn chosen(rules: &MatchRules) -> &'static AiPersonality {
+    let personalities = all();
+    &personalities[rules.personality % personalities.len()]
+}
+
+// How many times the ball's been returned this rally, and by which paddle last. A ball
+// overlapping a paddle for a few ticks only counts once.
+#[derive(Resource, Debug, Default)]
+pub struct Rally {
+    pub returns: usize,
+    last: Option<(Entity, entities::Side)>,
+}
+
+pub struct PersonalityPlugin;
+
+impl Plugin for PersonalityPlugin {
+    fn build(&self, app: &mut App) {
+        // rallies are counted whoever's playing, the toasts announce long ones
+        app.init_resource::<Rally>().add_systems(
+            FixedUpdate,
+            (
+                count_returns,
+                (
+                    // volleyball has its own way of returning, see `volleyball.rs`
+                    play_returns.run_if(not(rules::variant_is(rules::Variant::Volleyball))),
+                    taunt,
+                )
+                    .run_if(crate::net::computer_is_ai)
+                    .run_if(not(crate::replay::ghost_active)),
+            )
+                .chain()
+                .in_set(TickSet::Effects),
+        );
+    }
+}
+
+fn count_returns(
+    mut points: EventReader<PointScored>,
+    mut collision_events: EventReader<CollisionEvent>,
+    mut rally: ResMut<Rally>,
+) {
+    if points.read().count() > 0 {
+        *rally = Rally::default();
+    }
+    for ev in collision_events.read() {
+        // a partner's return keeps the rally going as much as anyone's
+        if let CollisionEvent::Paddle(ball, side, _) | CollisionEvent::Partner(ball, side, _) = ev {
+            if rally.last != Some((*ball, *side)) {
+                rally.last = Some((*ball, *side));
+                rally.returns += 1;
+            }
+        }
+    }
+}
+
+// The computer's returns are smashed and steered to its liking
+fn play_returns(
+    rally: Res<Rally>,
+    rules: Res<MatchRules>,
+    ends: Res<Ends>,
+    mut balls: Query<&mut entities::Velocity, With<entities::Ball>>,
+    mut rng: ResMut<GameRng>,
+) {
+    if !rally.is_changed() {
+        return;
+    }
+    let Some((ball, side)) = rally.last else {
+        return;
+    };
+    let Ok(mut velocity) = balls.get_mut(ball) else {
+        return;
+    };
+    if ends.at(side) != Competitor::Computer {
+        return;
+    }
+
+    let personality = chosen(&rules);
+    // no roll at all without any aggression, so a classic match plays out as it always has
+    let smash =
+        personality.aggression > 0.0 && rng.gen_bool(personality.aggression.min(1.0) as f64);
+    if !smash && personality.angle_preference <= 0.0 {
+        return;
+    }
+
+    let mut speed = velocity.length();
+    if smash {
+        speed = (speed * SMASH_SPEEDUP).min(MAX_RAMPED_BALL_SPEED);
+    }
+    let mut direction = velocity.normalize_or_zero();
+    if personality.angle_preference > 0.0 {
+        direction = collision::enforce_min_horizontal(
+            personality.steer(direction),
+            MIN_HORIZONTAL_SPEED_FRACTION,
+        );
+    }
+    velocity.0 = direction * speed;
+}
+
+fn taunt(
+    mut points: EventReader<PointScored>,
+    rules: Res<MatchRules>,
+    mut emotes: EventWriter<EmoteEvent>,
+    mut rng: ResMut<GameRng>,
+) {
+    let personality = chosen(&rules);
+    for point in points.read() {
+        if point.scorer != Competitor::Computer
+            || personality.taunts.is_empty()
+            || !rng.gen_bool(personality.taunt_chance.clamp(0.0, 1.0) as f64)
+        {
+            continue;
+        }
+        let emote = personality.taunts[rng.gen_range(0..personality.taunts.len())];
+        emotes.send(EmoteEvent {
+            competitor: Competitor::Computer,
+            emote,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn personalities_load() {
+        let personalities = all();
+        assert_eq!(personalities[0].name, "Classic");
+        for personality in personalities {
+            assert!((0.0..=1.0).contains(&personality.aggression));
+            assert!((0.0..=1.0).contains(&personality.angle_preference));
+        }
+        // the classic computer doesn't steer at all
+        let direction = Vec2::new(0.6, -0.8);
+        assert!(personalities[0]
+            .steer(direction)
+            .abs_diff_eq(direction, 1e-6));
+    }
+}
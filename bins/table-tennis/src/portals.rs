@@ -0,0 +1,160 @@
+use bevy::{prelude::*, sprite::collide_aabb::collide};
+
+use crate::{
+    constants::*,
+    entities::{self, WallSide},
+    mutators::{self, MatchState, Mutator},
+};
+
+// One end of a portal pair, laid over a wall. A ball that touches it comes out of the portal
+// on the other wall, moving the same way it was going. Portals are sensors: they have no
+// `Collider` so nothing bounces off them.
+#[derive(Component, Debug)]
+pub struct Portal {
+    wall: WallSide,
+}
+
+impl Portal {
+    // which way is into the court from this portal
+    fn inward(&self) -> f32 {
+        match self.wall {
+            WallSide::Top => -1.0,
+            WallSide::Bottom => 1.0,
+        }
+    }
+}
+
+// A ball went through a portal
+#[derive(Debug, Event)]
+pub struct PortalTravel {
+    pub from: Vec2,
+    pub to: Vec2,
+}
+
+// The spinning flourish left at both ends of a trip through a portal
+#[derive(Component, Debug)]
+struct Swirl(Timer);
+
+pub struct PortalPlugin;
+
+impl Plugin for PortalPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PortalTravel>()
+            .add_systems(
+                OnEnter(MatchState::Playing),
+                spawn_portals.run_if(mutators::enabled(Mutator::Portals)),
+            )
+            .add_systems(OnExit(MatchState::Playing), despawn_portals)
+            .add_systems(Update, (spawn_swirls, animate_swirls).chain());
+    }
+}
+
+fn spawn_portals(mut commands: Commands) {
+    for wall in [WallSide::Top, WallSide::Bottom] {
+        commands.spawn((
+            SpriteBundle {
+                transform: Transform {
+                    // just in front of the wall, so balls touch the portal first
+                    translation: Vec3::new(PORTAL_X, wall.position().y, 0.5),
+                    scale: PORTAL_SIZE,
+                    ..default()
+                },
+                sprite: Sprite {
+                    color: PORTAL_COLOR,
+                    ..default()
+                },
+                ..default()
+            },
+            Portal { wall },
+        ));
+    }
+}
+
+fn despawn_portals(mut commands: Commands, portals: Query<Entity, With<Portal>>) {
+    for portal in &portals {
+        commands.entity(portal).despawn();
+    }
+}
+
+// Runs after balls have moved and before they bounce, so a ball in a portal never hits the wall
+// behind it
+pub fn teleport_balls(
+    mut balls: Query<&mut Transform, (With<entities::Ball>, Without<Portal>)>,
+    portals: Query<(&Transform, &Portal)>,
+    mut travel: EventWriter<PortalTravel>,
+) {
+    for mut ball_transform in &mut balls {
+        let ball_size = ball_transform.scale.truncate();
+
+        let Some((entry_transform, entry)) = portals.iter().find(|(transform, _)| {
+            collide(
+                ball_transform.translation,
+                ball_size,
+                transform.translation,
+                transform.scale.truncate(),
+            )
+            .is_some()
+        }) else {
+            continue;
+        };
+
+        let Some((exit_transform, exit)) = portals.iter().find(|(_, exit)| exit.wall != entry.wall)
+        else {
+            continue;
+        };
+
+        let from = ball_transform.translation.truncate();
+        // keep the ball's offset along the portal, and put it clear of the exit so it doesn't
+        // go straight back through
+        ball_transform.translation.x +=
+            exit_transform.translation.x - entry_transform.translation.x;
+        ball_transform.translation.y = exit_transform.translation.y
+            + exit.inward() * (exit_transform.scale.y + ball_size.y) / 2.0;
+
+        travel.send(PortalTravel {
+            from,
+            to: ball_transform.translation.truncate(),
+        });
+    }
+}
+
+fn spawn_swirls(mut commands: Commands, mut travel: EventReader<PortalTravel>) {
+    for trip in travel.read() {
+        for position in [trip.from, trip.to] {
+            commands.spawn((
+                SpriteBundle {
+                    transform: Transform {
+                        translation: position.extend(1.5),
+                        scale: SWIRL_SIZE,
+                        ..default()
+                    },
+                    sprite: Sprite {
+                        color: PORTAL_COLOR,
+                        ..default()
+                    },
+                    ..default()
+                },
+                Swirl(Timer::from_seconds(SWIRL_DURATION, TimerMode::Once)),
+            ));
+        }
+    }
+}
+
+fn animate_swirls(
+    mut commands: Commands,
+    mut swirls: Query<(Entity, &mut Transform, &mut Sprite, &mut Swirl)>,
+    time: Res<Time>,
+) {
+    for (entity, mut transform, mut sprite, mut swirl) in &mut swirls {
+        if swirl.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        // spin up while shrinking away to nothing
+        let remaining = 1.0 - swirl.0.percent();
+        transform.rotate_z(SWIRL_SPIN_SPEED * swirl.0.percent() * time.delta_seconds());
+        transform.scale = SWIRL_SIZE * remaining;
+        sprite.color.set_a(remaining);
+    }
+}
@@ -0,0 +1,103 @@
+use bevy::{
+    core_pipeline::{bloom::BloomSettings, tonemapping::Tonemapping},
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+
+use crate::{constants::*, settings::Settings};
+
+#[derive(Component)]
+struct Vignette;
+
+pub struct PostProcessingPlugin;
+
+impl Plugin for PostProcessingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_vignette).add_systems(
+            Update,
+            apply_post_processing.run_if(resource_changed::<Settings>()),
+        );
+    }
+}
+
+// Darkens the corners of the screen. Generated rather than loaded so there's no asset to ship.
+fn vignette_image(size: u32) -> Image {
+    let mut data = Vec::with_capacity((size * size * 4) as usize);
+
+    for y in 0..size {
+        for x in 0..size {
+            // -1..1 across the image, so the corners are at sqrt(2)
+            let u = (x as f32 + 0.5) / size as f32 * 2.0 - 1.0;
+            let v = (y as f32 + 0.5) / size as f32 * 2.0 - 1.0;
+            let distance = Vec2::new(u, v).length() / std::f32::consts::SQRT_2;
+
+            let falloff = ((distance - VIGNETTE_INNER_RADIUS) / (1.0 - VIGNETTE_INNER_RADIUS))
+                .clamp(0.0, 1.0)
+                .powf(1.5);
+            data.extend_from_slice(&[0, 0, 0, (falloff * VIGNETTE_STRENGTH * 255.0) as u8]);
+        }
+    }
+
+    Image::new(
+        Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+    )
+}
+
+fn spawn_vignette(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    commands.spawn((
+        ImageBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                ..default()
+            },
+            image: images.add(vignette_image(VIGNETTE_IMAGE_SIZE)).into(),
+            visibility: Visibility::Hidden,
+            z_index: ZIndex::Global(VIGNETTE_Z_INDEX),
+            ..default()
+        },
+        Vignette,
+    ));
+}
+
+fn apply_post_processing(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    mut cameras: Query<(Entity, &mut Camera, &mut Tonemapping), With<Camera2d>>,
+    mut vignettes: Query<&mut Visibility, With<Vignette>>,
+) {
+    let hdr = settings.theme.is_hdr();
+    let pp = &settings.post_processing;
+
+    for (entity, mut camera, mut tonemapping) in &mut cameras {
+        camera.hdr = hdr;
+
+        if hdr && pp.bloom_intensity > 0.0 {
+            // bloom looks washed out without a tonemapper to bring the bright colors back in range
+            *tonemapping = Tonemapping::TonyMcMapface;
+            commands.entity(entity).insert(BloomSettings {
+                intensity: pp.bloom_intensity,
+                ..BloomSettings::OLD_SCHOOL
+            });
+        } else {
+            *tonemapping = Tonemapping::None;
+            commands.entity(entity).remove::<BloomSettings>();
+        }
+    }
+
+    for mut visibility in &mut vignettes {
+        *visibility = if pp.vignette {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
@@ -0,0 +1,269 @@
+use bevy::{prelude::*, sprite::collide_aabb::collide};
+use rand::{seq::SliceRandom, Rng};
+
+use crate::{
+    constants::*, entities, mutators::Mutators, rng::GameRng, settings::Settings, CollisionEvent,
+    PointScored, TickSet,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerUpKind {
+    // the paddle that last hit the ball gets bigger
+    GrowPaddle,
+    // the other paddle gets smaller
+    ShrinkOpponent,
+    // the paddle that last hit the ball moves faster
+    Boost,
+}
+
+impl PowerUpKind {
+    pub const ALL: [PowerUpKind; 3] = [
+        PowerUpKind::GrowPaddle,
+        PowerUpKind::ShrinkOpponent,
+        PowerUpKind::Boost,
+    ];
+
+    pub fn color(&self) -> Color {
+        match self {
+            PowerUpKind::GrowPaddle => GROW_PADDLE_COLOR,
+            PowerUpKind::ShrinkOpponent => SHRINK_OPPONENT_COLOR,
+            PowerUpKind::Boost => BOOST_COLOR,
+        }
+    }
+}
+
+// A pickup floating in the middle of the court, collected by running the ball through it
+#[derive(Component, Debug)]
+pub struct PowerUp(pub PowerUpKind);
+
+// Who touched the ball last. Power-ups are awarded to them.
+#[derive(Resource, Default, Debug)]
+pub struct LastHit(pub Option<entities::Side>);
+
+#[derive(Resource, Deref, DerefMut)]
+pub struct PowerUpSpawnTimer(Timer);
+
+impl Default for PowerUpSpawnTimer {
+    fn default() -> Self {
+        Self(Timer::from_seconds(
+            POWERUP_SPAWN_INTERVAL,
+            TimerMode::Repeating,
+        ))
+    }
+}
+
+// Tracks a paddle's height as a multiple of `PADDLE_SIZE.y`, smoothly animating between
+// sizes as effects start and expire.
+#[derive(Component, Debug)]
+pub struct PaddleSize {
+    from: f32,
+    to: f32,
+    tween: Timer,
+    // returns the paddle to its normal size when it runs out
+    effect: Timer,
+}
+
+fn finished_timer(duration: f32) -> Timer {
+    let mut timer = Timer::from_seconds(duration, TimerMode::Once);
+    timer.tick(timer.duration());
+    timer
+}
+
+impl Default for PaddleSize {
+    fn default() -> Self {
+        Self {
+            from: 1.0,
+            to: 1.0,
+            tween: finished_timer(PADDLE_RESIZE_DURATION),
+            effect: finished_timer(POWERUP_DURATION),
+        }
+    }
+}
+
+impl PaddleSize {
+    fn current(&self) -> f32 {
+        // smoothstep so the paddle eases in and out of its new size
+        let t = self.tween.percent();
+        let t = t * t * (3.0 - 2.0 * t);
+        self.from + (self.to - self.from) * t
+    }
+
+    // start animating towards `multiplier`, from wherever the paddle is right now
+    fn resize(&mut self, multiplier: f32) {
+        self.from = self.current();
+        self.to = multiplier;
+        self.tween.reset();
+    }
+
+    pub fn apply(&mut self, multiplier: f32, duration: f32) {
+        self.resize(multiplier);
+        self.effect = Timer::from_seconds(duration, TimerMode::Once);
+    }
+}
+
+// Raises a paddle's top speed while the timer runs
+#[derive(Component, Debug)]
+pub struct PaddleBoost(Timer);
+
+impl Default for PaddleBoost {
+    fn default() -> Self {
+        Self(finished_timer(POWERUP_DURATION))
+    }
+}
+
+impl PaddleBoost {
+    pub fn multiplier(&self) -> f32 {
+        if self.0.finished() {
+            1.0
+        } else {
+            BOOST_SPEED_MULTIPLIER
+        }
+    }
+}
+
+pub struct PowerUpPlugin;
+
+impl Plugin for PowerUpPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LastHit>()
+            .init_resource::<PowerUpSpawnTimer>()
+            .add_systems(
+                FixedUpdate,
+                (
+                    (track_last_hit, collect_power_ups).chain(),
+                    spawn_power_ups,
+                    animate_paddle_size,
+                    expire_paddle_boosts,
+                )
+                    .in_set(TickSet::Effects),
+            );
+    }
+}
+
+fn spawn_power_ups(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    mut timer: ResMut<PowerUpSpawnTimer>,
+    mut rng: ResMut<GameRng>,
+    power_ups: Query<Entity, With<PowerUp>>,
+    time: Res<Time>,
+) {
+    if !settings.gameplay.power_ups {
+        for power_up in &power_ups {
+            commands.entity(power_up).despawn();
+        }
+        return;
+    }
+
+    if !timer.tick(time.delta()).just_finished() || power_ups.iter().len() >= MAX_POWERUPS {
+        return;
+    }
+
+    let kind = *PowerUpKind::ALL.choose(&mut **rng).unwrap();
+    let x = rng.gen_range(-POWERUP_SPAWN_HALF_WIDTH..POWERUP_SPAWN_HALF_WIDTH);
+    let y = rng.gen_range(-POWERUP_SPAWN_HALF_HEIGHT..POWERUP_SPAWN_HALF_HEIGHT);
+
+    commands.spawn((
+        SpriteBundle {
+            transform: Transform {
+                translation: Vec3::new(x, y, 0.5),
+                scale: POWERUP_SIZE,
+                ..default()
+            },
+            sprite: Sprite {
+                color: kind.color(),
+                ..default()
+            },
+            ..default()
+        },
+        PowerUp(kind),
+    ));
+}
+
+fn track_last_hit(
+    mut collision_events: EventReader<CollisionEvent>,
+    mut points: EventReader<PointScored>,
+    mut last_hit: ResMut<LastHit>,
+) {
+    for ev in collision_events.read() {
+        // whoever on the side hit it, the power-up goes to its own paddle
+        if let CollisionEvent::Paddle(_, side, _) | CollisionEvent::Partner(_, side, _) = ev {
+            last_hit.0 = Some(*side);
+        }
+    }
+
+    // a new ball is served after every point and belongs to nobody
+    if points.read().next().is_some() {
+        last_hit.0 = None;
+    }
+}
+
+fn collect_power_ups(
+    mut commands: Commands,
+    last_hit: Res<LastHit>,
+    balls: Query<&Transform, With<entities::Ball>>,
+    power_ups: Query<(Entity, &Transform, &PowerUp)>,
+    mut paddles: Query<
+        (&mut PaddleSize, &mut PaddleBoost, &entities::Side),
+        With<entities::Paddle>,
+    >,
+) {
+    // nobody to give it to, let the ball pass through
+    let Some(owner) = &last_hit.0 else {
+        return;
+    };
+
+    for ball_transform in &balls {
+        for (entity, transform, power_up) in &power_ups {
+            if collide(
+                ball_transform.translation,
+                ball_transform.scale.truncate(),
+                transform.translation,
+                transform.scale.truncate(),
+            )
+            .is_none()
+            {
+                continue;
+            }
+
+            commands.entity(entity).despawn();
+
+            for (mut size, mut boost, side) in &mut paddles {
+                let is_owner = side == owner;
+                match (power_up.0, is_owner) {
+                    (PowerUpKind::GrowPaddle, true) => {
+                        size.apply(GROW_PADDLE_MULTIPLIER, POWERUP_DURATION)
+                    }
+                    (PowerUpKind::ShrinkOpponent, false) => {
+                        size.apply(SHRINK_OPPONENT_MULTIPLIER, POWERUP_DURATION)
+                    }
+                    (PowerUpKind::Boost, true) => {
+                        boost.0 = Timer::from_seconds(POWERUP_DURATION, TimerMode::Once)
+                    }
+                    _ => (),
+                }
+            }
+        }
+    }
+}
+
+fn animate_paddle_size(
+    mut paddles: Query<(&mut Transform, &mut PaddleSize)>,
+    mutators: Res<Mutators>,
+    time: Res<Time>,
+) {
+    for (mut transform, mut size) in &mut paddles {
+        if size.effect.tick(time.delta()).just_finished() {
+            size.resize(1.0);
+        }
+
+        size.tween.tick(time.delta());
+        transform.scale.y = PADDLE_SIZE.y * mutators.paddle_scale() * size.current();
+    }
+}
+
+fn expire_paddle_boosts(mut paddles: Query<&mut PaddleBoost>, time: Res<Time>) {
+    for mut boost in &mut paddles {
+        boost.0.tick(time.delta());
+    }
+}
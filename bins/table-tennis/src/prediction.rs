@@ -0,0 +1,238 @@
+// Where balls are going, from `bevy_common::trajectory`. The computer uses it to meet the
+// ball when it's set to anticipate, aim assist marks where the next ball will reach the
+// player, and F6 draws every ball's path to the paddle it's heading for. In practice the
+// ball coming at the player has its path dotted in, bending under gravity as it will.
+use bevy::prelude::*;
+use bevy_common::trajectory::{self, Arena, Trajectory};
+
+use crate::{constants::*, doubles::Partner, entities, mutators, rules, settings::Settings};
+
+// When to mark where the incoming ball will reach the player's paddle
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AimAssist {
+    #[default]
+    Off,
+    // only for balls slower than `AIM_ASSIST_MAX_SPEED`, so it helps with the basics without
+    // giving away fast rallies
+    SlowBalls,
+    Always,
+}
+
+impl AimAssist {
+    pub fn name(&self) -> &'static str {
+        match self {
+            AimAssist::Off => "Off",
+            AimAssist::SlowBalls => "Slow balls",
+            AimAssist::Always => "Always",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            AimAssist::Off => AimAssist::SlowBalls,
+            AimAssist::SlowBalls => AimAssist::Always,
+            AimAssist::Always => AimAssist::Off,
+        }
+    }
+
+    fn shows(&self, speed: f32) -> bool {
+        match self {
+            AimAssist::Off => false,
+            AimAssist::SlowBalls => speed < AIM_ASSIST_MAX_SPEED,
+            AimAssist::Always => true,
+        }
+    }
+}
+
+// Where the next ball will reach the player, see `AimAssist`
+#[derive(Component)]
+struct AimMarker;
+
+// Whether F6's trajectory lines are showing
+#[derive(Resource, Debug, Default)]
+pub struct ShowTrajectories(pub bool);
+
+pub struct PredictionPlugin;
+
+impl Plugin for PredictionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ShowTrajectories>()
+            .add_systems(Startup, spawn_aim_marker)
+            .add_systems(
+                Update,
+                (
+                    toggle_trajectories.run_if(crate::net::keyboard_is_local),
+                    draw_trajectories
+                        .run_if(|show: Res<ShowTrajectories>| show.0)
+                        .run_if(predictable),
+                    place_aim_marker,
+                    draw_practice_path
+                        .run_if(rules::practicing)
+                        .run_if(|rules: Res<rules::MatchRules>| rules.practice_path)
+                        // the floor ends the point there, it's no wall to bounce off
+                        .run_if(not(rules::variant_is(rules::Variant::Volleyball))),
+                )
+                    .chain(),
+            );
+    }
+}
+
+// A run condition for balls flying straight, which is all a prediction knows about
+pub fn predictable(mutators: Res<mutators::Mutators>, rules: Res<rules::MatchRules>) -> bool {
+    !mutators.contains(mutators::Mutator::Gravity) && rules.variant != rules::Variant::Volleyball
+}
+
+// The stretch of court a ball's center can cover between the walls
+fn arena() -> Arena {
+    let inset = WALL_THICKNESS / 2.0 + BALL_SIZE.y / 2.0;
+    Arena {
+        bottom: BOTTOM_WALL + inset,
+        top: TOP_WALL - inset,
+    }
+}
+
+// Where `ball` will be when it reaches the face of the paddle at `paddle`, defending `side`
+pub fn predict(
+    ball: &Transform,
+    velocity: Vec2,
+    paddle: &Transform,
+    side: entities::Side,
+) -> Trajectory {
+    let target_x = paddle.translation.x - side.sign() * (paddle.scale.x + ball.scale.x) / 2.0;
+    trajectory::predict_trajectory(
+        ball.translation.truncate(),
+        velocity,
+        arena(),
+        target_x,
+        TRAJECTORY_MAX_BOUNCES,
+    )
+}
+
+fn toggle_trajectories(keyboard_input: Res<Input<KeyCode>>, mut show: ResMut<ShowTrajectories>) {
+    if keyboard_input.just_pressed(KeyCode::F6) {
+        show.0 = !show.0;
+    }
+}
+
+fn draw_trajectories(
+    balls: Query<(&Transform, &entities::Velocity), With<entities::Ball>>,
+    // to the side's own paddle, not a partner in front of it
+    paddles: Query<(&Transform, &entities::Side), (With<entities::Paddle>, Without<Partner>)>,
+    mut gizmos: Gizmos,
+) {
+    for (ball, velocity) in &balls {
+        let Some((paddle, side)) = paddles
+            .iter()
+            .find(|(_, side)| velocity.x * side.sign() > 0.0)
+        else {
+            continue;
+        };
+
+        let trajectory = predict(ball, velocity.0, paddle, *side);
+        gizmos.linestrip_2d(
+            trajectory.points(ball.translation.truncate()),
+            TRAJECTORY_COLOR,
+        );
+        if let Some(intercept) = trajectory.intercept {
+            gizmos.circle_2d(intercept.position, BALL_SIZE.x / 2.0, TRAJECTORY_COLOR);
+        }
+    }
+}
+
+fn spawn_aim_marker(mut commands: Commands) {
+    commands.spawn((
+        SpriteBundle {
+            transform: Transform::from_xyz(0.0, 0.0, AIM_MARKER_Z).with_scale(AIM_MARKER_SIZE),
+            sprite: Sprite {
+                color: AIM_MARKER_COLOR,
+                ..default()
+            },
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        AimMarker,
+    ));
+}
+
+fn place_aim_marker(
+    settings: Res<Settings>,
+    mutators: Res<mutators::Mutators>,
+    rules: Res<rules::MatchRules>,
+    balls: Query<(&Transform, &entities::Velocity), With<entities::Ball>>,
+    paddles: Query<(&Transform, &entities::Side), (With<entities::Player>, With<entities::Paddle>)>,
+    mut markers: Query<
+        (&mut Transform, &mut Visibility),
+        (
+            With<AimMarker>,
+            Without<entities::Ball>,
+            Without<entities::Paddle>,
+        ),
+    >,
+) {
+    let assist = settings.gameplay.aim_assist;
+    let intercept = paddles.get_single().ok().and_then(|(paddle, side)| {
+        if !predictable(mutators, rules) {
+            return None;
+        }
+        // the first ball to arrive, of those it's on for
+        balls
+            .iter()
+            .filter(|(_, velocity)| {
+                velocity.x * side.sign() > 0.0 && assist.shows(velocity.length())
+            })
+            .filter_map(|(ball, velocity)| predict(ball, velocity.0, paddle, *side).intercept)
+            .min_by(|a, b| a.time.total_cmp(&b.time))
+    });
+
+    for (mut transform, mut visibility) in &mut markers {
+        match intercept {
+            Some(intercept) => {
+                transform.translation.x = intercept.position.x;
+                transform.translation.y = intercept.position.y;
+                *visibility = Visibility::Visible;
+            }
+            None => *visibility = Visibility::Hidden,
+        }
+    }
+}
+
+fn draw_practice_path(
+    mutators: Res<mutators::Mutators>,
+    balls: Query<(&Transform, &entities::Velocity), With<entities::Ball>>,
+    paddles: Query<(&Transform, &entities::Side), (With<entities::Player>, With<entities::Paddle>)>,
+    fixed: Res<Time<Fixed>>,
+    mut gizmos: Gizmos,
+) {
+    let Ok((paddle, side)) = paddles.get_single() else {
+        return;
+    };
+    // the nearest ball on its way
+    let Some((ball, velocity)) = balls
+        .iter()
+        .filter(|(_, velocity)| velocity.x * side.sign() > 0.0)
+        .min_by(|(a, _), (b, _)| {
+            let to = |ball: &Transform| (paddle.translation.x - ball.translation.x).abs();
+            to(a).total_cmp(&to(b))
+        })
+    else {
+        return;
+    };
+
+    let gravity = if mutators.contains(mutators::Mutator::Gravity) {
+        GRAVITY
+    } else {
+        0.0
+    };
+    let path = trajectory::simulate_path(
+        ball.translation.truncate(),
+        velocity.0,
+        gravity,
+        arena(),
+        paddle.translation.x - side.sign() * (paddle.scale.x + ball.scale.x) / 2.0,
+        fixed.timestep().as_secs_f32(),
+        PRACTICE_PATH_MAX_TIME,
+    );
+    for dash in path.chunks(PRACTICE_PATH_DASH + 1).step_by(2) {
+        gizmos.linestrip_2d(dash.iter().copied(), PRACTICE_PATH_COLOR);
+    }
+}
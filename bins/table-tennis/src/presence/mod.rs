@@ -0,0 +1,157 @@
+// Tells whatever the player has running (a chat client, a launcher) what they're up to, like
+// "In menu" or "Table tennis vs Computer, 7–5".
+//
+// Statuses are worked out from the match state and the scoreboard and handed to a
+// `PresenceBackend`, which is only told when the status changes. Nothing is reported by
+// default; building with `--features discord` reports to Discord when it's running and
+// `DISCORD_CLIENT_ID` is set.
+#[cfg(all(feature = "discord", unix))]
+mod discord;
+
+use bevy::prelude::*;
+
+use crate::{
+    entities::Competitor,
+    mutators::MatchState,
+    net::NetRole,
+    rules::{MatchRules, MatchWinner},
+    Scoreboard,
+};
+
+// Somewhere to report the player's status to
+pub trait PresenceBackend: Send + Sync {
+    fn report(&mut self, status: &str);
+}
+
+// Reports nowhere
+pub struct NoPresence;
+
+impl PresenceBackend for NoPresence {
+    fn report(&mut self, _status: &str) {}
+}
+
+#[derive(Resource)]
+pub struct Presence {
+    backend: Box<dyn PresenceBackend>,
+    // the last status reported, so an unchanged one isn't sent again every frame
+    status: Option<String>,
+}
+
+impl Presence {
+    pub fn new(backend: impl PresenceBackend + 'static) -> Self {
+        Self {
+            backend: Box::new(backend),
+            status: None,
+        }
+    }
+}
+
+impl Default for Presence {
+    fn default() -> Self {
+        #[cfg(all(feature = "discord", unix))]
+        if let Some(discord) = discord::DiscordPresence::from_env() {
+            return Self::new(discord);
+        }
+        Self::new(NoPresence)
+    }
+}
+
+pub struct PresencePlugin;
+
+impl Plugin for PresencePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Presence>().add_systems(
+            Update,
+            // a headless server has nobody to report for
+            report_presence.run_if(crate::net::keyboard_is_local),
+        );
+    }
+}
+
+// Who the player's up against, as it reads after "vs"
+fn opponent(rules: &MatchRules, role: &NetRole) -> &'static str {
+    match role {
+        NetRole::Client(_) => "Online player",
+        _ => rules.opponent.name(),
+    }
+}
+
+pub fn status(
+    state: MatchState,
+    rules: &MatchRules,
+    role: &NetRole,
+    scoreboard: &Scoreboard,
+    winner: Option<Competitor>,
+) -> String {
+    let score = format!(
+        "{}–{}",
+        scoreboard[Competitor::Player],
+        scoreboard[Competitor::Computer]
+    );
+    let game = rules.variant.name();
+    let opponent = opponent(rules, role);
+
+    match (state, role) {
+        (MatchState::PreMatch, _) => "In menu".to_string(),
+        (MatchState::GameOver, NetRole::Spectator(_)) => format!("Watched a {game} match"),
+        (_, NetRole::Spectator(_)) => format!("Watching {game}, {score}"),
+        (MatchState::Playing, _) => format!("{game} vs {opponent}, {score}"),
+        (MatchState::Intermission, _) => format!("{game} vs {opponent}, between games"),
+        (MatchState::GameOver, _) => {
+            let result = match winner {
+                Some(Competitor::Player) => "won",
+                Some(Competitor::Computer) => "lost",
+                None => "finished",
+            };
+            format!("{game} vs {opponent}, {result}")
+        }
+    }
+}
+
+fn report_presence(
+    mut presence: ResMut<Presence>,
+    state: Res<State<MatchState>>,
+    rules: Res<MatchRules>,
+    role: Res<NetRole>,
+    scoreboard: Res<Scoreboard>,
+    winner: Res<MatchWinner>,
+) {
+    let status = status(**state, &rules, &role, &scoreboard, winner.0);
+    if presence.status.as_ref() == Some(&status) {
+        return;
+    }
+    presence.backend.report(&status);
+    presence.status = Some(status);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn statuses_follow_the_match() {
+        let rules = MatchRules::default();
+        let mut scoreboard = Scoreboard::default();
+        scoreboard[Competitor::Player] = 7;
+        scoreboard[Competitor::Computer] = 5;
+
+        let status =
+            |state, role: &NetRole, winner| status(state, &rules, role, &scoreboard, winner);
+        assert_eq!(
+            status(MatchState::PreMatch, &NetRole::Local, None),
+            "In menu"
+        );
+        assert_eq!(
+            status(MatchState::Playing, &NetRole::Local, None),
+            "Table tennis vs Computer, 7–5"
+        );
+        assert_eq!(
+            status(
+                MatchState::GameOver,
+                &NetRole::Client("10.0.0.1:7373".to_string()),
+                Some(Competitor::Computer)
+            ),
+            "Table tennis vs Online player, lost"
+        );
+    }
+}
@@ -0,0 +1,97 @@
+use bevy::prelude::*;
+use bevy_common::storage;
+
+use crate::{
+    constants::*,
+    entities::Competitor,
+    mutators::MatchState,
+    rules::{MatchRules, MatchWinner, Opponent},
+};
+
+// The player's Elo rating, kept between runs. Only matches against the computer count, and
+// the computer always plays at `COMPUTER_RATING`.
+#[derive(Resource, Debug)]
+pub struct Rating {
+    pub rating: f32,
+    // how much the last match moved it, for the game over screen
+    pub change: Option<f32>,
+}
+
+impl Rating {
+    fn load() -> Self {
+        let rating = storage::load(GAME_NAME, RATING_FILE)
+            .and_then(|rating| rating.trim().parse().ok())
+            .unwrap_or(STARTING_RATING);
+        Self {
+            rating,
+            change: None,
+        }
+    }
+
+    fn save(&self) {
+        if let Err(err) = storage::save(GAME_NAME, RATING_FILE, &self.rating.to_string()) {
+            warn!("Couldn't save the rating: {err}");
+        }
+    }
+}
+
+// The share of a match `rating` is expected to take off `opponent`
+pub fn expected_score(rating: f32, opponent: f32) -> f32 {
+    1.0 / (1.0 + 10f32.powf((opponent - rating) / 400.0))
+}
+
+// `rating` after a match against `opponent` where it scored `score`: 1 for a win, 0 for a
+// loss
+pub fn rate(rating: f32, opponent: f32, score: f32) -> f32 {
+    rating + RATING_K * (score - expected_score(rating, opponent))
+}
+
+pub struct RatingPlugin;
+
+impl Plugin for RatingPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Rating::load()).add_systems(
+            OnEnter(MatchState::GameOver),
+            rate_match
+                .run_if(crate::net::has_input_authority)
+                .run_if(crate::net::keyboard_is_local)
+                .run_if(not(crate::kiosk::attracting)),
+        );
+    }
+}
+
+pub fn rate_match(rules: Res<MatchRules>, winner: Res<MatchWinner>, mut rating: ResMut<Rating>) {
+    rating.change = None;
+    // a ghost is only ever as good as the player was
+    if rules.opponent != Opponent::Computer || rules.practice {
+        return;
+    }
+    let Some(winner) = winner.0 else {
+        return;
+    };
+
+    let score = if winner == Competitor::Player {
+        1.0
+    } else {
+        0.0
+    };
+    let rated = rate(rating.rating, COMPUTER_RATING, score);
+    rating.change = Some(rated - rating.rating);
+    rating.rating = rated;
+    rating.save();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn upsets_move_ratings_further() {
+        assert_eq!(expected_score(1500.0, 1500.0), 0.5);
+        assert_eq!(rate(1500.0, 1500.0, 1.0), 1500.0 + RATING_K / 2.0);
+
+        let upset = rate(1000.0, 1400.0, 1.0) - 1000.0;
+        let expected = rate(1400.0, 1000.0, 1.0) - 1400.0;
+        assert!(upset > expected && expected > 0.0);
+    }
+}
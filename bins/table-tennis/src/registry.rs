@@ -0,0 +1,75 @@
+// Registers the game's components and resources with bevy's type registry, so anything that
+// goes through reflection (an inspector, scenes, snapshots) sees the whole match the same way.
+// Each type also derives serde's traits, and says so with `#[reflect(Serialize, Deserialize)]`,
+// so reflection serializes it the same as serde would on its own.
+use bevy::prelude::*;
+use bevy_common::motion::Velocity;
+
+use crate::{arena::Arena, entities, mutators, rules, Scoreboard};
+
+pub struct RegistryPlugin;
+
+impl Plugin for RegistryPlugin {
+    fn build(&self, app: &mut App) {
+        // what's on the court
+        app.register_type::<entities::Paddle>()
+            .register_type::<entities::Side>()
+            .register_type::<entities::Competitor>()
+            .register_type::<entities::PerCompetitor<usize>>()
+            .register_type::<entities::Player>()
+            .register_type::<entities::AI>()
+            .register_type::<entities::Ball>()
+            .register_type::<entities::Collider>()
+            .register_type::<entities::Obstacle>()
+            .register_type::<entities::StallTimer>()
+            .register_type::<entities::PaddleVelocity>()
+            .register_type::<entities::WallSide>()
+            .register_type::<entities::GoalZone>()
+            .register_type::<entities::ScoreboardText>()
+            .register_type::<entities::ServeSpot>()
+            .register_type::<Velocity>();
+
+        // how the match is going
+        app.register_type::<Scoreboard>()
+            .register_type::<Arena>()
+            .register_type::<mutators::Mutator>()
+            .register_type::<mutators::Mutators>()
+            .register_type::<rules::MatchMode>()
+            .register_type::<rules::Scoring>()
+            .register_type::<rules::Variant>()
+            .register_type::<rules::Opponent>()
+            .register_type::<rules::MatchRules>()
+            .register_type::<rules::Ends>()
+            .register_type::<rules::OpeningEnds>()
+            .register_type::<rules::Lives>()
+            .register_type::<rules::MatchClock>()
+            .register_type::<rules::Games>()
+            .register_type::<rules::MatchWinner>()
+            .register_type::<rules::Streak>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::reflect::serde::{ReflectSerializer, UntypedReflectDeserializer};
+    use serde::de::DeserializeSeed;
+
+    use super::*;
+    use crate::mutators::{Mutator, Mutators};
+
+    #[test]
+    fn registered_state_round_trips() {
+        let mut app = App::new();
+        app.add_plugins(RegistryPlugin);
+        let registry = app.world.resource::<AppTypeRegistry>().read();
+
+        let mutators: Mutators = [Mutator::Gravity, Mutator::Portals].into_iter().collect();
+        let serialized = ron::to_string(&ReflectSerializer::new(&mutators, &registry)).unwrap();
+        let mut deserializer = ron::Deserializer::from_str(&serialized).unwrap();
+        let value = UntypedReflectDeserializer::new(&registry)
+            .deserialize(&mut deserializer)
+            .unwrap();
+
+        assert_eq!(Mutators::from_reflect(&*value), Some(mutators));
+    }
+}
@@ -0,0 +1,115 @@
+use bevy::prelude::*;
+
+use crate::{
+    constants::*,
+    entities,
+    mutators::MatchState,
+    rules::{MatchRules, Opponent},
+    TickSet,
+};
+
+// One paddle's input for every simulation tick, as a direction between -1 (down) and 1 (up)
+#[derive(Debug, Clone, Default)]
+pub struct InputRecording {
+    ticks: Vec<f32>,
+}
+
+impl InputRecording {
+    pub fn push(&mut self, direction: f32) {
+        self.ticks.push(direction);
+    }
+
+    pub fn get(&self, tick: usize) -> Option<f32> {
+        self.ticks.get(tick).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.ticks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ticks.is_empty()
+    }
+}
+
+// The player's inputs so far this match
+#[derive(Resource, Debug, Default)]
+pub struct PlayerRecording(pub InputRecording);
+
+// The player's last finished match, played back by the ghost opponent
+#[derive(Resource, Debug, Default)]
+pub struct GhostPlayback {
+    recording: InputRecording,
+    tick: usize,
+}
+
+impl GhostPlayback {
+    pub fn is_empty(&self) -> bool {
+        self.recording.is_empty()
+    }
+
+    // the next tick's input, starting over once the recording runs out
+    pub fn next(&mut self) -> f32 {
+        let direction = self.recording.get(self.tick).unwrap_or(0.0);
+        self.tick = (self.tick + 1) % self.recording.len().max(1);
+        direction
+    }
+}
+
+// A run condition for when the opponent's paddle is replaying the player's ghost. Falls back to
+// the computer until there's a recording to play.
+pub fn ghost_active(rules: Res<MatchRules>, ghost: Res<GhostPlayback>) -> bool {
+    rules.opponent == Opponent::Ghost && !ghost.is_empty()
+}
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PlayerRecording>()
+            .init_resource::<GhostPlayback>()
+            .add_systems(OnExit(MatchState::PreMatch), start_recording)
+            .add_systems(OnExit(MatchState::GameOver), start_recording)
+            // a demo's stand-in player isn't anybody's ghost
+            .add_systems(
+                OnEnter(MatchState::GameOver),
+                keep_recording.run_if(not(crate::kiosk::attracting)),
+            )
+            .add_systems(FixedUpdate, record_player_input.in_set(TickSet::Input))
+            .add_systems(Update, show_ghost.after(crate::theme::apply_theme));
+    }
+}
+
+fn start_recording(mut recording: ResMut<PlayerRecording>, mut ghost: ResMut<GhostPlayback>) {
+    recording.0 = InputRecording::default();
+    ghost.tick = 0;
+}
+
+fn record_player_input(
+    actions: Res<crate::actions::ActionState>,
+    mut recording: ResMut<PlayerRecording>,
+) {
+    recording.0.push(actions.move_axis.y);
+}
+
+// the match that just finished becomes the next ghost
+fn keep_recording(mut recording: ResMut<PlayerRecording>, mut ghost: ResMut<GhostPlayback>) {
+    ghost.recording = std::mem::take(&mut recording.0);
+    ghost.tick = 0;
+}
+
+fn show_ghost(
+    rules: Res<MatchRules>,
+    ghost: Res<GhostPlayback>,
+    mut paddles: Query<&mut Sprite, (With<entities::AI>, With<entities::Paddle>)>,
+) {
+    let alpha = if ghost_active(rules, ghost) {
+        GHOST_ALPHA
+    } else {
+        1.0
+    };
+
+    for mut sprite in &mut paddles {
+        sprite.color.set_a(alpha);
+    }
+}
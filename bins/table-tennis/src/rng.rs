@@ -0,0 +1,43 @@
+use bevy::prelude::*;
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::constants::*;
+
+// All gameplay randomness comes from here so a match can be reproduced from its seed.
+// Set TABLE_TENNIS_SEED to replay a specific one.
+#[derive(Resource, Deref, DerefMut)]
+pub struct GameRng(StdRng);
+
+impl GameRng {
+    pub fn seeded(seed: u64) -> Self {
+        Self(StdRng::seed_from_u64(seed))
+    }
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        let seed = env_seed().unwrap_or_else(rand::random);
+
+        info!("RNG seed: {seed}");
+        Self::seeded(seed)
+    }
+}
+
+fn env_seed() -> Option<u64> {
+    std::env::var("TABLE_TENNIS_SEED")
+        .ok()
+        .and_then(|seed| seed.parse().ok())
+}
+
+// How sounds vary, see `audio_bus.rs`. Its own stream from the same seed, so what's heard
+// doesn't change how a seeded match plays.
+#[derive(Resource, Deref, DerefMut)]
+pub struct SoundRng(StdRng);
+
+impl Default for SoundRng {
+    fn default() -> Self {
+        Self(StdRng::seed_from_u64(
+            env_seed().unwrap_or_else(rand::random) ^ SOUND_RNG_STREAM,
+        ))
+    }
+}
@@ -0,0 +1,1061 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    arena::Arena,
+    constants::*,
+    doubles,
+    entities::{Competitor, PerCompetitor, Side},
+    mutators::MatchState,
+    net::NetRole,
+    personality,
+    settings::{on_off, Settings},
+    GoalEvent, PointScored, Scoreboard, TickSet,
+};
+
+// How a game is won. A match is one or more games, see `MatchRules::games`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Reflect, Serialize, Deserialize)]
+#[reflect(Serialize, Deserialize)]
+pub enum MatchMode {
+    // first to `MatchRules::target_score`, or play on forever without one
+    #[default]
+    Points,
+    // everyone has a few lives and loses one for every goal conceded, last one standing wins
+    Lives,
+    // the highest score when the clock runs out wins, a tie goes to golden goal
+    Timed,
+}
+
+impl MatchMode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            MatchMode::Points => "Points",
+            MatchMode::Lives => "Lives",
+            MatchMode::Timed => "Timed",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            MatchMode::Points => MatchMode::Lives,
+            MatchMode::Lives => MatchMode::Timed,
+            MatchMode::Timed => MatchMode::Points,
+        }
+    }
+}
+
+// What a point is worth
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Reflect, Serialize, Deserialize)]
+#[reflect(Serialize, Deserialize)]
+pub enum Scoring {
+    // every point is worth one
+    #[default]
+    Standard,
+    // each point in a row without conceding is worth one more than the last
+    Streak,
+}
+
+impl Scoring {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Scoring::Standard => "Standard",
+            Scoring::Streak => "Streak",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            Scoring::Standard => Scoring::Streak,
+            Scoring::Streak => Scoring::Standard,
+        }
+    }
+}
+
+// Which game is played on the court
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Reflect, Serialize, Deserialize)]
+#[reflect(Serialize, Deserialize)]
+pub enum Variant {
+    #[default]
+    TableTennis,
+    // paddles move around their own half, the puck slides to a stop and the goals are slots
+    // in the end walls, see `air_hockey.rs`
+    AirHockey,
+    // seen from the side: the paddles stand on the floor either side of a net, and the ball
+    // falls, see `volleyball.rs`
+    Volleyball,
+}
+
+impl Variant {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Variant::TableTennis => "Table tennis",
+            Variant::AirHockey => "Air hockey",
+            Variant::Volleyball => "Volleyball",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            Variant::TableTennis => Variant::AirHockey,
+            Variant::AirHockey => Variant::Volleyball,
+            Variant::Volleyball => Variant::TableTennis,
+        }
+    }
+}
+
+// Who drives the other paddle
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Reflect, Serialize, Deserialize)]
+#[reflect(Serialize, Deserialize)]
+pub enum Opponent {
+    #[default]
+    Computer,
+    // the player's own inputs from their last match, see `replay.rs`
+    Ghost,
+    // somebody else here, on a device of their own, see `devices.rs`
+    Local,
+}
+
+impl Opponent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Opponent::Computer => "Computer",
+            Opponent::Ghost => "Your ghost",
+            Opponent::Local => "Second player",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            Opponent::Computer => Opponent::Ghost,
+            Opponent::Ghost => Opponent::Local,
+            Opponent::Local => Opponent::Computer,
+        }
+    }
+}
+
+// How a match is played, picked on the pre-match screen
+#[derive(Resource, Debug, Clone, Reflect, Serialize, Deserialize)]
+#[reflect(Resource, Serialize, Deserialize)]
+pub struct MatchRules {
+    pub variant: Variant,
+    // the court it's played on, see `arena.rs`
+    pub arena: Arena,
+    pub opponent: Opponent,
+    // which of `personality::all()` the computer plays as
+    pub personality: usize,
+    // each side gets a second paddle the computer drives, see `doubles.rs`
+    pub doubles: bool,
+    pub mode: MatchMode,
+    pub scoring: Scoring,
+    // how many lives each competitor starts with in `MatchMode::Lives`
+    pub lives: usize,
+    // how long a `MatchMode::Timed` game lasts, in seconds
+    pub length: f32,
+    // the score that wins a `MatchMode::Points` game, if any
+    pub target_score: Option<usize>,
+    // the match is best of this many games, always odd
+    pub games: usize,
+    // competitors change ends between games
+    pub swap_ends: bool,
+    // nothing's at stake: it isn't rated, unlocks nothing and savestates work, see
+    // `savestate.rs`
+    pub practice: bool,
+    // in practice, the incoming ball's path is drawn as it curves, see `prediction.rs`
+    pub practice_path: bool,
+}
+
+impl MatchRules {
+    // games needed to take the match
+    pub fn games_to_win(&self) -> usize {
+        self.games / 2 + 1
+    }
+}
+
+impl Default for MatchRules {
+    fn default() -> Self {
+        Self {
+            variant: Variant::default(),
+            arena: Arena::default(),
+            opponent: Opponent::default(),
+            personality: 0,
+            doubles: false,
+            mode: MatchMode::default(),
+            scoring: Scoring::default(),
+            lives: DEFAULT_LIVES,
+            length: DEFAULT_MATCH_LENGTH,
+            target_score: None,
+            games: 1,
+            swap_ends: true,
+            practice: false,
+            practice_path: true,
+        }
+    }
+}
+
+// minutes:seconds, rounding up so the clock reads 0:00 exactly when it runs out
+pub fn format_clock(seconds: f32) -> String {
+    let seconds = seconds.ceil() as u32;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+// Every row of rules on the pre-match screen, in display order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleItem {
+    Variant,
+    Arena,
+    Opponent,
+    Personality,
+    Doubles,
+    Mode,
+    Scoring,
+    TargetScore,
+    Lives,
+    Length,
+    Games,
+    SwapEnds,
+    Practice,
+    PracticePath,
+}
+
+impl RuleItem {
+    pub const ALL: [RuleItem; 14] = [
+        RuleItem::Variant,
+        RuleItem::Arena,
+        RuleItem::Opponent,
+        RuleItem::Personality,
+        RuleItem::Doubles,
+        RuleItem::Mode,
+        RuleItem::Scoring,
+        RuleItem::TargetScore,
+        RuleItem::Lives,
+        RuleItem::Length,
+        RuleItem::Games,
+        RuleItem::SwapEnds,
+        RuleItem::Practice,
+        RuleItem::PracticePath,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            RuleItem::Variant => "Game",
+            RuleItem::Arena => "Court",
+            RuleItem::Opponent => "Opponent",
+            RuleItem::Personality => "Computer plays as",
+            RuleItem::Doubles => "Doubles",
+            RuleItem::Mode => "Mode",
+            RuleItem::Scoring => "Scoring",
+            RuleItem::TargetScore => "Points to win",
+            RuleItem::Lives => "Lives",
+            RuleItem::Length => "Game length",
+            RuleItem::Games => "Match",
+            RuleItem::SwapEnds => "Swap ends between games",
+            RuleItem::Practice => "Practice",
+            RuleItem::PracticePath => "Show the ball's path in practice",
+        }
+    }
+
+    pub fn value(&self, rules: &MatchRules) -> String {
+        match self {
+            RuleItem::Variant => rules.variant.name().to_string(),
+            RuleItem::Arena => rules.arena.name().to_string(),
+            RuleItem::Opponent => rules.opponent.name().to_string(),
+            RuleItem::Personality => personality::chosen(rules).name.clone(),
+            RuleItem::Doubles => on_off(rules.doubles).to_string(),
+            RuleItem::Mode => rules.mode.name().to_string(),
+            RuleItem::Scoring => rules.scoring.name().to_string(),
+            RuleItem::Lives => rules.lives.to_string(),
+            RuleItem::Length => format_clock(rules.length),
+            RuleItem::TargetScore => match rules.target_score {
+                Some(target) => target.to_string(),
+                None => "Endless".to_string(),
+            },
+            RuleItem::Games => match rules.games {
+                1 => "Single game".to_string(),
+                games => format!("Best of {games}"),
+            },
+            RuleItem::SwapEnds => on_off(rules.swap_ends).to_string(),
+            RuleItem::Practice => on_off(rules.practice).to_string(),
+            RuleItem::PracticePath => on_off(rules.practice_path).to_string(),
+        }
+    }
+
+    // whether it's on the pre-match screen, not every rule applies to every match
+    pub fn shown(&self, rules: &MatchRules, role: &NetRole) -> bool {
+        match self {
+            RuleItem::Doubles => doubles::available(rules, role),
+            _ => true,
+        }
+    }
+
+    // step is -1 or 1 depending on which way the player pressed
+    pub fn adjust(&self, rules: &mut MatchRules, step: f32) {
+        match self {
+            RuleItem::Variant => rules.variant = rules.variant.next(),
+            RuleItem::Arena => rules.arena = rules.arena.next(),
+            RuleItem::Opponent => rules.opponent = rules.opponent.next(),
+            RuleItem::Personality => {
+                let count = personality::all().len() as isize;
+                rules.personality =
+                    (rules.personality as isize + step as isize).rem_euclid(count) as usize;
+            }
+            RuleItem::Doubles => rules.doubles = !rules.doubles,
+            RuleItem::Mode => rules.mode = rules.mode.next(),
+            RuleItem::Scoring => rules.scoring = rules.scoring.next(),
+            RuleItem::Lives => {
+                rules.lives = (rules.lives as f32 + step).clamp(1.0, MAX_LIVES as f32) as usize
+            }
+            RuleItem::Length => {
+                rules.length = (rules.length + step * MATCH_LENGTH_STEP)
+                    .clamp(MATCH_LENGTH_STEP, MAX_MATCH_LENGTH)
+            }
+            // endless sits below the smallest target
+            RuleItem::TargetScore => {
+                let target = rules.target_score.unwrap_or(0) as f32 + step;
+                rules.target_score =
+                    (target >= 1.0).then_some(target.min(MAX_TARGET_SCORE as f32) as usize);
+            }
+            RuleItem::Games => {
+                rules.games =
+                    (rules.games as f32 + step * 2.0).clamp(1.0, MAX_GAMES as f32) as usize
+            }
+            RuleItem::SwapEnds => rules.swap_ends = !rules.swap_ends,
+            RuleItem::Practice => rules.practice = !rules.practice,
+            RuleItem::PracticePath => rules.practice_path = !rules.practice_path,
+        }
+    }
+}
+
+// Which end of the court each competitor is playing from this game
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Reflect, Serialize, Deserialize)]
+#[reflect(Resource, Serialize, Deserialize)]
+pub struct Ends {
+    player: Side,
+}
+
+// The ends each match starts from. "Swap sides and rematch" flips them until the next new match.
+#[derive(Resource, Debug, Default, Deref, DerefMut, Reflect, Serialize, Deserialize)]
+#[reflect(Resource, Serialize, Deserialize)]
+pub struct OpeningEnds(pub Ends);
+
+impl Default for Ends {
+    fn default() -> Self {
+        Self {
+            player: PLAYER_SIDE,
+        }
+    }
+}
+
+impl Ends {
+    pub fn new(player: Side) -> Self {
+        Self { player }
+    }
+
+    pub fn side_of(&self, competitor: Competitor) -> Side {
+        match competitor {
+            Competitor::Player => self.player,
+            Competitor::Computer => self.player.opponent(),
+        }
+    }
+
+    // who is playing from `side`
+    pub fn at(&self, side: Side) -> Competitor {
+        if side == self.player {
+            Competitor::Player
+        } else {
+            Competitor::Computer
+        }
+    }
+
+    pub fn swap(&mut self) {
+        self.player = self.player.opponent();
+    }
+}
+
+// How many lives each competitor has left in `MatchMode::Lives`
+#[derive(Resource, Debug, Default, Deref, DerefMut, Reflect, Serialize, Deserialize)]
+#[reflect(Resource, Serialize, Deserialize)]
+pub struct Lives(PerCompetitor<usize>);
+
+// Counts down a `MatchMode::Timed` match, and how long any match has been played for
+#[derive(Resource, Debug, Reflect, Serialize, Deserialize)]
+#[reflect(Resource, Serialize, Deserialize)]
+pub struct MatchClock {
+    timer: Timer,
+    // the clock ran out on a tie, the next point wins
+    overtime: bool,
+    // seconds in play so far, across every game, not counting pauses or intermissions
+    pub elapsed: f32,
+}
+
+impl Default for MatchClock {
+    fn default() -> Self {
+        Self::new(DEFAULT_MATCH_LENGTH)
+    }
+}
+
+impl MatchClock {
+    fn new(length: f32) -> Self {
+        Self {
+            timer: Timer::from_seconds(length, TimerMode::Once),
+            overtime: false,
+            elapsed: 0.0,
+        }
+    }
+}
+
+// A game has been decided
+#[derive(Debug, Event)]
+pub struct GameWon {
+    pub winner: Competitor,
+}
+
+// Games won by each competitor so far this match
+#[derive(Resource, Debug, Default, Reflect, Serialize, Deserialize)]
+#[reflect(Resource, Serialize, Deserialize)]
+pub struct Games {
+    pub won: PerCompetitor<usize>,
+}
+
+impl Games {
+    pub fn played(&self) -> usize {
+        self.won.player + self.won.computer
+    }
+}
+
+// Who won the last match, for the game over screen
+#[derive(Resource, Debug, Default, Reflect, Serialize, Deserialize)]
+#[reflect(Resource, Serialize, Deserialize)]
+pub struct MatchWinner(pub Option<Competitor>);
+
+// Who has won the most recent points in a row, and how many
+#[derive(Resource, Debug, Default, Reflect, Serialize, Deserialize)]
+#[reflect(Resource, Serialize, Deserialize)]
+pub struct Streak {
+    competitor: Option<Competitor>,
+    count: usize,
+}
+
+impl Streak {
+    // returns the length of `scorer`'s streak including this point
+    fn record(&mut self, scorer: Competitor) -> usize {
+        if self.competitor == Some(scorer) {
+            self.count += 1;
+        } else {
+            self.competitor = Some(scorer);
+            self.count = 1;
+        }
+        self.count
+    }
+
+    pub fn of(&self, competitor: Competitor) -> usize {
+        if self.competitor == Some(competitor) {
+            self.count
+        } else {
+            0
+        }
+    }
+}
+
+#[derive(Component, Debug)]
+struct StreakText;
+
+#[derive(Component, Debug)]
+struct MatchClockText;
+
+#[derive(Component, Debug)]
+struct MatchDurationText;
+
+// One of a side's lives under its scoreboard, the `index`th from the left
+#[derive(Component, Debug)]
+struct LifeIcon {
+    index: usize,
+}
+
+pub struct RulesPlugin;
+
+impl Plugin for RulesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MatchRules>()
+            .init_resource::<Streak>()
+            .init_resource::<Lives>()
+            .init_resource::<MatchWinner>()
+            .init_resource::<MatchClock>()
+            .init_resource::<Games>()
+            .init_resource::<Ends>()
+            .init_resource::<OpeningEnds>()
+            .add_event::<GameWon>()
+            .add_systems(
+                Startup,
+                (
+                    spawn_streak_indicators,
+                    spawn_life_icons,
+                    spawn_match_clock,
+                    spawn_match_duration,
+                ),
+            )
+            // the settings may have moved the player to the other end since
+            .add_systems(
+                OnExit(MatchState::PreMatch),
+                (reset_opening_ends, reset_match).chain(),
+            )
+            // a rematch goes straight back to playing
+            .add_systems(OnExit(MatchState::GameOver), reset_match)
+            .add_systems(OnEnter(MatchState::Playing), reset_game)
+            .add_systems(OnEnter(MatchState::Intermission), swap_ends)
+            .add_systems(
+                FixedUpdate,
+                (
+                    lose_lives
+                        .after(award_points)
+                        .in_set(TickSet::Scoring)
+                        .run_if(mode_is(MatchMode::Lives)),
+                    check_target_score
+                        .after(crate::tally_score)
+                        .in_set(TickSet::Scoring)
+                        .run_if(mode_is(MatchMode::Points)),
+                    run_match_clock
+                        .after(crate::tally_score)
+                        .in_set(TickSet::Scoring)
+                        .run_if(mode_is(MatchMode::Timed)),
+                    (count_match_time, end_game).in_set(TickSet::Effects),
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    update_streak_indicators,
+                    update_life_icons,
+                    update_match_clock_text,
+                    update_match_duration_text,
+                ),
+            );
+    }
+}
+
+// The scoring rules: turns balls going out into points for the other side
+pub fn award_points(
+    mut goal_events: EventReader<GoalEvent>,
+    mut points: EventWriter<PointScored>,
+    rules: Res<MatchRules>,
+    mut streak: ResMut<Streak>,
+    ends: Res<Ends>,
+) {
+    for ev in goal_events.read() {
+        let scorer = ends.at(ev.defender).opponent();
+        let streak = streak.record(scorer);
+
+        points.send(PointScored {
+            scorer,
+            points: match rules.scoring {
+                Scoring::Standard => 1,
+                Scoring::Streak => streak,
+            },
+            ball: ev.ball,
+        });
+    }
+}
+
+// A run condition for systems that only apply in one mode
+pub fn mode_is(mode: MatchMode) -> impl FnMut(Res<MatchRules>) -> bool + Clone {
+    move |rules: Res<MatchRules>| rules.mode == mode
+}
+
+// A run condition for systems that only apply in practice matches
+pub fn practicing(rules: Res<MatchRules>) -> bool {
+    rules.practice
+}
+
+// A run condition for systems that only apply to one variant
+pub fn variant_is(variant: Variant) -> impl FnMut(Res<MatchRules>) -> bool + Clone {
+    move |rules: Res<MatchRules>| rules.variant == variant
+}
+
+fn reset_opening_ends(settings: Res<Settings>, mut opening: ResMut<OpeningEnds>) {
+    opening.0 = Ends::new(settings.gameplay.player_side);
+}
+
+fn reset_match(
+    mut games: ResMut<Games>,
+    mut winner: ResMut<MatchWinner>,
+    opening: Res<OpeningEnds>,
+    mut ends: ResMut<Ends>,
+    mut clock: ResMut<MatchClock>,
+) {
+    *games = Games::default();
+    clock.elapsed = 0.0;
+    winner.0 = None;
+    *ends = opening.0;
+}
+
+fn swap_ends(rules: Res<MatchRules>, mut ends: ResMut<Ends>) {
+    if rules.swap_ends {
+        ends.swap();
+    }
+}
+
+fn reset_game(
+    rules: Res<MatchRules>,
+    mut streak: ResMut<Streak>,
+    mut lives: ResMut<Lives>,
+    mut clock: ResMut<MatchClock>,
+) {
+    *streak = Streak::default();
+    *lives = Lives(PerCompetitor::splat(rules.lives));
+    // the match's time carries on from the last game
+    *clock = MatchClock {
+        elapsed: clock.elapsed,
+        ..MatchClock::new(rules.length)
+    };
+}
+
+fn check_target_score(
+    mut points: EventReader<PointScored>,
+    rules: Res<MatchRules>,
+    scores: Res<Scoreboard>,
+    mut game_won: EventWriter<GameWon>,
+) {
+    let Some(target) = rules.target_score else {
+        points.clear();
+        return;
+    };
+
+    if let Some(point) = points.read().find(|point| scores[point.scorer] >= target) {
+        game_won.send(GameWon {
+            winner: point.scorer,
+        });
+    }
+}
+
+fn lose_lives(
+    mut points: EventReader<PointScored>,
+    mut lives: ResMut<Lives>,
+    mut game_won: EventWriter<GameWon>,
+) {
+    for point in points.read() {
+        let loser = point.scorer.opponent();
+        if lives[loser] == 0 {
+            // already out, a second ball from the same serve
+            continue;
+        }
+
+        lives[loser] -= 1;
+        if lives[loser] == 0 {
+            game_won.send(GameWon {
+                winner: point.scorer,
+            });
+        }
+    }
+}
+
+fn run_match_clock(
+    mut points: EventReader<PointScored>,
+    scores: Res<Scoreboard>,
+    mut clock: ResMut<MatchClock>,
+    mut game_won: EventWriter<GameWon>,
+    time: Res<Time>,
+) {
+    // golden goal
+    if clock.overtime {
+        if let Some(point) = points.read().next() {
+            game_won.send(GameWon {
+                winner: point.scorer,
+            });
+        }
+        return;
+    }
+    points.clear();
+
+    if !clock.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let (player, computer) = (scores[Competitor::Player], scores[Competitor::Computer]);
+    if player == computer {
+        clock.overtime = true;
+    } else {
+        let winner = if player > computer {
+            Competitor::Player
+        } else {
+            Competitor::Computer
+        };
+        game_won.send(GameWon { winner });
+    }
+}
+
+fn count_match_time(mut clock: ResMut<MatchClock>, time: Res<Time>) {
+    clock.elapsed += time.delta_seconds();
+}
+
+fn end_game(
+    mut game_won: EventReader<GameWon>,
+    rules: Res<MatchRules>,
+    mut games: ResMut<Games>,
+    mut winner: ResMut<MatchWinner>,
+    mut next_state: ResMut<NextState<MatchState>>,
+) {
+    // in multi-ball both sides can be knocked out on the same tick, first one counts
+    if let Some(ev) = game_won.read().next() {
+        games.won[ev.winner] += 1;
+
+        if games.won[ev.winner] >= rules.games_to_win() {
+            winner.0 = Some(ev.winner);
+            next_state.set(MatchState::GameOver);
+        } else {
+            next_state.set(MatchState::Intermission);
+        }
+    }
+    game_won.clear();
+}
+
+fn spawn_streak_indicators(mut commands: Commands) {
+    for side in Side::ALL {
+        commands.spawn((
+            Text2dBundle {
+                text: Text::from_sections([TextSection::from_style(TextStyle {
+                    font_size: STREAK_FONT_SIZE,
+                    color: STREAK_COLOR,
+                    ..default()
+                })]),
+                // under the scoreboard and lives
+                transform: Transform::from_translation(Vec3::new(
+                    side.inset_x(SCOREBOARD_PADDING_X),
+                    TOP_WALL - SCOREBOARD_PADDING_Y - SCOREBOARD_FONT_SIZE * 1.5,
+                    1.0,
+                )),
+                ..default()
+            },
+            StreakText,
+            side,
+        ));
+    }
+}
+
+fn update_streak_indicators(
+    rules: Res<MatchRules>,
+    streak: Res<Streak>,
+    ends: Res<Ends>,
+    mut indicators: Query<(&mut Text, &Side), With<StreakText>>,
+) {
+    if !rules.is_changed() && !streak.is_changed() && !ends.is_changed() {
+        return;
+    }
+
+    for (mut text, side) in &mut indicators {
+        let count = streak.of(ends.at(*side));
+        text.sections[0].value = if rules.scoring == Scoring::Streak && count > 0 {
+            // the multiplier the next point will be worth
+            format!("x{}", count + 1)
+        } else {
+            String::new()
+        };
+    }
+}
+
+fn spawn_life_icons(mut commands: Commands) {
+    for side in Side::ALL {
+        for index in 0..MAX_LIVES {
+            let offset = index as f32 - (MAX_LIVES - 1) as f32 / 2.0;
+            commands.spawn((
+                SpriteBundle {
+                    transform: Transform {
+                        // just under the scoreboard
+                        translation: Vec3::new(
+                            side.inset_x(SCOREBOARD_PADDING_X) + offset * LIFE_ICON_SPACING,
+                            TOP_WALL - SCOREBOARD_PADDING_Y - SCOREBOARD_FONT_SIZE * 0.75,
+                            1.0,
+                        ),
+                        scale: LIFE_ICON_SIZE,
+                        ..default()
+                    },
+                    sprite: Sprite {
+                        color: LIFE_ICON_COLOR,
+                        ..default()
+                    },
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                LifeIcon { index },
+                side,
+            ));
+        }
+    }
+}
+
+fn update_life_icons(
+    rules: Res<MatchRules>,
+    lives: Res<Lives>,
+    ends: Res<Ends>,
+    mut icons: Query<(&mut Visibility, &LifeIcon, &Side)>,
+) {
+    if !rules.is_changed() && !lives.is_changed() && !ends.is_changed() {
+        return;
+    }
+
+    for (mut visibility, icon, side) in &mut icons {
+        *visibility = if rules.mode == MatchMode::Lives && icon.index < lives[ends.at(*side)] {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+fn spawn_match_clock(mut commands: Commands) {
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_sections([TextSection::from_style(TextStyle {
+                font_size: MATCH_CLOCK_FONT_SIZE,
+                color: MATCH_CLOCK_COLOR,
+                ..default()
+            })]),
+            // centered between the scoreboards
+            transform: Transform::from_translation(Vec3::new(
+                0.0,
+                TOP_WALL - SCOREBOARD_PADDING_Y,
+                1.0,
+            )),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        MatchClockText,
+    ));
+}
+
+fn update_match_clock_text(
+    rules: Res<MatchRules>,
+    clock: Res<MatchClock>,
+    mut text: Query<(&mut Text, &mut Visibility), With<MatchClockText>>,
+) {
+    if !rules.is_changed() && !clock.is_changed() {
+        return;
+    }
+
+    for (mut text, mut visibility) in &mut text {
+        *visibility = if rules.mode == MatchMode::Timed {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+
+        text.sections[0].value = if clock.overtime {
+            "Golden goal".to_string()
+        } else {
+            format_clock(clock.timer.remaining_secs())
+        };
+    }
+}
+
+fn spawn_match_duration(mut commands: Commands) {
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_sections([TextSection::from_style(TextStyle {
+                font_size: MATCH_DURATION_FONT_SIZE,
+                color: MATCH_DURATION_COLOR,
+                ..default()
+            })]),
+            transform: Transform::from_translation(Vec3::new(
+                0.0,
+                BOTTOM_WALL + MATCH_DURATION_FONT_SIZE,
+                1.0,
+            )),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        MatchDurationText,
+    ));
+}
+
+// A timed match already shows its clock counting down
+fn update_match_duration_text(
+    rules: Res<MatchRules>,
+    clock: Res<MatchClock>,
+    state: Res<State<MatchState>>,
+    mut text: Query<(&mut Text, &mut Visibility), With<MatchDurationText>>,
+) {
+    if !rules.is_changed() && !clock.is_changed() && !state.is_changed() {
+        return;
+    }
+
+    for (mut text, mut visibility) in &mut text {
+        *visibility = if rules.mode != MatchMode::Timed && *state.get() == MatchState::Playing {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+        text.sections[0].value = format_clock(clock.elapsed.floor());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::Contact;
+
+    // each point's scorer and worth from one tick of goals against `defenders`
+    fn score(app: &mut App, defenders: &[Side]) -> Vec<(Competitor, usize)> {
+        for defender in defenders {
+            app.world.send_event(GoalEvent {
+                ball: Entity::PLACEHOLDER,
+                defender: *defender,
+                crossing: Contact(0.0),
+            });
+        }
+        app.update();
+        app.world
+            .resource::<Events<PointScored>>()
+            .iter_current_update_events()
+            .map(|point| (point.scorer, point.points))
+            .collect()
+    }
+
+    // who won a game in one tick where `scorers` won points
+    fn play(app: &mut App, scorers: &[Competitor]) -> Vec<Competitor> {
+        for scorer in scorers {
+            app.world.send_event(PointScored {
+                scorer: *scorer,
+                points: 1,
+                ball: Entity::PLACEHOLDER,
+            });
+        }
+        app.update();
+        app.world
+            .resource::<Events<GameWon>>()
+            .iter_current_update_events()
+            .map(|won| won.winner)
+            .collect()
+    }
+
+    #[test]
+    fn streaks_are_worth_more_each_point() {
+        let mut app = App::new();
+        app.add_event::<GoalEvent>()
+            .add_event::<PointScored>()
+            .insert_resource(MatchRules {
+                scoring: Scoring::Streak,
+                ..default()
+            })
+            .init_resource::<Streak>()
+            .insert_resource(Ends::new(Side::Left))
+            .add_systems(Update, award_points);
+
+        let (player, computer) = (Competitor::Player, Competitor::Computer);
+        assert_eq!(score(&mut app, &[Side::Right]), [(player, 1)]);
+        assert_eq!(score(&mut app, &[Side::Right]), [(player, 2)]);
+        // two balls in on the same tick carry the streak on between them
+        assert_eq!(
+            score(&mut app, &[Side::Right, Side::Right]),
+            [(player, 3), (player, 4)]
+        );
+        // conceding starts it over
+        assert_eq!(score(&mut app, &[Side::Left]), [(computer, 1)]);
+        assert_eq!(score(&mut app, &[Side::Right]), [(player, 1)]);
+
+        // and standard scoring keeps every point at one
+        app.world.resource_mut::<MatchRules>().scoring = Scoring::Standard;
+        assert_eq!(score(&mut app, &[Side::Right]), [(player, 1)]);
+    }
+
+    #[test]
+    fn last_one_with_lives_wins() {
+        let mut app = App::new();
+        app.add_event::<PointScored>()
+            .add_event::<GameWon>()
+            .insert_resource(Lives(PerCompetitor::splat(2)))
+            .add_systems(Update, lose_lives);
+
+        let (player, computer) = (Competitor::Player, Competitor::Computer);
+        assert!(play(&mut app, &[player]).is_empty());
+        assert_eq!(app.world.resource::<Lives>()[computer], 1);
+        // knocked out by the first of two balls, the second doesn't win it twice
+        assert_eq!(play(&mut app, &[player, player]), [player]);
+        assert_eq!(
+            **app.world.resource::<Lives>(),
+            PerCompetitor {
+                player: 2,
+                computer: 0
+            }
+        );
+    }
+
+    #[test]
+    fn a_tie_at_time_goes_to_golden_goal() {
+        let mut app = App::new();
+        app.add_event::<PointScored>()
+            .add_event::<GameWon>()
+            .init_resource::<Time>()
+            .insert_resource(Scoreboard(PerCompetitor::splat(1)))
+            .insert_resource(MatchClock::new(1.0))
+            .add_systems(Update, run_match_clock);
+        let tick = |app: &mut App, scorers: &[Competitor]| {
+            app.world
+                .resource_mut::<Time>()
+                .advance_by(Duration::from_secs_f32(0.5));
+            play(app, scorers)
+        };
+
+        let (player, computer) = (Competitor::Player, Competitor::Computer);
+        // points before time's up don't end it
+        assert!(tick(&mut app, &[player]).is_empty());
+        // nor does the clock running out on a tie
+        assert!(tick(&mut app, &[]).is_empty());
+        assert!(app.world.resource::<MatchClock>().overtime);
+        // the next point does, whenever it comes
+        assert!(tick(&mut app, &[]).is_empty());
+        assert_eq!(tick(&mut app, &[computer]), [computer]);
+    }
+
+    #[test]
+    fn the_higher_score_at_time_wins() {
+        let mut app = App::new();
+        app.add_event::<PointScored>()
+            .add_event::<GameWon>()
+            .init_resource::<Time>()
+            .insert_resource(Scoreboard(PerCompetitor {
+                player: 3,
+                computer: 2,
+            }))
+            .insert_resource(MatchClock::new(1.0))
+            .add_systems(Update, run_match_clock);
+
+        app.world
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs_f32(1.0));
+        assert_eq!(play(&mut app, &[]), [Competitor::Player]);
+    }
+
+    #[test]
+    fn best_of_three_goes_to_whoever_takes_two() {
+        let mut app = App::new();
+        app.add_event::<GameWon>()
+            .insert_resource(MatchRules {
+                games: 3,
+                ..default()
+            })
+            .init_resource::<Games>()
+            .init_resource::<MatchWinner>()
+            .init_resource::<NextState<MatchState>>()
+            .add_systems(Update, end_game);
+        let win = |app: &mut App, winners: &[Competitor]| {
+            for winner in winners {
+                app.world.send_event(GameWon { winner: *winner });
+            }
+            app.update();
+            app.world.resource::<NextState<MatchState>>().0
+        };
+
+        let (player, computer) = (Competitor::Player, Competitor::Computer);
+        assert_eq!(win(&mut app, &[player]), Some(MatchState::Intermission));
+        // both knocked out on the same tick, only the first counts
+        assert_eq!(
+            win(&mut app, &[computer, player]),
+            Some(MatchState::Intermission)
+        );
+        assert_eq!(app.world.resource::<Games>().won, PerCompetitor::splat(1));
+        assert_eq!(app.world.resource::<MatchWinner>().0, None);
+
+        assert_eq!(win(&mut app, &[player]), Some(MatchState::GameOver));
+        assert_eq!(app.world.resource::<MatchWinner>().0, Some(player));
+    }
+}
@@ -0,0 +1,113 @@
+// The player's gamepad shakes when they return the ball (harder the faster it was going), more
+// when they smash it and most when they concede, each as hard as the settings say.
+use std::time::Duration;
+
+use bevy::{
+    input::gamepad::{GamepadRumbleIntensity, GamepadRumbleRequest},
+    prelude::*,
+};
+
+use crate::{
+    constants::*,
+    entities::{self, Competitor},
+    rules::Ends,
+    settings::Settings,
+    smash::Smashed,
+    CollisionEvent, PointScored, TickSet,
+};
+
+pub struct RumblePlugin;
+
+impl Plugin for RumblePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            rumble
+                .run_if(|settings: Res<Settings>| settings.rumble.enabled)
+                .run_if(crate::net::keyboard_is_local)
+                .run_if(not(crate::kiosk::attracting))
+                .in_set(TickSet::Effects),
+        );
+    }
+}
+
+// How hard a return at `speed` shakes, at `scale` for the fastest ball
+fn hit_strength(speed: f32, scale: f32) -> f32 {
+    scale * (speed / MAX_RAMPED_BALL_SPEED).clamp(MIN_RUMBLE_HIT_FRACTION, 1.0)
+}
+
+fn rumble(
+    settings: Res<Settings>,
+    gamepads: Res<Gamepads>,
+    ends: Res<Ends>,
+    mut collision_events: EventReader<CollisionEvent>,
+    mut points: EventReader<PointScored>,
+    mut smashes: EventReader<Smashed>,
+    balls: Query<&entities::Velocity>,
+    // a ball overlapping the paddle for a few ticks is only one return
+    mut last_return: Local<Option<(Entity, entities::Side)>>,
+    mut requests: EventWriter<GamepadRumbleRequest>,
+) {
+    let mut shakes = Vec::new();
+    let rumble = &settings.rumble;
+
+    for ev in collision_events.read() {
+        let CollisionEvent::Paddle(ball, side, _) = ev else {
+            continue;
+        };
+        if ends.at(*side) != Competitor::Player || *last_return == Some((*ball, *side)) {
+            continue;
+        }
+        *last_return = Some((*ball, *side));
+        let speed = balls.get(*ball).map_or(0.0, |velocity| velocity.length());
+        shakes.push((
+            GamepadRumbleIntensity::weak_motor(hit_strength(speed, rumble.hits)),
+            RUMBLE_HIT_DURATION,
+        ));
+    }
+    for _ in smashes.read() {
+        shakes.push((
+            GamepadRumbleIntensity::strong_motor(rumble.smashes),
+            RUMBLE_SMASH_DURATION,
+        ));
+    }
+    for point in points.read() {
+        *last_return = None;
+        if point.scorer != Competitor::Player {
+            shakes.push((
+                GamepadRumbleIntensity {
+                    strong_motor: rumble.goals,
+                    weak_motor: rumble.goals,
+                },
+                RUMBLE_GOAL_DURATION,
+            ));
+        }
+    }
+
+    // the same gamepad that's playing, see `stick.rs`
+    let Some(gamepad) = gamepads.iter().next() else {
+        return;
+    };
+    for (intensity, seconds) in shakes {
+        if intensity.strong_motor <= 0.0 && intensity.weak_motor <= 0.0 {
+            continue;
+        }
+        requests.send(GamepadRumbleRequest::Add {
+            duration: Duration::from_secs_f32(seconds),
+            intensity,
+            gamepad,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn faster_returns_shake_harder() {
+        assert_eq!(hit_strength(MAX_RAMPED_BALL_SPEED * 2.0, 0.5), 0.5);
+        assert!(hit_strength(BALL_SPEED, 1.0) < hit_strength(BALL_SPEED * 1.5, 1.0));
+        assert_eq!(hit_strength(0.0, 1.0), MIN_RUMBLE_HIT_FRACTION);
+    }
+}
@@ -0,0 +1,73 @@
+// Savestates for practice matches: F5 keeps where everything is right now and F7 puts it all
+// back, for another go at a tricky ball as many times as it takes. They're built on the
+// `SnapshotBuffer`, and only work in a practice match played here, never over the network.
+use bevy::prelude::*;
+
+use crate::{
+    mutators::MatchState,
+    net::NetRole,
+    rules,
+    settings::MenuState,
+    snapshot::{Restore, Snapshot, SnapshotBuffer},
+    toast::Toast,
+};
+
+#[derive(Resource, Debug, Default)]
+struct SaveState(Option<Snapshot>);
+
+pub struct SaveStatePlugin;
+
+impl Plugin for SaveStatePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SaveState>()
+            // its score belongs to the game it was saved in
+            .add_systems(OnEnter(MatchState::Playing), forget_save_state)
+            .add_systems(
+                Update,
+                (save_state, load_state)
+                    .run_if(rules::practicing)
+                    .run_if(|role: Res<NetRole>| *role == NetRole::Local)
+                    .run_if(in_state(MatchState::Playing))
+                    .run_if(in_state(MenuState::Closed)),
+            );
+    }
+}
+
+fn forget_save_state(mut save: ResMut<SaveState>) {
+    save.0 = None;
+}
+
+fn save_state(
+    keyboard_input: Res<Input<KeyCode>>,
+    snapshots: Res<SnapshotBuffer>,
+    mut save: ResMut<SaveState>,
+    mut toasts: EventWriter<Toast>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::F5) {
+        return;
+    }
+    if let Some(snapshot) = snapshots.latest() {
+        save.0 = Some(snapshot.clone());
+        toasts.send(Toast("State saved (F7 to load)".to_string()));
+    }
+}
+
+fn load_state(
+    mut commands: Commands,
+    keyboard_input: Res<Input<KeyCode>>,
+    save: Res<SaveState>,
+    mut toasts: EventWriter<Toast>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::F7) {
+        return;
+    }
+    match &save.0 {
+        Some(snapshot) => {
+            commands.add(Restore(snapshot.clone()));
+            toasts.send(Toast("State loaded".to_string()));
+        }
+        None => {
+            toasts.send(Toast("Nothing saved yet, F5 saves".to_string()));
+        }
+    }
+}
@@ -0,0 +1,499 @@
+// Scripted sequences: timelines of steps like "say this", "wait for the player to return the
+// ball three times" or "ease the camera over there", played one after another. They're
+// written in RON under `assets/sequences/` and started with a `PlaySequence`.
+use bevy::prelude::*;
+use bevy_common::fx::JuiceSettings;
+use serde::Deserialize;
+
+use crate::{
+    celebration::{self, Bounce, Fanfare},
+    constants::*,
+    entities::{self, Competitor},
+    instant_replay::{self, ReplayFinished},
+    minimap::MinimapCamera,
+    mutators::MatchState,
+    rules::{Ends, MatchWinner},
+    split_screen::SplitCamera,
+    CollisionEvent, PointScored, TickSet,
+};
+
+// Every sequence, by name
+const SEQUENCES: [(&str, &str); 3] = [
+    ("tutorial", include_str!("../assets/sequences/tutorial.ron")),
+    (
+        "game_over",
+        include_str!("../assets/sequences/game_over.ron"),
+    ),
+    ("victory", include_str!("../assets/sequences/victory.ron")),
+];
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub enum Step {
+    // puts `text` up at the top of the screen, replacing what was there. Empty takes it down.
+    Say(String),
+    // points the arrow at something, or takes it away
+    Point(Option<Pointer>),
+    // in seconds
+    Wait(f32),
+    // until the player's done something this many times
+    WaitFor {
+        cue: Cue,
+        times: usize,
+    },
+    // eases the camera to `to`, zoomed in by `zoom` (1 is normal), over `seconds`
+    MoveCamera {
+        to: (f32, f32),
+        zoom: f32,
+        seconds: f32,
+    },
+    SpawnBall {
+        position: (f32, f32),
+        velocity: (f32, f32),
+    },
+    // the rest are for when somebody's won the match, see `celebration.rs`. They start
+    // straight away and carry on through the steps after them, apart from `FocusWinner`.
+    //
+    // the winner's paddle hops in towards the court `hops` times
+    Bounce {
+        hops: usize,
+        seconds: f32,
+    },
+    // this many pieces
+    Confetti(usize),
+    Fanfare,
+    // like `MoveCamera`, partway over to the winner's end
+    FocusWinner {
+        zoom: f32,
+        seconds: f32,
+    },
+}
+
+// Something the player did that a sequence can wait for
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum Cue {
+    // pressed up or down
+    Move,
+    Return,
+    // returned the ball with the paddle moving, so it went off at an angle
+    AngledReturn,
+    Score,
+}
+
+// What the arrow can point at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum Pointer {
+    PlayerPaddle,
+    OpponentGoal,
+}
+
+pub fn steps(name: &str) -> Vec<Step> {
+    let (_, source) = SEQUENCES
+        .iter()
+        .find(|(sequence, _)| *sequence == name)
+        .unwrap_or_else(|| panic!("no sequence called {name}"));
+    ron::from_str(source)
+        .unwrap_or_else(|err| panic!("assets/sequences/{name}.ron isn't valid: {err}"))
+}
+
+#[derive(Event, Debug, Clone, Copy)]
+pub struct PlaySequence(pub &'static str);
+
+#[derive(Event, Debug, Clone, Copy)]
+pub struct SequenceFinished(pub &'static str);
+
+struct Playing {
+    name: &'static str,
+    steps: Vec<Step>,
+    step: usize,
+    // how long the current step's been going, and how many of its cues have been seen
+    elapsed: f32,
+    cues: usize,
+    // where the camera was when the current step started moving it
+    camera_from: Option<(Vec3, f32)>,
+}
+
+#[derive(Resource, Default)]
+pub struct Sequencer {
+    playing: Option<Playing>,
+    pointer: Option<Pointer>,
+}
+
+impl Sequencer {
+    pub fn is_playing(&self) -> bool {
+        self.playing.is_some()
+    }
+}
+
+#[derive(Component)]
+struct SequenceText;
+
+pub struct SequencePlugin;
+
+impl Plugin for SequencePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Sequencer>()
+            .add_event::<PlaySequence>()
+            .add_event::<SequenceFinished>()
+            .add_event::<Cue>()
+            .add_systems(Startup, spawn_text)
+            // after the replay of the winning point if there is one, see `instant_replay.rs`
+            .add_systems(
+                OnEnter(MatchState::GameOver),
+                play_game_over
+                    .after(instant_replay::start_replay)
+                    .run_if(not(instant_replay::playing)),
+            )
+            .add_systems(
+                Update,
+                play_game_over
+                    .run_if(on_event::<ReplayFinished>())
+                    .run_if(in_state(MatchState::GameOver)),
+            )
+            .add_systems(
+                FixedUpdate,
+                cue_returns_and_points
+                    .in_set(TickSet::Effects)
+                    .run_if(playing),
+            )
+            .add_systems(
+                Update,
+                (
+                    start_sequences,
+                    cue_moves.run_if(playing),
+                    run_sequence.run_if(playing),
+                    point_arrow,
+                )
+                    .chain(),
+            );
+    }
+}
+
+pub fn playing(sequencer: Res<Sequencer>) -> bool {
+    sequencer.is_playing()
+}
+
+fn start_sequences(mut plays: EventReader<PlaySequence>, mut sequencer: ResMut<Sequencer>) {
+    // a new sequence takes over from whatever was playing
+    if let Some(PlaySequence(name)) = plays.read().last() {
+        sequencer.playing = Some(Playing {
+            name,
+            steps: steps(name),
+            step: 0,
+            elapsed: 0.0,
+            cues: 0,
+            camera_from: None,
+        });
+    }
+}
+
+// A celebration for whoever won, or just the camera punch if nobody did
+fn play_game_over(winner: Res<MatchWinner>, mut sequences: EventWriter<PlaySequence>) {
+    sequences.send(PlaySequence(if winner.0.is_some() {
+        "victory"
+    } else {
+        "game_over"
+    }));
+}
+
+fn cue_moves(keyboard_input: Res<Input<KeyCode>>, mut cues: EventWriter<Cue>) {
+    let presses = bevy_common::input::UP
+        .into_iter()
+        .chain(bevy_common::input::DOWN)
+        .filter(|key| keyboard_input.just_pressed(*key))
+        .count();
+    cues.send_batch(std::iter::repeat_n(Cue::Move, presses));
+}
+
+fn cue_returns_and_points(
+    mut collision_events: EventReader<CollisionEvent>,
+    mut points: EventReader<PointScored>,
+    ends: Res<Ends>,
+    paddles: Query<&entities::PaddleVelocity, With<entities::Player>>,
+    mut cues: EventWriter<Cue>,
+    // the ball the player last returned, so a ball that stays on the paddle for a few ticks
+    // only counts once
+    mut last_return: Local<Option<Entity>>,
+) {
+    let player_side = ends.side_of(Competitor::Player);
+    for ev in collision_events.read() {
+        let CollisionEvent::Paddle(ball, side, _) = ev else {
+            continue;
+        };
+        if *side != player_side || *last_return == Some(*ball) {
+            continue;
+        }
+        *last_return = Some(*ball);
+
+        cues.send(Cue::Return);
+        let moving = paddles
+            .iter()
+            .any(|velocity| velocity.abs() >= ANGLED_RETURN_MIN_PADDLE_SPEED);
+        if moving {
+            cues.send(Cue::AngledReturn);
+        }
+    }
+
+    for point in points.read() {
+        // the rally's over, the next ball is someone else's to return
+        *last_return = None;
+        if point.scorer == Competitor::Player {
+            cues.send(Cue::Score);
+        }
+    }
+}
+
+fn spawn_text(mut commands: Commands) {
+    let mut text = TextBundle::from_sections([
+        TextSection::from_style(TextStyle {
+            font_size: SETTINGS_MENU_FONT_SIZE,
+            color: Color::WHITE,
+            ..default()
+        }),
+        TextSection::from_style(TextStyle {
+            font_size: SETTINGS_MENU_FONT_SIZE,
+            color: Color::GRAY,
+            ..default()
+        }),
+    ])
+    .with_background_color(SETTINGS_MENU_BACKDROP_COLOR);
+    text.visibility = Visibility::Hidden;
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                top: Val::Px(SEQUENCE_TEXT_PADDING),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn((text, SequenceText));
+        });
+}
+
+fn run_sequence(
+    mut commands: Commands,
+    mut sequencer: ResMut<Sequencer>,
+    mut cues: EventReader<Cue>,
+    mut finished: EventWriter<SequenceFinished>,
+    mut texts: Query<(&mut Text, &mut Visibility), With<SequenceText>>,
+    mut cameras: Query<
+        (&mut Transform, &mut OrthographicProjection),
+        (With<Camera2d>, Without<MinimapCamera>, Without<SplitCamera>),
+    >,
+    paddles: Query<(Entity, &Transform, &Competitor), (With<entities::Paddle>, Without<Camera2d>)>,
+    winner: Res<MatchWinner>,
+    ends: Res<Ends>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    juice: Res<JuiceSettings>,
+    time: Res<Time>,
+) {
+    let sequencer = &mut *sequencer;
+    let Some(playing) = &mut sequencer.playing else {
+        return;
+    };
+    playing.elapsed += time.delta_seconds();
+    let cued: Vec<Cue> = cues.read().copied().collect();
+
+    // instant steps run straight through to the next one that takes time
+    while let Some(step) = playing.steps.get(playing.step) {
+        let done = match step {
+            Step::Say(text) => {
+                for (mut shown, mut visibility) in &mut texts {
+                    shown.sections[0].value = text.clone();
+                    shown.sections[1].value = String::new();
+                    *visibility = if text.is_empty() {
+                        Visibility::Hidden
+                    } else {
+                        Visibility::Visible
+                    };
+                }
+                true
+            }
+            Step::Point(pointer) => {
+                sequencer.pointer = *pointer;
+                true
+            }
+            Step::Wait(seconds) => playing.elapsed >= *seconds,
+            Step::WaitFor { cue, times } => {
+                playing.cues += cued.iter().filter(|cued| *cued == cue).count();
+                for (mut shown, _) in &mut texts {
+                    shown.sections[1].value = if *times > 1 {
+                        format!("  {}/{times}", playing.cues.min(*times))
+                    } else {
+                        String::new()
+                    };
+                }
+                playing.cues >= *times
+            }
+            Step::MoveCamera { to, zoom, seconds } => move_camera(
+                &mut cameras,
+                &mut playing.camera_from,
+                playing.elapsed,
+                Vec2::from(*to),
+                *zoom,
+                *seconds,
+            ),
+            Step::SpawnBall { position, velocity } => {
+                let velocity = Vec2::from(*velocity);
+                let (mesh, ball, _, stall) = crate::spawn_ball(
+                    &mut materials,
+                    &mut meshes,
+                    Color::WHITE,
+                    Vec2::from(*position).extend(1.0),
+                    velocity,
+                );
+                commands.spawn((mesh, ball, entities::Velocity(velocity), stall));
+                true
+            }
+            Step::Bounce { hops, seconds } => {
+                let winning = paddles
+                    .iter()
+                    .find(|(_, _, competitor)| winner.0 == Some(**competitor));
+                if let Some((paddle, transform, competitor)) = winning {
+                    let inwards = -ends.side_of(*competitor).sign();
+                    commands.entity(paddle).insert(Bounce::new(
+                        transform.translation,
+                        inwards,
+                        *hops,
+                        *seconds,
+                    ));
+                }
+                true
+            }
+            Step::Confetti(count) => {
+                celebration::confetti(&mut commands, juice.count(*count));
+                true
+            }
+            Step::Fanfare => {
+                commands.insert_resource(Fanfare::default());
+                true
+            }
+            Step::FocusWinner { zoom, seconds } => {
+                let to = winner.0.map_or(Vec2::ZERO, |competitor| {
+                    let side = ends.side_of(competitor);
+                    Vec2::new(
+                        side.inset_x(GAP_BETWEEN_PADDLE_AND_WALL) * CELEBRATION_FOCUS_SHARE,
+                        0.0,
+                    )
+                });
+                move_camera(
+                    &mut cameras,
+                    &mut playing.camera_from,
+                    playing.elapsed,
+                    to,
+                    *zoom,
+                    *seconds,
+                )
+            }
+        };
+        if !done {
+            return;
+        }
+
+        playing.step += 1;
+        playing.elapsed = 0.0;
+        playing.cues = 0;
+        playing.camera_from = None;
+    }
+
+    finished.send(SequenceFinished(playing.name));
+    sequencer.playing = None;
+    sequencer.pointer = None;
+    for (_, mut visibility) in &mut texts {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+// Eases the camera from wherever it was when the step started to `to`, zoomed in by `zoom`.
+// Returns whether it's there.
+fn move_camera(
+    cameras: &mut Query<
+        (&mut Transform, &mut OrthographicProjection),
+        (With<Camera2d>, Without<MinimapCamera>, Without<SplitCamera>),
+    >,
+    camera_from: &mut Option<(Vec3, f32)>,
+    elapsed: f32,
+    to: Vec2,
+    zoom: f32,
+    seconds: f32,
+) -> bool {
+    let mut done = true;
+    for (mut transform, mut projection) in cameras {
+        let (from, from_zoom) =
+            *camera_from.get_or_insert((transform.translation, projection.scale));
+        // eased in and out
+        let t = (elapsed / seconds.max(f32::EPSILON)).min(1.0);
+        let t = t * t * (3.0 - 2.0 * t);
+        let to = to.extend(from.z);
+        transform.translation = from.lerp(to, t);
+        // zooming in is a smaller projection
+        projection.scale = from_zoom + (1.0 / zoom - from_zoom) * t;
+        done &= t >= 1.0;
+    }
+    done
+}
+
+// An arrow coming in from the middle of the court
+fn point_arrow(
+    sequencer: Res<Sequencer>,
+    ends: Res<Ends>,
+    paddles: Query<&Transform, (With<entities::Player>, With<entities::Paddle>)>,
+    mut gizmos: Gizmos,
+) {
+    let Some(pointer) = sequencer.pointer else {
+        return;
+    };
+
+    let player_side = ends.side_of(Competitor::Player);
+    let (tip, inwards) = match pointer {
+        Pointer::PlayerPaddle => {
+            let Ok(paddle) = paddles.get_single() else {
+                return;
+            };
+            let inwards = -player_side.sign();
+            let face = paddle.translation.x + inwards * paddle.scale.x / 2.0;
+            (
+                Vec2::new(face + inwards * SEQUENCE_ARROW_GAP, paddle.translation.y),
+                inwards,
+            )
+        }
+        Pointer::OpponentGoal => {
+            let goal = player_side.opponent();
+            (
+                Vec2::new(goal.inset_x(SEQUENCE_ARROW_GAP), 0.0),
+                -goal.sign(),
+            )
+        }
+    };
+
+    let tail = tip + Vec2::new(inwards * SEQUENCE_ARROW_LENGTH, 0.0);
+    let head = SEQUENCE_ARROW_LENGTH / 3.0;
+    gizmos.line_2d(tail, tip, SEQUENCE_ARROW_COLOR);
+    gizmos.line_2d(
+        tip,
+        tip + Vec2::new(inwards * head, head),
+        SEQUENCE_ARROW_COLOR,
+    );
+    gizmos.line_2d(
+        tip,
+        tip + Vec2::new(inwards * head, -head),
+        SEQUENCE_ARROW_COLOR,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sequences_load() {
+        for (name, _) in SEQUENCES {
+            assert!(!steps(name).is_empty());
+        }
+    }
+}
@@ -0,0 +1,159 @@
+// Serves wind up rather than popping into existence. Each ball's held where it's served from
+// for `settings.gameplay.serve_countdown`, growing from nothing while rings pulse out from it
+// and an arrow points the way it's about to go, so the receiver has a moment to get there.
+// Then it's let go at the speed it was served at.
+use bevy::prelude::*;
+use bevy_common::fx::JuiceSettings;
+
+use crate::{constants::*, entities, settings::Settings, TickSet};
+
+// A ball that's been served but hasn't left yet
+#[derive(Component, Debug)]
+pub struct Serving {
+    countdown: Timer,
+    // what it leaves with
+    velocity: Vec2,
+    // and how big it is by then
+    size: Vec3,
+}
+
+impl Serving {
+    // Keeps the ball's velocity and size to give back once the countdown's up, leaving it
+    // still and too small to see until then
+    pub fn hold(
+        seconds: f32,
+        transform: &mut Transform,
+        velocity: &mut entities::Velocity,
+    ) -> Self {
+        let serving = Self {
+            countdown: Timer::from_seconds(seconds, TimerMode::Once),
+            velocity: velocity.0,
+            size: transform.scale,
+        };
+        transform.scale = Vec3::ZERO;
+        velocity.0 = Vec2::ZERO;
+        serving
+    }
+
+    pub fn velocity(&self) -> Vec2 {
+        self.velocity
+    }
+
+    // quick to start with and settling into its full size
+    fn grown(&self) -> Vec3 {
+        let t = self.countdown.percent();
+        self.size * (1.0 - (1.0 - t).powi(2))
+    }
+}
+
+pub struct ServePlugin;
+
+impl Plugin for ServePlugin {
+    fn build(&self, app: &mut App) {
+        // before anything moves, so a ball let go this tick goes straight away
+        app.add_systems(FixedUpdate, wind_up_serves.in_set(TickSet::Input))
+            .add_systems(Update, telegraph_serves);
+    }
+}
+
+// On the simulation's clock rather than the frame's, so a serve goes the same way in the
+// replays and over the network
+fn wind_up_serves(
+    mut commands: Commands,
+    mut balls: Query<(
+        Entity,
+        &mut Transform,
+        &mut entities::Velocity,
+        &mut Serving,
+    )>,
+    time: Res<Time>,
+) {
+    for (entity, mut transform, mut velocity, mut serving) in &mut balls {
+        if serving.countdown.tick(time.delta()).finished() {
+            transform.scale = serving.size;
+            velocity.0 = serving.velocity;
+            commands.entity(entity).remove::<Serving>();
+        } else {
+            transform.scale = serving.grown();
+        }
+    }
+}
+
+// The rings and the arrow
+fn telegraph_serves(
+    balls: Query<(&Transform, &Serving)>,
+    settings: Res<Settings>,
+    juice: Res<JuiceSettings>,
+    mut gizmos: Gizmos,
+) {
+    let color = settings.theme.palette().ball;
+
+    for (transform, serving) in &balls {
+        let center = transform.translation.truncate();
+        let t = serving.countdown.percent();
+
+        // each ring fades as it spreads. The arrow's more than decoration, so it stays
+        // however far down the juice is.
+        let pulse = (t * SERVE_RING_PULSES).fract();
+        gizmos.circle_2d(
+            center,
+            SERVE_RING_RADIUS * pulse,
+            color.with_a(juice.scale(1.0 - pulse)),
+        );
+
+        // fading in, starting from where the ball's edge will be
+        let direction = serving.velocity.normalize_or_zero();
+        let tail = center + direction * (serving.size.x / 2.0 + SERVE_ARROW_GAP);
+        let tip = tail + direction * SERVE_ARROW_LENGTH;
+        let head = SERVE_ARROW_LENGTH / 3.0;
+        let color = color.with_a(t);
+        gizmos.line_2d(tail, tip, color);
+        for angle in [0.5, -0.5] {
+            let back = Vec2::from_angle(angle).rotate(-direction);
+            gizmos.line_2d(tip, tip + back * head, color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn held_ball_grows_then_leaves() {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .add_systems(Update, wind_up_serves);
+
+        let mut transform = Transform::from_scale(BALL_SIZE);
+        let mut velocity = entities::Velocity(Vec2::new(BALL_SPEED, 0.0));
+        let serving = Serving::hold(0.5, &mut transform, &mut velocity);
+        assert_eq!(velocity.0, Vec2::ZERO);
+        let ball = app.world.spawn((transform, velocity, serving)).id();
+
+        let step = |app: &mut App| {
+            app.world
+                .resource_mut::<Time>()
+                .advance_by(Duration::from_secs_f32(0.25));
+            app.update();
+        };
+
+        step(&mut app);
+        let scale = app.world.get::<Transform>(ball).unwrap().scale;
+        assert!(scale.x > 0.0 && scale.x < BALL_SIZE.x, "{scale}");
+        assert_eq!(
+            app.world.get::<entities::Velocity>(ball).unwrap().0,
+            Vec2::ZERO
+        );
+
+        step(&mut app);
+        assert!(app.world.get::<Serving>(ball).is_none());
+        assert_eq!(app.world.get::<Transform>(ball).unwrap().scale, BALL_SIZE);
+        assert_eq!(
+            app.world.get::<entities::Velocity>(ball).unwrap().0,
+            Vec2::new(BALL_SPEED, 0.0)
+        );
+    }
+}
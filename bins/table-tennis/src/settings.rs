@@ -0,0 +1,678 @@
+use bevy::prelude::*;
+use bevy_common::{
+    input,
+    screens::{self, AddScreen},
+};
+
+use crate::{
+    actions::ActionState, constants::*, entities::Side, focus::WhenUnfocused, minimap::MinimapMode,
+    movement::AccelerationCurve, orientation::Layout, prediction::AimAssist, stick::ResponseCurve,
+    theme::Theme,
+};
+
+#[derive(Resource, Debug, Clone)]
+pub struct Settings {
+    pub theme: Theme,
+    pub layout: Layout,
+    pub minimap: Minimap,
+    // each end gets half the window during play, see `split_screen.rs`
+    pub split_screen: bool,
+    // the backing track that picks up with the rallies, see `music.rs`
+    pub music: bool,
+    // how much of the effects to show, from 0 to 1, see `fx.rs`
+    pub juice: f32,
+    pub pacing: Pacing,
+    pub post_processing: PostProcessing,
+    pub gameplay: Gameplay,
+    pub stick: Stick,
+    pub rumble: Rumble,
+}
+
+// How often frames are drawn, see `pacing.rs`
+#[derive(Debug, Clone)]
+pub struct Pacing {
+    // frames per second at most, 0 for as many as the display takes
+    pub frame_cap: u32,
+    // menus and pauses only redraw a few times a second
+    pub power_saving: bool,
+    // local matches tick less often while the simulation can't keep up, see `overload.rs`
+    pub ease_overload: bool,
+}
+
+// The picture-in-picture of the whole court, see `minimap.rs`
+#[derive(Debug, Clone)]
+pub struct Minimap {
+    pub mode: MinimapMode,
+    // how much of the window's width it takes
+    pub size: f32,
+}
+
+// How a gamepad's analog stick is read, see `stick.rs`
+#[derive(Debug, Clone)]
+pub struct Stick {
+    // how far it has to be pushed, from 0 to 1, before it does anything
+    pub dead_zone: f32,
+    pub curve: ResponseCurve,
+    // how far the paddle's pushed for how far the stick is, past the dead zone
+    pub sensitivity: f32,
+}
+
+// How hard a gamepad shakes for each thing that happens, from 0 to 1, see `rumble.rs`
+#[derive(Debug, Clone)]
+pub struct Rumble {
+    pub enabled: bool,
+    // scaled by how fast the ball was going
+    pub hits: f32,
+    // conceded ones
+    pub goals: f32,
+    pub smashes: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct Gameplay {
+    // the end of the court the player starts each match at, the computer takes the other
+    pub player_side: Side,
+    pub power_ups: bool,
+    // how many balls are served at once with the multi-ball mutator on
+    pub multi_balls: usize,
+    pub acceleration_curve: AccelerationCurve,
+    // fraction of the paddle's velocity given to the ball when it's returned
+    pub momentum_transfer: f32,
+    // how far past its ends the player's paddle still returns a ball, in units
+    pub edge_grace: f32,
+    // how many ticks early a smash can be pressed and still hit, see `smash.rs`
+    pub input_buffer: u32,
+    // serves leave within this many degrees of horizontal
+    pub serve_cone_degrees: f32,
+    // seconds a serve is held before it leaves, 0 for straight away, see `serve.rs`
+    pub serve_countdown: f32,
+    // the computer moves to where the ball's going rather than where it is, see
+    // `prediction.rs`
+    pub predictive_ai: bool,
+    pub aim_assist: AimAssist,
+    // how quickly the computer adapts to the score to keep the match close, 0 for not at
+    // all, see `difficulty.rs`
+    pub adaptive_ai: f32,
+    // seconds without any input before a match pauses itself, 0 for never, see `idle.rs`
+    pub idle_pause: f32,
+    pub when_unfocused: WhenUnfocused,
+}
+
+#[derive(Debug, Clone)]
+pub struct PostProcessing {
+    // only applied when the theme renders in HDR
+    pub bloom_intensity: f32,
+    pub vignette: bool,
+    // the retro scanline filter, see `crt.rs`
+    pub crt: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            theme: Theme::default(),
+            layout: Layout::default(),
+            minimap: Minimap {
+                mode: MinimapMode::default(),
+                size: DEFAULT_MINIMAP_SIZE,
+            },
+            split_screen: false,
+            music: true,
+            juice: DEFAULT_JUICE,
+            pacing: Pacing {
+                frame_cap: 0,
+                power_saving: true,
+                ease_overload: false,
+            },
+            post_processing: PostProcessing {
+                bloom_intensity: DEFAULT_BLOOM_INTENSITY,
+                vignette: false,
+                crt: false,
+            },
+            gameplay: Gameplay {
+                player_side: PLAYER_SIDE,
+                power_ups: true,
+                multi_balls: DEFAULT_MULTI_BALLS,
+                acceleration_curve: AccelerationCurve::default(),
+                momentum_transfer: DEFAULT_MOMENTUM_TRANSFER,
+                edge_grace: DEFAULT_EDGE_GRACE,
+                input_buffer: DEFAULT_INPUT_BUFFER,
+                serve_cone_degrees: DEFAULT_SERVE_CONE_DEGREES,
+                serve_countdown: DEFAULT_SERVE_COUNTDOWN,
+                predictive_ai: false,
+                aim_assist: AimAssist::default(),
+                adaptive_ai: 0.0,
+                idle_pause: DEFAULT_IDLE_PAUSE,
+                when_unfocused: WhenUnfocused::default(),
+            },
+            stick: Stick {
+                dead_zone: DEFAULT_STICK_DEAD_ZONE,
+                curve: ResponseCurve::default(),
+                sensitivity: DEFAULT_STICK_SENSITIVITY,
+            },
+            rumble: Rumble {
+                enabled: true,
+                hits: DEFAULT_RUMBLE_HITS,
+                goals: DEFAULT_RUMBLE_GOALS,
+                smashes: DEFAULT_RUMBLE_SMASHES,
+            },
+        }
+    }
+}
+
+// The in-game settings menu pauses the simulation while it's open
+#[derive(States, Debug, Clone, Copy, Default, Hash, PartialEq, Eq)]
+pub enum MenuState {
+    #[default]
+    Closed,
+    Settings,
+    // paused without the menu, see `idle.rs`
+    Paused,
+}
+
+// Every row in the settings menu, in display order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingsItem {
+    Theme,
+    Layout,
+    Minimap,
+    MinimapSize,
+    SplitScreen,
+    FrameCap,
+    PowerSaving,
+    EaseOverload,
+    Juice,
+    BloomIntensity,
+    Vignette,
+    Crt,
+    Music,
+    PlayerSide,
+    PowerUps,
+    MultiBall,
+    AccelerationCurve,
+    MomentumTransfer,
+    EdgeGrace,
+    InputBuffer,
+    ServeAngle,
+    ServeCountdown,
+    PredictiveAi,
+    AdaptiveAi,
+    AimAssist,
+    IdlePause,
+    WhenUnfocused,
+    StickDeadZone,
+    StickCurve,
+    StickSensitivity,
+    Rumble,
+    RumbleHits,
+    RumbleGoals,
+    RumbleSmashes,
+}
+
+impl SettingsItem {
+    pub const ALL: [SettingsItem; 34] = [
+        SettingsItem::Theme,
+        SettingsItem::Layout,
+        SettingsItem::Minimap,
+        SettingsItem::MinimapSize,
+        SettingsItem::SplitScreen,
+        SettingsItem::FrameCap,
+        SettingsItem::PowerSaving,
+        SettingsItem::EaseOverload,
+        SettingsItem::Juice,
+        SettingsItem::BloomIntensity,
+        SettingsItem::Vignette,
+        SettingsItem::Crt,
+        SettingsItem::Music,
+        SettingsItem::PlayerSide,
+        SettingsItem::PowerUps,
+        SettingsItem::MultiBall,
+        SettingsItem::AccelerationCurve,
+        SettingsItem::MomentumTransfer,
+        SettingsItem::EdgeGrace,
+        SettingsItem::InputBuffer,
+        SettingsItem::ServeAngle,
+        SettingsItem::ServeCountdown,
+        SettingsItem::PredictiveAi,
+        SettingsItem::AdaptiveAi,
+        SettingsItem::AimAssist,
+        SettingsItem::IdlePause,
+        SettingsItem::WhenUnfocused,
+        SettingsItem::StickDeadZone,
+        SettingsItem::StickCurve,
+        SettingsItem::StickSensitivity,
+        SettingsItem::Rumble,
+        SettingsItem::RumbleHits,
+        SettingsItem::RumbleGoals,
+        SettingsItem::RumbleSmashes,
+    ];
+
+    // The ones this build has something behind, in menu order
+    fn shown() -> Vec<SettingsItem> {
+        SettingsItem::ALL
+            .into_iter()
+            .filter(|item| cfg!(feature = "post-processing") || item.section() != "Post-processing")
+            .collect()
+    }
+
+    pub fn section(&self) -> &'static str {
+        match self {
+            SettingsItem::Theme
+            | SettingsItem::Layout
+            | SettingsItem::Minimap
+            | SettingsItem::MinimapSize
+            | SettingsItem::SplitScreen
+            | SettingsItem::FrameCap
+            | SettingsItem::PowerSaving
+            | SettingsItem::EaseOverload
+            | SettingsItem::Juice => "Display",
+            SettingsItem::BloomIntensity | SettingsItem::Vignette | SettingsItem::Crt => {
+                "Post-processing"
+            }
+            SettingsItem::Music => "Audio",
+            SettingsItem::PlayerSide
+            | SettingsItem::PowerUps
+            | SettingsItem::MultiBall
+            | SettingsItem::AccelerationCurve
+            | SettingsItem::MomentumTransfer
+            | SettingsItem::EdgeGrace
+            | SettingsItem::InputBuffer
+            | SettingsItem::ServeAngle
+            | SettingsItem::ServeCountdown
+            | SettingsItem::PredictiveAi
+            | SettingsItem::AdaptiveAi => "Gameplay",
+            SettingsItem::AimAssist => "Accessibility",
+            SettingsItem::IdlePause | SettingsItem::WhenUnfocused => "Pausing",
+            SettingsItem::StickDeadZone
+            | SettingsItem::StickCurve
+            | SettingsItem::StickSensitivity
+            | SettingsItem::Rumble
+            | SettingsItem::RumbleHits
+            | SettingsItem::RumbleGoals
+            | SettingsItem::RumbleSmashes => "Controller",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SettingsItem::Theme => "Theme",
+            SettingsItem::Layout => "Court layout",
+            SettingsItem::Minimap => "Minimap",
+            SettingsItem::MinimapSize => "Minimap size",
+            SettingsItem::SplitScreen => "Split screen",
+            SettingsItem::FrameCap => "Frame rate cap",
+            SettingsItem::PowerSaving => "Power saving in menus",
+            SettingsItem::EaseOverload => "Slow ticks when overloaded",
+            SettingsItem::Juice => "Game feel intensity",
+            SettingsItem::BloomIntensity => "Bloom intensity",
+            SettingsItem::Vignette => "Vignette",
+            SettingsItem::Crt => "CRT filter (F2)",
+            SettingsItem::Music => "Music",
+            SettingsItem::PlayerSide => "Your side",
+            SettingsItem::PowerUps => "Power-ups",
+            SettingsItem::MultiBall => "Multi-ball size",
+            SettingsItem::AccelerationCurve => "Paddle acceleration",
+            SettingsItem::MomentumTransfer => "Momentum transfer",
+            SettingsItem::EdgeGrace => "Edge forgiveness",
+            SettingsItem::InputBuffer => "Smash buffer (space)",
+            SettingsItem::ServeAngle => "Serve angle",
+            SettingsItem::ServeCountdown => "Serve countdown",
+            SettingsItem::PredictiveAi => "Computer anticipates",
+            SettingsItem::AdaptiveAi => "Adaptive computer",
+            SettingsItem::AimAssist => "Aim assist",
+            SettingsItem::IdlePause => "Pause when idle for",
+            SettingsItem::WhenUnfocused => "In the background",
+            SettingsItem::StickDeadZone => "Stick dead zone",
+            SettingsItem::StickCurve => "Stick response",
+            SettingsItem::StickSensitivity => "Stick sensitivity",
+            SettingsItem::Rumble => "Rumble",
+            SettingsItem::RumbleHits => "Rumble on returns",
+            SettingsItem::RumbleGoals => "Rumble on goals conceded",
+            SettingsItem::RumbleSmashes => "Rumble on smashes",
+        }
+    }
+
+    pub fn value(&self, settings: &Settings) -> String {
+        match self {
+            SettingsItem::Theme => settings.theme.name().to_string(),
+            SettingsItem::Layout => settings.layout.name().to_string(),
+            SettingsItem::Minimap => settings.minimap.mode.name().to_string(),
+            SettingsItem::MinimapSize => format!("{:.0}%", settings.minimap.size * 100.0),
+            SettingsItem::SplitScreen => on_off(settings.split_screen).to_string(),
+            SettingsItem::FrameCap => match settings.pacing.frame_cap {
+                0 => "Off".to_string(),
+                fps => format!("{fps} FPS"),
+            },
+            SettingsItem::PowerSaving => on_off(settings.pacing.power_saving).to_string(),
+            SettingsItem::EaseOverload => on_off(settings.pacing.ease_overload).to_string(),
+            SettingsItem::Juice => format!("{:.0}%", settings.juice * 100.0),
+            SettingsItem::BloomIntensity => {
+                format!("{:.0}%", settings.post_processing.bloom_intensity * 100.0)
+            }
+            SettingsItem::Vignette => on_off(settings.post_processing.vignette).to_string(),
+            SettingsItem::Crt => on_off(settings.post_processing.crt).to_string(),
+            SettingsItem::Music => on_off(settings.music).to_string(),
+            SettingsItem::PlayerSide => settings.gameplay.player_side.name().to_string(),
+            SettingsItem::PowerUps => on_off(settings.gameplay.power_ups).to_string(),
+            SettingsItem::MultiBall => format!("{} balls", settings.gameplay.multi_balls),
+            SettingsItem::AccelerationCurve => {
+                settings.gameplay.acceleration_curve.name().to_string()
+            }
+            SettingsItem::MomentumTransfer => {
+                format!("{:.0}%", settings.gameplay.momentum_transfer * 100.0)
+            }
+            SettingsItem::EdgeGrace => match settings.gameplay.edge_grace {
+                0.0 => "Off".to_string(),
+                grace => format!("{grace:.0} units"),
+            },
+            SettingsItem::InputBuffer => match settings.gameplay.input_buffer {
+                0 => "Off".to_string(),
+                ticks => format!("{ticks} ticks"),
+            },
+            SettingsItem::ServeAngle => {
+                format!("±{:.0}°", settings.gameplay.serve_cone_degrees)
+            }
+            SettingsItem::ServeCountdown => match settings.gameplay.serve_countdown {
+                0.0 => "Off".to_string(),
+                seconds => format!("{seconds:.1}s"),
+            },
+            SettingsItem::PredictiveAi => on_off(settings.gameplay.predictive_ai).to_string(),
+            SettingsItem::AdaptiveAi => match settings.gameplay.adaptive_ai {
+                0.0 => "Off".to_string(),
+                intensity => format!("{:.0}%", intensity * 100.0),
+            },
+            SettingsItem::AimAssist => settings.gameplay.aim_assist.name().to_string(),
+            SettingsItem::IdlePause => match settings.gameplay.idle_pause {
+                0.0 => "Never".to_string(),
+                seconds => format!("{seconds:.0}s"),
+            },
+            SettingsItem::WhenUnfocused => settings.gameplay.when_unfocused.name().to_string(),
+            SettingsItem::StickDeadZone => format!("{:.0}%", settings.stick.dead_zone * 100.0),
+            SettingsItem::StickCurve => settings.stick.curve.name().to_string(),
+            SettingsItem::StickSensitivity => format!("{:.1}x", settings.stick.sensitivity),
+            SettingsItem::Rumble => on_off(settings.rumble.enabled).to_string(),
+            SettingsItem::RumbleHits => format!("{:.0}%", settings.rumble.hits * 100.0),
+            SettingsItem::RumbleGoals => format!("{:.0}%", settings.rumble.goals * 100.0),
+            SettingsItem::RumbleSmashes => format!("{:.0}%", settings.rumble.smashes * 100.0),
+        }
+    }
+
+    // step is -1 or 1 depending on which way the player pressed
+    pub fn adjust(&self, settings: &mut Settings, step: f32) {
+        match self {
+            SettingsItem::Theme => settings.theme = settings.theme.next(),
+            SettingsItem::Layout => settings.layout = settings.layout.next(),
+            SettingsItem::Minimap => settings.minimap.mode = settings.minimap.mode.next(),
+            SettingsItem::SplitScreen => settings.split_screen = !settings.split_screen,
+            SettingsItem::Music => settings.music = !settings.music,
+            SettingsItem::MinimapSize => {
+                let size = &mut settings.minimap.size;
+                *size =
+                    (*size + step * MINIMAP_SIZE_STEP).clamp(MINIMAP_SIZE_STEP, MAX_MINIMAP_SIZE);
+            }
+            SettingsItem::FrameCap => {
+                let caps = FRAME_CAPS.len();
+                let current = FRAME_CAPS
+                    .iter()
+                    .position(|cap| *cap == settings.pacing.frame_cap)
+                    .unwrap_or(0);
+                let next = (current as isize + step as isize).rem_euclid(caps as isize);
+                settings.pacing.frame_cap = FRAME_CAPS[next as usize];
+            }
+            SettingsItem::PowerSaving => {
+                settings.pacing.power_saving = !settings.pacing.power_saving
+            }
+            SettingsItem::EaseOverload => {
+                settings.pacing.ease_overload = !settings.pacing.ease_overload
+            }
+            SettingsItem::Juice => {
+                settings.juice = (settings.juice + step * JUICE_STEP).clamp(0.0, 1.0)
+            }
+            SettingsItem::BloomIntensity => {
+                let pp = &mut settings.post_processing;
+                pp.bloom_intensity =
+                    (pp.bloom_intensity + step * BLOOM_INTENSITY_STEP).clamp(0.0, 1.0);
+            }
+            SettingsItem::Vignette => {
+                settings.post_processing.vignette = !settings.post_processing.vignette
+            }
+            SettingsItem::Crt => settings.post_processing.crt = !settings.post_processing.crt,
+            // takes effect from the next match
+            SettingsItem::PlayerSide => {
+                settings.gameplay.player_side = settings.gameplay.player_side.opponent()
+            }
+            SettingsItem::PowerUps => settings.gameplay.power_ups = !settings.gameplay.power_ups,
+            // takes effect from the next serve
+            SettingsItem::MultiBall => {
+                settings.gameplay.multi_balls = (settings.gameplay.multi_balls as f32 + step)
+                    .clamp(2.0, MAX_BALLS as f32)
+                    as usize
+            }
+            SettingsItem::AccelerationCurve => {
+                settings.gameplay.acceleration_curve = settings.gameplay.acceleration_curve.next()
+            }
+            SettingsItem::MomentumTransfer => {
+                let gameplay = &mut settings.gameplay;
+                gameplay.momentum_transfer = (gameplay.momentum_transfer
+                    + step * MOMENTUM_TRANSFER_STEP)
+                    .clamp(0.0, MAX_MOMENTUM_TRANSFER);
+            }
+            SettingsItem::EdgeGrace => {
+                let gameplay = &mut settings.gameplay;
+                gameplay.edge_grace = (gameplay.edge_grace + step).clamp(0.0, MAX_EDGE_GRACE);
+            }
+            SettingsItem::InputBuffer => {
+                let gameplay = &mut settings.gameplay;
+                gameplay.input_buffer = (gameplay.input_buffer as f32 + step)
+                    .clamp(0.0, MAX_INPUT_BUFFER as f32)
+                    as u32;
+            }
+            // takes effect from the next serve
+            SettingsItem::ServeAngle => {
+                let gameplay = &mut settings.gameplay;
+                gameplay.serve_cone_degrees = (gameplay.serve_cone_degrees
+                    + step * SERVE_CONE_STEP_DEGREES)
+                    .clamp(0.0, MAX_SERVE_CONE_DEGREES);
+            }
+            // takes effect from the next serve
+            SettingsItem::ServeCountdown => {
+                // in whole steps, so it comes back down to exactly off
+                let gameplay = &mut settings.gameplay;
+                let steps = (gameplay.serve_countdown / SERVE_COUNTDOWN_STEP).round() + step;
+                gameplay.serve_countdown =
+                    (steps * SERVE_COUNTDOWN_STEP).clamp(0.0, MAX_SERVE_COUNTDOWN);
+            }
+            SettingsItem::PredictiveAi => {
+                settings.gameplay.predictive_ai = !settings.gameplay.predictive_ai
+            }
+            SettingsItem::AdaptiveAi => {
+                let gameplay = &mut settings.gameplay;
+                gameplay.adaptive_ai =
+                    (gameplay.adaptive_ai + step * ADAPTIVE_AI_STEP).clamp(0.0, 1.0);
+            }
+            SettingsItem::AimAssist => {
+                settings.gameplay.aim_assist = settings.gameplay.aim_assist.next()
+            }
+            SettingsItem::IdlePause => {
+                let gameplay = &mut settings.gameplay;
+                gameplay.idle_pause =
+                    (gameplay.idle_pause + step * IDLE_PAUSE_STEP).clamp(0.0, MAX_IDLE_PAUSE);
+            }
+            SettingsItem::WhenUnfocused => {
+                settings.gameplay.when_unfocused = settings.gameplay.when_unfocused.next()
+            }
+            SettingsItem::StickDeadZone => {
+                let stick = &mut settings.stick;
+                stick.dead_zone =
+                    (stick.dead_zone + step * STICK_DEAD_ZONE_STEP).clamp(0.0, MAX_STICK_DEAD_ZONE);
+            }
+            SettingsItem::StickCurve => settings.stick.curve = settings.stick.curve.next(),
+            SettingsItem::StickSensitivity => {
+                let stick = &mut settings.stick;
+                stick.sensitivity = (stick.sensitivity + step * STICK_SENSITIVITY_STEP)
+                    .clamp(MIN_STICK_SENSITIVITY, MAX_STICK_SENSITIVITY);
+            }
+            SettingsItem::Rumble => settings.rumble.enabled = !settings.rumble.enabled,
+            SettingsItem::RumbleHits => {
+                let hits = &mut settings.rumble.hits;
+                *hits = (*hits + step * RUMBLE_STEP).clamp(0.0, 1.0);
+            }
+            SettingsItem::RumbleGoals => {
+                let goals = &mut settings.rumble.goals;
+                *goals = (*goals + step * RUMBLE_STEP).clamp(0.0, 1.0);
+            }
+            SettingsItem::RumbleSmashes => {
+                let smashes = &mut settings.rumble.smashes;
+                *smashes = (*smashes + step * RUMBLE_STEP).clamp(0.0, 1.0);
+            }
+        }
+    }
+}
+
+pub fn on_off(value: bool) -> &'static str {
+    if value {
+        "On"
+    } else {
+        "Off"
+    }
+}
+
+#[derive(Resource, Default)]
+struct SettingsMenuCursor(usize);
+
+#[derive(Component)]
+struct SettingsMenu;
+
+#[derive(Component)]
+struct SettingsMenuText;
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<MenuState>()
+            .init_resource::<Settings>()
+            .init_resource::<SettingsMenuCursor>()
+            .add_systems(Update, toggle_settings_menu)
+            .add_screen::<SettingsMenu, _>(MenuState::Settings, spawn_settings_menu)
+            .add_systems(
+                Update,
+                (navigate_settings_menu, update_settings_menu_text)
+                    .chain()
+                    .run_if(in_state(MenuState::Settings)),
+            );
+    }
+}
+
+fn toggle_settings_menu(
+    actions: Res<ActionState>,
+    state: Res<State<MenuState>>,
+    mut next_state: ResMut<NextState<MenuState>>,
+) {
+    if !actions.pause {
+        return;
+    }
+
+    next_state.set(match state.get() {
+        MenuState::Closed | MenuState::Paused => MenuState::Settings,
+        MenuState::Settings => MenuState::Closed,
+    });
+}
+
+fn navigate_settings_menu(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut cursor: ResMut<SettingsMenuCursor>,
+    mut settings: ResMut<Settings>,
+) {
+    let shown = SettingsItem::shown();
+    let items = shown.len();
+
+    if keyboard_input.any_just_pressed(input::UP) {
+        cursor.0 = (cursor.0 + items - 1) % items;
+    }
+    if keyboard_input.any_just_pressed(input::DOWN) {
+        cursor.0 = (cursor.0 + 1) % items;
+    }
+
+    let step = if keyboard_input.any_just_pressed(input::LEFT) {
+        -1.0
+    } else if keyboard_input.any_just_pressed(input::RIGHT)
+        || keyboard_input.just_pressed(KeyCode::Return)
+    {
+        1.0
+    } else {
+        return;
+    };
+
+    shown[cursor.0].adjust(&mut settings, step);
+}
+
+fn menu_sections(settings: &Settings, cursor: usize) -> Vec<TextSection> {
+    let style = |color| TextStyle {
+        font_size: SETTINGS_MENU_FONT_SIZE,
+        color,
+        ..default()
+    };
+
+    let mut sections = vec![TextSection::new("Settings\n", style(Color::WHITE))];
+    let mut current_section = None;
+
+    for (i, item) in SettingsItem::shown().iter().enumerate() {
+        if current_section != Some(item.section()) {
+            current_section = Some(item.section());
+            sections.push(TextSection::new(
+                format!("\n{}\n", item.section()),
+                style(Color::GRAY),
+            ));
+        }
+
+        let (marker, color) = if i == cursor {
+            ("> ", Color::YELLOW)
+        } else {
+            ("  ", Color::WHITE)
+        };
+        sections.push(TextSection::new(
+            format!("{marker}{}: < {} >\n", item.label(), item.value(settings)),
+            style(color),
+        ));
+    }
+
+    sections.push(TextSection::new(
+        "\nup/down: select  left/right: change  tab: close",
+        style(Color::GRAY),
+    ));
+
+    sections
+}
+
+fn spawn_settings_menu(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    cursor: Res<SettingsMenuCursor>,
+) {
+    commands
+        .spawn((
+            screens::overlay(SETTINGS_MENU_BACKDROP_COLOR, SETTINGS_MENU_Z_INDEX),
+            SettingsMenu,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_sections(menu_sections(&settings, cursor.0)),
+                SettingsMenuText,
+            ));
+        });
+}
+
+fn update_settings_menu_text(
+    settings: Res<Settings>,
+    cursor: Res<SettingsMenuCursor>,
+    mut text: Query<&mut Text, With<SettingsMenuText>>,
+) {
+    if !settings.is_changed() && !cursor.is_changed() {
+        return;
+    }
+
+    for mut text in &mut text {
+        text.sections = menu_sections(&settings, cursor.0);
+    }
+}
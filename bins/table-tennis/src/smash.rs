@@ -0,0 +1,124 @@
+// The player's smash: space hits their next return harder. At 64 ticks a second it's easy to
+// press a frame or two before the ball actually arrives, so a press is held on to for a few
+// ticks (`Gameplay::input_buffer`) and spent on the first return in that time.
+use bevy::prelude::*;
+
+use crate::{
+    actions::ActionState,
+    constants::*,
+    entities::Competitor,
+    rules::{self, Ends},
+    settings::Settings,
+    CollisionEvent, TickSet,
+};
+
+// A press remembered for a few ticks, so one made a little early is still there when it's
+// needed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InputBuffer {
+    ticks_left: u32,
+}
+
+impl InputBuffer {
+    // held for `window` ticks, or just the next one with buffering off
+    pub fn press(&mut self, window: u32) {
+        self.ticks_left = window.max(1);
+    }
+
+    pub fn tick(&mut self) {
+        self.ticks_left = self.ticks_left.saturating_sub(1);
+    }
+
+    // whether there was a press waiting, which is used up
+    pub fn take(&mut self) -> bool {
+        std::mem::take(&mut self.ticks_left) > 0
+    }
+}
+
+#[derive(Resource, Debug, Default)]
+struct SmashPress(InputBuffer);
+
+// The player's return was smashed
+#[derive(Event, Debug)]
+pub struct Smashed;
+
+pub struct SmashPlugin;
+
+impl Plugin for SmashPlugin {
+    fn build(&self, app: &mut App) {
+        // presses are seen every frame, however many ticks it runs
+        app.init_resource::<SmashPress>()
+            .add_event::<Smashed>()
+            .add_systems(
+                Update,
+                press_smash
+                    .run_if(crate::net::keyboard_is_local)
+                    .run_if(not(crate::kiosk::attracting)),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    // volleyball has its own way of returning, see `volleyball.rs`
+                    smash_returns.run_if(not(rules::variant_is(rules::Variant::Volleyball))),
+                    age_press,
+                )
+                    .chain()
+                    .in_set(TickSet::Effects),
+            );
+    }
+}
+
+fn press_smash(actions: Res<ActionState>, settings: Res<Settings>, mut press: ResMut<SmashPress>) {
+    if actions.smash {
+        press.0.press(settings.gameplay.input_buffer);
+    }
+}
+
+pub fn smash_returns(
+    mut collision_events: EventReader<CollisionEvent>,
+    ends: Res<Ends>,
+    mut press: ResMut<SmashPress>,
+    mut balls: Query<&mut crate::entities::Velocity>,
+    mut smashes: EventWriter<Smashed>,
+) {
+    for ev in collision_events.read() {
+        let CollisionEvent::Paddle(ball, side, _) = ev else {
+            continue;
+        };
+        if ends.at(*side) != Competitor::Player || !press.0.take() {
+            continue;
+        }
+        if let Ok(mut velocity) = balls.get_mut(*ball) {
+            let speed = (velocity.length() * SMASH_SPEEDUP).min(MAX_RAMPED_BALL_SPEED);
+            velocity.0 = velocity.normalize_or_zero() * speed;
+            smashes.send(Smashed);
+        }
+    }
+}
+
+fn age_press(mut press: ResMut<SmashPress>) {
+    press.0.tick();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presses_last_for_the_window() {
+        let mut buffer = InputBuffer::default();
+        buffer.press(2);
+        buffer.tick();
+        assert!(buffer.take());
+        assert!(!buffer.take());
+
+        buffer.press(2);
+        buffer.tick();
+        buffer.tick();
+        assert!(!buffer.take());
+
+        // with buffering off it's only good for the next tick
+        buffer.press(0);
+        assert!(buffer.take());
+    }
+}
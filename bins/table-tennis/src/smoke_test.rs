@@ -0,0 +1,109 @@
+// A quick check that the game still plays after a refactor:
+//
+//   table-tennis --smoke-test
+//
+// Plays a few hundred ticks of table tennis headless with the computer on both sides (the
+// player's paddle is the kiosk's stand-in, see `kiosk.rs`, letting rallies go after a
+// return so there are points), and exits with 0 if no system panicked and somebody scored,
+// or 1 if not.
+use std::{panic, time::Duration};
+
+use bevy::{ecs::schedule::ExecutorKind, log::LogPlugin, prelude::*, time::TimeUpdateStrategy};
+
+use crate::{
+    actions::{ActionSet, ActionState},
+    build_app,
+    constants::*,
+    kiosk,
+    mutators::MatchState,
+    net::{self, NetOptions},
+    personality::Rally,
+    rules::{MatchRules, Opponent},
+    PointScored,
+};
+
+#[derive(Resource, Debug, Default)]
+struct PointsScored(usize);
+
+pub fn requested() -> bool {
+    std::env::args()
+        .nth(1)
+        .is_some_and(|arg| arg == "--smoke-test")
+}
+
+// The exit status
+pub fn run() -> i32 {
+    match panic::catch_unwind(play) {
+        Ok(points) if points > 0 => {
+            println!("ok: {points} points in {SMOKE_TEST_TICKS} ticks");
+            0
+        }
+        Ok(_) => {
+            println!("failed: nobody scored in {SMOKE_TEST_TICKS} ticks");
+            1
+        }
+        // the panic's already been printed
+        Err(_) => {
+            println!("failed: a system panicked");
+            1
+        }
+    }
+}
+
+// How many points were scored
+fn play() -> usize {
+    let mut app = build_app(
+        net::headless_plugins().disable::<LogPlugin>(),
+        NetOptions::default(),
+    );
+    // one fixed tick every update, like the golden replays
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+        1.0 / 64.0,
+    )))
+    .insert_resource(MatchRules {
+        opponent: Opponent::Computer,
+        ..default()
+    })
+    .init_resource::<PointsScored>()
+    .add_systems(
+        PreUpdate,
+        (kiosk::steer, lapse)
+            .chain()
+            .in_set(ActionSet::StandIns)
+            .run_if(in_state(MatchState::Playing)),
+    )
+    .add_systems(Last, count_points);
+    for (_, schedule) in app.world.resource_mut::<Schedules>().iter_mut() {
+        schedule.set_executor_kind(ExecutorKind::SingleThreaded);
+    }
+
+    app.update();
+    app.world
+        .resource_mut::<NextState<MatchState>>()
+        .set(MatchState::Playing);
+    for _ in 0..SMOKE_TEST_TICKS {
+        app.update();
+    }
+    app.world.resource::<PointsScored>().0
+}
+
+// Left to itself the stand-in never misses, so a rally that's gone on long enough it lets go
+fn lapse(rally: Res<Rally>, mut actions: ResMut<ActionState>) {
+    if rally.returns >= SMOKE_TEST_RALLY_RETURNS {
+        actions.move_axis = Vec2::ZERO;
+    }
+}
+
+fn count_points(mut points: EventReader<PointScored>, mut scored: ResMut<PointsScored>) {
+    scored.0 += points.read().count();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_smoke_test_passes() {
+        assert_eq!(run(), 0);
+    }
+}
@@ -0,0 +1,337 @@
+// A few seconds of history: where the balls and paddles were and what the score was, every
+// tick. Anything that needs to look back (instant replays) or go back reads `SnapshotBuffer`,
+// and `Restore` puts the court back the way a snapshot has it.
+
+use std::collections::VecDeque;
+
+use bevy::{
+    ecs::system::{Command, RunSystemOnce},
+    prelude::*,
+};
+
+use crate::{
+    constants::*,
+    entities::{self, PerCompetitor},
+    mutators::MatchState,
+    serve::Serving,
+    settings::Settings,
+    Scoreboard, TickSet,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BallState {
+    pub position: Vec3,
+    pub velocity: Vec2,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    pub balls: Vec<BallState>,
+    // the whole transform, power-ups change paddles' sizes
+    pub paddles: Vec<(Entity, Transform)>,
+    pub score: PerCompetitor<usize>,
+}
+
+// The most recent snapshots, oldest first, up to `capacity` of them
+#[derive(Resource, Debug)]
+pub struct SnapshotBuffer {
+    snapshots: VecDeque<Snapshot>,
+    capacity: usize,
+}
+
+impl Default for SnapshotBuffer {
+    fn default() -> Self {
+        Self::new(SNAPSHOT_BUFFER_TICKS)
+    }
+}
+
+impl SnapshotBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            snapshots: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    // Keeps this tick's state, forgetting the oldest if it's full
+    pub fn push(
+        &mut self,
+        balls: Vec<BallState>,
+        paddles: Vec<(Entity, Transform)>,
+        score: PerCompetitor<usize>,
+    ) {
+        if self.snapshots.len() >= self.capacity {
+            self.snapshots.pop_front();
+        }
+        self.snapshots.push_back(Snapshot {
+            balls,
+            paddles,
+            score,
+        });
+    }
+
+    // the last `ticks` of them, oldest first
+    pub fn recent(&self, ticks: usize) -> impl Iterator<Item = &Snapshot> {
+        self.snapshots
+            .iter()
+            .skip(self.snapshots.len().saturating_sub(ticks))
+    }
+
+    pub fn latest(&self) -> Option<&Snapshot> {
+        self.snapshots.back()
+    }
+
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+    }
+}
+
+// Puts the paddles, balls and score back as the snapshot has them. The paddles are put back
+// still, and balls that are already there are moved rather than respawned, so they keep the
+// skin and anything else they've picked up.
+pub struct Restore(pub Snapshot);
+
+impl Command for Restore {
+    fn apply(self, world: &mut World) {
+        world.run_system_once_with(self.0, restore);
+    }
+}
+
+fn restore(
+    In(snapshot): In<Snapshot>,
+    mut commands: Commands,
+    mut scores: ResMut<Scoreboard>,
+    settings: Res<Settings>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut paddles: Query<(&mut Transform, &mut entities::PaddleVelocity), With<entities::Paddle>>,
+    mut balls: Query<
+        (Entity, &mut Transform, &mut entities::Velocity),
+        (With<entities::Ball>, Without<entities::Paddle>),
+    >,
+) {
+    for (entity, transform) in &snapshot.paddles {
+        if let Ok((mut paddle, mut velocity)) = paddles.get_mut(*entity) {
+            *paddle = *transform;
+            velocity.0 = 0.0;
+        }
+    }
+    **scores = snapshot.score;
+
+    let mut snapshot_balls = snapshot.balls.iter();
+    for (entity, mut transform, mut velocity) in &mut balls {
+        match snapshot_balls.next() {
+            // one still being served goes straight away, as it was saved
+            Some(ball) => {
+                transform.translation = ball.position;
+                transform.scale = BALL_SIZE;
+                velocity.0 = ball.velocity;
+                commands.entity(entity).remove::<Serving>();
+            }
+            None => commands.entity(entity).despawn(),
+        }
+    }
+    // the rest are shaped like any other new ball, see `cosmetics::shape_balls`
+    for ball in snapshot_balls {
+        let (mesh, marker, _, stall) = crate::spawn_ball(
+            &mut materials,
+            &mut meshes,
+            settings.theme.palette().ball,
+            ball.position,
+            Vec2::X,
+        );
+        commands.spawn((mesh, marker, entities::Velocity(ball.velocity), stall));
+    }
+}
+
+pub struct SnapshotPlugin;
+
+impl Plugin for SnapshotPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SnapshotBuffer>()
+            // a new game's history starts from nothing
+            .add_systems(OnEnter(MatchState::Playing), clear_snapshots)
+            .add_systems(FixedUpdate, take_snapshot.in_set(TickSet::Effects));
+    }
+}
+
+fn clear_snapshots(mut buffer: ResMut<SnapshotBuffer>) {
+    buffer.clear();
+}
+
+fn take_snapshot(
+    mut buffer: ResMut<SnapshotBuffer>,
+    scores: Res<Scoreboard>,
+    paddles: Query<(Entity, &Transform), With<entities::Paddle>>,
+    balls: Query<(&Transform, &entities::Velocity, Option<&Serving>), With<entities::Ball>>,
+) {
+    buffer.push(
+        balls
+            .iter()
+            // a ball waiting to be served is put back already on its way
+            .map(|(transform, velocity, serving)| BallState {
+                position: transform.translation,
+                velocity: serving.map_or(velocity.0, Serving::velocity),
+            })
+            .collect(),
+        paddles
+            .iter()
+            .map(|(entity, transform)| (entity, *transform))
+            .collect(),
+        **scores,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn push_tick(buffer: &mut SnapshotBuffer, x: f32) {
+        let ball = BallState {
+            position: Vec3::new(x, 0.0, 0.0),
+            velocity: Vec2::X,
+        };
+        buffer.push(vec![ball], Vec::new(), PerCompetitor::default());
+    }
+
+    #[test]
+    fn only_the_most_recent_ticks_are_kept() {
+        let mut buffer = SnapshotBuffer::new(3);
+        for x in 0..5 {
+            push_tick(&mut buffer, x as f32);
+        }
+        let xs = |ticks| {
+            buffer
+                .recent(ticks)
+                .map(|snapshot| snapshot.balls[0].position.x)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(xs(10), [2.0, 3.0, 4.0]);
+        assert_eq!(xs(2), [3.0, 4.0]);
+        assert_eq!(buffer.latest().unwrap().balls[0].position.x, 4.0);
+    }
+
+    #[test]
+    fn restoring_puts_the_balls_and_score_back() {
+        let mut world = World::new();
+        world.init_resource::<Assets<Mesh>>();
+        world.init_resource::<Assets<ColorMaterial>>();
+        world.init_resource::<Settings>();
+        world.init_resource::<Scoreboard>();
+        let kept = world
+            .spawn((
+                entities::Ball,
+                Transform::default(),
+                entities::Velocity(Vec2::X),
+            ))
+            .id();
+        world.spawn((
+            entities::Ball,
+            Transform::default(),
+            entities::Velocity(Vec2::X),
+        ));
+        let paddle = world
+            .spawn((
+                entities::Paddle,
+                Transform::default(),
+                entities::PaddleVelocity(300.0),
+            ))
+            .id();
+
+        let snapshot = Snapshot {
+            balls: vec![BallState {
+                position: Vec3::new(10.0, 20.0, 0.0),
+                velocity: Vec2::new(-100.0, 0.0),
+            }],
+            paddles: vec![(paddle, Transform::from_xyz(0.0, 50.0, 0.0))],
+            score: PerCompetitor {
+                player: 3,
+                computer: 1,
+            },
+        };
+        Restore(snapshot).apply(&mut world);
+
+        let balls: Vec<_> = world
+            .query_filtered::<(&Transform, &entities::Velocity), With<entities::Ball>>()
+            .iter(&world)
+            .map(|(transform, velocity)| (transform.translation, velocity.0))
+            .collect();
+        assert_eq!(
+            balls,
+            [(Vec3::new(10.0, 20.0, 0.0), Vec2::new(-100.0, 0.0))]
+        );
+        // the ball that was already there is the one moved
+        assert!(world.get::<entities::Ball>(kept).is_some());
+        assert_eq!(world.get::<Transform>(paddle).unwrap().translation.y, 50.0);
+        assert_eq!(
+            world.get::<entities::PaddleVelocity>(paddle).unwrap().0,
+            0.0
+        );
+        assert_eq!(world.resource::<Scoreboard>().player, 3);
+    }
+
+    #[test]
+    fn a_restored_snapshot_is_taken_again_the_same() {
+        let mut app = App::new();
+        app.init_resource::<Assets<Mesh>>()
+            .init_resource::<Assets<ColorMaterial>>()
+            .init_resource::<Settings>()
+            .init_resource::<Scoreboard>()
+            .init_resource::<SnapshotBuffer>()
+            .add_systems(Update, take_snapshot);
+        let paddle = app
+            .world
+            .spawn((
+                entities::Paddle,
+                Transform::from_xyz(-390.0, 20.0, 0.0).with_scale(PADDLE_SIZE),
+                entities::PaddleVelocity(0.0),
+            ))
+            .id();
+        let flying = app
+            .world
+            .spawn((
+                entities::Ball,
+                Transform::from_xyz(100.0, -50.0, 0.0).with_scale(BALL_SIZE),
+                entities::Velocity(Vec2::new(-300.0, 120.0)),
+            ))
+            .id();
+        let mut transform = Transform::from_xyz(0.0, 80.0, 0.0).with_scale(BALL_SIZE);
+        let mut velocity = entities::Velocity(Vec2::new(BALL_SPEED, 0.0));
+        let serving = Serving::hold(0.5, &mut transform, &mut velocity);
+        app.world
+            .spawn((entities::Ball, transform, velocity, serving));
+        app.world.resource_mut::<Scoreboard>().computer = 2;
+
+        let taken = |app: &mut App| {
+            app.update();
+            let mut snapshot = app
+                .world
+                .resource::<SnapshotBuffer>()
+                .latest()
+                .unwrap()
+                .clone();
+            // balls come back in whatever order they're queried in
+            snapshot
+                .balls
+                .sort_by(|a, b| a.position.x.total_cmp(&b.position.x));
+            snapshot
+        };
+        let saved = taken(&mut app);
+
+        // then the point plays on: a ball goes out, the paddle moves and grows and it's scored
+        app.world.despawn(flying);
+        let mut moved = app.world.get_mut::<Transform>(paddle).unwrap();
+        moved.translation.y = -200.0;
+        moved.scale.y *= 2.0;
+        app.world.resource_mut::<Scoreboard>().player = 1;
+
+        Restore(saved.clone()).apply(&mut app.world);
+        assert_eq!(taken(&mut app), saved);
+        assert!(app
+            .world
+            .query::<&Serving>()
+            .iter(&app.world)
+            .next()
+            .is_none());
+    }
+}
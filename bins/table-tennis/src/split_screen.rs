@@ -0,0 +1,158 @@
+// Split screen: each end of the court gets half the window to itself, a camera zoomed in so
+// the court's full height fills it and its end wall sits at the outside edge. The court's
+// wider than either half can show, so each side sees its own end and a little past the net,
+// with the minimap for the rest. It's the one simulation drawn twice.
+//
+// While it's on the main camera draws only the menus and overlays, over the top of both.
+use bevy::{
+    core_pipeline::clear_color::ClearColorConfig,
+    prelude::*,
+    render::{
+        camera::{ScalingMode, Viewport},
+        view::RenderLayers,
+    },
+    window::PrimaryWindow,
+};
+
+use crate::{
+    constants::*, entities::Side, minimap::MinimapCamera, mutators::MatchState,
+    orientation::Orientation, settings::Settings,
+};
+
+// One end's half of the window
+#[derive(Component)]
+pub struct SplitCamera(Side);
+
+// Whether the window's split right now, only during play in landscape
+#[derive(Resource, Debug, Default)]
+pub struct SplitScreen {
+    pub active: bool,
+}
+
+pub struct SplitScreenPlugin;
+
+impl Plugin for SplitScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SplitScreen>()
+            .add_systems(Startup, spawn_split_cameras)
+            .add_systems(
+                Update,
+                (
+                    split_window,
+                    place_split_cameras.run_if(|split: Res<SplitScreen>| split.active),
+                )
+                    .chain(),
+            );
+    }
+}
+
+// Where a half's camera sits so `side`'s end wall is `view_width / 2` out from it
+fn camera_x(side: Side, view_width: f32) -> f32 {
+    side.inset_x(view_width / 2.0 - WALL_THICKNESS / 2.0 - SPLIT_SCREEN_MARGIN)
+}
+
+fn spawn_split_cameras(mut commands: Commands) {
+    for (order, side) in Side::ALL.into_iter().enumerate() {
+        commands.spawn((
+            Camera2dBundle {
+                camera: Camera {
+                    // under the main camera, which draws the menus over them
+                    order: order as isize - Side::ALL.len() as isize,
+                    is_active: false,
+                    ..default()
+                },
+                projection: OrthographicProjection {
+                    scaling_mode: ScalingMode::FixedVertical(
+                        TOP_WALL - BOTTOM_WALL + WALL_THICKNESS + 2.0 * SPLIT_SCREEN_MARGIN,
+                    ),
+                    ..default()
+                },
+                ..default()
+            },
+            UiCameraConfig { show_ui: false },
+            SplitCamera(side),
+        ));
+    }
+}
+
+// Turns the split on or off, swapping what the main camera draws to match
+fn split_window(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    orientation: Res<Orientation>,
+    state: Res<State<MatchState>>,
+    mut split: ResMut<SplitScreen>,
+    mut mains: Query<(Entity, &mut Camera2d), (Without<SplitCamera>, Without<MinimapCamera>)>,
+    mut halves: Query<&mut Camera, With<SplitCamera>>,
+) {
+    let active = settings.split_screen
+        && *orientation == Orientation::Landscape
+        && *state.get() == MatchState::Playing;
+    if split.active == active {
+        return;
+    }
+    split.active = active;
+
+    for (entity, mut camera) in &mut mains {
+        if active {
+            // nothing's on this layer, so it's only the UI, over the halves already drawn
+            camera.clear_color = ClearColorConfig::None;
+            commands
+                .entity(entity)
+                .insert(RenderLayers::layer(SPLIT_SCREEN_UI_LAYER));
+        } else {
+            camera.clear_color = ClearColorConfig::Default;
+            commands.entity(entity).remove::<RenderLayers>();
+        }
+    }
+    for mut camera in &mut halves {
+        camera.is_active = active;
+    }
+}
+
+fn place_split_cameras(
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut halves: Query<(
+        &SplitCamera,
+        &mut Camera,
+        &mut Transform,
+        &OrthographicProjection,
+    )>,
+) {
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    let half = UVec2::new(window.physical_width() / 2, window.physical_height());
+    if half.min_element() == 0 {
+        return;
+    }
+
+    for (split, mut camera, mut transform, projection) in &mut halves {
+        camera.viewport = Some(Viewport {
+            physical_position: match split.0 {
+                Side::Left => UVec2::ZERO,
+                Side::Right => UVec2::new(half.x, 0),
+            },
+            physical_size: half,
+            ..default()
+        });
+        transform.translation.x = camera_x(split.0, projection.area.width());
+        transform.translation.y = 0.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_half_sees_its_own_end() {
+        let width = 500.0;
+        let left = camera_x(Side::Left, width);
+        // the end wall's just in view at the outside edge
+        let outer_edge = left - width / 2.0;
+        assert!(outer_edge < LEFT_WALL - WALL_THICKNESS / 2.0);
+        assert!(outer_edge > LEFT_WALL - WALL_THICKNESS / 2.0 - 2.0 * SPLIT_SCREEN_MARGIN);
+        assert_eq!(camera_x(Side::Right, width), -left);
+    }
+}
@@ -0,0 +1,340 @@
+// Numbers kept over a match for the game over screen
+use bevy::prelude::*;
+
+use crate::{
+    constants::*,
+    entities::{Competitor, PerCompetitor},
+    mutators::MatchState,
+    rules::Ends,
+    CollisionEvent, Contact, GoalEvent, PointScored, TickSet,
+};
+#[cfg(feature = "capture")]
+use crate::{
+    personality::Rally,
+    rules::{Games, MatchClock},
+};
+
+// How many times something landed along a paddle or goal, counted in buckets from the top
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Heatmap([usize; HEATMAP_BUCKETS]);
+
+impl Heatmap {
+    fn record(&mut self, contact: Contact) {
+        let from_top = (1.0 - contact.0) / 2.0;
+        let bucket = (from_top * HEATMAP_BUCKETS as f32) as usize;
+        self.0[bucket.min(HEATMAP_BUCKETS - 1)] += 1;
+    }
+
+    // only saved with `capture`, see `export.rs`
+    #[cfg(feature = "capture")]
+    pub fn counts(&self) -> [usize; HEATMAP_BUCKETS] {
+        self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|count| *count == 0)
+    }
+
+    // each bucket's count as a fraction of the busiest one's
+    fn heat(&self) -> impl Iterator<Item = f32> + '_ {
+        let busiest = self.0.iter().copied().max().unwrap_or(0).max(1);
+        self.0
+            .iter()
+            .map(move |count| *count as f32 / busiest as f32)
+    }
+}
+
+// Only the running score's kept without `capture`, the rest is just for `export.rs`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PointRecord {
+    // counting from 0
+    #[cfg(feature = "capture")]
+    pub game: usize,
+    #[cfg(feature = "capture")]
+    pub scorer: Competitor,
+    #[cfg(feature = "capture")]
+    pub points: usize,
+    // the points each competitor had won this match, across every game, after this one
+    pub score: PerCompetitor<usize>,
+    // times the ball was returned in the rally
+    #[cfg(feature = "capture")]
+    pub returns: usize,
+    // seconds into the match's play, and since the last point
+    #[cfg(feature = "capture")]
+    pub at: f32,
+    #[cfg(feature = "capture")]
+    pub duration: f32,
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct MatchStats {
+    pub points: Vec<PointRecord>,
+    // where on their paddle each competitor returned the ball
+    pub returns: PerCompetitor<Heatmap>,
+    // where the ball got past each competitor
+    pub conceded: PerCompetitor<Heatmap>,
+}
+
+impl MatchStats {
+    // how far the player was ahead (or behind) at the start and after each point
+    pub fn differential(&self) -> Vec<i32> {
+        std::iter::once(0)
+            .chain(
+                self.points
+                    .iter()
+                    .map(|point| point.score.player as i32 - point.score.computer as i32),
+            )
+            .collect()
+    }
+}
+
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MatchStats>()
+            .add_systems(OnExit(MatchState::PreMatch), reset_stats)
+            // a rematch goes straight back to playing
+            .add_systems(OnExit(MatchState::GameOver), reset_stats)
+            .add_systems(
+                FixedUpdate,
+                (
+                    record_points
+                        .after(crate::rules::award_points)
+                        .in_set(TickSet::Scoring),
+                    record_contacts.in_set(TickSet::Effects),
+                ),
+            );
+    }
+}
+
+fn reset_stats(mut stats: ResMut<MatchStats>) {
+    *stats = MatchStats::default();
+}
+
+impl MatchStats {
+    // another `points` to `scorer`
+    fn record(&mut self, scorer: Competitor, points: usize) {
+        let mut score = self
+            .points
+            .last()
+            .map(|point| point.score)
+            .unwrap_or_default();
+        score[scorer] += points;
+        self.points.push(PointRecord { score, ..default() });
+    }
+}
+
+// The rally hasn't been reset for the next one yet, that's done after scoring
+fn record_points(
+    mut points: EventReader<PointScored>,
+    #[cfg(feature = "capture")] games: Res<Games>,
+    #[cfg(feature = "capture")] rally: Res<Rally>,
+    #[cfg(feature = "capture")] clock: Res<MatchClock>,
+    mut stats: ResMut<MatchStats>,
+) {
+    for point in points.read() {
+        #[cfg(feature = "capture")]
+        let last = stats.points.last().map_or(0.0, |point| point.at);
+        stats.record(point.scorer, point.points);
+
+        #[cfg(feature = "capture")]
+        if let Some(record) = stats.points.last_mut() {
+            record.game = games.played();
+            record.scorer = point.scorer;
+            record.points = point.points;
+            record.returns = rally.returns;
+            record.at = clock.elapsed;
+            record.duration = clock.elapsed - last;
+        }
+    }
+}
+
+fn record_contacts(
+    mut collision_events: EventReader<CollisionEvent>,
+    mut goal_events: EventReader<GoalEvent>,
+    ends: Res<Ends>,
+    mut stats: ResMut<MatchStats>,
+) {
+    for ev in collision_events.read() {
+        if let CollisionEvent::Paddle(_, side, contact) = ev {
+            stats.returns[ends.at(*side)].record(*contact);
+        }
+    }
+    for ev in goal_events.read() {
+        stats.conceded[ends.at(ev.defender)].record(ev.crossing);
+    }
+}
+
+// The lines of a step graph of `values` filling `size`, zero through the middle and up being
+// positive. Each is a rect from its top left, in pixels.
+fn graph_lines(values: &[i32], size: Vec2) -> Vec<Rect> {
+    let furthest = values
+        .iter()
+        .map(|value| value.abs())
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let y = |value: i32| size.y / 2.0 * (1.0 - value as f32 / furthest as f32);
+    let step = size.x / values.len().max(1) as f32;
+    let half = SCORE_GRAPH_LINE_WIDTH / 2.0;
+
+    let mut lines = Vec::new();
+    for (i, value) in values.iter().enumerate() {
+        let x = i as f32 * step;
+        lines.push(Rect::new(x, y(*value) - half, x + step, y(*value) + half));
+        // up or down from the last value
+        if let Some(last) = i.checked_sub(1).map(|last| values[last]) {
+            let (top, bottom) = (y(last).min(y(*value)), y(last).max(y(*value)));
+            lines.push(Rect::new(x - half, top - half, x + half, bottom + half));
+        }
+    }
+    lines
+}
+
+fn line(rect: Rect, color: Color) -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            left: Val::Px(rect.min.x),
+            top: Val::Px(rect.min.y),
+            width: Val::Px(rect.width()),
+            height: Val::Px(rect.height()),
+            ..default()
+        },
+        background_color: color.into(),
+        ..default()
+    }
+}
+
+// How the score went over the match, above the middle while the player was ahead
+pub fn spawn_score_graph(parent: &mut ChildBuilder, stats: &MatchStats) {
+    if stats.points.is_empty() {
+        return;
+    }
+
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Px(SCORE_GRAPH_SIZE.x),
+                height: Val::Px(SCORE_GRAPH_SIZE.y),
+                margin: UiRect::vertical(Val::Px(SCORE_GRAPH_SIZE.y / 4.0)),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|graph| {
+            let middle = SCORE_GRAPH_SIZE.y / 2.0;
+            graph.spawn(line(
+                Rect::new(0.0, middle - 0.5, SCORE_GRAPH_SIZE.x, middle + 0.5),
+                SCORE_GRAPH_AXIS_COLOR,
+            ));
+            for rect in graph_lines(&stats.differential(), SCORE_GRAPH_SIZE) {
+                graph.spawn(line(rect, SCORE_GRAPH_COLOR));
+            }
+        });
+}
+
+// A column of cells, hotter where more landed, under `label`
+fn spawn_heatmap(parent: &mut ChildBuilder, label: &str, heatmap: &Heatmap) {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                margin: UiRect::horizontal(Val::Px(HEATMAP_CELL_SIZE.x / 2.0)),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|column| {
+            column.spawn(TextBundle::from_section(
+                label,
+                TextStyle {
+                    font_size: SETTINGS_MENU_FONT_SIZE,
+                    color: Color::GRAY,
+                    ..default()
+                },
+            ));
+            for heat in heatmap.heat() {
+                let [r, g, b, _] = HEATMAP_COLD_COLOR.as_rgba_f32();
+                let [hot_r, hot_g, hot_b, _] = HEATMAP_HOT_COLOR.as_rgba_f32();
+                column.spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Px(HEATMAP_CELL_SIZE.x),
+                        height: Val::Px(HEATMAP_CELL_SIZE.y),
+                        ..default()
+                    },
+                    background_color: Color::rgb(
+                        r + (hot_r - r) * heat,
+                        g + (hot_g - g) * heat,
+                        b + (hot_b - b) * heat,
+                    )
+                    .into(),
+                    ..default()
+                });
+            }
+        });
+}
+
+// Where the player's returns met their paddle and where the ball got past them, to show up
+// the spots they struggle to reach
+pub fn spawn_heatmaps(parent: &mut ChildBuilder, stats: &MatchStats) {
+    let returns = &stats.returns[Competitor::Player];
+    let conceded = &stats.conceded[Competitor::Player];
+    if returns.is_empty() && conceded.is_empty() {
+        return;
+    }
+
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                margin: UiRect::bottom(Val::Px(SCORE_GRAPH_SIZE.y / 4.0)),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|row| {
+            spawn_heatmap(row, "your returns", returns);
+            spawn_heatmap(row, "past you", conceded);
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::Competitor;
+
+    #[test]
+    fn differential_follows_the_points() {
+        let mut stats = MatchStats::default();
+        stats.record(Competitor::Player, 1);
+        stats.record(Competitor::Computer, 1);
+        stats.record(Competitor::Computer, 2);
+        assert_eq!(stats.differential(), vec![0, 1, 0, -2]);
+    }
+
+    #[test]
+    fn heatmap_buckets_from_the_top() {
+        let mut heatmap = Heatmap::default();
+        for contact in [1.0, 0.9, -1.0, 0.0] {
+            heatmap.record(Contact(contact));
+        }
+        assert_eq!(heatmap.0[0], 2);
+        assert_eq!(heatmap.0[HEATMAP_BUCKETS / 2], 1);
+        assert_eq!(heatmap.0[HEATMAP_BUCKETS - 1], 1);
+        assert_eq!(heatmap.heat().next(), Some(1.0));
+    }
+
+    #[test]
+    fn graph_spans_its_size() {
+        let size = Vec2::new(100.0, 50.0);
+        let lines = graph_lines(&[0, 2, -2], size);
+        // a step for each value and a riser between each
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines[0].center().y, 25.0);
+        assert_eq!(lines[1].center().y, 0.0);
+        assert_eq!(lines[3].center().y, 50.0);
+        assert_eq!(lines[3].max.x, 100.0);
+    }
+}
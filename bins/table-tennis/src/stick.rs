@@ -0,0 +1,292 @@
+// A gamepad as another way of filling in the player's actions: the left stick moves (once
+// it's past the dead zone, through the response curve and sensitivity in the settings),
+// south smashes and start pauses. The settings menu shows where the stick is before and
+// after all that, so the dead zone can be fitted to a worn stick.
+use bevy::prelude::*;
+use bevy_common::screens::AddScreen;
+
+use crate::{
+    actions::{self, ActionSet, ActionState},
+    constants::*,
+    settings::{MenuState, Settings, Stick},
+};
+
+// How far the paddle's pushed for how far past the dead zone the stick is
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ResponseCurve {
+    #[default]
+    Linear,
+    // gentle near the middle for fine aim, still full speed at the edge
+    Cubic,
+}
+
+impl ResponseCurve {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ResponseCurve::Linear => "Linear",
+            ResponseCurve::Cubic => "Cubic",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            ResponseCurve::Linear => ResponseCurve::Cubic,
+            ResponseCurve::Cubic => ResponseCurve::Linear,
+        }
+    }
+
+    fn apply(&self, amount: f32) -> f32 {
+        match self {
+            ResponseCurve::Linear => amount,
+            ResponseCurve::Cubic => amount * amount * amount,
+        }
+    }
+}
+
+// Where the stick is as the gamepad reports it, and what that's turned into
+pub fn process(raw: Vec2, stick: &Stick) -> Vec2 {
+    let length = raw.length();
+    if length <= stick.dead_zone {
+        return Vec2::ZERO;
+    }
+    // past the dead zone, stretched back out to the whole way
+    let amount = ((length - stick.dead_zone) / (1.0 - stick.dead_zone)).min(1.0);
+    let amount = stick.curve.apply(amount) * stick.sensitivity;
+    (raw / length * amount).clamp(Vec2::NEG_ONE, Vec2::ONE)
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct StickReading {
+    connected: bool,
+    raw: Vec2,
+    processed: Vec2,
+}
+
+#[derive(Component)]
+struct StickVisualizer;
+
+#[derive(Component)]
+struct DeadZoneBox;
+
+// the processed position, or the raw one
+#[derive(Component)]
+struct StickDot {
+    processed: bool,
+}
+
+#[derive(Component)]
+struct StickCaption;
+
+pub struct StickPlugin;
+
+impl Plugin for StickPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<StickReading>()
+            .add_systems(
+                PreUpdate,
+                read_stick
+                    .after(actions::read_keyboard)
+                    .in_set(ActionSet::Devices),
+            )
+            .add_screen::<StickVisualizer, _>(MenuState::Settings, spawn_visualizer)
+            .add_systems(
+                Update,
+                update_visualizer.run_if(in_state(MenuState::Settings)),
+            );
+    }
+}
+
+// Where `gamepad`'s left stick is, before any processing
+fn stick_position(gamepad: Gamepad, axes: &Axis<GamepadAxis>) -> Vec2 {
+    let axis = |axis_type| {
+        axes.get(GamepadAxis::new(gamepad, axis_type))
+            .unwrap_or(0.0)
+    };
+    Vec2::new(
+        axis(GamepadAxisType::LeftStickX),
+        axis(GamepadAxisType::LeftStickY),
+    )
+}
+
+// Everything `gamepad` is asking for
+pub fn gamepad_actions(
+    gamepad: Gamepad,
+    axes: &Axis<GamepadAxis>,
+    buttons: &Input<GamepadButton>,
+    stick: &Stick,
+) -> ActionState {
+    let pressed = |button_type| buttons.just_pressed(GamepadButton::new(gamepad, button_type));
+    ActionState {
+        move_axis: process(stick_position(gamepad, axes), stick),
+        smash: pressed(GamepadButtonType::South),
+        pause: pressed(GamepadButtonType::Start),
+    }
+}
+
+// The first gamepad plugged in, and only while the keyboard isn't moving the paddle itself
+pub fn read_stick(
+    gamepads: Res<Gamepads>,
+    axes: Res<Axis<GamepadAxis>>,
+    buttons: Res<Input<GamepadButton>>,
+    settings: Res<Settings>,
+    mut reading: ResMut<StickReading>,
+    mut actions: ResMut<ActionState>,
+) {
+    let Some(gamepad) = gamepads.iter().next() else {
+        *reading = StickReading::default();
+        return;
+    };
+    let gamepad_actions = gamepad_actions(gamepad, &axes, &buttons, &settings.stick);
+    *reading = StickReading {
+        connected: true,
+        raw: stick_position(gamepad, &axes),
+        processed: gamepad_actions.move_axis,
+    };
+
+    if actions.move_axis == Vec2::ZERO {
+        actions.move_axis = gamepad_actions.move_axis;
+    }
+    actions.smash |= gamepad_actions.smash;
+    actions.pause |= gamepad_actions.pause;
+}
+
+fn dot(processed: bool) -> impl Bundle {
+    let color = if processed {
+        Color::YELLOW
+    } else {
+        Color::GRAY
+    };
+    (
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Px(STICK_DOT_SIZE),
+                height: Val::Px(STICK_DOT_SIZE),
+                margin: UiRect::all(Val::Px(-STICK_DOT_SIZE / 2.0)),
+                ..default()
+            },
+            background_color: color.into(),
+            ..default()
+        },
+        StickDot { processed },
+    )
+}
+
+fn spawn_visualizer(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    right: Val::Px(STICK_VISUALIZER_PADDING),
+                    bottom: Val::Px(STICK_VISUALIZER_PADDING),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                z_index: ZIndex::Global(SETTINGS_MENU_Z_INDEX + 1),
+                ..default()
+            },
+            StickVisualizer,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Px(STICK_VISUALIZER_SIZE),
+                        height: Val::Px(STICK_VISUALIZER_SIZE),
+                        ..default()
+                    },
+                    background_color: STICK_VISUALIZER_COLOR.into(),
+                    ..default()
+                })
+                .with_children(|panel| {
+                    panel.spawn((
+                        NodeBundle {
+                            style: Style {
+                                position_type: PositionType::Absolute,
+                                ..default()
+                            },
+                            background_color: STICK_DEAD_ZONE_COLOR.into(),
+                            ..default()
+                        },
+                        DeadZoneBox,
+                    ));
+                    panel.spawn(dot(false));
+                    panel.spawn(dot(true));
+                });
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: SETTINGS_MENU_FONT_SIZE * 0.75,
+                        color: Color::GRAY,
+                        ..default()
+                    },
+                ),
+                StickCaption,
+            ));
+        });
+}
+
+fn update_visualizer(
+    settings: Res<Settings>,
+    reading: Res<StickReading>,
+    mut dead_zones: Query<&mut Style, (With<DeadZoneBox>, Without<StickDot>)>,
+    mut dots: Query<(&mut Style, &StickDot)>,
+    mut captions: Query<&mut Text, With<StickCaption>>,
+) {
+    let dead_zone = settings.stick.dead_zone * 100.0;
+    for mut style in &mut dead_zones {
+        style.width = Val::Percent(dead_zone);
+        style.height = Val::Percent(dead_zone);
+        style.left = Val::Percent(50.0 - dead_zone / 2.0);
+        style.top = Val::Percent(50.0 - dead_zone / 2.0);
+    }
+
+    // from the top left, with up being up
+    for (mut style, dot) in &mut dots {
+        let at = if dot.processed {
+            reading.processed
+        } else {
+            reading.raw
+        };
+        style.left = Val::Percent((at.x + 1.0) * 50.0);
+        style.top = Val::Percent((1.0 - at.y) * 50.0);
+    }
+
+    for mut text in &mut captions {
+        text.sections[0].value = if reading.connected {
+            format!(
+                "stick {:.2}, {:.2}\npaddle {:.2}, {:.2}",
+                reading.raw.x, reading.raw.y, reading.processed.x, reading.processed.y
+            )
+        } else {
+            "no controller".to_string()
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sticks_are_processed_past_the_dead_zone() {
+        let mut stick = Stick {
+            dead_zone: 0.2,
+            curve: ResponseCurve::Linear,
+            sensitivity: 1.0,
+        };
+        assert_eq!(process(Vec2::new(0.1, 0.1), &stick), Vec2::ZERO);
+        assert!((process(Vec2::Y, &stick) - Vec2::Y).length() < 1e-6);
+        assert!((process(Vec2::new(0.0, 0.6), &stick).y - 0.5).abs() < 1e-6);
+
+        stick.curve = ResponseCurve::Cubic;
+        assert!((process(Vec2::new(0.0, 0.6), &stick).y - 0.125).abs() < 1e-6);
+
+        // more sensitive gets to full speed sooner, but no further
+        stick.sensitivity = 2.0;
+        assert_eq!(process(Vec2::Y, &stick), Vec2::Y);
+    }
+}
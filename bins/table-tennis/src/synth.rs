@@ -0,0 +1,184 @@
+// Sounds made from scratch: each is a few square, sine or noise tones, rendered into a WAV in
+// memory. They stand in for any sound file that won't load (see `asset_check.rs`), so the
+// game plays fine without any, bounces, goals and fanfare included, and the crowd's and
+// music's loops (see `crowd.rs` and `music.rs`) are only ever made here. The tones are in
+// `assets/synth.ron`, and a `synth.ron` in the save directory can change any of them.
+use std::{collections::HashMap, f32::consts::TAU, sync::Arc};
+
+use bevy::prelude::*;
+use bevy_common::storage;
+use serde::Deserialize;
+
+use crate::constants::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum Waveform {
+    Sine,
+    Square,
+    // hiss, with the pitch as how high it goes
+    Noise,
+}
+
+impl Waveform {
+    // from -1 to 1, `phase` in turns. Noise doesn't have a phase, it's done in `render`.
+    fn sample(self, phase: f32) -> f32 {
+        let sine = (TAU * phase).sin();
+        match self {
+            Self::Sine | Self::Noise => sine,
+            Self::Square => sine.signum(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct Tone {
+    pub wave: Waveform,
+    // in Hz
+    pub pitch: f32,
+    // in seconds
+    pub length: f32,
+    // from 0 to 1
+    pub volume: f32,
+    // dies away over its length so it doesn't click at the end, which loops don't want
+    #[serde(default = "fades")]
+    pub fade: bool,
+}
+
+fn fades() -> bool {
+    true
+}
+
+impl Default for Tone {
+    fn default() -> Self {
+        Self {
+            wave: Waveform::Sine,
+            pitch: SYNTH_BEEP_PITCH,
+            length: SYNTH_BEEP_LENGTH,
+            volume: SYNTH_BEEP_VOLUME,
+            fade: true,
+        }
+    }
+}
+
+// Every sound's tones, by the file it stands in for
+#[derive(Resource, Debug, Clone, Deserialize)]
+#[serde(transparent)]
+pub struct Synth(HashMap<String, Vec<Tone>>);
+
+impl Synth {
+    fn built_in() -> Self {
+        ron::from_str(include_str!("../assets/synth.ron"))
+            .expect("assets/synth.ron should be valid")
+    }
+
+    // with any from mods, and then the player's own, in place of those they change
+    fn load() -> Self {
+        let mut synth = Self::built_in();
+        synth.0.extend(crate::mods::loaded().sounds.clone());
+        let Some(contents) = storage::load(GAME_NAME, SYNTH_FILE) else {
+            return synth;
+        };
+        match ron::from_str::<Self>(&contents) {
+            Ok(theirs) => synth.0.extend(theirs.0),
+            Err(err) => warn!("Couldn't read {SYNTH_FILE}: {err}"),
+        }
+        synth
+    }
+
+    // A plain beep for anything it doesn't know
+    pub fn sound(&self, name: &str) -> AudioSource {
+        match self.0.get(name) {
+            Some(tones) => render(tones),
+            None => render(&[Tone::default()]),
+        }
+    }
+}
+
+// The tones one after another, as a 16 bit mono WAV
+pub fn render(tones: &[Tone]) -> AudioSource {
+    let mut samples: Vec<i16> = Vec::new();
+    // the same hiss every time, from a xorshift
+    let mut noise = 0x2545_f491_u32;
+    let mut filtered = 0.0;
+    for tone in tones {
+        let count = (SYNTH_SAMPLE_RATE as f32 * tone.length).round() as u32;
+        // how much of each new noise sample gets through the one pole low pass
+        let smoothing = 1.0 - (-TAU * tone.pitch / SYNTH_SAMPLE_RATE as f32).exp();
+        samples.extend((0..count).map(|i| {
+            let t = i as f32 / SYNTH_SAMPLE_RATE as f32;
+            let fade = if tone.fade {
+                1.0 - i as f32 / count as f32
+            } else {
+                1.0
+            };
+            let wave = if tone.wave == Waveform::Noise {
+                noise ^= noise << 13;
+                noise ^= noise >> 17;
+                noise ^= noise << 5;
+                let white = noise as f32 / u32::MAX as f32 * 2.0 - 1.0;
+                filtered += (white - filtered) * smoothing;
+                filtered
+            } else {
+                tone.wave.sample(tone.pitch * t)
+            };
+            let sample = (wave * fade * tone.volume.clamp(0.0, 1.0)).clamp(-1.0, 1.0);
+            (sample * i16::MAX as f32) as i16
+        }));
+    }
+    let data_size = samples.len() as u32 * 2;
+
+    let mut bytes = Vec::with_capacity(44 + data_size as usize);
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(36 + data_size).to_le_bytes());
+    bytes.extend_from_slice(b"WAVEfmt ");
+    bytes.extend_from_slice(&16u32.to_le_bytes());
+    // PCM, one channel
+    bytes.extend_from_slice(&1u16.to_le_bytes());
+    bytes.extend_from_slice(&1u16.to_le_bytes());
+    bytes.extend_from_slice(&SYNTH_SAMPLE_RATE.to_le_bytes());
+    bytes.extend_from_slice(&(SYNTH_SAMPLE_RATE * 2).to_le_bytes());
+    bytes.extend_from_slice(&2u16.to_le_bytes());
+    bytes.extend_from_slice(&16u16.to_le_bytes());
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data_size.to_le_bytes());
+    for sample in samples {
+        bytes.extend_from_slice(&sample.to_le_bytes());
+    }
+
+    AudioSource {
+        bytes: Arc::from(bytes),
+    }
+}
+
+pub struct SynthPlugin;
+
+impl Plugin for SynthPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Synth::load());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_built_in_sound_renders() {
+        let synth = Synth::built_in();
+        for name in [
+            "high_beep_short.ogg",
+            "low_beep_short.ogg",
+            "thud_short.wav",
+            "crowd_murmur",
+            "crowd_cheer",
+            "music_bass",
+            "music_arp",
+            "music_drums",
+        ] {
+            let tones = &synth.0[name];
+            let seconds: f32 = tones.iter().map(|tone| tone.length).sum();
+            let samples = (synth.sound(name).bytes.len() - 44) / 2;
+            assert!((samples as f32 / SYNTH_SAMPLE_RATE as f32 - seconds).abs() < 0.01);
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use bevy::prelude::*;
+
+use crate::{constants::*, entities, mods, settings::Settings};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Theme {
+    #[default]
+    Classic,
+    // Over-bright colors intended to be rendered with HDR + bloom
+    Neon,
+    // one from a content pack, by index, see `mods.rs`
+    Mod(usize),
+}
+
+// The colors every themed entity pulls from
+#[derive(Debug, Clone, Copy)]
+pub struct Palette {
+    pub background: Color,
+    pub paddle: Color,
+    pub ball: Color,
+    pub wall: Color,
+    pub goal: Color,
+    pub goal_flash: Color,
+    pub scoreboard: Color,
+}
+
+impl Theme {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Theme::Classic => "Classic",
+            Theme::Neon => "Neon",
+            Theme::Mod(i) => mods::loaded()
+                .themes
+                .get(*i)
+                .map_or("Classic", |theme| &theme.name),
+        }
+    }
+
+    // through the built-in ones and then any from mods
+    pub fn next(&self) -> Self {
+        let modded = mods::loaded().themes.len();
+        match self {
+            Theme::Classic => Theme::Neon,
+            Theme::Neon if modded > 0 => Theme::Mod(0),
+            Theme::Mod(i) if i + 1 < modded => Theme::Mod(i + 1),
+            Theme::Neon | Theme::Mod(_) => Theme::Classic,
+        }
+    }
+
+    // whether the camera needs an HDR target (and bloom) to render this theme properly
+    pub fn is_hdr(&self) -> bool {
+        match self {
+            Theme::Classic => false,
+            Theme::Neon => true,
+            Theme::Mod(i) => mods::loaded().themes.get(*i).is_some_and(|theme| theme.hdr),
+        }
+    }
+
+    pub fn palette(&self) -> Palette {
+        match self {
+            Theme::Mod(i) => mods::loaded()
+                .themes
+                .get(*i)
+                .map_or_else(|| Theme::Classic.palette(), mods::ModTheme::palette),
+            Theme::Classic => Palette {
+                background: BACKGROUND_COLOR,
+                paddle: PADDLE_COLOR,
+                ball: BALL_COLOR,
+                wall: WALL_COLOR,
+                goal: GOAL_COLOR,
+                goal_flash: GOAL_FLASH_COLOR,
+                scoreboard: SCOREBOARD_COLOR,
+            },
+            Theme::Neon => Palette {
+                background: NEON_BACKGROUND_COLOR,
+                paddle: NEON_PADDLE_COLOR,
+                ball: NEON_BALL_COLOR,
+                wall: NEON_WALL_COLOR,
+                goal: NEON_GOAL_COLOR,
+                goal_flash: NEON_GOAL_FLASH_COLOR,
+                scoreboard: NEON_SCOREBOARD_COLOR,
+            },
+        }
+    }
+}
+
+// recolors the arena whenever the theme setting changes, the paddles and scoreboards are
+// done in `paddle_colors.rs`
+pub fn apply_theme(
+    settings: Res<Settings>,
+    mut clear_color: ResMut<ClearColor>,
+    mut walls: Query<&mut Sprite, (Or<(With<entities::WallSide>, With<entities::Obstacle>)>,)>,
+    mut goals: Query<
+        &mut Sprite,
+        (
+            With<entities::GoalZone>,
+            Without<entities::WallSide>,
+            Without<entities::Obstacle>,
+        ),
+    >,
+    balls: Query<&Handle<ColorMaterial>, With<entities::Ball>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let palette = settings.theme.palette();
+
+    clear_color.0 = palette.background;
+
+    for mut sprite in &mut walls {
+        sprite.color = palette.wall;
+    }
+
+    for mut sprite in &mut goals {
+        sprite.color = palette.goal;
+    }
+
+    for handle in &balls {
+        if let Some(material) = materials.get_mut(handle) {
+            material.color = palette.ball;
+        }
+    }
+}
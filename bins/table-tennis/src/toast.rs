@@ -0,0 +1,238 @@
+// Announcement banners that slide in from the right, one at a time: "Game point", "Longest
+// rally!", opponents dropping out. Anything can put one up by sending a `Toast`.
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::{
+    constants::*,
+    entities::{Competitor, PerCompetitor},
+    personality::Rally,
+    rules::{Games, MatchMode, MatchRules},
+    PointScored, Scoreboard, TickSet,
+};
+
+#[derive(Event, Debug, Clone)]
+pub struct Toast(pub String);
+
+// Toasts waiting for the one on screen to go
+#[derive(Resource, Debug, Default)]
+struct Queue(VecDeque<String>);
+
+#[derive(Component, Debug, Default)]
+struct Banner {
+    age: f32,
+}
+
+pub struct ToastPlugin;
+
+impl Plugin for ToastPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Queue>()
+            .add_event::<Toast>()
+            .add_systems(
+                FixedUpdate,
+                announce_game_point
+                    .after(crate::tally_score)
+                    .in_set(TickSet::Scoring)
+                    .run_if(crate::rules::mode_is(MatchMode::Points)),
+            )
+            .add_systems(
+                Update,
+                (
+                    announce_longest_rally.run_if(resource_changed::<Rally>()),
+                    queue_toasts,
+                    show_toasts,
+                    slide_banners,
+                )
+                    .chain(),
+            );
+        // only a server match can be left waiting for somebody
+        #[cfg(feature = "networking")]
+        app.add_systems(
+            Update,
+            announce_dropouts
+                .run_if(resource_changed::<crate::net::Waiting>())
+                .before(queue_toasts),
+        );
+    }
+}
+
+// How far on screen a banner `age` seconds old is, from 0 (off it) to 1, easing in and out
+fn shown(age: f32) -> f32 {
+    let sliding_in = age / TOAST_SLIDE_DURATION;
+    let sliding_out = (TOAST_DURATION - age) / TOAST_SLIDE_DURATION;
+    let t = sliding_in.min(sliding_out).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+// What to call out after a point, e.g. "Game point, You"
+fn call(
+    scores: PerCompetitor<usize>,
+    target: usize,
+    games: &Games,
+    rules: &MatchRules,
+) -> Option<String> {
+    if target < 2 {
+        return None;
+    }
+
+    let close: Vec<Competitor> = Competitor::ALL
+        .into_iter()
+        .filter(|competitor| scores[*competitor] + 1 == target)
+        .collect();
+    match close[..] {
+        [_, _] => Some("Deuce, next point wins".to_string()),
+        [competitor] => {
+            // the last game they need
+            let point = if rules.games > 1 && games.won[competitor] + 1 == rules.games_to_win() {
+                "Match point"
+            } else {
+                "Game point"
+            };
+            Some(format!("{point}, {}", competitor.name()))
+        }
+        _ => None,
+    }
+}
+
+fn announce_game_point(
+    mut points: EventReader<PointScored>,
+    scores: Res<Scoreboard>,
+    rules: Res<MatchRules>,
+    games: Res<Games>,
+    mut toasts: EventWriter<Toast>,
+) {
+    if points.read().count() == 0 {
+        return;
+    }
+    let Some(target) = rules.target_score else {
+        return;
+    };
+    if let Some(call) = call(**scores, target, &games, &rules) {
+        toasts.send(Toast(call));
+    }
+}
+
+fn announce_longest_rally(
+    rally: Res<Rally>,
+    mut toasts: EventWriter<Toast>,
+    // the longest so far, and whether this rally's been announced for beating it
+    mut best: Local<(usize, bool)>,
+) {
+    let (longest, announced) = &mut *best;
+    if rally.returns == 0 {
+        *announced = false;
+    }
+    if rally.returns <= *longest {
+        return;
+    }
+
+    *longest = rally.returns;
+    if rally.returns >= LONGEST_RALLY_MIN_RETURNS && !*announced {
+        *announced = true;
+        toasts.send(Toast("Longest rally!".to_string()));
+    }
+}
+
+#[cfg(feature = "networking")]
+fn announce_dropouts(
+    waiting: Res<crate::net::Waiting>,
+    mut toasts: EventWriter<Toast>,
+    mut missing: Local<Option<Competitor>>,
+) {
+    let now = waiting.0.map(|dropout| dropout.competitor);
+    if now == *missing {
+        return;
+    }
+
+    if let Some(competitor) = now {
+        toasts.send(Toast(format!("{} dropped out", competitor.name())));
+    } else if let Some(competitor) = *missing {
+        toasts.send(Toast(format!("{} is back", competitor.name())));
+    }
+    *missing = now;
+}
+
+fn queue_toasts(mut toasts: EventReader<Toast>, mut queue: ResMut<Queue>) {
+    queue
+        .0
+        .extend(toasts.read().map(|Toast(text)| text.clone()));
+}
+
+fn show_toasts(mut commands: Commands, mut queue: ResMut<Queue>, banners: Query<(), With<Banner>>) {
+    if !banners.is_empty() {
+        return;
+    }
+    let Some(text) = queue.0.pop_front() else {
+        return;
+    };
+
+    commands.spawn((
+        TextBundle::from_section(
+            text,
+            TextStyle {
+                font_size: TOAST_FONT_SIZE,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(TOAST_TOP),
+            right: Val::Px(-TOAST_SLIDE_DISTANCE),
+            padding: UiRect::all(Val::Px(TOAST_PADDING / 2.0)),
+            ..default()
+        })
+        .with_background_color(SETTINGS_MENU_BACKDROP_COLOR),
+        Banner::default(),
+    ));
+}
+
+fn slide_banners(
+    mut commands: Commands,
+    mut banners: Query<(Entity, &mut Banner, &mut Style)>,
+    time: Res<Time>,
+) {
+    for (entity, mut banner, mut style) in &mut banners {
+        banner.age += time.delta_seconds();
+        if banner.age >= TOAST_DURATION {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        let off = -TOAST_SLIDE_DISTANCE;
+        style.right = Val::Px(off + (TOAST_PADDING - off) * shown(banner.age));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn calls_the_point_before_the_target() {
+        let rules = MatchRules {
+            games: 3,
+            ..default()
+        };
+        let mut games = Games::default();
+        let scores = |player, computer| PerCompetitor { player, computer };
+
+        assert_eq!(call(scores(3, 5), 7, &games, &rules), None);
+        assert_eq!(
+            call(scores(6, 5), 7, &games, &rules).as_deref(),
+            Some("Game point, You")
+        );
+        assert_eq!(
+            call(scores(6, 6), 7, &games, &rules).as_deref(),
+            Some("Deuce, next point wins")
+        );
+
+        games.won.computer = 1;
+        assert_eq!(
+            call(scores(2, 6), 7, &games, &rules).as_deref(),
+            Some("Match point, Computer")
+        );
+    }
+}
@@ -0,0 +1,139 @@
+// A guided first match, started with T on the pre-match screen. It's a normal endless match
+// against the computer while the "tutorial" sequence walks through one lesson at a time, see
+// `assets/sequences/tutorial.ron`.
+use bevy::prelude::*;
+use bevy_common::screens::{self, AddScreen};
+
+use crate::{
+    constants::*,
+    mutators::{MatchState, Mutators},
+    rules::MatchRules,
+    sequence::{PlaySequence, SequenceFinished},
+};
+
+#[derive(States, Debug, Clone, Copy, Default, Hash, PartialEq, Eq)]
+pub enum TutorialState {
+    #[default]
+    Off,
+    Lessons,
+    // every lesson's done, waiting to go back to the menu
+    Complete,
+}
+
+// The rules and mutators picked before the tutorial, put back after it
+#[derive(Resource, Debug)]
+struct Stash {
+    rules: MatchRules,
+    mutators: Mutators,
+}
+
+#[derive(Component)]
+struct TutorialCompleteScreen;
+
+pub struct TutorialPlugin;
+
+impl Plugin for TutorialPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<TutorialState>()
+            .add_screen::<TutorialCompleteScreen, _>(TutorialState::Complete, spawn_complete_screen)
+            .add_systems(
+                Update,
+                start_tutorial
+                    .run_if(in_state(MatchState::PreMatch))
+                    .run_if(in_state(TutorialState::Off))
+                    .run_if(in_state(crate::devices::Joining::Closed))
+                    .run_if(in_state(crate::cosmetics::LockerState::Closed))
+                    .run_if(in_state(crate::daily::ChallengeState::Off))
+                    .run_if(crate::net::has_input_authority)
+                    .run_if(crate::net::keyboard_is_local),
+            )
+            .add_systems(Update, end_lessons.run_if(in_state(TutorialState::Lessons)))
+            .add_systems(
+                Update,
+                finish_tutorial.run_if(in_state(TutorialState::Complete)),
+            );
+    }
+}
+
+// A plain match against the computer, whatever was picked
+fn start_tutorial(
+    mut commands: Commands,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut rules: ResMut<MatchRules>,
+    mut mutators: ResMut<Mutators>,
+    mut sequences: EventWriter<PlaySequence>,
+    mut next_tutorial: ResMut<NextState<TutorialState>>,
+    mut next_match: ResMut<NextState<MatchState>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::T) {
+        return;
+    }
+
+    commands.insert_resource(Stash {
+        rules: std::mem::take(&mut *rules),
+        mutators: std::mem::take(&mut *mutators),
+    });
+    sequences.send(PlaySequence("tutorial"));
+    next_tutorial.set(TutorialState::Lessons);
+    next_match.set(MatchState::Playing);
+}
+
+fn end_lessons(
+    mut finished: EventReader<SequenceFinished>,
+    mut next_tutorial: ResMut<NextState<TutorialState>>,
+) {
+    if finished
+        .read()
+        .any(|SequenceFinished(name)| *name == "tutorial")
+    {
+        next_tutorial.set(TutorialState::Complete);
+    }
+}
+
+fn style(font_size: f32, color: Color) -> TextStyle {
+    TextStyle {
+        font_size,
+        color,
+        ..default()
+    }
+}
+
+fn spawn_complete_screen(mut commands: Commands) {
+    commands
+        .spawn((
+            screens::overlay(SETTINGS_MENU_BACKDROP_COLOR, SETTINGS_MENU_Z_INDEX - 1),
+            TutorialCompleteScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_sections([
+                TextSection::new(
+                    "That's everything!\n\n",
+                    style(GAME_OVER_FONT_SIZE, Color::WHITE),
+                ),
+                TextSection::new(
+                    "enter: back to the menu",
+                    style(SETTINGS_MENU_FONT_SIZE, Color::GRAY),
+                ),
+            ]));
+        });
+}
+
+fn finish_tutorial(
+    mut commands: Commands,
+    keyboard_input: Res<Input<KeyCode>>,
+    stash: Option<Res<Stash>>,
+    mut next_tutorial: ResMut<NextState<TutorialState>>,
+    mut next_match: ResMut<NextState<MatchState>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::Return) {
+        return;
+    }
+
+    if let Some(stash) = stash {
+        commands.insert_resource(stash.rules.clone());
+        commands.insert_resource(stash.mutators.clone());
+        commands.remove_resource::<Stash>();
+    }
+    next_tutorial.set(TutorialState::Off);
+    next_match.set(MatchState::PreMatch);
+}
@@ -0,0 +1,15 @@
+// The simulation's laid out in court units, not pixels. One's a centimetre: the court's 9 m
+// by 6 m between its walls, the ball's 30 cm across and served at 4 m/s. Only the cameras
+// turn them into pixels, each fitting its view of the court to however much of the window
+// it has, so the court's drawn the same at any resolution and nothing in the simulation
+// knows how big the window is. Showing more or less of the court (a bigger arena, or the
+// camera zoomed in) is only a different view.
+use bevy::{prelude::*, render::camera::ScalingMode};
+
+// At least `view` of the court in court units, scaled to fill the camera's viewport
+pub fn fit(view: Vec2) -> ScalingMode {
+    ScalingMode::AutoMin {
+        min_width: view.x,
+        min_height: view.y,
+    }
+}
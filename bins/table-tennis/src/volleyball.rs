@@ -0,0 +1,283 @@
+use bevy::prelude::*;
+use bevy_common::motion;
+
+use crate::{
+    air_hockey::{self, PaddleStrafe},
+    constants::*,
+    entities,
+    mutators::MatchState,
+    rules::{variant_is, Variant},
+    settings::Settings,
+    CollisionEvent, Contact, GoalEvent, TickSet,
+};
+
+pub struct VolleyballPlugin;
+
+impl Plugin for VolleyballPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(MatchState::Playing),
+            (spawn_net_and_end_walls, close_goals).run_if(variant_is(Variant::Volleyball)),
+        )
+        .add_systems(
+            FixedUpdate,
+            (
+                stand_paddles,
+                strafe_ai_paddle.run_if(crate::net::computer_is_ai),
+            )
+                .in_set(TickSet::Input)
+                .run_if(variant_is(Variant::Volleyball)),
+        )
+        .add_systems(
+            FixedUpdate,
+            (
+                keep_paddles_off_end_walls.before(air_hockey::strike_pucks),
+                ground_balls
+                    .after(motion::apply_velocity)
+                    .before(crate::check_ball_bounce_collisions),
+                bump_balls.after(air_hockey::strike_pucks),
+                keep_balls_in.after(bump_balls),
+            )
+                .in_set(TickSet::Physics)
+                .run_if(variant_is(Variant::Volleyball)),
+        );
+    }
+}
+
+// The net, and solid walls where the goals would be. They're obstacles so they get themed
+// and cleared away with the mutator's.
+fn spawn_net_and_end_walls(mut commands: Commands, settings: Res<Settings>) {
+    let end_wall = Vec3::new(WALL_THICKNESS, TOP_WALL - BOTTOM_WALL + WALL_THICKNESS, 0.0);
+    let blocks = [
+        (
+            Vec3::new(0.0, BOTTOM_WALL + NET_SIZE.y / 2.0, 0.0),
+            NET_SIZE,
+        ),
+        (Vec3::new(LEFT_WALL, 0.0, 0.0), end_wall),
+        (Vec3::new(RIGHT_WALL, 0.0, 0.0), end_wall),
+    ];
+
+    for (translation, scale) in blocks {
+        commands.spawn((
+            SpriteBundle {
+                transform: Transform {
+                    translation,
+                    scale,
+                    ..default()
+                },
+                sprite: Sprite {
+                    color: settings.theme.palette().wall,
+                    ..default()
+                },
+                ..default()
+            },
+            entities::Obstacle,
+            entities::Collider,
+        ));
+    }
+}
+
+// Points come from the floor instead, so the goals are tucked away under it where no ball
+// can reach them. `restore_court` puts them back.
+fn close_goals(mut goals: Query<&mut Transform, With<entities::GoalZone>>) {
+    for mut transform in &mut goals {
+        transform.translation.y = BOTTOM_WALL - (TOP_WALL - BOTTOM_WALL);
+    }
+}
+
+// Keeps the paddles standing on the floor, whatever size they are
+fn stand_paddles(
+    mut paddles: Query<(&mut Transform, &mut entities::PaddleVelocity), With<entities::Paddle>>,
+) {
+    for (mut transform, mut velocity) in &mut paddles {
+        transform.translation.y = BOTTOM_WALL + WALL_THICKNESS / 2.0 + transform.scale.y / 2.0;
+        velocity.0 = 0.0;
+    }
+}
+
+// There's no goal behind a paddle to let a ball out, so one that gets there has to fit. A
+// paddle right at the back would leave it squeezed against the end wall and knocked through.
+fn keep_paddles_off_end_walls(
+    mut paddles: Query<
+        (&mut Transform, &mut PaddleStrafe, &entities::Side),
+        With<entities::Paddle>,
+    >,
+) {
+    for (mut transform, mut strafe, side) in &mut paddles {
+        let back = side
+            .inset_x(WALL_THICKNESS / 2.0 + BALL_SIZE.x + PADDLE_PADDING + transform.scale.x / 2.0);
+        if (transform.translation.x - back) * side.sign() > 0.0 {
+            transform.translation.x = back;
+            strafe.0 = 0.0;
+        }
+    }
+}
+
+// In case one's struck into an end wall anyway, it's put back in front of it heading away
+fn keep_balls_in(
+    mut balls: Query<(&mut Transform, &mut entities::Velocity), With<entities::Ball>>,
+) {
+    for (mut transform, mut velocity) in &mut balls {
+        let inset = WALL_THICKNESS / 2.0 + transform.scale.x / 2.0;
+        let (min, max) = (LEFT_WALL + inset, RIGHT_WALL - inset);
+        if transform.translation.x < min {
+            transform.translation.x = min;
+            velocity.x = velocity.x.abs();
+        } else if transform.translation.x > max {
+            transform.translation.x = max;
+            velocity.x = -velocity.x.abs();
+        }
+    }
+}
+
+// Where a ball falling under gravity comes down to `y`, ignoring anything it'd bounce off
+// on the way
+pub fn landing_x(position: Vec2, velocity: Vec2, y: f32) -> Option<f32> {
+    let discriminant = velocity.y * velocity.y + 2.0 * GRAVITY * (position.y - y);
+    if discriminant < 0.0 {
+        return None;
+    }
+    let time = (velocity.y + discriminant.sqrt()) / GRAVITY;
+    Some(position.x + velocity.x * time)
+}
+
+// The computer gets under whichever ball is coming down on its side first, and otherwise
+// waits at the back
+fn strafe_ai_paddle(
+    mut paddles: Query<
+        (
+            &mut Transform,
+            &mut PaddleStrafe,
+            &entities::Side,
+            &crate::powerups::PaddleBoost,
+        ),
+        (With<entities::AI>, With<entities::Paddle>),
+    >,
+    balls: Query<
+        (&Transform, &entities::Velocity),
+        (With<entities::Ball>, Without<entities::Paddle>),
+    >,
+    settings: Res<Settings>,
+    time: Res<Time>,
+) {
+    for (mut transform, mut strafe, side, boost) in &mut paddles {
+        let top = transform.translation.y + transform.scale.y / 2.0;
+        let (min, max) = air_hockey::half_court(*side, transform.scale.x);
+
+        let target = balls
+            .iter()
+            .filter_map(|(ball, velocity)| landing_x(ball.translation.truncate(), velocity.0, top))
+            .find(|x| (min..=max).contains(x))
+            .unwrap_or(side.inset_x(GAP_BETWEEN_PADDLE_AND_WALL));
+
+        let max_speed = MAX_AI_PADDLE_SPEED * boost.multiplier();
+        let speed = ((target - transform.translation.x) / time.delta_seconds())
+            .clamp(-max_speed, max_speed);
+        air_hockey::strafe(
+            &mut transform,
+            &mut strafe,
+            *side,
+            speed,
+            max_speed,
+            &settings,
+            time.delta_seconds(),
+        );
+    }
+}
+
+// A ball that touches the floor is a point against whoever's side it landed on
+fn ground_balls(
+    balls: Query<(Entity, &Transform), With<entities::Ball>>,
+    mut goal_events: EventWriter<GoalEvent>,
+) {
+    let floor = BOTTOM_WALL + WALL_THICKNESS / 2.0;
+
+    for (ball, transform) in &balls {
+        if transform.translation.y - transform.scale.y / 2.0 > floor {
+            continue;
+        }
+        let defender = if transform.translation.x < 0.0 {
+            entities::Side::Left
+        } else {
+            entities::Side::Right
+        };
+        // the goal line is that side's half of the floor
+        let half = (RIGHT_WALL - LEFT_WALL) / 2.0;
+        goal_events.send(GoalEvent {
+            ball,
+            defender,
+            crossing: Contact::along(transform.translation.x, defender.sign() * half / 2.0, half),
+        });
+    }
+}
+
+// Paddles pop the ball up and over towards the net, or it'd never get across
+fn bump_balls(
+    mut collision_events: EventReader<CollisionEvent>,
+    mut balls: Query<&mut entities::Velocity, With<entities::Ball>>,
+) {
+    for event in collision_events.read() {
+        let CollisionEvent::Paddle(ball, side, _) = event else {
+            continue;
+        };
+        let Ok(mut velocity) = balls.get_mut(*ball) else {
+            continue;
+        };
+
+        velocity.y = velocity.y.max(BUMP_SPEED);
+        let forward = -side.sign();
+        velocity.x = forward * (velocity.x * forward).max(BUMP_FORWARD_SPEED);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn landing_follows_the_arc() {
+        // thrown straight out sideways, it drops 150 in sqrt(2 * 150 / g) seconds
+        let x = landing_x(Vec2::new(0.0, 150.0), Vec2::new(100.0, 0.0), 0.0).unwrap();
+        assert!((x - 100.0 * (300.0 / GRAVITY).sqrt()).abs() < 1e-3);
+
+        // can't get back up to somewhere higher than it'll ever be
+        assert_eq!(landing_x(Vec2::ZERO, Vec2::new(0.0, 10.0), 100.0), None);
+    }
+
+    #[test]
+    fn balls_fit_behind_the_paddles() {
+        let mut app = App::new();
+        app.add_systems(Update, (keep_paddles_off_end_walls, keep_balls_in));
+
+        let paddle = app
+            .world
+            .spawn((
+                Transform::from_xyz(RIGHT_WALL, 0.0, 0.0).with_scale(PADDLE_SIZE),
+                PaddleStrafe(200.0),
+                entities::Side::Right,
+                entities::Paddle,
+            ))
+            .id();
+        let ball = app
+            .world
+            .spawn((
+                Transform::from_xyz(LEFT_WALL - 20.0, 0.0, 0.0).with_scale(BALL_SIZE),
+                entities::Velocity(Vec2::new(-300.0, 50.0)),
+                entities::Ball,
+            ))
+            .id();
+        app.update();
+
+        let paddle_x = app.world.get::<Transform>(paddle).unwrap().translation.x;
+        let gap = (RIGHT_WALL - WALL_THICKNESS / 2.0) - (paddle_x + PADDLE_SIZE.x / 2.0);
+        assert!(gap >= BALL_SIZE.x, "{gap}");
+        assert_eq!(app.world.get::<PaddleStrafe>(paddle).unwrap().0, 0.0);
+
+        let ball_x = app.world.get::<Transform>(ball).unwrap().translation.x;
+        assert_eq!(ball_x, LEFT_WALL + (WALL_THICKNESS + BALL_SIZE.x) / 2.0);
+        assert_eq!(
+            app.world.get::<entities::Velocity>(ball).unwrap().0,
+            Vec2::new(300.0, 50.0)
+        );
+    }
+}
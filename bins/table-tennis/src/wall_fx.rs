@@ -0,0 +1,111 @@
+// The goals breathe, slowly brightening towards their flash color and back, so it's clear at a
+// glance which ends score and which walls just bounce. The end a competitor at match point is
+// attacking pulses harder and in time with the heartbeat, see `match_point.rs`. A goal that's
+// flashing from a point leaves its color to the flash until it's done.
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use bevy_common::fx::{lerp_color, Flash, JuiceSettings};
+
+use crate::{
+    constants::*,
+    entities::{self, Competitor},
+    match_point::MatchPoint,
+    rules::Ends,
+    settings::Settings,
+};
+
+#[derive(Component, Debug, Default)]
+pub struct GoalPulse {
+    // 0 normally and 1 at match point, easing between them
+    urgency: f32,
+    // how far through a pulse it is, from 0 to 1
+    phase: f32,
+}
+
+impl GoalPulse {
+    fn period(&self) -> f32 {
+        GOAL_PULSE_PERIOD + (MATCH_POINT_HEARTBEAT_PERIOD - GOAL_PULSE_PERIOD) * self.urgency
+    }
+
+    // how far towards the flash color it is right now
+    fn glow(&self) -> f32 {
+        let strength = GOAL_PULSE_STRENGTH
+            + (GOAL_PULSE_MATCH_POINT_STRENGTH - GOAL_PULSE_STRENGTH) * self.urgency;
+        strength * (0.5 - 0.5 * (self.phase * TAU).cos())
+    }
+}
+
+pub struct WallFxPlugin;
+
+impl Plugin for WallFxPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (add_goal_pulses, pulse_goals).chain());
+    }
+}
+
+// Whenever an arena's built
+fn add_goal_pulses(
+    mut commands: Commands,
+    goals: Query<Entity, (With<entities::GoalZone>, Without<GoalPulse>)>,
+) {
+    for goal in &goals {
+        commands.entity(goal).insert(GoalPulse::default());
+    }
+}
+
+fn pulse_goals(
+    mut goals: Query<
+        (&mut Sprite, &entities::Side, &mut GoalPulse),
+        (With<entities::GoalZone>, Without<Flash>),
+    >,
+    match_point: Res<MatchPoint>,
+    ends: Res<Ends>,
+    settings: Res<Settings>,
+    juice: Res<JuiceSettings>,
+    time: Res<Time>,
+) {
+    let palette = settings.theme.palette();
+
+    for (mut sprite, side, mut pulse) in &mut goals {
+        // it's under threat from whoever's attacking it
+        let threatened = Competitor::ALL
+            .iter()
+            .any(|competitor| match_point.0[*competitor] && ends.side_of(*competitor) != *side);
+        let target = if threatened { 1.0 } else { 0.0 };
+        let ease = (GOAL_PULSE_SMOOTHING * time.delta_seconds()).min(1.0);
+        pulse.urgency += (target - pulse.urgency) * ease;
+        pulse.phase = (pulse.phase + time.delta_seconds() / pulse.period()).fract();
+
+        sprite.color = lerp_color(palette.goal, palette.goal_flash, juice.scale(pulse.glow()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn match_point_pulses_harder() {
+        let peak = |urgency| {
+            GoalPulse {
+                urgency,
+                phase: 0.5,
+            }
+            .glow()
+        };
+        let rest = GoalPulse::default().glow();
+
+        assert_eq!(rest, 0.0);
+        assert!(peak(0.0) > rest);
+        assert!(peak(1.0) > peak(0.0));
+        assert!(
+            GoalPulse {
+                urgency: 1.0,
+                phase: 0.0
+            }
+            .period()
+                < GoalPulse::default().period()
+        );
+    }
+}
@@ -0,0 +1,69 @@
+// Finds every overlapping ball and collider in a crowded court, like a chaos mode with
+// dozens of balls and obstacles, once by checking every pair and once through a
+// `collision::Grid`.
+use bevy::prelude::*;
+use bevy_common::collision::{self, Grid};
+use criterion::{criterion_group, criterion_main, Criterion};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+const BALLS: usize = 200;
+const COLLIDERS: usize = 100;
+const COURT: Vec2 = Vec2::new(900.0, 600.0);
+const BALL_SIZE: Vec2 = Vec2::splat(30.0);
+const COLLIDER_SIZE: Vec2 = Vec2::splat(40.0);
+const CELL_SIZE: f32 = 100.0;
+
+fn scatter(rng: &mut StdRng, count: usize) -> Vec<Vec2> {
+    (0..count)
+        .map(|_| (Vec2::new(rng.gen(), rng.gen()) - 0.5) * COURT)
+        .collect()
+}
+
+fn overlaps(ball: Vec2, collider: Vec2) -> bool {
+    collision::aabb_overlap(ball, BALL_SIZE, collider, COLLIDER_SIZE).is_some()
+}
+
+fn brute_force(balls: &[Vec2], colliders: &[Vec2]) -> usize {
+    balls
+        .iter()
+        .map(|&ball| colliders.iter().filter(|&&c| overlaps(ball, c)).count())
+        .sum()
+}
+
+// the grid's rebuilt every time, the way the game does every tick
+fn grid(balls: &[Vec2], colliders: &[Vec2]) -> usize {
+    let mut grid = Grid::new(CELL_SIZE);
+    for (index, &collider) in colliders.iter().enumerate() {
+        grid.insert(index, collider, COLLIDER_SIZE);
+    }
+    balls
+        .iter()
+        .map(|&ball| {
+            grid.candidates(ball, BALL_SIZE)
+                .into_iter()
+                .filter(|&index| overlaps(ball, colliders[index]))
+                .count()
+        })
+        .sum()
+}
+
+fn broad_phase(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(0);
+    let balls = scatter(&mut rng, BALLS);
+    let colliders = scatter(&mut rng, COLLIDERS);
+    assert_eq!(
+        brute_force(&balls, &colliders),
+        grid(&balls, &colliders),
+        "the grid missed an overlap"
+    );
+
+    let mut group = c.benchmark_group("broad_phase");
+    group.bench_function("brute_force", |b| {
+        b.iter(|| brute_force(&balls, &colliders))
+    });
+    group.bench_function("grid", |b| b.iter(|| grid(&balls, &colliders)));
+    group.finish();
+}
+
+criterion_group!(benches, broad_phase);
+criterion_main!(benches);
@@ -0,0 +1,157 @@
+// The per-ball math of a simulation tick on a crowded court: moving every ball, bouncing
+// them off what they've hit and off each other, and steering returns across the court.
+// The game's own `--bench-sim` times the whole tick, this is each piece on its own.
+use std::time::Duration;
+
+use bevy::{prelude::*, sprite::collide_aabb::Collision};
+use bevy_common::{
+    collision,
+    motion::{self, Velocity},
+};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+const BALL_COUNTS: [usize; 3] = [10, 100, 1000];
+const COURT: Vec2 = Vec2::new(900.0, 600.0);
+const BALL_SIZE: Vec2 = Vec2::splat(30.0);
+const COLLIDER_SIZE: Vec2 = Vec2::new(20.0, 120.0);
+const SPEED: f32 = 400.0;
+const TICK: Duration = Duration::from_nanos(1_000_000_000 / 64);
+
+fn position(rng: &mut StdRng) -> Vec2 {
+    (Vec2::new(rng.gen(), rng.gen()) - 0.5) * COURT
+}
+
+fn velocity(rng: &mut StdRng) -> Vec2 {
+    Vec2::from_angle(rng.gen_range(0.0..std::f32::consts::TAU)) * SPEED
+}
+
+fn apply_velocity(c: &mut Criterion) {
+    let mut group = c.benchmark_group("apply_velocity");
+    for balls in BALL_COUNTS {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut world = World::new();
+        let mut time = Time::<()>::default();
+        time.advance_by(TICK);
+        world.insert_resource(time);
+        world.spawn_batch(
+            (0..balls)
+                .map(|_| {
+                    (
+                        Transform::from_translation(position(&mut rng).extend(0.0)),
+                        Velocity(velocity(&mut rng)),
+                    )
+                })
+                .collect::<Vec<_>>(),
+        );
+        let mut schedule = Schedule::default();
+        schedule.add_systems(motion::apply_velocity);
+
+        group.bench_with_input(BenchmarkId::from_parameter(balls), &balls, |b, _| {
+            b.iter(|| schedule.run(&mut world))
+        });
+    }
+    group.finish();
+}
+
+// each ball against a collider it's overlapping, the way `check_ball_bounce_collisions`
+// finds them
+fn bounce_off_colliders(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bounce_off_colliders");
+    for balls in BALL_COUNTS {
+        let mut rng = StdRng::seed_from_u64(0);
+        let hits: Vec<_> = (0..balls)
+            .map(|_| {
+                let collider = position(&mut rng);
+                let offset = (Vec2::new(rng.gen(), rng.gen()) - 0.5) * (BALL_SIZE + COLLIDER_SIZE);
+                (collider + offset, velocity(&mut rng), collider)
+            })
+            .collect();
+
+        group.bench_with_input(BenchmarkId::from_parameter(balls), &hits, |b, hits| {
+            b.iter(|| {
+                for &(ball, velocity, collider) in hits {
+                    black_box(collision::resolve_box(
+                        ball,
+                        BALL_SIZE,
+                        velocity,
+                        collider,
+                        COLLIDER_SIZE,
+                    ));
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+fn reflect(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(0);
+    let sides = [
+        Collision::Left,
+        Collision::Right,
+        Collision::Top,
+        Collision::Bottom,
+    ];
+    let hits: Vec<_> = (0..1000)
+        .map(|i| (velocity(&mut rng), sides[i % sides.len()]))
+        .collect();
+
+    c.bench_function("reflect", |b| {
+        b.iter(|| {
+            for &(velocity, side) in &hits {
+                black_box(collision::reflect(velocity, side));
+            }
+        })
+    });
+}
+
+// multi-ball, two balls at a time
+fn bounce_off_balls(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(0);
+    let pairs: Vec<_> = (0..1000)
+        .map(|_| {
+            let a = position(&mut rng);
+            let b = a + Vec2::from_angle(rng.gen_range(0.0..std::f32::consts::TAU)) * BALL_SIZE.x;
+            (a, velocity(&mut rng), b, velocity(&mut rng))
+        })
+        .collect();
+    let radius = BALL_SIZE.x / 2.0;
+
+    c.bench_function("bounce_off_balls", |b| {
+        b.iter(|| {
+            for &(a, a_velocity, b, b_velocity) in &pairs {
+                if let Some(contact) = collision::circle_contact(a, radius, b, radius) {
+                    black_box(collision::elastic_bounce(
+                        a_velocity,
+                        b_velocity,
+                        contact.normal,
+                    ));
+                }
+            }
+        })
+    });
+}
+
+fn enforce_min_horizontal(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(0);
+    let returns: Vec<_> = (0..1000).map(|_| velocity(&mut rng)).collect();
+
+    c.bench_function("enforce_min_horizontal", |b| {
+        b.iter(|| {
+            for &velocity in &returns {
+                black_box(collision::enforce_min_horizontal(velocity, 0.5));
+            }
+        })
+    });
+}
+
+criterion_group!(
+    benches,
+    apply_velocity,
+    bounce_off_colliders,
+    reflect,
+    bounce_off_balls,
+    enforce_min_horizontal
+);
+criterion_main!(benches);
@@ -0,0 +1,82 @@
+// Churns through short-lived sprites the way a busy `fx::burst` does, once spawning and
+// despawning every one and once recycling them through a `Pool`. Each frame spawns a burst
+// and finishes the one from `LIFETIME` frames before.
+use std::{cell::RefCell, collections::VecDeque};
+
+use bevy::{ecs::system::CommandQueue, prelude::*};
+use bevy_common::{motion::Velocity, pool::Pool};
+use criterion::{criterion_group, criterion_main, Criterion};
+
+const FRAMES: usize = 200;
+const BURST: usize = 200;
+const LIFETIME: usize = 30;
+
+#[derive(Component)]
+struct Speck;
+
+fn speck() -> impl Bundle {
+    (
+        SpriteBundle {
+            transform: Transform::from_scale(Vec3::splat(4.0)),
+            ..default()
+        },
+        Velocity(Vec2::X),
+        Speck,
+    )
+}
+
+fn churn(
+    mut finish: impl FnMut(&mut World, Entity),
+    mut spawn: impl FnMut(&mut Commands) -> Entity,
+) {
+    let mut world = World::new();
+    let mut queue = CommandQueue::default();
+    let mut live = VecDeque::new();
+
+    for _ in 0..FRAMES {
+        if live.len() == LIFETIME {
+            for entity in live.pop_front().unwrap_or_default() {
+                finish(&mut world, entity);
+            }
+        }
+
+        let mut commands = Commands::new(&mut queue, &world);
+        let burst: Vec<_> = (0..BURST).map(|_| spawn(&mut commands)).collect();
+        queue.apply(&mut world);
+        live.push_back(burst);
+    }
+}
+
+fn pool(c: &mut Criterion) {
+    let mut group = c.benchmark_group("pool");
+    group.bench_function("spawn_despawn", |b| {
+        b.iter(|| {
+            churn(
+                |world, entity| {
+                    world.despawn(entity);
+                },
+                |commands| commands.spawn(speck()).id(),
+            )
+        })
+    });
+
+    group.bench_function("pooled", |b| {
+        b.iter(|| {
+            // both closures want the pool, and only one runs at a time. A new one each
+            // time, since what's in it belongs to the last run's world.
+            let pool = RefCell::new(Pool::<Speck>::default());
+            churn(
+                |world, entity| {
+                    if let Some(mut visibility) = world.get_mut::<Visibility>(entity) {
+                        pool.borrow_mut().recycle(entity, &mut visibility);
+                    }
+                },
+                |commands| pool.borrow_mut().spawn(commands, speck()),
+            )
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, pool);
+criterion_main!(benches);
@@ -0,0 +1,36 @@
+use std::time::Duration;
+
+use bevy::{audio::PitchBundle, prelude::*};
+
+// A short sine tone, for games that don't ship any sound files. Send one of these and
+// `BeepPlugin` plays it.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct Beep {
+    pub frequency: f32,
+    // in seconds
+    pub duration: f32,
+}
+
+pub struct BeepPlugin;
+
+impl Plugin for BeepPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Beep>().add_systems(Update, play_beeps);
+    }
+}
+
+fn play_beeps(
+    mut commands: Commands,
+    mut beeps: EventReader<Beep>,
+    mut pitches: ResMut<Assets<Pitch>>,
+) {
+    for beep in beeps.read() {
+        commands.spawn(PitchBundle {
+            source: pitches.add(Pitch::new(
+                beep.frequency,
+                Duration::from_secs_f32(beep.duration),
+            )),
+            settings: PlaybackSettings::DESPAWN,
+        });
+    }
+}
@@ -0,0 +1,374 @@
+use bevy::{prelude::*, sprite::collide_aabb::Collision, utils::HashMap};
+
+use crate::scalar::{self, SimVec2};
+
+// The overlap between two axis-aligned boxes on each axis, if they intersect at all
+pub fn aabb_overlap(a_center: Vec2, a_size: Vec2, b_center: Vec2, b_size: Vec2) -> Option<Vec2> {
+    let overlap = (a_size + b_size) / 2.0 - (a_center - b_center).abs();
+    (overlap.x > 0.0 && overlap.y > 0.0).then_some(overlap)
+}
+
+// Bounce `velocity` off the side of a collider it hit. Only reflects if the velocity is going
+// in the opposite direction of the collision, so an object that's still overlapping on the
+// next tick doesn't get flipped back into the collider.
+pub fn reflect(velocity: Vec2, collision: Collision) -> Vec2 {
+    let mut velocity = velocity;
+    let (reflect_x, reflect_y) = match collision {
+        Collision::Left => (velocity.x > 0.0, false),
+        Collision::Right => (velocity.x < 0.0, false),
+        Collision::Top => (false, velocity.y < 0.0),
+        Collision::Bottom => (false, velocity.y > 0.0),
+        Collision::Inside => (false, false),
+    };
+
+    if reflect_x {
+        velocity.x = -velocity.x;
+    }
+    if reflect_y {
+        velocity.y = -velocity.y;
+    }
+    velocity
+}
+
+// Push a ball out of a collider it's overlapping, back out whichever side it's least far
+// into, and `reflect` it off that side. Returns where it ends up and how fast it's going,
+// or None if they aren't touching.
+pub fn resolve_box(
+    center: Vec2,
+    size: Vec2,
+    velocity: Vec2,
+    collider_center: Vec2,
+    collider_size: Vec2,
+) -> Option<(Vec2, Vec2)> {
+    let overlap = aabb_overlap(center, size, collider_center, collider_size)?;
+    let offset = center - collider_center;
+    let (collision, push) = if overlap.x < overlap.y {
+        if offset.x < 0.0 {
+            (Collision::Left, Vec2::new(-overlap.x, 0.0))
+        } else {
+            (Collision::Right, Vec2::new(overlap.x, 0.0))
+        }
+    } else if offset.y < 0.0 {
+        (Collision::Bottom, Vec2::new(0.0, -overlap.y))
+    } else {
+        (Collision::Top, Vec2::new(0.0, overlap.y))
+    };
+
+    Some((center + push, reflect(velocity, collision)))
+}
+
+// Push a vertically moving box (a paddle) back out of any walls it moved into, keeping
+// `padding` between them. The box rebounds with `restitution` of its speed.
+//
+// Returns the speed it was travelling into the wall at, if it hit one.
+pub fn resolve_vertical<'a>(
+    transform: &mut Transform,
+    velocity: &mut f32,
+    walls: impl IntoIterator<Item = &'a Transform>,
+    padding: f32,
+    restitution: f32,
+) -> Option<f32> {
+    let mut impact = None;
+
+    for wall in walls {
+        let Some(overlap) = aabb_overlap(
+            transform.translation.truncate(),
+            transform.scale.truncate() + Vec2::new(0.0, padding * 2.0),
+            wall.translation.truncate(),
+            wall.scale.truncate(),
+        ) else {
+            continue;
+        };
+
+        // which way is out of the wall
+        let push = if transform.translation.y > wall.translation.y {
+            1.0
+        } else {
+            -1.0
+        };
+        transform.translation.y += push * overlap.y;
+
+        if *velocity * push < 0.0 {
+            impact = Some(velocity.abs().max(impact.unwrap_or(0.0)));
+            *velocity = -*velocity * restitution;
+        }
+    }
+
+    impact
+}
+
+// Steer `velocity` so at least `min_fraction` of its speed is horizontal, keeping its speed
+// and which way it's heading. A ball with no horizontal speed at all is sent right.
+pub fn enforce_min_horizontal(velocity: Vec2, min_fraction: f32) -> Vec2 {
+    let sim = scalar::to_sim(velocity);
+    let speed = sim.length();
+    let min_x = speed * scalar::widen(min_fraction);
+    if sim.x.abs() >= min_x {
+        return velocity;
+    }
+
+    let x = min_x * sim.x.signum();
+    let y = (speed * speed - x * x).sqrt() * sim.y.signum();
+    scalar::to_render(SimVec2::new(x, y))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Contact {
+    // unit vector pointing from the first shape towards the second
+    pub normal: Vec2,
+    // how far the shapes overlap along the normal
+    pub depth: f32,
+}
+
+pub fn circle_contact(
+    a_center: Vec2,
+    a_radius: f32,
+    b_center: Vec2,
+    b_radius: f32,
+) -> Option<Contact> {
+    let offset = scalar::to_sim(b_center) - scalar::to_sim(a_center);
+    let distance = offset.length();
+    let depth = scalar::widen(a_radius) + scalar::widen(b_radius) - distance;
+    if depth <= 0.0 {
+        return None;
+    }
+
+    Some(Contact {
+        // perfectly stacked circles have no meaningful normal, pick one
+        normal: scalar::to_render(offset.try_normalize().unwrap_or(SimVec2::X)),
+        depth: scalar::narrow(depth),
+    })
+}
+
+// An elastic collision between two equal masses: they exchange the components of their
+// velocities along the contact normal and keep the rest. Returns None if the bodies are
+// already moving apart, so overlapping bodies aren't pulled back together.
+pub fn elastic_bounce(a_velocity: Vec2, b_velocity: Vec2, normal: Vec2) -> Option<(Vec2, Vec2)> {
+    let (a, b, normal) = (
+        scalar::to_sim(a_velocity),
+        scalar::to_sim(b_velocity),
+        scalar::to_sim(normal),
+    );
+    let approach = (a - b).dot(normal);
+    if approach <= 0.0 {
+        return None;
+    }
+
+    let exchange = normal * approach;
+    Some((
+        scalar::to_render(a - exchange),
+        scalar::to_render(b + exchange),
+    ))
+}
+
+// A uniform grid broad phase: boxes are filed under every cell they cover, so finding what
+// might touch a box only has to look at the few cells around it rather than everything.
+// Items are usually indices into a list of colliders built each tick.
+#[derive(Debug, Clone)]
+pub struct Grid<T> {
+    cell_size: f32,
+    cells: HashMap<IVec2, Vec<T>>,
+}
+
+impl<T: Copy + Ord> Grid<T> {
+    // `cell_size` is best a little bigger than the common things going in, like a ball
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            cells: HashMap::default(),
+        }
+    }
+
+    pub fn insert(&mut self, item: T, center: Vec2, size: Vec2) {
+        let (min, max) = self.cell_range(center, size);
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                self.cells.entry(IVec2::new(x, y)).or_default().push(item);
+            }
+        }
+    }
+
+    // Everything sharing a cell with the box, once each and in order. These only might
+    // overlap it, the narrow phase still has to check.
+    pub fn candidates(&self, center: Vec2, size: Vec2) -> Vec<T> {
+        let (min, max) = self.cell_range(center, size);
+        let mut found = vec![];
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                if let Some(items) = self.cells.get(&IVec2::new(x, y)) {
+                    found.extend_from_slice(items);
+                }
+            }
+        }
+        found.sort_unstable();
+        found.dedup();
+        found
+    }
+
+    fn cell_range(&self, center: Vec2, size: Vec2) -> (IVec2, IVec2) {
+        let half = size.abs() / 2.0;
+        let cell = |point: Vec2| (point / self.cell_size).floor().as_ivec2();
+        (cell(center - half), cell(center + half))
+    }
+}
+
+// Invariants of the math above, checked against random inputs. A failure is shrunk down to
+// the simplest case proptest can find.
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    // rounding can leave a box that's been pushed out a hair inside
+    const EPSILON: f32 = 1e-3;
+
+    fn vector(max: f32) -> impl Strategy<Value = Vec2> {
+        (-max..=max, -max..=max).prop_map(|(x, y)| Vec2::new(x, y))
+    }
+
+    fn size(min: f32, max: f32) -> impl Strategy<Value = Vec2> {
+        (min..=max, min..=max).prop_map(|(x, y)| Vec2::new(x, y))
+    }
+
+    fn collision() -> impl Strategy<Value = Collision> {
+        prop_oneof![
+            Just(Collision::Left),
+            Just(Collision::Right),
+            Just(Collision::Top),
+            Just(Collision::Bottom),
+            Just(Collision::Inside),
+        ]
+    }
+
+    // which way a box has to go to get out of what it hit, see `reflect`
+    fn outwards(collision: Collision) -> Vec2 {
+        match collision {
+            Collision::Left => Vec2::NEG_X,
+            Collision::Right => Vec2::X,
+            Collision::Top => Vec2::Y,
+            Collision::Bottom => Vec2::NEG_Y,
+            Collision::Inside => Vec2::ZERO,
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn reflecting_keeps_speed_and_heads_out(
+            velocity in vector(1000.0),
+            collision in collision(),
+        ) {
+            let reflected = reflect(velocity, collision);
+
+            prop_assert!(reflected.is_finite());
+            prop_assert_eq!(reflected.length(), velocity.length());
+            prop_assert!(reflected.dot(outwards(collision)) >= 0.0);
+            // reflecting again changes nothing, so a ball still overlapping next tick isn't
+            // sent back in
+            prop_assert_eq!(reflect(reflected, collision), reflected);
+        }
+
+        #[test]
+        fn resolved_balls_end_outside_colliders(
+            center in vector(100.0),
+            size in size(1.0, 40.0),
+            velocity in vector(1000.0),
+            collider_center in vector(100.0),
+            collider_size in size(1.0, 200.0),
+        ) {
+            let Some((resolved, bounced)) =
+                resolve_box(center, size, velocity, collider_center, collider_size)
+            else {
+                return Ok(());
+            };
+
+            prop_assert!(resolved.is_finite() && bounced.is_finite());
+            prop_assert_eq!(bounced.length(), velocity.length());
+            let inside = aabb_overlap(resolved, size, collider_center, collider_size);
+            prop_assert!(inside.map_or(true, |overlap| overlap.min_element() <= EPSILON));
+            // and it isn't left heading back in
+            let out = resolved - center;
+            prop_assert!(bounced.dot(out) >= 0.0);
+        }
+
+        #[test]
+        fn min_horizontal_keeps_speed_and_heading(
+            velocity in vector(1000.0),
+            fraction in 0.0f32..=1.0,
+        ) {
+            let steered = enforce_min_horizontal(velocity, fraction);
+
+            prop_assert!(steered.is_finite());
+            prop_assert!((steered.length() - velocity.length()).abs() <= velocity.length() * 1e-4);
+            prop_assert!(steered.x.abs() >= velocity.length() * fraction * (1.0 - 1e-4));
+            prop_assert!(steered.x * velocity.x >= 0.0 && steered.y * velocity.y >= 0.0);
+        }
+
+        #[test]
+        fn elastic_bounces_conserve_momentum_and_energy(
+            a_center in vector(100.0),
+            b_center in vector(100.0),
+            a in vector(1000.0),
+            b in vector(1000.0),
+        ) {
+            let Some(contact) = circle_contact(a_center, 50.0, b_center, 50.0) else {
+                return Ok(());
+            };
+            prop_assert!((contact.normal.length() - 1.0).abs() < 1e-4);
+            prop_assert!(contact.depth > 0.0 && contact.depth <= 100.0);
+
+            let Some((a_after, b_after)) = elastic_bounce(a, b, contact.normal) else {
+                return Ok(());
+            };
+            prop_assert!(a_after.is_finite() && b_after.is_finite());
+            prop_assert!((a_after + b_after - (a + b)).length() < 0.1);
+            let energy = a.length_squared() + b.length_squared();
+            let energy_after = a_after.length_squared() + b_after.length_squared();
+            prop_assert!((energy_after - energy).abs() <= energy * 1e-4);
+            // and they end up moving apart
+            prop_assert!(elastic_bounce(a_after, b_after, contact.normal).is_none());
+        }
+
+        #[test]
+        fn resolved_paddles_end_outside_walls(
+            y in -40.0f32..=40.0,
+            velocity in -1000.0f32..=1000.0,
+        ) {
+            let wall =
+                Transform::from_xyz(0.0, 0.0, 0.0).with_scale(Vec3::new(1000.0, 20.0, 1.0));
+            let mut paddle =
+                Transform::from_xyz(0.0, y, 0.0).with_scale(Vec3::new(20.0, 100.0, 1.0));
+            let mut velocity = velocity;
+            resolve_vertical(&mut paddle, &mut velocity, [&wall], 5.0, 0.5);
+
+            prop_assert!(velocity.is_finite());
+            let overlap = aabb_overlap(
+                paddle.translation.truncate(),
+                paddle.scale.truncate(),
+                wall.translation.truncate(),
+                wall.scale.truncate(),
+            );
+            prop_assert_eq!(overlap, None);
+        }
+
+        #[test]
+        fn grid_candidates_include_every_overlap(
+            boxes in prop::collection::vec((vector(500.0), size(0.0, 80.0)), 0..200),
+            center in vector(600.0),
+            size in size(0.0, 80.0),
+        ) {
+            let mut grid = Grid::new(50.0);
+            for (index, (center, size)) in boxes.iter().enumerate() {
+                grid.insert(index, *center, *size);
+            }
+
+            let candidates = grid.candidates(center, size);
+            prop_assert!(candidates.windows(2).all(|pair| pair[0] < pair[1]));
+            for (index, (other_center, other_size)) in boxes.iter().enumerate() {
+                if aabb_overlap(center, size, *other_center, *other_size).is_some() {
+                    prop_assert!(candidates.contains(&index));
+                }
+            }
+        }
+    }
+}
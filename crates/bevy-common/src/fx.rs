@@ -0,0 +1,178 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{motion::Velocity, pool::Pool};
+
+pub fn lerp_color(from: Color, to: Color, t: f32) -> Color {
+    let [fr, fg, fb, fa] = from.as_rgba_f32();
+    let [tr, tg, tb, ta] = to.as_rgba_f32();
+    Color::rgba(
+        fr + (tr - fr) * t,
+        fg + (tg - fg) * t,
+        fb + (tb - fb) * t,
+        fa + (ta - fa) * t,
+    )
+}
+
+// How much of each effect to show, from 0 for none to 1 for all of it: how bright flashes
+// get, how many particles a burst throws and the like. The effects here go through it, and a
+// game's own should too.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct JuiceSettings {
+    pub intensity: f32,
+}
+
+impl Default for JuiceSettings {
+    fn default() -> Self {
+        Self { intensity: 1.0 }
+    }
+}
+
+impl JuiceSettings {
+    pub fn scale(&self, amount: f32) -> f32 {
+        amount * self.intensity
+    }
+
+    // rounded, so there's still a little of a few unless it's all the way down
+    pub fn count(&self, count: usize) -> usize {
+        (count as f32 * self.intensity).round() as usize
+    }
+}
+
+// Lights a sprite up in one color and fades it back to another, then goes away
+#[derive(Component, Debug)]
+pub struct Flash {
+    from: Color,
+    to: Color,
+    timer: Timer,
+}
+
+impl Flash {
+    pub fn new(from: Color, to: Color, duration: f32) -> Self {
+        Self {
+            from,
+            to,
+            timer: Timer::from_seconds(duration, TimerMode::Once),
+        }
+    }
+}
+
+pub struct FlashPlugin;
+
+impl Plugin for FlashPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<JuiceSettings>()
+            .add_systems(Update, fade_flashes);
+    }
+}
+
+// A speck thrown out by `burst`, fading out as it goes
+#[derive(Component, Debug)]
+pub struct Particle {
+    color: Color,
+    lifetime: Timer,
+}
+
+// Throws `count` specks of `color` out from `position` in every direction, for explosions
+// and impacts, fewer with the juice turned down. They move with `motion::apply_velocity`,
+// which the game has to be running.
+pub fn burst(
+    commands: &mut Commands,
+    pool: &mut Pool<Particle>,
+    juice: &JuiceSettings,
+    position: Vec2,
+    color: Color,
+    count: usize,
+    speed: f32,
+) {
+    let mut rng = rand::thread_rng();
+
+    for _ in 0..juice.count(count) {
+        let direction = Vec2::from_angle(rng.gen_range(0.0..std::f32::consts::TAU));
+        let lifetime = rng.gen_range(PARTICLE_LIFETIME / 2.0..PARTICLE_LIFETIME);
+
+        pool.spawn(
+            commands,
+            (
+                SpriteBundle {
+                    transform: Transform::from_translation(position.extend(PARTICLE_Z))
+                        .with_scale(Vec3::splat(PARTICLE_SIZE)),
+                    sprite: Sprite { color, ..default() },
+                    ..default()
+                },
+                Velocity(direction * speed * rng.gen_range(0.5..1.0)),
+                Particle {
+                    color,
+                    lifetime: Timer::from_seconds(lifetime, TimerMode::Once),
+                },
+            ),
+        );
+    }
+}
+
+const PARTICLE_LIFETIME: f32 = 0.8;
+const PARTICLE_SIZE: f32 = 4.0;
+// in front of most things
+const PARTICLE_Z: f32 = 5.0;
+
+pub struct ParticlePlugin;
+
+impl Plugin for ParticlePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Pool<Particle>>()
+            .init_resource::<JuiceSettings>()
+            .add_systems(Update, fade_particles);
+    }
+}
+
+fn fade_particles(
+    mut pool: ResMut<Pool<Particle>>,
+    mut particles: Query<(Entity, &mut Sprite, &mut Visibility, &mut Particle)>,
+    time: Res<Time>,
+) {
+    for (entity, mut sprite, mut visibility, mut particle) in &mut particles {
+        if *visibility == Visibility::Hidden {
+            continue;
+        }
+        if particle.lifetime.tick(time.delta()).finished() {
+            pool.recycle(entity, &mut visibility);
+            continue;
+        }
+        sprite.color = particle
+            .color
+            .with_a(particle.color.a() * particle.lifetime.percent_left());
+    }
+}
+
+fn fade_flashes(
+    mut commands: Commands,
+    mut sprites: Query<(Entity, &mut Sprite, &mut Flash)>,
+    juice: Res<JuiceSettings>,
+    time: Res<Time>,
+) {
+    for (entity, mut sprite, mut flash) in &mut sprites {
+        flash.timer.tick(time.delta());
+        // turned down, it starts that much of the way back already
+        let from = lerp_color(flash.to, flash.from, juice.intensity);
+        sprite.color = lerp_color(from, flash.to, flash.timer.percent());
+
+        if flash.timer.finished() {
+            commands.entity(entity).remove::<Flash>();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn juice_scales_counts() {
+        let juice = |intensity| JuiceSettings { intensity };
+
+        assert_eq!(juice(1.0).count(20), 20);
+        assert_eq!(juice(0.5).count(20), 10);
+        assert_eq!(juice(0.1).count(3), 0);
+        assert_eq!(juice(0.0).count(20), 0);
+    }
+}
@@ -0,0 +1,29 @@
+use bevy::prelude::*;
+
+// Every game takes the arrow keys, WASD and vim keys for the same directions
+pub const UP: [KeyCode; 3] = [KeyCode::Up, KeyCode::W, KeyCode::K];
+pub const DOWN: [KeyCode; 3] = [KeyCode::Down, KeyCode::S, KeyCode::J];
+pub const LEFT: [KeyCode; 3] = [KeyCode::Left, KeyCode::A, KeyCode::H];
+pub const RIGHT: [KeyCode; 3] = [KeyCode::Right, KeyCode::D, KeyCode::L];
+
+// Which way is being held: 1 for up, -1 for down, 0 for neither
+pub fn vertical(keyboard_input: &Input<KeyCode>) -> f32 {
+    if keyboard_input.any_pressed(UP) {
+        1.0
+    } else if keyboard_input.any_pressed(DOWN) {
+        -1.0
+    } else {
+        0.0
+    }
+}
+
+// Which way is being held: 1 for right, -1 for left, 0 for neither
+pub fn horizontal(keyboard_input: &Input<KeyCode>) -> f32 {
+    if keyboard_input.any_pressed(RIGHT) {
+        1.0
+    } else if keyboard_input.any_pressed(LEFT) {
+        -1.0
+    } else {
+        0.0
+    }
+}
@@ -0,0 +1,14 @@
+// Pieces shared by the games under `bins/`, so a new one can start from them instead of
+// copying bits out of another game. Nothing in here knows about any particular game.
+pub mod audio;
+pub mod collision;
+pub mod fx;
+pub mod input;
+pub mod motion;
+pub mod pool;
+pub mod scalar;
+pub mod screens;
+pub mod settings;
+pub mod storage;
+pub mod trajectory;
+pub mod window;
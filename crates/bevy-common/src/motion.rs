@@ -0,0 +1,23 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::scalar;
+
+// How fast something moves, in units per second
+#[derive(
+    Component, Debug, Default, Clone, Copy, Deref, DerefMut, Reflect, Serialize, Deserialize,
+)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct Velocity(pub Vec2);
+
+// Moves everything with a `Velocity`. Games add this to whichever schedule their
+// simulation runs in.
+pub fn apply_velocity(mut query: Query<(&mut Transform, &Velocity)>, time: Res<Time>) {
+    let dt = scalar::widen(time.delta_seconds());
+    for (mut transform, velocity) in &mut query {
+        let position = scalar::to_sim(transform.translation.truncate());
+        let moved = scalar::to_render(position + scalar::to_sim(velocity.0) * dt);
+        transform.translation.x = moved.x;
+        transform.translation.y = moved.y;
+    }
+}
@@ -0,0 +1,84 @@
+// Recycling for short-lived entities like particles and trails. Spawning and despawning
+// hundreds of them a second keeps bevy allocating entities and moving rows around its tables,
+// so a `Pool` hides finished ones instead and hands them back out. The new components go in on
+// top of the old ones, which as they're the same kinds leaves the entity where it was.
+use std::marker::PhantomData;
+
+use bevy::prelude::*;
+
+// Spare entities carrying a `T`, one pool for each kind of thing recycled
+#[derive(Resource, Debug)]
+pub struct Pool<T> {
+    spare: Vec<Entity>,
+    kind: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for Pool<T> {
+    fn default() -> Self {
+        Self {
+            spare: Vec::new(),
+            kind: PhantomData,
+        }
+    }
+}
+
+impl<T: Component> Pool<T> {
+    // Puts `bundle` on a spare entity if there is one, or spawns it. It has to set the
+    // `Visibility` back, the way `SpriteBundle` does.
+    pub fn spawn(&mut self, commands: &mut Commands, bundle: impl Bundle) -> Entity {
+        // something else may have despawned a spare since
+        while let Some(entity) = self.spare.pop() {
+            if let Some(mut spare) = commands.get_entity(entity) {
+                spare.insert(bundle);
+                return entity;
+            }
+        }
+        commands.spawn(bundle).id()
+    }
+
+    // Rather than despawning it, it's hidden until it's wanted again. Whatever's updating
+    // them has to leave the hidden ones alone.
+    pub fn recycle(&mut self, entity: Entity, visibility: &mut Visibility) {
+        *visibility = Visibility::Hidden;
+        self.spare.push(entity);
+    }
+
+    pub fn spare(&self) -> usize {
+        self.spare.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::CommandQueue;
+
+    use super::*;
+
+    #[derive(Component)]
+    struct Speck;
+
+    #[test]
+    fn recycled_entities_are_handed_back_out() {
+        let mut world = World::new();
+        let mut queue = CommandQueue::default();
+        let mut pool = Pool::<Speck>::default();
+
+        let first = pool.spawn(
+            &mut Commands::new(&mut queue, &world),
+            (Speck, Visibility::Visible),
+        );
+        queue.apply(&mut world);
+        pool.recycle(first, &mut world.get_mut::<Visibility>(first).unwrap());
+        assert_eq!(pool.spare(), 1);
+
+        let second = pool.spawn(
+            &mut Commands::new(&mut queue, &world),
+            (Speck, Visibility::Visible),
+        );
+        queue.apply(&mut world);
+        assert_eq!(second, first);
+        assert_eq!(world.get::<Visibility>(second), Some(&Visibility::Visible));
+        assert_eq!(world.entities().len(), 1);
+        assert_eq!(pool.spare(), 0);
+    }
+}
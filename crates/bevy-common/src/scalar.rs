@@ -0,0 +1,67 @@
+// The number type the core physics does its sums in: f32 like everything else, or f64 with
+// the `f64-physics` feature, for simulations that have to stay in step with a copy running
+// on another machine. Components and transforms stay f32, since they're what gets drawn, so
+// values are widened on their way into the physics and narrowed on their way back out.
+use bevy::prelude::*;
+
+#[cfg(not(feature = "f64-physics"))]
+pub type Scalar = f32;
+#[cfg(not(feature = "f64-physics"))]
+pub type SimVec2 = Vec2;
+
+#[cfg(feature = "f64-physics")]
+pub type Scalar = f64;
+#[cfg(feature = "f64-physics")]
+pub type SimVec2 = bevy::math::DVec2;
+
+#[cfg(not(feature = "f64-physics"))]
+pub fn widen(value: f32) -> Scalar {
+    value
+}
+
+#[cfg(feature = "f64-physics")]
+pub fn widen(value: f32) -> Scalar {
+    value.into()
+}
+
+#[cfg(not(feature = "f64-physics"))]
+pub fn narrow(value: Scalar) -> f32 {
+    value
+}
+
+#[cfg(feature = "f64-physics")]
+pub fn narrow(value: Scalar) -> f32 {
+    value as f32
+}
+
+#[cfg(not(feature = "f64-physics"))]
+pub fn to_sim(vector: Vec2) -> SimVec2 {
+    vector
+}
+
+#[cfg(feature = "f64-physics")]
+pub fn to_sim(vector: Vec2) -> SimVec2 {
+    vector.as_dvec2()
+}
+
+#[cfg(not(feature = "f64-physics"))]
+pub fn to_render(vector: SimVec2) -> Vec2 {
+    vector
+}
+
+#[cfg(feature = "f64-physics")]
+pub fn to_render(vector: SimVec2) -> Vec2 {
+    vector.as_vec2()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drawn_values_survive_the_round_trip() {
+        let vector = Vec2::new(0.1, -123.456);
+        assert_eq!(to_render(to_sim(vector)), vector);
+        assert_eq!(narrow(widen(0.1)), 0.1);
+    }
+}
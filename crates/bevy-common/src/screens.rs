@@ -0,0 +1,46 @@
+use bevy::prelude::*;
+
+// A backdrop covering the whole window with its contents centered, for menus and overlays
+pub fn overlay(backdrop: Color, z_index: i32) -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        background_color: backdrop.into(),
+        z_index: ZIndex::Global(z_index),
+        ..default()
+    }
+}
+
+// Despawns everything marked with `T`, along with its children
+pub fn despawn_with<T: Component>(mut commands: Commands, entities: Query<Entity, With<T>>) {
+    for entity in &entities {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+pub trait AddScreen {
+    // A screen that's only up in one state: `spawn` puts it up on entering `state`, and
+    // everything marked with `T` is taken down on leaving
+    fn add_screen<T: Component, M>(
+        &mut self,
+        state: impl States,
+        spawn: impl IntoSystemConfigs<M>,
+    ) -> &mut Self;
+}
+
+impl AddScreen for App {
+    fn add_screen<T: Component, M>(
+        &mut self,
+        state: impl States,
+        spawn: impl IntoSystemConfigs<M>,
+    ) -> &mut Self {
+        self.add_systems(OnEnter(state.clone()), spawn)
+            .add_systems(OnExit(state), despawn_with::<T>)
+    }
+}
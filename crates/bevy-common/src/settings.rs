@@ -0,0 +1,57 @@
+use bevy::prelude::*;
+
+use crate::storage;
+
+const SETTINGS_FILE: &str = "settings";
+const VOLUME_STEP: f32 = 0.1;
+
+// Settings every game has, kept between runs with `storage`. For now that's the master
+// volume, turned down and up with - and =. It only applies to sounds started after it
+// changes.
+pub struct SettingsPlugin {
+    // where the settings are kept, see `storage::path`
+    pub game: &'static str,
+}
+
+#[derive(Resource, Debug)]
+struct SettingsFile(&'static str);
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        let volume = storage::load(self.game, SETTINGS_FILE)
+            .and_then(|settings| {
+                settings
+                    .lines()
+                    .filter_map(|line| line.split_once('='))
+                    .find(|(key, _)| key.trim() == "volume")
+                    .and_then(|(_, volume)| volume.trim().parse::<f32>().ok())
+            })
+            .unwrap_or(1.0);
+
+        app.insert_resource(GlobalVolume::new(volume.clamp(0.0, 1.0)))
+            .insert_resource(SettingsFile(self.game))
+            .add_systems(Update, adjust_volume);
+    }
+}
+
+fn adjust_volume(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut volume: ResMut<GlobalVolume>,
+    file: Res<SettingsFile>,
+) {
+    let step = if keyboard_input.just_pressed(KeyCode::Minus) {
+        -VOLUME_STEP
+    } else if keyboard_input.just_pressed(KeyCode::Equals) {
+        VOLUME_STEP
+    } else {
+        return;
+    };
+
+    let level = (volume.volume.get() + step).clamp(0.0, 1.0);
+    *volume = GlobalVolume::new(level);
+    info!("volume: {:.0}%", level * 100.0);
+
+    if let Err(err) = storage::save(file.0, SETTINGS_FILE, &format!("volume={level}\n")) {
+        warn!("couldn't save the settings: {err}");
+    }
+}
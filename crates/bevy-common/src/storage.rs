@@ -0,0 +1,32 @@
+use std::{fs, io, path::PathBuf};
+
+// Small files a game keeps between runs, like its settings or high scores. Each game gets
+// its own directory under the user's data directory. Browsers don't have one, so nothing
+// is ever found or kept there.
+pub fn path(game: &str, file: &str) -> Option<PathBuf> {
+    if cfg!(target_arch = "wasm32") {
+        return None;
+    }
+
+    let data = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("APPDATA").map(PathBuf::from))
+        .or_else(|| {
+            std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share"))
+        })?;
+    Some(data.join("billy-bevy-learning").join(game).join(file))
+}
+
+// `None` if it's never been saved, or can't be read
+pub fn load(game: &str, file: &str) -> Option<String> {
+    fs::read_to_string(path(game, file)?).ok()
+}
+
+pub fn save(game: &str, file: &str, contents: &str) -> io::Result<()> {
+    let path = path(game, file)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::Unsupported, "nowhere to save to"))?;
+    if let Some(directory) = path.parent() {
+        fs::create_dir_all(directory)?;
+    }
+    fs::write(path, contents)
+}
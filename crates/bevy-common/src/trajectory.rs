@@ -0,0 +1,176 @@
+use bevy::prelude::*;
+
+// The floor and ceiling a ball bounces between, as the lowest and highest its center can go
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Arena {
+    pub bottom: f32,
+    pub top: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Intercept {
+    pub position: Vec2,
+    // seconds from now
+    pub time: f32,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Trajectory {
+    // where the ball will bounce off the floor or ceiling, in order
+    pub bounces: Vec<Vec2>,
+    // where it crosses the target x, unless it's heading away or bounces too often first
+    pub intercept: Option<Intercept>,
+}
+
+impl Trajectory {
+    // every point the ball passes through, from `start`, for drawing it
+    pub fn points(&self, start: Vec2) -> impl Iterator<Item = Vec2> + '_ {
+        std::iter::once(start)
+            .chain(self.bounces.iter().copied())
+            .chain(self.intercept.map(|intercept| intercept.position))
+    }
+}
+
+// Where a ball at `position` moving at `velocity` goes next, bouncing straight off the
+// arena's floor and ceiling, up to `max_bounces` times or until it reaches `target_x`. Nothing
+// else it could hit is taken into account.
+pub fn predict_trajectory(
+    position: Vec2,
+    velocity: Vec2,
+    arena: Arena,
+    target_x: f32,
+    max_bounces: usize,
+) -> Trajectory {
+    let mut trajectory = Trajectory::default();
+    let mut position = Vec2::new(position.x, position.y.clamp(arena.bottom, arena.top));
+    let mut velocity = velocity;
+    let mut elapsed = 0.0;
+
+    loop {
+        let to_target = (target_x - position.x) / velocity.x;
+        let to_wall = if velocity.y > 0.0 {
+            (arena.top - position.y) / velocity.y
+        } else if velocity.y < 0.0 {
+            (arena.bottom - position.y) / velocity.y
+        } else {
+            f32::INFINITY
+        };
+
+        // a ball that's not moving sideways, or is heading away, never gets there
+        if to_target.is_finite() && to_target >= 0.0 && to_target <= to_wall {
+            trajectory.intercept = Some(Intercept {
+                position: position + velocity * to_target,
+                time: elapsed + to_target,
+            });
+            return trajectory;
+        }
+        if !to_wall.is_finite() || trajectory.bounces.len() >= max_bounces {
+            return trajectory;
+        }
+
+        position += velocity * to_wall;
+        elapsed += to_wall;
+        velocity.y = -velocity.y;
+        trajectory.bounces.push(position);
+    }
+}
+
+// The path a ball at `position` moving at `velocity` traces with `gravity` pulling it down (in
+// units/s²), stepped `dt` seconds at a time the way the simulation moves it and bouncing off
+// the arena's floor and ceiling. It stops once it reaches `target_x`, or after `max_time`.
+pub fn simulate_path(
+    position: Vec2,
+    velocity: Vec2,
+    gravity: f32,
+    arena: Arena,
+    target_x: f32,
+    dt: f32,
+    max_time: f32,
+) -> Vec<Vec2> {
+    let mut path = vec![position];
+    let mut position = position;
+    let mut velocity = velocity;
+    let mut elapsed = 0.0;
+
+    while elapsed < max_time {
+        velocity.y -= gravity * dt;
+        position += velocity * dt;
+        elapsed += dt;
+
+        if position.y > arena.top {
+            position.y = 2.0 * arena.top - position.y;
+            velocity.y = -velocity.y.abs();
+        } else if position.y < arena.bottom {
+            position.y = 2.0 * arena.bottom - position.y;
+            velocity.y = velocity.y.abs();
+        }
+
+        if (position.x - target_x) * velocity.x.signum() >= 0.0 {
+            path.push(position);
+            break;
+        }
+        path.push(position);
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ARENA: Arena = Arena {
+        bottom: -100.0,
+        top: 100.0,
+    };
+
+    #[test]
+    fn bounces_on_the_way_to_the_target() {
+        let trajectory = predict_trajectory(Vec2::ZERO, Vec2::new(100.0, 100.0), ARENA, 300.0, 4);
+        assert_eq!(trajectory.bounces, vec![Vec2::new(100.0, 100.0)]);
+        assert_eq!(
+            trajectory.intercept,
+            Some(Intercept {
+                position: Vec2::new(300.0, -100.0),
+                time: 3.0,
+            })
+        );
+
+        // one more bounce is one too many
+        let trajectory = predict_trajectory(Vec2::ZERO, Vec2::new(100.0, 100.0), ARENA, 350.0, 1);
+        assert_eq!(trajectory.bounces.len(), 1);
+        assert_eq!(trajectory.intercept, None);
+    }
+
+    #[test]
+    fn balls_heading_away_never_arrive() {
+        let trajectory = predict_trajectory(Vec2::ZERO, Vec2::new(-100.0, 0.0), ARENA, 300.0, 4);
+        assert_eq!(trajectory, Trajectory::default());
+    }
+
+    #[test]
+    fn gravity_bends_the_path_down() {
+        let straight = simulate_path(
+            Vec2::ZERO,
+            Vec2::new(100.0, 0.0),
+            0.0,
+            ARENA,
+            100.0,
+            0.125,
+            5.0,
+        );
+        assert_eq!(straight.len(), 9);
+        assert!(straight.iter().all(|point| point.y == 0.0));
+
+        let curved = simulate_path(
+            Vec2::ZERO,
+            Vec2::new(100.0, 0.0),
+            50.0,
+            ARENA,
+            100.0,
+            0.125,
+            5.0,
+        );
+        assert!(curved.last().unwrap().y < -10.0);
+        assert!(curved.windows(2).all(|pair| pair[1].y <= pair[0].y));
+    }
+}
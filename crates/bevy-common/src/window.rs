@@ -0,0 +1,231 @@
+use bevy::{
+    app::AppExit,
+    prelude::*,
+    window::{PrimaryWindow, WindowMode, WindowMoved, WindowResized, WindowResolution},
+    winit::WinitWindows,
+};
+
+use crate::storage;
+
+const WINDOW_FILE: &str = "window";
+
+// Where a game's window was and how big, kept between runs with `storage` beside its settings
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct WindowGeometry {
+    // logical pixels
+    pub width: f32,
+    pub height: f32,
+    // physical pixels, from the top left of the whole desktop
+    pub position: Option<IVec2>,
+    // the name of the monitor it was on, if it has one
+    pub monitor: Option<String>,
+}
+
+impl WindowGeometry {
+    pub fn load(game: &str) -> Option<Self> {
+        Self::parse(&storage::load(game, WINDOW_FILE)?)
+    }
+
+    pub fn save(&self, game: &str) {
+        if let Err(err) = storage::save(game, WINDOW_FILE, &self.to_string()) {
+            warn!("couldn't save the window's position: {err}");
+        }
+    }
+
+    // `key=value` lines, like the settings file
+    fn parse(file: &str) -> Option<Self> {
+        let value = |key: &str| {
+            file.lines()
+                .filter_map(|line| line.split_once('='))
+                .find(|(name, _)| name.trim() == key)
+                .map(|(_, value)| value.trim().to_string())
+        };
+        let number = |key: &str| value(key).and_then(|value| value.parse::<f32>().ok());
+
+        let (width, height) = (number("width")?, number("height")?);
+        if width < 1.0 || height < 1.0 {
+            return None;
+        }
+        let position = number("x")
+            .zip(number("y"))
+            .map(|(x, y)| IVec2::new(x as i32, y as i32));
+        Some(Self {
+            width,
+            height,
+            position,
+            monitor: value("monitor").filter(|monitor| !monitor.is_empty()),
+        })
+    }
+
+    // The window as it was last time, otherwise just like `window`
+    pub fn apply(&self, window: Window) -> Window {
+        Window {
+            resolution: WindowResolution::new(self.width, self.height),
+            position: match self.position {
+                Some(position) => WindowPosition::At(position),
+                None => window.position,
+            },
+            ..window
+        }
+    }
+}
+
+impl std::fmt::Display for WindowGeometry {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(f, "width={}", self.width)?;
+        writeln!(f, "height={}", self.height)?;
+        if let Some(position) = self.position {
+            writeln!(f, "x={}", position.x)?;
+            writeln!(f, "y={}", position.y)?;
+        }
+        if let Some(monitor) = &self.monitor {
+            writeln!(f, "monitor={monitor}")?;
+        }
+        Ok(())
+    }
+}
+
+// The primary window put back where it was last time, for `DefaultPlugins.set(..)`
+pub fn window_plugin(game: &str) -> WindowPlugin {
+    let window = Window::default();
+    WindowPlugin {
+        primary_window: Some(match WindowGeometry::load(game) {
+            Some(geometry) => geometry.apply(window),
+            None => window,
+        }),
+        ..default()
+    }
+}
+
+// Keeps track of the primary window as it's moved and resized, saving it on the way out.
+// A window that'd come back on a monitor that's no longer there is centered on the main
+// one instead.
+pub struct WindowMemoryPlugin {
+    pub game: &'static str,
+}
+
+#[derive(Resource, Debug)]
+struct WindowFile(&'static str);
+
+impl Plugin for WindowMemoryPlugin {
+    fn build(&self, app: &mut App) {
+        let saved = WindowGeometry::load(self.game);
+        app.insert_resource(WindowFile(self.game))
+            .add_systems(Update, (check_monitor, remember_window))
+            .add_systems(Last, save_on_exit);
+        if let Some(saved) = saved {
+            app.insert_resource(saved);
+        }
+    }
+}
+
+// Once the window exists, it can be checked against the monitors that are plugged in
+fn check_monitor(
+    saved: Option<Res<WindowGeometry>>,
+    winit_windows: Option<NonSend<WinitWindows>>,
+    mut windows: Query<(Entity, &mut Window), With<PrimaryWindow>>,
+    mut checked: Local<bool>,
+) {
+    // headless apps have no windows to check
+    let Some(winit_windows) = winit_windows.filter(|_| !*checked) else {
+        return;
+    };
+    let Ok((entity, mut window)) = windows.get_single_mut() else {
+        return;
+    };
+    let Some(winit_window) = winit_windows.get_window(entity) else {
+        return;
+    };
+    *checked = true;
+    let Some(saved) = saved else {
+        return;
+    };
+
+    let monitors: Vec<_> = winit_window.available_monitors().collect();
+    let monitor_there = saved.monitor.as_ref().is_none_or(|name| {
+        monitors
+            .iter()
+            .any(|monitor| monitor.name().as_ref() == Some(name))
+    });
+    // at least its top left corner has to be on a screen to be grabbed
+    let on_screen = saved.position.is_none_or(|position| {
+        monitors.iter().any(|monitor| {
+            let (origin, size) = (monitor.position(), monitor.size());
+            (origin.x..origin.x + size.width as i32).contains(&position.x)
+                && (origin.y..origin.y + size.height as i32).contains(&position.y)
+        })
+    });
+    if !monitor_there || !on_screen {
+        window.position = WindowPosition::Centered(MonitorSelection::Primary);
+    }
+}
+
+fn remember_window(
+    mut commands: Commands,
+    mut moves: EventReader<WindowMoved>,
+    mut resizes: EventReader<WindowResized>,
+    geometry: Option<ResMut<WindowGeometry>>,
+    winit_windows: Option<NonSend<WinitWindows>>,
+    windows: Query<(Entity, &Window), With<PrimaryWindow>>,
+) {
+    let moved = moves.read().count() > 0;
+    let resized = resizes.read().count() > 0;
+    if !moved && !resized {
+        return;
+    }
+    let Ok((entity, window)) = windows.get_single() else {
+        return;
+    };
+    // fullscreen isn't where the window goes back to
+    if window.mode != WindowMode::Windowed {
+        return;
+    }
+
+    let winit_window = winit_windows
+        .as_ref()
+        .and_then(|windows| windows.get_window(entity));
+    let now = WindowGeometry {
+        width: window.resolution.width(),
+        height: window.resolution.height(),
+        position: winit_window
+            .and_then(|window| window.outer_position().ok())
+            .map(|position| IVec2::new(position.x, position.y)),
+        monitor: winit_window
+            .and_then(|window| window.current_monitor())
+            .and_then(|monitor| monitor.name()),
+    };
+    match geometry {
+        Some(mut geometry) => *geometry = now,
+        None => commands.insert_resource(now),
+    }
+}
+
+fn save_on_exit(
+    mut exits: EventReader<AppExit>,
+    geometry: Option<Res<WindowGeometry>>,
+    file: Res<WindowFile>,
+) {
+    if exits.read().next().is_none() {
+        return;
+    }
+    if let Some(geometry) = geometry {
+        geometry.save(file.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn geometry_round_trips_through_its_file() {
+        let geometry = WindowGeometry {
+            width: 1280.0,
+            height: 720.0,
+            position: Some(IVec2::new(-1900, 40)),
+            monitor: Some("DP-1".to_string()),
+        };
+        assert_eq!(WindowGeometry::parse(&geometry.to_string()), Some(geometry));
+        assert_eq!(WindowGeometry::parse("width=0\nheight=720"), None);
+    }
+}